@@ -0,0 +1,73 @@
+//! Times long, sparse signatures against the same signatures without wildcards, which take the
+//! KMP path, and checks that both agree with a brute-force scan.
+//!
+//! Run with `cargo bench --features aob --bench wildcard_scan`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use verity_memory::pattern::Scanner;
+
+const DATA_SIZE: usize = 16 * 1024 * 1024;
+const ITERATIONS: u32 = 10;
+
+fn data() -> Vec<u8> {
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    (0..DATA_SIZE)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 4) as u8 + 1
+        })
+        .collect()
+}
+
+fn brute_force(data: &[u8], pattern: &str) -> Vec<*mut u8> {
+    let pattern: Vec<Option<u8>> = pattern
+        .split_whitespace()
+        .map(|token| u8::from_str_radix(token, 16).ok())
+        .collect();
+
+    data.windows(pattern.len())
+        .enumerate()
+        .filter(|(_, window)| window.iter().zip(&pattern).all(|(byte, expected)| expected.unwrap_or(*byte) == *byte))
+        .map(|(index, _)| index as *mut u8)
+        .collect()
+}
+
+fn time(scanner: &Scanner, pattern: &str) -> (Duration, usize) {
+    let mut found = 0;
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        found = black_box(scanner.find_all(black_box(pattern)).unwrap_or_default()).len();
+    }
+    (start.elapsed() / ITERATIONS, found)
+}
+
+fn main() {
+    let data = data();
+    let scanner = Scanner::from_bytes(&data, 0);
+
+    let cases = [
+        ("01 ?? 02 ?? 03 ?? 04", "01 02 02 03 03 04 04"),
+        ("01 02 ?? 01 01", "01 02 03 01 01"),
+        (
+            "02 ?? 02 ?? 02 ?? 02 ?? 02 ?? 02 ?? 02 ?? 02 ?? 02 ?? 02",
+            "02 03 02 03 02 03 02 03 02 03 02 03 02 03 02 03 02 03 02",
+        ),
+        (
+            "01 02 03 04 ?? ?? ?? ?? 04 03 02 01 ?? ?? ?? ?? 01 01 02 02",
+            "01 02 03 04 01 01 01 01 04 03 02 01 01 01 01 01 01 01 02 02",
+        ),
+    ];
+
+    for (wildcard, plain) in cases {
+        assert_eq!(scanner.find_all(wildcard).unwrap_or_default(), brute_force(&data, wildcard));
+        assert_eq!(scanner.find_all(plain).unwrap_or_default(), brute_force(&data, plain));
+
+        let (naive, naive_found) = time(&scanner, wildcard);
+        let (kmp, kmp_found) = time(&scanner, plain);
+        println!("{wildcard}\n    naive: {naive:?} ({naive_found} matches)\n    kmp:   {kmp:?} ({kmp_found} matches)");
+    }
+}
@@ -0,0 +1,286 @@
+use crate::errors::{AobScanError, CheatError};
+use crate::ops::patch::ToggleState;
+use crate::ops::read::{read_bytes, region_slice};
+use crate::ops::write::write_bytes;
+use crate::pattern::memory::get_text_section;
+use crate::pattern::Scanner;
+use crate::types::instruction::{Instruction, InstructionVecExt};
+
+/// A byte patch located by a signature.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Patch {
+    /// The name reported in errors.
+    pub name: String,
+    /// The pattern locating the patch, e.g. `"89 83 ?? ?? ?? ?? 8B 45"`.
+    pub signature: String,
+    /// The offset of the patch from the start of the match.
+    pub offset: isize,
+    /// The bytes written at the patch.
+    pub bytes: Vec<u8>,
+}
+
+/// A named toggle made of one or more byte patches, e.g. "infinite ammo" replacing the
+/// instruction that decrements the ammo counter with NOPs.
+///
+/// Every patch is located by a signature when the cheat is enabled, in a single snapshot of the
+/// scanned range taken for all of them, then overwritten with its replacement bytes. Disabling
+/// the cheat, or dropping it while enabled, restores the original bytes.
+///
+/// By default signatures are scanned in the `.text` section of the executable like
+/// [`scan_unique`](crate::pattern::aob::scan_unique); use [`Cheat::in_region`] to scan another
+/// range, e.g. the code of a DLL.
+///
+/// # Example
+/// ```rust,no_run
+/// use verity_memory::cheat::Cheat;
+///
+/// let mut infinite_ammo = Cheat::new("Infinite ammo")
+///     .patch("decrement", "FF 8E ?? ?? ?? ?? 8B 86", 0, &[0x90; 6]);
+///
+/// unsafe {
+///     infinite_ammo.enable().unwrap();
+///     assert!(infinite_ammo.is_enabled());
+///     infinite_ammo.disable().unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Cheat {
+    name: String,
+    patches: Vec<Patch>,
+    region: Option<(usize, usize)>,
+    originals: Vec<Instruction>,
+    state: ToggleState,
+}
+
+impl Cheat {
+    /// Creates a cheat without patches.
+    pub fn new(name: &str) -> Self {
+        Cheat {
+            name: name.to_string(),
+            patches: Vec::new(),
+            region: None,
+            originals: Vec::new(),
+            state: ToggleState::default(),
+        }
+    }
+
+    /// Adds a patch writing `bytes` at `offset` bytes from the match of `signature`.
+    pub fn patch(mut self, name: &str, signature: &str, offset: isize, bytes: &[u8]) -> Self {
+        self.patches.push(Patch {
+            name: name.to_string(),
+            signature: signature.to_string(),
+            offset,
+            bytes: bytes.to_vec(),
+        });
+        self
+    }
+
+    /// Scans the `len` bytes at `address` instead of the `.text` section of the executable.
+    pub fn in_region(mut self, address: *mut u8, len: usize) -> Self {
+        self.region = Some((address as usize, len));
+        self
+    }
+
+    /// Returns the name of the cheat.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the patches of the cheat.
+    pub fn patches(&self) -> &[Patch] {
+        &self.patches
+    }
+
+    /// Returns whether the patches are currently applied.
+    pub fn is_enabled(&self) -> bool {
+        self.state.is_enabled()
+    }
+
+    /// Applies every patch. Does nothing if the cheat is already enabled.
+    ///
+    /// All signatures are resolved before anything is written, and the patches already written
+    /// are restored if a later one fails, so the cheat is either fully applied or not at all.
+    ///
+    /// # Safety
+    /// This function is unsafe because it rewrites memory, usually code. No thread may be
+    /// executing the patched bytes while they are written.
+    ///
+    /// # Errors
+    /// - `CheatError::Scan`: If a signature is invalid, not found, or its bytes couldn't be read.
+    /// - `CheatError::Write`: If a patch could not be written.
+    pub unsafe fn enable(&mut self) -> Result<(), CheatError> {
+        self.state.set(true, |_| {
+            self.originals = apply_patches(&self.patches, self.region)?;
+            Ok(())
+        })
+    }
+
+    /// Restores the original bytes of every patch. Does nothing if the cheat is disabled.
+    ///
+    /// # Safety
+    /// Same as [`Cheat::enable`].
+    ///
+    /// # Errors
+    /// - `CheatError::Restore`: If the original bytes could not be written back. The cheat stays
+    ///   enabled, so disabling it can be retried.
+    pub unsafe fn disable(&mut self) -> Result<(), CheatError> {
+        self.state.set(false, |_| restore_patches(&mut self.originals))
+    }
+
+    /// Enables the cheat if it is disabled and the other way around, returning the new state.
+    ///
+    /// # Safety
+    /// Same as [`Cheat::enable`].
+    ///
+    /// # Errors
+    /// - Same as [`Cheat::enable`] and [`Cheat::disable`].
+    pub unsafe fn toggle(&mut self) -> Result<bool, CheatError> {
+        self.state.toggle(|enabled| {
+            if enabled {
+                self.originals = apply_patches(&self.patches, self.region)?;
+                Ok(())
+            } else {
+                restore_patches(&mut self.originals)
+            }
+        })
+    }
+}
+
+/// Resolves every patch, then writes them, returning the bytes they replaced.
+///
+/// The patches already written are restored if a later one fails.
+unsafe fn apply_patches(patches: &[Patch], region: Option<(usize, usize)>) -> Result<Vec<Instruction>, CheatError> {
+    let Some(first) = patches.first() else {
+        return Ok(Vec::new());
+    };
+    let (bytes, base) = snapshot(region).map_err(|error| CheatError::Scan {
+        patch: first.name.clone(),
+        error,
+    })?;
+    let scanner = Scanner::from_bytes(&bytes, base);
+
+    let mut targets = Vec::with_capacity(patches.len());
+    for patch in patches {
+        let address = resolve(&scanner, patch).map_err(|error| CheatError::Scan {
+            patch: patch.name.clone(),
+            error,
+        })?;
+        let original = read_bytes(address, patch.bytes.len()).map_err(|_| CheatError::Scan {
+            patch: patch.name.clone(),
+            error: AobScanError::InvalidAccess,
+        })?;
+        targets.push(Instruction::new(address, original));
+    }
+
+    let mut originals = Vec::with_capacity(targets.len());
+    for (patch, target) in patches.iter().zip(targets) {
+        if let Err(error) = write_bytes(target.address, &patch.bytes) {
+            let _ = originals.restore_all();
+            return Err(CheatError::Write {
+                patch: patch.name.clone(),
+                error,
+            });
+        }
+        originals.push(target);
+    }
+
+    Ok(originals)
+}
+
+/// Writes back the bytes replaced by [`apply_patches`].
+unsafe fn restore_patches(originals: &mut Vec<Instruction>) -> Result<(), CheatError> {
+    originals.restore_all().map_err(CheatError::Restore)?;
+    originals.clear();
+    Ok(())
+}
+
+/// Copies the scanned range, `region` or the `.text` section of the executable, with its address.
+unsafe fn snapshot(region: Option<(usize, usize)>) -> Result<(Vec<u8>, usize), AobScanError> {
+    match region {
+        Some((address, len)) => {
+            let bytes = region_slice(address as *const u8, len).map_err(|_| AobScanError::InvalidAccess)?;
+            Ok((bytes.to_vec(), address))
+        }
+        None => Ok(get_text_section()?),
+    }
+}
+
+fn resolve(scanner: &Scanner, patch: &Patch) -> Result<*mut u8, AobScanError> {
+    let address = scanner.find_unique(&patch.signature)?;
+    Ok(address.wrapping_offset(patch.offset))
+}
+
+impl Drop for Cheat {
+    fn drop(&mut self) {
+        let _ = unsafe { self.disable() };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code() -> Vec<u8> {
+        // push rbp; mov rbp, rsp; dec dword ptr [rsi + 0x10]; mov eax, [rsi + 0x10]; pop rbp; ret
+        vec![0x55, 0x48, 0x89, 0xE5, 0xFF, 0x4E, 0x10, 0x8B, 0x46, 0x10, 0x5D, 0xC3]
+    }
+
+    #[test]
+    fn test_cheat_enable_disable() {
+        let mut buffer = code();
+        let mut cheat = Cheat::new("Infinite ammo")
+            .patch("decrement", "FF 4E ?? 8B 46", 0, &[0x90; 3])
+            .patch("epilogue", "5D C3", -1, &[0x11])
+            .in_region(buffer.as_mut_ptr(), buffer.len());
+
+        assert_eq!(cheat.name(), "Infinite ammo");
+        assert_eq!(cheat.patches().len(), 2);
+        assert!(!cheat.is_enabled());
+
+        unsafe { cheat.enable() }.expect("Failed to enable cheat");
+        assert!(cheat.is_enabled());
+        assert_eq!(&buffer[4..7], &[0x90; 3]);
+        assert_eq!(buffer[9], 0x11);
+
+        unsafe { cheat.disable() }.expect("Failed to disable cheat");
+        assert!(!cheat.is_enabled());
+        assert_eq!(buffer, code());
+
+        assert_eq!(unsafe { cheat.toggle() }, Ok(true));
+        assert_eq!(unsafe { cheat.toggle() }, Ok(false));
+        assert_eq!(buffer, code());
+    }
+
+    #[test]
+    fn test_cheat_restored_on_drop() {
+        let mut buffer = code();
+        let mut cheat = Cheat::new("Drop")
+            .patch("ret", "C3", 0, &[0xCC])
+            .in_region(buffer.as_mut_ptr(), buffer.len());
+
+        unsafe { cheat.enable() }.unwrap();
+        assert_eq!(buffer[11], 0xCC);
+
+        drop(cheat);
+        assert_eq!(buffer, code());
+    }
+
+    #[test]
+    fn test_cheat_scan_failure_writes_nothing() {
+        let mut buffer = code();
+        let mut cheat = Cheat::new("Missing")
+            .patch("decrement", "FF 4E", 0, &[0x90; 3])
+            .patch("missing", "CC CC CC", 0, &[0x90])
+            .in_region(buffer.as_mut_ptr(), buffer.len());
+
+        assert_eq!(
+            unsafe { cheat.enable() },
+            Err(CheatError::Scan {
+                patch: "missing".to_string(),
+                error: AobScanError::PatternNotFound,
+            })
+        );
+        assert!(!cheat.is_enabled());
+        assert_eq!(buffer, code());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,15 @@
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllocError {
+    ZeroSize,
+    FailedToAllocate,
+    FailedToFlushInstructionCache,
+}
+
+impl std::fmt::Display for AllocError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for AllocError {}
\ No newline at end of file
@@ -1,14 +1,64 @@
 
-#[derive(Debug, PartialEq)]
+use crate::errors::PeParseError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AobScanError {
     PatternNotFound,
-    InvalidPattern,
+    InvalidPattern { token: String, position: usize },
+    EmptyPattern,
+    NotUnique,
+    InvalidAccess,
+    Changed,
+    TooCloseToEdge,
+    ValidationFailed { offset: isize },
+    GapsUnsupported,
+    Pe(PeParseError),
 }
 
 impl std::fmt::Display for AobScanError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            AobScanError::InvalidPattern { token, position } => {
+                write!(f, "InvalidPattern: token {:?} at position {}", token, position)
+            }
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+impl std::error::Error for AobScanError {}
+
+impl From<PeParseError> for AobScanError {
+    fn from(error: PeParseError) -> Self {
+        AobScanError::Pe(error)
     }
 }
 
-impl std::error::Error for AobScanError {}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::{ReadMemoryError, WriteMemoryError};
+    use crate::pattern::algorithm::convert_pattern;
+
+    #[test]
+    fn test_scan_errors_clone_and_eq() {
+        fn assert_eq_bound<T: Clone + Eq + std::fmt::Debug>(value: &T) {
+            assert_eq!(value.clone(), *value);
+        }
+
+        let invalid = convert_pattern("48 8B XY").unwrap_err();
+        assert_eq!(invalid, AobScanError::InvalidPattern { token: "XY".to_string(), position: 2 });
+        assert_eq_bound(&invalid);
+
+        // Errors can be memoized next to results.
+        let mut cache = std::collections::HashMap::new();
+        cache.insert("48 8B XY", Err::<usize, _>(invalid.clone()));
+        cache.insert("48 8B", Ok(0x1000));
+        assert_eq!(cache["48 8B XY"].clone(), Err(invalid));
+        assert_ne!(cache["48 8B XY"], cache["48 8B"]);
+
+        assert_eq_bound(&AobScanError::ValidationFailed { offset: -4 });
+        assert_eq_bound(&WriteMemoryError::CopyOnWrite);
+        assert_eq_bound(&ReadMemoryError::InvalidChainStep(2));
+    }
+}
@@ -0,0 +1,17 @@
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BpError {
+    NullPointer,
+    AlreadySet,
+    FailedToRead,
+    FailedToWrite,
+    FailedToInstallHandler,
+}
+
+impl std::fmt::Display for BpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for BpError {}
\ No newline at end of file
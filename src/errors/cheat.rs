@@ -0,0 +1,17 @@
+
+use crate::errors::{AobScanError, WriteMemoryError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheatError {
+    Scan { patch: String, error: AobScanError },
+    Write { patch: String, error: WriteMemoryError },
+    Restore(WriteMemoryError),
+}
+
+impl std::fmt::Display for CheatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for CheatError {}
\ No newline at end of file
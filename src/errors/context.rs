@@ -0,0 +1,15 @@
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContextError {
+    NullHandle,
+    FailedToGetContext,
+    FailedToSetContext,
+}
+
+impl std::fmt::Display for ContextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for ContextError {}
\ No newline at end of file
@@ -0,0 +1,18 @@
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetourError {
+    NullPointer,
+    InvalidInstruction,
+    FailedToAllocate,
+    FailedToWrite,
+    FailedToFree,
+    TargetTooSmall,
+}
+
+impl std::fmt::Display for DetourError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for DetourError {}
\ No newline at end of file
@@ -1,9 +1,37 @@
+pub mod alloc;
+pub mod module;
 pub mod read_memory;
 pub mod write_memory;
+#[cfg(feature = "runtime")]
+pub mod breakpoint;
+#[cfg(feature = "runtime")]
+pub mod context;
+#[cfg(feature = "runtime")]
+pub mod veh;
 #[cfg(feature = "aob")]
 pub mod aob_scan;
+#[cfg(feature = "aob")]
+pub mod cheat;
+#[cfg(feature = "advanced-write")]
+pub mod detour;
+#[cfg(feature = "pe")]
+pub mod pe_parse;
 
+pub use alloc::AllocError;
+pub use module::ModuleError;
 pub use read_memory::ReadMemoryError;
 pub use write_memory::WriteMemoryError;
 #[cfg(feature = "runtime")]
-pub use aob_scan::AobScanError;
\ No newline at end of file
+pub use breakpoint::BpError;
+#[cfg(feature = "runtime")]
+pub use context::ContextError;
+#[cfg(feature = "runtime")]
+pub use veh::VehError;
+#[cfg(feature = "aob")]
+pub use aob_scan::AobScanError;
+#[cfg(feature = "aob")]
+pub use cheat::CheatError;
+#[cfg(feature = "advanced-write")]
+pub use detour::DetourError;
+#[cfg(feature = "pe")]
+pub use pe_parse::PeParseError;
\ No newline at end of file
@@ -0,0 +1,19 @@
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModuleError {
+    EmptyName,
+    InteriorNull,
+    NotLoaded(u32),
+    OutOfBounds,
+}
+
+impl std::fmt::Display for ModuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ModuleError::NotLoaded(code) => write!(f, "NotLoaded: {}", crate::utils::error_message(*code)),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+impl std::error::Error for ModuleError {}
\ No newline at end of file
@@ -0,0 +1,37 @@
+use crate::errors::ModuleError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeParseError {
+    NullPointer,
+    InvalidDosHeader,
+    InvalidNtHeader,
+    UnsupportedFormat,
+    BitnessMismatch,
+    FailedToRead,
+    ModuleNotFound,
+    InvalidModuleName,
+    OutOfBounds,
+    SectionNotFound,
+    SectionOutOfBounds,
+    UnsupportedRelocation,
+    FailedToLoadLibrary,
+    FailedToResolveImport,
+}
+
+impl std::fmt::Display for PeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for PeParseError {}
+
+impl From<ModuleError> for PeParseError {
+    fn from(error: ModuleError) -> Self {
+        match error {
+            ModuleError::EmptyName | ModuleError::InteriorNull => PeParseError::InvalidModuleName,
+            ModuleError::NotLoaded(_) => PeParseError::ModuleNotFound,
+            ModuleError::OutOfBounds => PeParseError::OutOfBounds,
+        }
+    }
+}
\ No newline at end of file
@@ -1,11 +1,16 @@
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ReadMemoryError {
     NullPointer,
     InvalidAlignment,
     FailedToChangeProtection,
     FailedToRestoreProtection,
-    InvalidAccess
+    InvalidAccess,
+    RegionFree,
+    RegionReserved,
+    InvalidBit,
+    InvalidDiscriminant(u32),
+    InvalidChainStep(usize),
 }
 
 impl std::fmt::Display for ReadMemoryError {
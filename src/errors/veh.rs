@@ -0,0 +1,14 @@
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VehError {
+    FailedToInstallHandler,
+    NotRegistered,
+}
+
+impl std::fmt::Display for VehError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for VehError {}
\ No newline at end of file
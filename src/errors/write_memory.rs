@@ -1,11 +1,18 @@
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum WriteMemoryError {
     NullPointer,
     InvalidAlignment,
     InvalidAccess,
     FailedToChangeProtection,
-    FailedToRestoreProtection
+    FailedToRestoreProtection,
+    FailedToFlushInstructionCache,
+    CopyOnWrite,
+    VerificationFailed,
+    InvalidBit,
+    InvalidInstruction,
+    InvalidInstructionCount(usize),
+    UnsupportedType,
 }
 
 impl std::fmt::Display for WriteMemoryError {
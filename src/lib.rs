@@ -1,9 +1,16 @@
+#[cfg(feature = "aob")]
+pub mod cheat;
 pub mod errors;
 pub mod macros;
 pub mod ops;
 #[cfg(feature = "aob")]
 pub mod pattern;
+#[cfg(feature = "pe")]
+pub mod pe;
+pub mod prelude;
 #[cfg(feature = "runtime")]
 pub mod runtime;
+#[cfg(feature = "scan")]
+pub mod scan;
 pub mod types;
 pub mod utils;
\ No newline at end of file
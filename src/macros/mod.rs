@@ -1,2 +1,4 @@
 pub mod match_number;
+#[cfg(feature = "aob")]
+pub mod signature;
 pub mod wide_string;
\ No newline at end of file
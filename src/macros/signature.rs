@@ -0,0 +1,56 @@
+/// Declares a signature that is scanned on first use and cached afterwards.
+///
+/// `signature!(name = "pattern")` generates a function `name()` returning
+/// `Result<*mut u8, AobScanError>`, backed by a [`LazySignature`](crate::pattern::LazySignature).
+/// The function returns the address of the first match, which isn't checked to be unique. Add `module = "name.dll"` to scan that module instead of the executable.
+///
+/// A module of the same name holds the backing `SIGNATURE` static and a `reset()` function that
+/// forgets the cached address.
+///
+/// # Safety
+/// The generated function is `unsafe`, for the same reasons as
+/// [`LazySignature::resolve`](crate::pattern::LazySignature::resolve).
+///
+/// # Example
+/// ```rust
+/// use verity_memory::signature;
+///
+/// signature!(pub ret_addr = "C3");
+/// signature!(kernel32_ret = "C3", module = "kernel32.dll");
+///
+/// let address = unsafe { ret_addr() }.unwrap();
+/// assert_eq!(unsafe { ret_addr() }, Ok(address));
+/// assert_eq!(ret_addr::SIGNATURE.cached(), Some(address));
+///
+/// ret_addr::reset();
+/// assert_eq!(ret_addr::SIGNATURE.cached(), None);
+/// assert!(unsafe { kernel32_ret() }.is_ok());
+/// ```
+#[macro_export]
+macro_rules! signature {
+    (@declare $(#[$meta:meta])* $vis:vis $name:ident, $pattern:literal, $module:expr) => {
+        $(#[$meta])*
+        $vis unsafe fn $name() -> Result<*mut u8, $crate::errors::AobScanError> {
+            $name::SIGNATURE.resolve()
+        }
+
+        #[allow(dead_code)]
+        $vis mod $name {
+            pub static SIGNATURE: $crate::pattern::LazySignature =
+                $crate::pattern::LazySignature::new($pattern, $module);
+
+            /// Forgets the cached address, so the next call scans again.
+            pub fn reset() {
+                SIGNATURE.reset();
+            }
+        }
+    };
+
+    ($(#[$meta:meta])* $vis:vis $name:ident = $pattern:literal $(,)?) => {
+        $crate::signature!(@declare $(#[$meta])* $vis $name, $pattern, None);
+    };
+
+    ($(#[$meta:meta])* $vis:vis $name:ident = $pattern:literal, module = $module:literal $(,)?) => {
+        $crate::signature!(@declare $(#[$meta])* $vis $name, $pattern, Some($module));
+    };
+}
\ No newline at end of file
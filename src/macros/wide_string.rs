@@ -1,3 +1,16 @@
+/// Encodes a string as a NUL-terminated UTF-16 buffer for wide Win32 APIs.
+///
+/// Returns an owned `Vec<u16>` rather than a pointer, so the buffer stays alive for as long as the
+/// caller keeps it; pass `.as_ptr()` of a binding that outlives the call.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::w;
+///
+/// let name = w!("kernel{}.dll", 32);
+/// assert_eq!(name.last(), Some(&0));
+/// assert_eq!(name.len(), "kernel32.dll".len() + 1);
+/// ```
 #[macro_export]
 macro_rules! w {
     ($text:literal $(, $args:expr)*) => {{
@@ -6,7 +19,7 @@ macro_rules! w {
         let wide_string: Vec<u16> = std::os::windows::ffi::OsStrExt::encode_wide(os_str)
             .chain(Some(0))
             .collect();
-        wide_string.as_ptr()
+        wide_string
     }};
     
     ($text:expr) => {{
@@ -15,6 +28,6 @@ macro_rules! w {
         let wide_string: Vec<u16> = std::os::windows::ffi::OsStrExt::encode_wide(os_str)
             .chain(Some(0))
             .collect();
-        wide_string.as_ptr()
+        wide_string
     }};
 }
\ No newline at end of file
@@ -0,0 +1,329 @@
+use std::mem::{size_of, MaybeUninit};
+use std::sync::OnceLock;
+
+use winapi::shared::minwindef::{BOOL, FALSE, LPCVOID, LPVOID};
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::memoryapi::{ReadProcessMemory, VirtualProtectEx, WriteProcessMemory};
+use winapi::um::processthreadsapi::{GetCurrentProcess, OpenProcess};
+use winapi::um::wow64apiset::IsWow64Process;
+use winapi::um::winnt::{
+    HANDLE, PAGE_EXECUTE_READWRITE, PROCESS_QUERY_INFORMATION, PROCESS_VM_OPERATION,
+    PROCESS_VM_READ, PROCESS_VM_WRITE,
+};
+
+use crate::errors::{ReadMemoryError, WriteMemoryError};
+
+/// Raw byte access to the memory of a process.
+///
+/// Implemented by [`CurrentProcess`] for the current process and by [`RemoteProcess`] for a process
+/// opened by handle, so the generic [`read_memory`] and [`write_memory`] helpers work identically
+/// on both.
+pub trait MemoryAccess {
+    /// Reads `buf.len()` bytes starting at `address` into `buf`.
+    ///
+    /// # Safety
+    /// The range must be mapped in the target process.
+    unsafe fn read_bytes(&self, address: usize, buf: &mut [u8]) -> Result<(), ReadMemoryError>;
+
+    /// Writes `bytes` starting at `address`.
+    ///
+    /// # Safety
+    /// The range must be mapped in the target process and safe to modify.
+    unsafe fn write_bytes(&self, address: usize, bytes: &[u8]) -> Result<(), WriteMemoryError>;
+}
+
+/// The current process, accessed directly through pointers.
+///
+/// Reads and writes go through [`read_into`](super::read::read_into) and
+/// [`write_bytes`](super::write::write_bytes), so using it through [`MemoryAccess`] costs nothing
+/// over calling those directly.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CurrentProcess;
+
+/// The former name of [`CurrentProcess`].
+pub type LocalProcess = CurrentProcess;
+
+/// The pseudo-handle returned by `GetCurrentProcess`, stored as an address since `HANDLE` isn't `Sync`.
+static CURRENT_PROCESS_HANDLE: OnceLock<usize> = OnceLock::new();
+
+impl CurrentProcess {
+    /// Returns the pseudo-handle of the current process, for Win32 calls taking a process handle.
+    ///
+    /// `GetCurrentProcess` is only called once; the handle needs no closing.
+    pub fn handle(&self) -> HANDLE {
+        *CURRENT_PROCESS_HANDLE.get_or_init(|| unsafe { GetCurrentProcess() } as usize) as HANDLE
+    }
+}
+
+impl MemoryAccess for CurrentProcess {
+    unsafe fn read_bytes(&self, address: usize, buf: &mut [u8]) -> Result<(), ReadMemoryError> {
+        super::read::read_into(address as *const u8, buf)
+    }
+
+    unsafe fn write_bytes(&self, address: usize, bytes: &[u8]) -> Result<(), WriteMemoryError> {
+        super::write::write_bytes(address as *mut u8, bytes)
+    }
+}
+
+/// A process accessed through a handle with `ReadProcessMemory` / `WriteProcessMemory`.
+pub struct RemoteProcess {
+    handle: HANDLE,
+    owned: bool,
+}
+
+impl RemoteProcess {
+    /// Opens the process with the given id for reading and writing.
+    ///
+    /// The handle is closed when the `RemoteProcess` is dropped.
+    ///
+    /// # Returns
+    /// - `Some(RemoteProcess)` if the process could be opened.
+    /// - `None` if `OpenProcess` failed, e.g. because the process doesn't exist or access was denied.
+    pub fn open(pid: u32) -> Option<RemoteProcess> {
+        let handle = unsafe {
+            OpenProcess(
+                PROCESS_VM_READ | PROCESS_VM_WRITE | PROCESS_VM_OPERATION | PROCESS_QUERY_INFORMATION,
+                FALSE,
+                pid,
+            )
+        };
+
+        if handle.is_null() {
+            None
+        } else {
+            Some(RemoteProcess {
+                handle,
+                owned: true,
+            })
+        }
+    }
+
+    /// Wraps an existing process handle without taking ownership of it.
+    ///
+    /// # Safety
+    /// The handle must stay valid for the lifetime of the `RemoteProcess` and grant
+    /// `PROCESS_VM_READ`, `PROCESS_VM_WRITE` and `PROCESS_VM_OPERATION` access.
+    pub unsafe fn from_handle(handle: HANDLE) -> RemoteProcess {
+        RemoteProcess {
+            handle,
+            owned: false,
+        }
+    }
+
+    /// Returns the underlying process handle.
+    pub fn handle(&self) -> HANDLE {
+        self.handle
+    }
+
+    /// Returns whether the process is 64-bit, which can differ from the current process.
+    ///
+    /// A process running under WoW64 is 32-bit. Any other process is native to the OS, which is
+    /// 64-bit if the current process is 64-bit or itself runs under WoW64.
+    ///
+    /// # Returns
+    /// - `Some(bool)`: Whether the process is 64-bit.
+    /// - `None`: If `IsWow64Process` failed, e.g. because the handle lacks
+    ///   `PROCESS_QUERY_INFORMATION` or `PROCESS_QUERY_LIMITED_INFORMATION` access.
+    pub fn is_64bit(&self) -> Option<bool> {
+        if is_wow64(self.handle)? {
+            return Some(false);
+        }
+
+        if cfg!(target_pointer_width = "64") {
+            Some(true)
+        } else {
+            is_wow64(CurrentProcess.handle())
+        }
+    }
+}
+
+fn is_wow64(handle: HANDLE) -> Option<bool> {
+    let mut wow64: BOOL = FALSE;
+    if unsafe { IsWow64Process(handle, &mut wow64) } == 0 {
+        None
+    } else {
+        Some(wow64 != FALSE)
+    }
+}
+
+impl Drop for RemoteProcess {
+    fn drop(&mut self) {
+        if self.owned {
+            unsafe {
+                CloseHandle(self.handle);
+            }
+        }
+    }
+}
+
+impl MemoryAccess for RemoteProcess {
+    unsafe fn read_bytes(&self, address: usize, buf: &mut [u8]) -> Result<(), ReadMemoryError> {
+        if address == 0 {
+            return Err(ReadMemoryError::NullPointer);
+        }
+
+        let mut read = 0;
+        let res = ReadProcessMemory(
+            self.handle,
+            address as LPCVOID,
+            buf.as_mut_ptr() as LPVOID,
+            buf.len(),
+            &mut read,
+        );
+
+        if res == 0 || read != buf.len() {
+            return Err(ReadMemoryError::InvalidAccess);
+        }
+
+        Ok(())
+    }
+
+    unsafe fn write_bytes(&self, address: usize, bytes: &[u8]) -> Result<(), WriteMemoryError> {
+        if address == 0 {
+            return Err(WriteMemoryError::NullPointer);
+        }
+
+        let mut old_protect = 0;
+        let res = VirtualProtectEx(
+            self.handle,
+            address as LPVOID,
+            bytes.len(),
+            PAGE_EXECUTE_READWRITE,
+            &mut old_protect,
+        );
+        if res == 0 {
+            return Err(WriteMemoryError::FailedToChangeProtection);
+        }
+
+        let mut written = 0;
+        let res_write = WriteProcessMemory(
+            self.handle,
+            address as LPVOID,
+            bytes.as_ptr() as LPCVOID,
+            bytes.len(),
+            &mut written,
+        );
+
+        let res_restore = VirtualProtectEx(
+            self.handle,
+            address as LPVOID,
+            bytes.len(),
+            old_protect,
+            &mut old_protect,
+        );
+
+        if res_write == 0 || written != bytes.len() {
+            return Err(WriteMemoryError::InvalidAccess);
+        }
+
+        if res_restore == 0 {
+            return Err(WriteMemoryError::FailedToRestoreProtection);
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads a value of type `T` at `address` through any [`MemoryAccess`] implementation.
+///
+/// # Safety
+/// This function is `unsafe` because it reads arbitrary memory of the target process and
+/// reinterprets the bytes as `T`.
+///
+/// # Example
+/// ```
+/// use verity_memory::ops::access::{self, CurrentProcess};
+/// let value = 42i32;
+/// let result = unsafe { access::read_memory::<_, i32>(&CurrentProcess, &value as *const i32 as usize) };
+/// assert_eq!(result, Ok(42));
+/// ```
+pub unsafe fn read_memory<A: MemoryAccess + ?Sized, T: Copy>(
+    access: &A,
+    address: usize,
+) -> Result<T, ReadMemoryError> {
+    let mut value = MaybeUninit::<T>::uninit();
+    let buf = std::slice::from_raw_parts_mut(value.as_mut_ptr() as *mut u8, size_of::<T>());
+
+    access.read_bytes(address, buf)?;
+    Ok(value.assume_init())
+}
+
+/// Writes a value of type `T` at `address` through any [`MemoryAccess`] implementation.
+///
+/// # Safety
+/// This function is `unsafe` because it modifies arbitrary memory of the target process.
+///
+/// # Example
+/// ```
+/// use verity_memory::ops::access::{self, CurrentProcess};
+/// let mut value = 42i32;
+/// let result = unsafe { access::write_memory(&CurrentProcess, &mut value as *mut i32 as usize, 7i32) };
+/// assert!(result.is_ok());
+/// assert_eq!(value, 7);
+/// ```
+pub unsafe fn write_memory<A: MemoryAccess + ?Sized, T: Copy>(
+    access: &A,
+    address: usize,
+    value: T,
+) -> Result<(), WriteMemoryError> {
+    let bytes = std::slice::from_raw_parts(&value as *const T as *const u8, size_of::<T>());
+    access.write_bytes(address, bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise<A: MemoryAccess>(access: &A) {
+        let mut value: u64 = 0x1122_3344_5566_7788;
+        let address = &mut value as *mut u64 as usize;
+
+        unsafe {
+            assert_eq!(read_memory::<_, u64>(access, address), Ok(0x1122_3344_5566_7788));
+            assert!(write_memory(access, address, 0xDEAD_BEEF_u64).is_ok());
+            assert_eq!(read_memory::<_, u64>(access, address), Ok(0xDEAD_BEEF));
+        }
+    }
+
+    #[test]
+    fn test_current_process_access() {
+        exercise(&CurrentProcess);
+    }
+
+    #[test]
+    fn test_current_process_matches_read_memory() {
+        let values = [0x1234_5678u32, 0xDEAD_BEEF, 0];
+        for value in &values {
+            let address = value as *const u32;
+            unsafe {
+                assert_eq!(read_memory::<_, u32>(&CurrentProcess, address as usize), crate::ops::read::read_memory(address));
+            }
+        }
+
+        assert_eq!(
+            unsafe { read_memory::<_, u32>(&CurrentProcess, 0) },
+            unsafe { crate::ops::read::read_memory(std::ptr::null::<u32>()) }
+        );
+        assert_eq!(CurrentProcess.handle(), unsafe { GetCurrentProcess() });
+    }
+
+    #[test]
+    fn test_remote_process_access_current() {
+        let process = unsafe { RemoteProcess::from_handle(CurrentProcess.handle()) };
+        exercise(&process);
+    }
+
+    #[test]
+    fn test_remote_process_open_current() {
+        let process = RemoteProcess::open(std::process::id()).expect("Failed to open current process");
+        exercise(&process);
+    }
+
+    #[test]
+    fn test_access_null_pointer() {
+        let process = unsafe { RemoteProcess::from_handle(CurrentProcess.handle()) };
+
+        let result = unsafe { read_memory::<_, u32>(&process, 0) };
+        assert_eq!(result, Err(ReadMemoryError::NullPointer));
+    }
+}
@@ -0,0 +1,155 @@
+use winapi::shared::minwindef::LPVOID;
+use winapi::um::memoryapi::{VirtualAlloc, VirtualFree};
+use winapi::um::processthreadsapi::FlushInstructionCache;
+use winapi::um::winnt::{MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_EXECUTE_READWRITE};
+
+use crate::errors::AllocError;
+
+use super::access::CurrentProcess;
+
+/// A block of readable, writable and executable memory, released when dropped.
+#[derive(Debug)]
+pub struct ExecutableMemory {
+    ptr: *mut u8,
+    len: usize,
+}
+
+impl ExecutableMemory {
+    /// Allocates `len` bytes of `PAGE_EXECUTE_READWRITE` memory, rounded up to whole pages.
+    ///
+    /// # Errors
+    /// - `AllocError::ZeroSize`: If `len` is 0.
+    /// - `AllocError::FailedToAllocate`: If `VirtualAlloc` failed.
+    pub fn new(len: usize) -> Result<ExecutableMemory, AllocError> {
+        if len == 0 {
+            return Err(AllocError::ZeroSize);
+        }
+
+        let ptr = unsafe {
+            VirtualAlloc(std::ptr::null_mut(), len, MEM_COMMIT | MEM_RESERVE, PAGE_EXECUTE_READWRITE)
+        } as *mut u8;
+
+        if ptr.is_null() {
+            Err(AllocError::FailedToAllocate)
+        } else {
+            Ok(ExecutableMemory { ptr, len })
+        }
+    }
+
+    /// Allocates a block holding a copy of `code`, with the instruction cache flushed so the code
+    /// can be executed right away.
+    ///
+    /// # Errors
+    /// - `AllocError::ZeroSize`: If `code` is empty.
+    /// - `AllocError::FailedToAllocate`: If `VirtualAlloc` failed.
+    /// - `AllocError::FailedToFlushInstructionCache`: If `FlushInstructionCache` failed.
+    pub fn with_code(code: &[u8]) -> Result<ExecutableMemory, AllocError> {
+        let memory = ExecutableMemory::new(code.len())?;
+
+        unsafe {
+            std::ptr::copy_nonoverlapping(code.as_ptr(), memory.ptr, code.len());
+
+            if FlushInstructionCache(CurrentProcess.handle(), memory.ptr as LPVOID, code.len()) == 0 {
+                return Err(AllocError::FailedToFlushInstructionCache);
+            }
+        }
+
+        Ok(memory)
+    }
+
+    /// The start of the block.
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.ptr
+    }
+
+    /// The requested size of the block.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the block is empty, which is never the case.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Drop for ExecutableMemory {
+    fn drop(&mut self) {
+        unsafe {
+            VirtualFree(self.ptr as LPVOID, 0, MEM_RELEASE);
+        }
+    }
+}
+
+/// Copies `code` into freshly allocated executable memory, calls `call` with its address, and
+/// frees the memory once `call` returns (or panics).
+///
+/// This packages the usual "assemble, copy into RWX memory, flush, call" sequence for runtime
+/// generated stubs. The code is only executed by `call`, which typically transmutes the pointer
+/// into a function pointer: getting the signature and calling convention of that function pointer
+/// right is the responsibility of the caller, as is not keeping the pointer past `call`.
+///
+/// # Errors
+/// - Same as [`ExecutableMemory::with_code`].
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::alloc;
+///
+/// // mov eax, 5; ret
+/// let code = [0xB8, 0x05, 0x00, 0x00, 0x00, 0xC3];
+///
+/// let result = alloc::run_code(&code, |ptr| {
+///     let function: extern "C" fn() -> i32 = unsafe { std::mem::transmute(ptr) };
+///     function()
+/// });
+/// assert_eq!(result, Ok(5));
+/// ```
+pub fn run_code<R>(code: &[u8], call: impl FnOnce(*const u8) -> R) -> Result<R, AllocError> {
+    let memory = ExecutableMemory::with_code(code)?;
+    Ok(call(memory.as_ptr()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::query::is_executable;
+
+    #[test]
+    fn test_run_code() {
+        // mov eax, 5; ret
+        let code = [0xB8, 0x05, 0x00, 0x00, 0x00, 0xC3];
+
+        let result = run_code(&code, |ptr| {
+            assert!(is_executable(ptr));
+            let function: extern "C" fn() -> i32 = unsafe { std::mem::transmute(ptr) };
+            function()
+        });
+
+        assert_eq!(result, Ok(5));
+    }
+
+    #[test]
+    #[cfg(feature = "advanced-write")]
+    fn test_run_code_assembled() {
+        use dynasmrt::{dynasm, DynasmApi};
+
+        let code = crate::ops::asm::assemble(|ops| {
+            dynasm!(ops
+                ; mov eax, 5
+                ; ret
+            );
+        });
+
+        let result = run_code(&code, |ptr| {
+            let function: extern "C" fn() -> i32 = unsafe { std::mem::transmute(ptr) };
+            function()
+        });
+        assert_eq!(result, Ok(5));
+    }
+
+    #[test]
+    fn test_run_code_empty() {
+        assert_eq!(run_code(&[], |_| ()), Err(AllocError::ZeroSize));
+    }
+}
@@ -1,139 +1,542 @@
-use capstone::arch::x86::X86Insn;
-use capstone::arch::BuildsCapstone;
-use capstone::{Capstone, Insn};
+use capstone::arch::x86::{X86Insn, X86OpMem, X86OperandType, X86Reg};
+use capstone::arch::{ArchOperand, BuildsCapstone, BuildsCapstoneDetail};
+use capstone::{Capstone, Insn, InsnGroupType, RegAccessType, RegId};
 use dynasmrt::dynasm;
 use dynasmrt::DynasmApi;
 
+use crate::errors::WriteMemoryError;
 use crate::macros::match_number::{FloatType, IntegerType, IntegralType};
-use crate::types::Instruction;
+use crate::ops::write::write_bytes;
+use crate::types::{CallConv, DisasmMode, InsnKind, Instruction, InstructionDetail, InstructionGroup, OperandAccess};
 
 #[cfg(target_arch = "x86_64")]
-use dynasmrt::x64::Assembler;
+pub use dynasmrt::x64::Assembler;
 #[cfg(target_arch = "x86")]
-use dynasmrt::x86::Assembler;
+pub use dynasmrt::x86::Assembler;
 
-pub(crate) fn integer_ret(integer_type: IntegerType) -> Vec<u8> {
+/// Assembles a snippet of machine code with `dynasm`.
+///
+/// The closure receives an [`Assembler`] for the architecture of the current process
+/// (`dynasmrt::x64::Assembler` on x64, `dynasmrt::x86::Assembler` on x86), to be used with the
+/// `dynasm!` macro of the `dynasmrt` crate. On x86 the snippet must start with `; .arch x86`,
+/// since `dynasm!` assembles x64 code unless told otherwise.
+///
+/// The code is assembled in a scratch buffer, so it must be position independent: relative jumps
+/// and calls to addresses outside of the snippet will be wrong once the bytes are copied elsewhere.
+/// Call absolute addresses through a register instead, e.g. `mov rax, QWORD target; call rax`.
+///
+/// # Panics
+/// - If the assembler cannot be created or finalized, e.g. because of an undefined label.
+///
+/// # Example
+/// ```rust
+/// use dynasmrt::{dynasm, DynasmApi};
+/// use verity_memory::ops::asm;
+///
+/// let code = asm::assemble(|ops| {
+///     dynasm!(ops
+///         ; xor eax, eax
+///         ; ret
+///     );
+/// });
+/// assert_eq!(code.len(), 3);
+/// assert_eq!(code.last(), Some(&0xC3));
+/// ```
+pub fn assemble(build: impl FnOnce(&mut Assembler)) -> Vec<u8> {
+    let mut assembler = Assembler::new().expect("Failed to create assembler");
+    build(&mut assembler);
+
+    let code = assembler.finalize().expect("Failed to finalize assembler");
+    code.to_vec()
+}
+
+/// Assembles a snippet of machine code with [`assemble`] and writes it to `dest`.
+///
+/// # Safety
+/// This function is unsafe because it overwrites memory, typically executable code. No thread may
+/// be executing the overwritten bytes while they are written.
+///
+/// # Returns
+/// - `Ok(Vec<u8>)`: The written bytes.
+/// - `Err(WriteMemoryError)`: If the bytes could not be written.
+///
+/// # Example
+/// ```rust
+/// use dynasmrt::{dynasm, DynasmApi};
+/// use verity_memory::ops::asm;
+///
+/// let mut buffer = [0x90u8; 8];
+/// let written = unsafe {
+///     asm::write_code(buffer.as_mut_ptr(), |ops| {
+///         dynasm!(ops
+///             ; xor eax, eax
+///             ; ret
+///         );
+///     })
+/// }
+/// .unwrap();
+/// assert_eq!(&buffer[..written.len()], written.as_slice());
+/// ```
+pub unsafe fn write_code(
+    dest: *mut u8,
+    build: impl FnOnce(&mut Assembler),
+) -> Result<Vec<u8>, WriteMemoryError> {
+    let code = assemble(build);
+    write_bytes(dest, &code)?;
+    Ok(code)
+}
+
+pub(crate) fn integer_ret(integer_type: IntegerType, conv: CallConv) -> Vec<u8> {
     let mut assembler = Assembler::new().expect("Failed to create assembler");
 
     match integer_type {
         IntegerType::I32(value) => {
             dynasm!(assembler
                 ; mov eax, *value
-                ; ret
             );
         }
+        #[cfg(target_arch = "x86_64")]
         IntegerType::I64(value) => {
             dynasm!(assembler
                 ; mov rax, QWORD *value
-                ; ret
+            );
+        }
+        #[cfg(target_arch = "x86")]
+        IntegerType::I64(value) => {
+            dynasm!(assembler
+                ; mov eax, *value as i32
+                ; mov edx, (*value >> 32) as i32
             );
         }
     }
 
-    let code = assembler.finalize().expect("Failed to finalize assembler");
-
-    let code_slice = unsafe { std::slice::from_raw_parts(code.as_ptr(), code.len()) };
-    code_slice.to_vec()
+    finalize(assembler, conv)
 }
 
-pub(crate) fn float_ret(float_type: FloatType) -> Vec<u8> {
+pub(crate) fn float_ret(float_type: FloatType, conv: CallConv) -> Vec<u8> {
     let mut assembler = Assembler::new().expect("Failed to create assembler");
 
+    #[cfg(target_arch = "x86_64")]
     match float_type {
         FloatType::F32(value) => {
             dynasm!(assembler
-                ; mov eax, DWORD value.to_bits().try_into().unwrap()
+                ; mov eax, DWORD value.to_bits() as i32
                 ; movd xmm0, eax
-                ; ret
             );
         }
         FloatType::F64(value) => {
             dynasm!(assembler
-                ; mov rax, QWORD value.to_bits().try_into().unwrap()
+                ; mov rax, QWORD value.to_bits() as i64
                 ; movq xmm0, rax
-                ; ret
             );
         }
     }
 
-    let code = assembler.finalize().expect("Failed to finalize assembler");
+    #[cfg(target_arch = "x86")]
+    match float_type {
+        FloatType::F32(value) => {
+            dynasm!(assembler
+                ; push value.to_bits() as i32
+                ; fld DWORD [esp]
+                ; add esp, 4
+            );
+        }
+        FloatType::F64(value) => {
+            let bits = value.to_bits();
+            dynasm!(assembler
+                ; push (bits >> 32) as i32
+                ; push bits as i32
+                ; fld QWORD [esp]
+                ; add esp, 8
+            );
+        }
+    }
 
-    let code_slice = unsafe { std::slice::from_raw_parts(code.as_ptr(), code.len()) };
-    code_slice.to_vec()
+    finalize(assembler, conv)
 }
 
-pub(crate) fn integral_ret(integral_type: IntegralType) -> Vec<u8> {
+pub(crate) fn integral_ret(integral_type: IntegralType, conv: CallConv) -> Vec<u8> {
     let mut assembler = Assembler::new().expect("Failed to create assembler");
 
     match integral_type {
         IntegralType::U8(value) => {
             dynasm!(assembler
-                ; mov eax, (*value).try_into().unwrap()
-                ; ret
+                ; mov eax, *value as i32
             );
         }
         IntegralType::U16(value) => {
             dynasm!(assembler
-                ; mov ax, (*value).try_into().unwrap()
-                ; ret
+                ; mov ax, *value as i16
             );
         }
         IntegralType::U32(value) => {
             dynasm!(assembler
-                ; mov eax, (*value).try_into().unwrap()
-                ; ret
+                ; mov eax, *value as i32
             );
         }
+        #[cfg(target_arch = "x86_64")]
         IntegralType::U64(value) => {
             dynasm!(assembler
-                ; mov rax, (*value).try_into().unwrap()
-                ; ret
+                ; mov rax, QWORD *value as i64
+            );
+        }
+        #[cfg(target_arch = "x86")]
+        IntegralType::U64(value) => {
+            dynasm!(assembler
+                ; mov eax, *value as i32
+                ; mov edx, (*value >> 32) as i32
             );
         }
     }
 
+    finalize(assembler, conv)
+}
+
+fn finalize(assembler: Assembler, conv: CallConv) -> Vec<u8> {
     let code = assembler.finalize().expect("Failed to finalize assembler");
 
     let code_slice = unsafe { std::slice::from_raw_parts(code.as_ptr(), code.len()) };
-    code_slice.to_vec()
+    let mut bytes = code_slice.to_vec();
+    bytes.extend(conv.epilogue());
+    bytes
 }
 
-pub(crate) fn get_instruction(memory: *mut u8, length: usize) -> Option<Instruction> {
-    let cs = Capstone::new()
+fn build_capstone(detail: bool) -> Capstone {
+    build_capstone_for(detail, DisasmMode::host())
+}
+
+fn build_capstone_for(detail: bool, mode: DisasmMode) -> Capstone {
+    Capstone::new()
         .x86()
-        .mode(if cfg!(target_arch = "x86_64") {
-            capstone::arch::x86::ArchMode::Mode64
-        } else {
-            capstone::arch::x86::ArchMode::Mode32
+        .mode(match mode {
+            DisasmMode::X64 => capstone::arch::x86::ArchMode::Mode64,
+            DisasmMode::X86 => capstone::arch::x86::ArchMode::Mode32,
         })
+        .detail(detail)
         .build()
-        .unwrap();
+        .unwrap()
+}
 
+pub(crate) fn get_instruction(memory: *mut u8, length: usize) -> Option<Instruction> {
+    unsafe { get_instruction_mode(memory, length, DisasmMode::host()) }
+}
+
+/// Decodes the instruction at `memory` as code of the given architecture.
+///
+/// [`get_instruction`], used by the patching functions, always decodes for the architecture of
+/// the current process. Bytes read from a process or file of the other bitness must be decoded
+/// with their own mode, see [`DisasmMode`]. With the `insn-cache` feature, decoded instructions
+/// are cached per address and mode, so the same bytes decoded for the other mode are never served
+/// from the cache.
+///
+/// # Safety
+/// This function is `unsafe` because it reads raw memory.
+/// - The caller must ensure that `length` bytes starting at `memory` are readable.
+///
+/// # Parameters
+/// - `memory`: The address of the instruction.
+/// - `length`: The number of bytes available to decode from, 16 being enough for any instruction.
+/// - `mode`: The architecture to decode for.
+///
+/// # Returns
+/// - `Some(Instruction)` with the decoded instruction.
+/// - `None` if `memory` is null or the bytes are not a valid instruction.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::asm::get_instruction_mode;
+/// use verity_memory::types::DisasmMode;
+///
+/// // mov rax, [rip + 0x10] on x64; dec eax on x86
+/// let mut code = [0x48, 0x8B, 0x05, 0x10, 0x00, 0x00, 0x00];
+///
+/// let x64 = unsafe { get_instruction_mode(code.as_mut_ptr(), code.len(), DisasmMode::X64) }.unwrap();
+/// let x86 = unsafe { get_instruction_mode(code.as_mut_ptr(), code.len(), DisasmMode::X86) }.unwrap();
+/// assert_eq!((x64.size, x86.size), (7, 1));
+/// ```
+pub unsafe fn get_instruction_mode(memory: *mut u8, length: usize, mode: DisasmMode) -> Option<Instruction> {
     if memory.is_null() {
         return None;
     }
 
-    let memory_slice: &[u8] = unsafe { std::slice::from_raw_parts(memory, length) };
+    let memory_slice = std::slice::from_raw_parts(memory, length);
+
+    #[cfg(feature = "insn-cache")]
+    let hash = {
+        let hash = insn_cache::hash_bytes(memory_slice);
+        if let Some((bytes, kind)) = insn_cache::get(memory as usize, mode, hash) {
+            return Some(Instruction::with_kind(memory, bytes, kind));
+        }
+        hash
+    };
+
+    let instruction = decode_instruction(memory, memory_slice, mode)?;
+
+    #[cfg(feature = "insn-cache")]
+    insn_cache::insert(memory as usize, mode, hash, instruction.bytes.clone(), instruction.kind);
+
+    Some(instruction)
+}
 
-    let instructions = cs.disasm_all(memory_slice, 0x0).unwrap();
+fn decode_instruction(memory: *mut u8, memory_slice: &[u8], mode: DisasmMode) -> Option<Instruction> {
+    let cs = build_capstone_for(false, mode);
+    let instructions = cs.disasm_count(memory_slice, 0x0, 1).ok()?;
 
-    instructions.get(0).map(|insn: &Insn| {
+    instructions.iter().next().map(|insn| {
         let bytes = insn.bytes().to_vec();
-        Instruction::new(memory, bytes)
+        let kind = insn_kind(&insn);
+        Instruction::with_kind(memory, bytes, kind)
     })
 }
 
-pub(crate) fn _get_function(memory: *mut u8) -> Option<Vec<Instruction>> {
+/// Decodes the first instruction of `bytes`, returning its bytes and kind.
+pub(crate) fn decode_first(bytes: &[u8]) -> Option<(Vec<u8>, InsnKind)> {
+    let cs = build_capstone(false);
+    let instructions = cs.disasm_count(bytes, 0x0, 1).ok()?;
+    let insn = instructions.iter().next()?;
+    Some((insn.bytes().to_vec(), insn_kind(&insn)))
+}
 
-    let cs = Capstone::new()
-        .x86()
-        .mode(if cfg!(target_arch = "x86_64") {
-            capstone::arch::x86::ArchMode::Mode64
-        } else {
-            capstone::arch::x86::ArchMode::Mode32
+/// Classifies the control flow of a decoded instruction from its id, which is available without
+/// detail mode.
+fn insn_kind(insn: &Insn) -> InsnKind {
+    const RETURNS: &[X86Insn] = &[
+        X86Insn::X86_INS_RET,
+        X86Insn::X86_INS_RETF,
+        X86Insn::X86_INS_RETFQ,
+        X86Insn::X86_INS_IRET,
+        X86Insn::X86_INS_IRETD,
+        X86Insn::X86_INS_IRETQ,
+    ];
+    const CALLS: &[X86Insn] = &[X86Insn::X86_INS_CALL, X86Insn::X86_INS_LCALL];
+    const BRANCHES: &[X86Insn] = &[
+        X86Insn::X86_INS_JMP,
+        X86Insn::X86_INS_LJMP,
+        X86Insn::X86_INS_JA,
+        X86Insn::X86_INS_JAE,
+        X86Insn::X86_INS_JB,
+        X86Insn::X86_INS_JBE,
+        X86Insn::X86_INS_JCXZ,
+        X86Insn::X86_INS_JECXZ,
+        X86Insn::X86_INS_JRCXZ,
+        X86Insn::X86_INS_JE,
+        X86Insn::X86_INS_JNE,
+        X86Insn::X86_INS_JG,
+        X86Insn::X86_INS_JGE,
+        X86Insn::X86_INS_JL,
+        X86Insn::X86_INS_JLE,
+        X86Insn::X86_INS_JO,
+        X86Insn::X86_INS_JNO,
+        X86Insn::X86_INS_JP,
+        X86Insn::X86_INS_JNP,
+        X86Insn::X86_INS_JS,
+        X86Insn::X86_INS_JNS,
+        X86Insn::X86_INS_LOOP,
+        X86Insn::X86_INS_LOOPE,
+        X86Insn::X86_INS_LOOPNE,
+    ];
+
+    let id = insn.id().0;
+    let contains = |ids: &[X86Insn]| ids.iter().any(|&candidate| candidate as u32 == id);
+
+    if contains(RETURNS) {
+        InsnKind::Ret
+    } else if contains(CALLS) {
+        InsnKind::Call
+    } else if contains(BRANCHES) {
+        InsnKind::Branch
+    } else {
+        InsnKind::Other
+    }
+}
+
+/// Disassembles `count` instructions starting at `memory`, annotated with decoder metadata.
+///
+/// Unlike the plain decoding used by the patching functions, every returned [`Instruction`] carries
+/// an [`InstructionDetail`] with the registers it reads and writes, its groups (branch, call, ...),
+/// the access of each operand and, for relative branches, the absolute target.
+///
+/// # Safety
+/// This function is `unsafe` because it reads raw memory.
+/// - The caller must ensure that up to 16 bytes past each decoded instruction are readable.
+///
+/// # Parameters
+/// - `memory`: The address of the first instruction.
+/// - `count`: The maximum number of instructions to decode.
+///
+/// # Returns
+/// - `Some(Vec<Instruction>)` with up to `count` instructions; decoding stops early at bytes that
+///   are not a valid instruction.
+/// - `None` if `memory` is null or no instruction could be decoded.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::asm::disassemble_detailed;
+///
+/// // call +0; ret
+/// let mut code = [0xE8, 0x00, 0x00, 0x00, 0x00, 0xC3, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC];
+/// let instructions = unsafe { disassemble_detailed(code.as_mut_ptr(), 2) }.unwrap();
+///
+/// let call = instructions[0].detail.as_ref().unwrap();
+/// assert!(call.is_call());
+/// assert_eq!(call.branch_target, Some(code.as_ptr() as usize + 5));
+/// ```
+pub unsafe fn disassemble_detailed(memory: *mut u8, count: usize) -> Option<Vec<Instruction>> {
+    if memory.is_null() {
+        return None;
+    }
+
+    let cs = build_capstone(true);
+    let mut instructions = Vec::with_capacity(count);
+    let mut current = memory;
+
+    while instructions.len() < count {
+        let memory_slice: &[u8] = std::slice::from_raw_parts(current, 16);
+        let insns = match cs.disasm_count(memory_slice, current as u64, 1) {
+            Ok(insns) => insns,
+            Err(_) => break,
+        };
+        let Some(insn) = insns.iter().next() else {
+            break;
+        };
+
+        let mut instruction = Instruction::with_kind(current, insn.bytes().to_vec(), insn_kind(&insn));
+        instruction.detail = describe(&cs, &insn);
+        current = current.add(instruction.size);
+        instructions.push(instruction);
+    }
+
+    if instructions.is_empty() {
+        None
+    } else {
+        Some(instructions)
+    }
+}
+
+fn describe(cs: &Capstone, insn: &Insn) -> Option<InstructionDetail> {
+    let detail = cs.insn_detail(insn).ok()?;
+    let reg_name = |reg: RegId| cs.reg_name(reg).unwrap_or_default();
+
+    let mut regs_read: Vec<String> = detail.regs_read().iter().map(|&reg| reg_name(reg)).collect();
+    let mut regs_written: Vec<String> = detail.regs_write().iter().map(|&reg| reg_name(reg)).collect();
+
+    let groups: Vec<InstructionGroup> = detail
+        .groups()
+        .iter()
+        .filter_map(|group| match group.0 as u32 {
+            g if g == InsnGroupType::CS_GRP_JUMP as u32 => Some(InstructionGroup::Jump),
+            g if g == InsnGroupType::CS_GRP_CALL as u32 => Some(InstructionGroup::Call),
+            g if g == InsnGroupType::CS_GRP_RET as u32 => Some(InstructionGroup::Return),
+            g if g == InsnGroupType::CS_GRP_INT as u32 => Some(InstructionGroup::Interrupt),
+            g if g == InsnGroupType::CS_GRP_IRET as u32 => Some(InstructionGroup::InterruptReturn),
+            g if g == InsnGroupType::CS_GRP_PRIVILEGE as u32 => Some(InstructionGroup::Privilege),
+            g if g == InsnGroupType::CS_GRP_BRANCH_RELATIVE as u32 => Some(InstructionGroup::RelativeBranch),
+            _ => None,
         })
-        .build()
-        .unwrap();
+        .collect();
+    let is_relative_branch = groups.contains(&InstructionGroup::RelativeBranch);
+
+    let mut operands = Vec::new();
+    let mut branch_target = None;
+
+    for operand in detail.arch_detail().operands() {
+        let ArchOperand::X86Operand(op) = operand else {
+            continue;
+        };
+
+        let access = match op.access {
+            Some(RegAccessType::ReadOnly) => OperandAccess { read: true, write: false },
+            Some(RegAccessType::WriteOnly) => OperandAccess { read: false, write: true },
+            Some(RegAccessType::ReadWrite) => OperandAccess { read: true, write: true },
+            None => OperandAccess::default(),
+        };
+
+        match op.op_type {
+            X86OperandType::Reg(reg) => {
+                let name = reg_name(reg);
+                if access.read && !regs_read.contains(&name) {
+                    regs_read.push(name.clone());
+                }
+                if access.write && !regs_written.contains(&name) {
+                    regs_written.push(name);
+                }
+            }
+            X86OperandType::Imm(target) if is_relative_branch => branch_target = Some(target as usize),
+            _ => {}
+        }
+
+        operands.push(access);
+    }
+
+    Some(InstructionDetail {
+        mnemonic: insn.mnemonic().unwrap_or_default().to_string(),
+        op_str: insn.op_str().unwrap_or_default().to_string(),
+        regs_read,
+        regs_written,
+        groups,
+        operands,
+        branch_target,
+    })
+}
+
+/// Clears the cache of decoded instructions used by the `insn-cache` feature.
+///
+/// Entries are invalidated automatically when the bytes at their address change, so this is only
+/// needed to release memory, e.g. after unloading a module whose code was inspected.
+#[cfg(feature = "insn-cache")]
+pub fn clear_instruction_cache() {
+    insn_cache::clear();
+}
+
+/// Memoization of [`get_instruction_mode`], keyed by address and mode and validated against a hash
+/// of the bytes that were decoded, so patched code is never served from the cache.
+#[cfg(feature = "insn-cache")]
+mod insn_cache {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashMap;
+    use std::hash::{Hash, Hasher};
+    use std::sync::{Mutex, OnceLock};
+
+    use crate::types::{DisasmMode, InsnKind};
+
+    type Entry = (u64, Vec<u8>, InsnKind);
+
+    static CACHE: OnceLock<Mutex<HashMap<(usize, DisasmMode), Entry>>> = OnceLock::new();
+
+    fn cache() -> &'static Mutex<HashMap<(usize, DisasmMode), Entry>> {
+        CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+    }
+
+    pub(super) fn hash_bytes(bytes: &[u8]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub(super) fn get(address: usize, mode: DisasmMode, hash: u64) -> Option<(Vec<u8>, InsnKind)> {
+        let cache = cache().lock().ok()?;
+        match cache.get(&(address, mode)) {
+            Some((cached_hash, bytes, kind)) if *cached_hash == hash => Some((bytes.clone(), *kind)),
+            _ => None,
+        }
+    }
+
+    pub(super) fn insert(address: usize, mode: DisasmMode, hash: u64, bytes: Vec<u8>, kind: InsnKind) {
+        if let Ok(mut cache) = cache().lock() {
+            cache.insert((address, mode), (hash, bytes, kind));
+        }
+    }
+
+    pub(super) fn clear() {
+        if let Ok(mut cache) = cache().lock() {
+            cache.clear();
+        }
+    }
+}
+
+pub(crate) fn _get_function(memory: *mut u8) -> Option<Vec<Instruction>> {
+
+    let cs = build_capstone(false);
 
     if memory.is_null() {
         return None;
@@ -161,16 +564,15 @@ pub(crate) fn _get_function(memory: *mut u8) -> Option<Vec<Instruction>> {
         for insn in insns.iter() {
 
             let bytes = insn.bytes().to_vec();
-            let instruction = Instruction::new(insn.address() as *mut u8, bytes);
+            let kind = insn_kind(&insn);
+            let instruction = Instruction::with_kind(insn.address() as *mut u8, bytes, kind);
             instructions.push(instruction);
 
             current_address += insn.bytes().len();
 
             let insn_id = insn.id().0;
 
-            if insn_id == X86Insn::X86_INS_RET as u32
-                || insn_id == X86Insn::X86_INS_RETF as u32
-                || insn_id == X86Insn::X86_INS_RETFQ as u32
+            if kind == InsnKind::Ret
                 || insn_id == X86Insn::X86_INS_JMP as u32
                 || insn_id == X86Insn::X86_INS_LJMP as u32
             {
@@ -184,4 +586,596 @@ pub(crate) fn _get_function(memory: *mut u8) -> Option<Vec<Instruction>> {
     } else {
         None
     }
+}
+
+/// Decodes the smallest run of whole instructions starting at `ptr` that covers at least
+/// `min_bytes` bytes.
+///
+/// This is the decoding step of anything that overwrites the start of a function with a jump: the
+/// overwritten instructions must be copied whole, so the patch usually covers a few more bytes than
+/// the jump itself.
+///
+/// # Safety
+/// This function is `unsafe` because it reads raw memory.
+/// - The caller must ensure that up to 16 bytes past each decoded instruction are readable.
+///
+/// # Parameters
+/// - `ptr`: The address of the first instruction.
+/// - `min_bytes`: The number of bytes the instructions must cover.
+///
+/// # Returns
+/// - `Some((Vec<Instruction>, usize))` with the instructions and the number of bytes they actually
+///   span, which is at least `min_bytes`.
+/// - `None` if `ptr` is null or an instruction within the first `min_bytes` bytes could not be
+///   decoded.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::asm::instructions_covering;
+///
+/// // push rbp; mov rbp, rsp; sub rsp, 0x20
+/// let code = [0x55, 0x48, 0x89, 0xE5, 0x48, 0x83, 0xEC, 0x20, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC];
+/// let (instructions, span) = unsafe { instructions_covering(code.as_ptr(), 5) }.unwrap();
+/// assert_eq!(instructions.len(), 3);
+/// assert_eq!(span, 8);
+/// ```
+pub unsafe fn instructions_covering(ptr: *const u8, min_bytes: usize) -> Option<(Vec<Instruction>, usize)> {
+    if ptr.is_null() {
+        return None;
+    }
+
+    let mut instructions = Vec::new();
+    let mut span = 0;
+
+    while span < min_bytes {
+        let instruction = get_instruction(ptr.add(span) as *mut u8, 16)?;
+        span += instruction.size;
+        instructions.push(instruction);
+    }
+
+    Some((instructions, span))
+}
+
+pub(crate) fn steal_instructions(memory: *mut u8, min_bytes: usize) -> Option<Vec<Instruction>> {
+    unsafe { instructions_covering(memory, min_bytes) }.map(|(instructions, _)| instructions)
+}
+
+/// Whether execution never falls through to the next instruction: returns, unconditional jumps
+/// and `int3` padding.
+pub(crate) fn is_terminator(instruction: &Instruction) -> bool {
+    if instruction.kind == InsnKind::Ret {
+        return true;
+    }
+
+    let bytes = &instruction.bytes;
+    let opcode_at = bytes
+        .iter()
+        .position(|&byte| !is_prefix(byte))
+        .unwrap_or(bytes.len());
+
+    match bytes.get(opcode_at) {
+        Some(0xC3 | 0xC2 | 0xCB | 0xCA | 0xCC | 0xE9 | 0xEB) => true,
+        Some(0xFF) => bytes
+            .get(opcode_at + 1)
+            .is_some_and(|modrm| matches!((modrm >> 3) & 0x7, 4 | 5)),
+        _ => false,
+    }
+}
+
+fn is_prefix(byte: u8) -> bool {
+    matches!(byte, 0x66 | 0x67 | 0xF0 | 0xF2 | 0xF3 | 0x26 | 0x2E | 0x36 | 0x3E | 0x64 | 0x65)
+        || (cfg!(target_arch = "x86_64") && (0x40..=0x4F).contains(&byte))
+}
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn jump(from: usize, to: usize) -> Vec<u8> {
+    let rel = (to as i64).wrapping_sub(from as i64 + 5);
+    match i32::try_from(rel) {
+        Ok(rel) => {
+            let mut bytes = vec![0xE9];
+            bytes.extend_from_slice(&rel.to_le_bytes());
+            bytes
+        }
+        Err(_) => {
+            // jmp qword ptr [rip + 0]; dq to
+            let mut bytes = vec![0xFF, 0x25, 0x00, 0x00, 0x00, 0x00];
+            bytes.extend_from_slice(&(to as u64).to_le_bytes());
+            bytes
+        }
+    }
+}
+
+#[cfg(target_arch = "x86")]
+pub(crate) fn jump(from: usize, to: usize) -> Vec<u8> {
+    let rel = to.wrapping_sub(from.wrapping_add(5)) as i32;
+    let mut bytes = vec![0xE9];
+    bytes.extend_from_slice(&rel.to_le_bytes());
+    bytes
+}
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn call(from: usize, to: usize) -> Vec<u8> {
+    let rel = (to as i64).wrapping_sub(from as i64 + 5);
+    match i32::try_from(rel) {
+        Ok(rel) => {
+            let mut bytes = vec![0xE8];
+            bytes.extend_from_slice(&rel.to_le_bytes());
+            bytes
+        }
+        Err(_) => {
+            // call qword ptr [rip + 2]; jmp +8; dq to
+            let mut bytes = vec![0xFF, 0x15, 0x02, 0x00, 0x00, 0x00, 0xEB, 0x08];
+            bytes.extend_from_slice(&(to as u64).to_le_bytes());
+            bytes
+        }
+    }
+}
+
+#[cfg(target_arch = "x86")]
+pub(crate) fn call(from: usize, to: usize) -> Vec<u8> {
+    let rel = to.wrapping_sub(from.wrapping_add(5)) as i32;
+    let mut bytes = vec![0xE8];
+    bytes.extend_from_slice(&rel.to_le_bytes());
+    bytes
+}
+
+/// Re-encodes an instruction so it behaves identically when executed from `new_address`.
+///
+/// Relative `jmp`, `call` and `jcc` instructions are rewritten against their absolute target, and
+/// the displacement of RIP-relative memory operands is rewritten to address the same memory from
+/// `new_address`. Everything else is copied verbatim.
+///
+/// Returns `None` if the instruction can't be relocated: `loop`/`jcxz` (`E0`-`E3`), which only
+/// have a rel8 form, and RIP-relative operands whose memory is more than 2 GiB away from
+/// `new_address`.
+pub(crate) fn relocate(instruction: &Instruction, new_address: usize) -> Option<Vec<u8>> {
+    let old_end = instruction.address as usize + instruction.size;
+    let rel8 = |rel: u8| old_end.wrapping_add_signed(rel as i8 as isize);
+    let rel32 = |rel: &[u8]| {
+        old_end.wrapping_add_signed(i32::from_le_bytes([rel[0], rel[1], rel[2], rel[3]]) as isize)
+    };
+
+    let bytes = instruction.bytes.as_slice();
+    let opcode_at = bytes.iter().position(|&byte| !is_prefix(byte)).unwrap_or(bytes.len());
+    if matches!(bytes.get(opcode_at), Some(0xE0..=0xE3)) {
+        return None;
+    }
+
+    match bytes {
+        [0xEB, rel] => Some(jump(new_address, rel8(*rel))),
+        [0xE9, rel @ ..] if rel.len() == 4 => Some(jump(new_address, rel32(rel))),
+        [0xE8, rel @ ..] if rel.len() == 4 => Some(call(new_address, rel32(rel))),
+        [opcode @ 0x70..=0x7F, rel] => Some(conditional_jump(*opcode, new_address, rel8(*rel))),
+        [0x0F, opcode @ 0x80..=0x8F, rel @ ..] if rel.len() == 4 => {
+            Some(conditional_jump(*opcode - 0x10, new_address, rel32(rel)))
+        }
+        bytes => {
+            let mut bytes = bytes.to_vec();
+            if let Some(position) = rip_displacement_at(instruction) {
+                let disp = i32::from_le_bytes(bytes[position..position + 4].try_into().unwrap());
+                let target = old_end.wrapping_add_signed(disp as isize);
+                let new_disp = i32::try_from((target as i64).wrapping_sub((new_address + bytes.len()) as i64)).ok()?;
+                bytes[position..position + 4].copy_from_slice(&new_disp.to_le_bytes());
+            }
+            Some(bytes)
+        }
+    }
+}
+
+/// Returns the offset of the disp32 of the RIP-relative memory operand of `instruction`, if any.
+fn rip_displacement_at(instruction: &Instruction) -> Option<usize> {
+    let cs = build_capstone(true);
+    let instructions = cs.disasm_count(&instruction.bytes, instruction.address as u64, 1).ok()?;
+    let insn = instructions.iter().next()?;
+    let detail = cs.insn_detail(&insn).ok()?;
+
+    detail.arch_detail().operands().into_iter().find_map(|operand| match operand {
+        ArchOperand::X86Operand(op) => match op.op_type {
+            X86OperandType::Mem(mem) if mem.base().0 == X86Reg::X86_REG_RIP as u16 => {
+                let bytes = &instruction.bytes;
+                displacement_at(bytes).filter(|&at| disp32_at(bytes, at) == mem.disp() as i32)
+            }
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+fn conditional_jump(short_opcode: u8, from: usize, to: usize) -> Vec<u8> {
+    // Inverted condition skips over an unconditional jump to the original target.
+    let far_jump = jump(from + 2, to);
+    let mut bytes = vec![short_opcode ^ 1, far_jump.len() as u8];
+    bytes.extend(far_jump);
+    bytes
+}
+
+
+/// Marks the bytes of `code` that encode a relative branch displacement or a RIP-relative /
+/// absolute memory displacement, i.e. the operands that change when the code is relocated.
+pub(crate) fn relative_operand_mask(code: &[u8], address: usize, short_branches: bool) -> Vec<bool> {
+    let mut mask = vec![false; code.len()];
+    let cs = build_capstone(true);
+
+    let instructions = match cs.disasm_all(code, address as u64) {
+        Ok(instructions) => instructions,
+        Err(_) => return mask,
+    };
+
+    for insn in instructions.iter() {
+        let offset = insn.address() as usize - address;
+        let bytes = insn.bytes();
+
+        let detail = match cs.insn_detail(&insn) {
+            Ok(detail) => detail,
+            Err(_) => continue,
+        };
+
+        let is_relative_branch = detail
+            .groups()
+            .iter()
+            .any(|group| group.0 as u32 == InsnGroupType::CS_GRP_BRANCH_RELATIVE as u32);
+
+        if short_branches && is_relative_branch && bytes.len() == 2 {
+            mask[offset + 1] = true;
+            continue;
+        }
+
+        for operand in detail.arch_detail().operands() {
+            let position = match operand {
+                ArchOperand::X86Operand(op) => match op.op_type {
+                    X86OperandType::Imm(_) if is_relative_branch => rel32_at(bytes),
+                    X86OperandType::Mem(mem) if is_relocatable_memory(&mem) => {
+                        displacement_at(bytes).filter(|&at| disp32_at(bytes, at) == mem.disp() as i32)
+                    }
+                    _ => continue,
+                },
+                _ => continue,
+            };
+
+            if let Some(position) = position {
+                mask[offset + position..offset + position + 4].fill(true);
+            }
+        }
+    }
+
+    mask
+}
+
+fn is_relocatable_memory(mem: &X86OpMem) -> bool {
+    mem.base().0 == X86Reg::X86_REG_RIP as u16
+        || (mem.base() == RegId::INVALID_REG && mem.index() == RegId::INVALID_REG)
+}
+
+/// Returns the offset of the rel32 of a near `jmp`, `call` or `jcc`, which is always the last
+/// four bytes of the instruction.
+fn rel32_at(bytes: &[u8]) -> Option<usize> {
+    let opcode_at = bytes.iter().position(|&byte| !is_prefix(byte))?;
+    match bytes[opcode_at..] {
+        [0xE8 | 0xE9, _, _, _, _] | [0x0F, 0x80..=0x8F, _, _, _, _] => Some(bytes.len() - 4),
+        _ => None,
+    }
+}
+
+/// Returns the offset of the disp32 of the memory operand of an instruction with no base register,
+/// worked out from its layout: the displacement directly follows the prefixes, the opcode, the
+/// ModRM byte and the SIB byte if any, ahead of any immediate.
+fn displacement_at(bytes: &[u8]) -> Option<usize> {
+    let opcode_at = bytes.iter().position(|&byte| !is_prefix(byte))?;
+    let modrm_at = match bytes[opcode_at..] {
+        // mov between the accumulator and a moffs, which has no ModRM byte.
+        [0xA0..=0xA3, ..] => return Some(opcode_at + 1).filter(|&at| at + 4 <= bytes.len()),
+        [0xC5, next, ..] if is_vex(next) => opcode_at + 3,
+        [0xC4, next, ..] if is_vex(next) => opcode_at + 4,
+        [0x62, next, ..] if is_vex(next) => opcode_at + 5,
+        [0x0F, 0x38 | 0x3A, ..] => opcode_at + 3,
+        [0x0F, ..] => opcode_at + 2,
+        _ => opcode_at + 1,
+    };
+
+    let modrm = *bytes.get(modrm_at)?;
+    let has_sib = modrm >> 6 != 0b11 && modrm & 0b111 == 0b100;
+    let disp_at = modrm_at + 1 + has_sib as usize;
+    (disp_at + 4 <= bytes.len()).then_some(disp_at)
+}
+
+/// Whether `C4`, `C5` or `62` followed by `next` starts a VEX or EVEX prefix. On x86 they are
+/// `les`, `lds` and `bound` unless `next` would be a register ModRM byte.
+fn is_vex(next: u8) -> bool {
+    cfg!(target_arch = "x86_64") || next >> 6 == 0b11
+}
+
+fn disp32_at(bytes: &[u8], at: usize) -> i32 {
+    i32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assemble_xor_ret() {
+        let code = assemble(|ops| {
+            dynasm!(ops
+                ; xor eax, eax
+                ; ret
+            );
+        });
+
+        assert_eq!(code.len(), 3);
+        assert!(code[0] == 0x31 || code[0] == 0x33);
+        assert_eq!(&code[1..], &[0xC0, 0xC3]);
+    }
+
+    #[test]
+    fn test_write_code() {
+        let mut buffer = [0x90u8; 8];
+
+        let written = unsafe {
+            write_code(buffer.as_mut_ptr(), |ops| {
+                dynasm!(ops
+                    ; xor eax, eax
+                    ; ret
+                );
+            })
+        }
+        .expect("Failed to write code");
+
+        assert_eq!(&buffer[..written.len()], written.as_slice());
+        assert_eq!(&buffer[written.len()..], &[0x90; 5]);
+    }
+
+    fn contains(code: &[u8], bytes: &[u8]) -> bool {
+        code.windows(bytes.len()).any(|window| window == bytes)
+    }
+
+    #[test]
+    fn test_float_ret_edge_cases() {
+        for value in [f32::MAX, f32::MIN, -0.0, f32::NAN, f32::NEG_INFINITY, f32::from_bits(0xFFFF_FFFF)] {
+            let code = float_ret(FloatType::F32(&value), CallConv::Cdecl);
+            assert!(contains(&code, &value.to_bits().to_le_bytes()), "{:#x}", value.to_bits());
+            assert_eq!(code.last(), Some(&0xC3));
+        }
+
+        for value in [f64::MAX, -0.0, f64::NAN, f64::from_bits(u64::MAX)] {
+            let code = float_ret(FloatType::F64(&value), CallConv::Cdecl);
+            let bits = value.to_bits().to_le_bytes();
+
+            #[cfg(target_arch = "x86_64")]
+            assert!(contains(&code, &bits), "{:#x}", value.to_bits());
+            #[cfg(target_arch = "x86")]
+            assert!(contains(&code, &bits[..4]) && contains(&code, &bits[4..]), "{:#x}", value.to_bits());
+        }
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_float_ret_negative_zero_bytes() {
+        // mov eax, 0x80000000; movd xmm0, eax; ret
+        let code = float_ret(FloatType::F32(&-0.0), CallConv::Cdecl);
+        assert_eq!(code, vec![0xB8, 0x00, 0x00, 0x00, 0x80, 0x66, 0x0F, 0x6E, 0xC0, 0xC3]);
+    }
+
+    #[test]
+    fn test_integral_ret_max_values() {
+        // mov eax, 0xFFFFFFFF; ret
+        let code = integral_ret(IntegralType::U32(&u32::MAX), CallConv::Cdecl);
+        assert_eq!(code, vec![0xB8, 0xFF, 0xFF, 0xFF, 0xFF, 0xC3]);
+
+        let code = integral_ret(IntegralType::U16(&u16::MAX), CallConv::Cdecl);
+        assert!(contains(&code, &[0xFF, 0xFF]));
+
+        let code = integral_ret(IntegralType::U64(&u64::MAX), CallConv::Cdecl);
+        #[cfg(target_arch = "x86_64")]
+        assert_eq!(code, vec![0x48, 0xB8, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xC3]);
+        #[cfg(target_arch = "x86")]
+        assert_eq!(code, vec![0xB8, 0xFF, 0xFF, 0xFF, 0xFF, 0xBA, 0xFF, 0xFF, 0xFF, 0xFF, 0xC3]);
+    }
+
+    #[test]
+    fn test_is_terminator() {
+        let instruction = |bytes: &[u8]| Instruction::new(std::ptr::null_mut(), bytes.to_vec());
+
+        assert!(is_terminator(&instruction(&[0xC3])));
+        assert!(is_terminator(&instruction(&[0xF3, 0xC3])));
+        assert!(is_terminator(&instruction(&[0xC2, 0x08, 0x00])));
+        assert!(is_terminator(&instruction(&[0xEB, 0x05])));
+        assert!(is_terminator(&instruction(&[0xFF, 0xE0])));
+        assert!(!is_terminator(&instruction(&[0xFF, 0xD0])));
+        assert!(!is_terminator(&instruction(&[0x8D, 0x41, 0x01])));
+
+        // jmp fs:[...], call gs:[...]
+        assert!(is_terminator(&instruction(&[0x64, 0xFF, 0x25, 0x00, 0x00, 0x00, 0x00])));
+        assert!(!is_terminator(&instruction(&[0x65, 0xFF, 0x15, 0x00, 0x00, 0x00, 0x00])));
+    }
+
+    #[test]
+    fn test_get_instruction_kind() {
+        let kind = |bytes: &[u8]| {
+            let mut code = [0xCC; 32];
+            code[..bytes.len()].copy_from_slice(bytes);
+            get_instruction(code.as_mut_ptr(), 16).expect("Failed to decode instruction").kind
+        };
+
+        assert_eq!(kind(&[0xC3]), InsnKind::Ret);
+        assert_eq!(kind(&[0xC2, 0x08, 0x00]), InsnKind::Ret);
+        assert_eq!(kind(&[0xEB, 0x05]), InsnKind::Branch);
+        assert_eq!(kind(&[0xE9, 0x00, 0x01, 0x00, 0x00]), InsnKind::Branch);
+        assert_eq!(kind(&[0x74, 0x05]), InsnKind::Branch);
+        assert_eq!(kind(&[0xE8, 0x00, 0x00, 0x00, 0x00]), InsnKind::Call);
+        assert_eq!(kind(&[0xFF, 0xD0]), InsnKind::Call);
+        assert_eq!(kind(&[0x8D, 0x41, 0x01]), InsnKind::Other);
+    }
+
+    #[test]
+    fn test_disassemble_detailed() {
+        // call +2; mov eax, ecx; jmp -9; ret
+        let mut code = [0xCC; 32];
+        code[..12].copy_from_slice(&[0xE8, 0x02, 0x00, 0x00, 0x00, 0x89, 0xC8, 0xEB, 0xF7, 0xC3, 0xCC, 0xCC]);
+        let base = code.as_mut_ptr() as usize;
+
+        let instructions = unsafe { disassemble_detailed(code.as_mut_ptr(), 4) }.expect("Failed to disassemble");
+        assert_eq!(instructions.len(), 4);
+        assert_eq!(instructions.iter().map(|instruction| instruction.size).collect::<Vec<_>>(), vec![5, 2, 2, 1]);
+
+        let call = instructions[0].detail.as_ref().unwrap();
+        assert!(call.is_call());
+        assert!(call.is_branch());
+        assert_eq!(call.mnemonic, "call");
+        assert_eq!(call.branch_target, Some(base + 7));
+
+        let mov = instructions[1].detail.as_ref().unwrap();
+        assert!(!mov.is_branch());
+        assert_eq!(mov.branch_target, None);
+        assert!(mov.regs_read.contains(&"ecx".to_string()));
+        assert!(mov.regs_written.contains(&"eax".to_string()));
+        assert_eq!(
+            mov.operands,
+            vec![
+                OperandAccess { read: false, write: true },
+                OperandAccess { read: true, write: false },
+            ]
+        );
+
+        let jmp = instructions[2].detail.as_ref().unwrap();
+        assert!(jmp.is_branch());
+        assert!(!jmp.is_call());
+        assert_eq!(jmp.branch_target, Some(base));
+
+        let ret = instructions[3].detail.as_ref().unwrap();
+        assert!(ret.groups.contains(&InstructionGroup::Return));
+    }
+
+    #[test]
+    fn test_relative_operand_mask_layout() {
+        // mov eax, [rip + 0x05050505] (or [0x05050505] on x86); call +0xE8
+        let code = [0x8B, 0x05, 0x05, 0x05, 0x05, 0x05, 0xE8, 0xE8, 0x00, 0x00, 0x00];
+        let mask = relative_operand_mask(&code, code.as_ptr() as usize, false);
+
+        assert_eq!(mask, vec![false, false, true, true, true, true, false, true, true, true, true]);
+    }
+
+    #[test]
+    fn test_disassemble_detailed_null() {
+        assert!(unsafe { disassemble_detailed(std::ptr::null_mut(), 1) }.is_none());
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_relocate_rip_relative() {
+        // lea rax, [rip + 0x10]
+        let code = [0x48, 0x8D, 0x05, 0x10, 0x00, 0x00, 0x00];
+        let address = code.as_ptr() as usize;
+        let instruction = Instruction::new(address as *mut u8, code.to_vec());
+
+        let relocated = relocate(&instruction, address + 0x1000).expect("Failed to relocate");
+        let disp = i32::from_le_bytes(relocated[3..7].try_into().unwrap());
+        assert_eq!(&relocated[..3], &code[..3]);
+        assert_eq!((address + 0x1000 + 7).wrapping_add_signed(disp as isize), address + 7 + 0x10);
+
+        assert!(relocate(&instruction, address.wrapping_add(0x1_0000_0000)).is_none());
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_relocate_displacement_repeats_modrm() {
+        // mov eax, [rip + 0x05050505]
+        let code = [0x8B, 0x05, 0x05, 0x05, 0x05, 0x05];
+        let address = code.as_ptr() as usize;
+        let instruction = Instruction::new(address as *mut u8, code.to_vec());
+
+        let relocated = relocate(&instruction, address + 0x1000).expect("Failed to relocate");
+        let disp = i32::from_le_bytes(relocated[2..6].try_into().unwrap());
+        assert_eq!(&relocated[..2], &code[..2]);
+        assert_eq!((address + 0x1000 + 6).wrapping_add_signed(disp as isize), address + 6 + 0x05050505);
+    }
+
+    #[test]
+    fn test_relocate_rejects_counter_branches() {
+        let code = [0xE3, 0x05, 0xE2, 0xFE, 0x67, 0xE3, 0x05];
+        let address = code.as_ptr() as usize;
+
+        for (offset, len) in [(0, 2), (2, 2), (4, 3)] {
+            let instruction = Instruction::new((address + offset) as *mut u8, code[offset..offset + len].to_vec());
+            assert!(relocate(&instruction, address + 0x1000).is_none());
+        }
+
+        // Instructions without relative operands are copied as is.
+        let nop = Instruction::new(address as *mut u8, vec![0x90]);
+        assert_eq!(relocate(&nop, address + 0x1000), Some(vec![0x90]));
+    }
+
+    #[test]
+    fn test_get_instruction_mode() {
+        // mov rax, [rip + 0x10] on x64; dec eax followed by mov eax, [0x10] on x86
+        let mut code = [0x48, 0x8B, 0x05, 0x10, 0x00, 0x00, 0x00, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC];
+        let address = code.as_mut_ptr();
+
+        let x64 = unsafe { get_instruction_mode(address, code.len(), DisasmMode::X64) }.expect("Failed to decode x64");
+        let x86 = unsafe { get_instruction_mode(address, code.len(), DisasmMode::X86) }.expect("Failed to decode x86");
+        assert_eq!(x64.bytes, code[..7].to_vec());
+        assert_eq!(x86.bytes, vec![0x48]);
+
+        let host = unsafe { get_instruction_mode(address, code.len(), DisasmMode::default()) }.unwrap();
+        assert_eq!(host.bytes, get_instruction(address, code.len()).unwrap().bytes);
+
+        assert!(unsafe { get_instruction_mode(std::ptr::null_mut(), 16, DisasmMode::X64) }.is_none());
+    }
+
+    #[test]
+    fn test_instructions_covering() {
+        // push ebp; mov ebp, esp; sub esp, 0x20; int3 padding
+        let mut code = [0xCC; 32];
+        code[..6].copy_from_slice(&[0x55, 0x89, 0xE5, 0x83, 0xEC, 0x20]);
+
+        let (instructions, span) = unsafe { instructions_covering(code.as_ptr(), 5) }.expect("Failed to decode instructions");
+        assert_eq!(instructions.len(), 3);
+        assert_eq!(span, 6);
+        assert_eq!(instructions.iter().map(|instruction| instruction.size).sum::<usize>(), span);
+
+        let (instructions, span) = unsafe { instructions_covering(code.as_ptr(), 3) }.expect("Failed to decode instructions");
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(span, 3);
+
+        assert!(unsafe { instructions_covering(std::ptr::null(), 5) }.is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "insn-cache")]
+    fn test_instruction_cache_invalidation() {
+        // mov eax, 1; ret
+        let mut code = [0xB8, 0x01, 0x00, 0x00, 0x00, 0xC3, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90];
+        let address = code.as_mut_ptr();
+
+        let first = get_instruction(address, 16).expect("Failed to decode instruction");
+        assert_eq!(first.bytes, vec![0xB8, 0x01, 0x00, 0x00, 0x00]);
+
+        let host = DisasmMode::host();
+        let hash = insn_cache::hash_bytes(&code);
+        assert_eq!(insn_cache::get(address as usize, host, hash), Some((first.bytes.clone(), InsnKind::Other)));
+
+        // push rax
+        code[0] = 0x50;
+        let changed_hash = insn_cache::hash_bytes(&code);
+        assert_eq!(insn_cache::get(address as usize, host, changed_hash), None);
+
+        let second = get_instruction(address, 16).expect("Failed to decode instruction");
+        assert_eq!(second.bytes, vec![0x50]);
+        assert_eq!(insn_cache::get(address as usize, host, changed_hash), Some((vec![0x50], InsnKind::Other)));
+    }
+
+    #[test]
+    #[cfg(feature = "insn-cache")]
+    fn test_instruction_cache_keyed_by_mode() {
+        // mov rax, [rip + 0x10] on x64; dec eax on x86
+        let mut code = [0x48, 0x8B, 0x05, 0x10, 0x00, 0x00, 0x00, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90];
+        let address = code.as_mut_ptr();
+
+        let x64 = unsafe { get_instruction_mode(address, code.len(), DisasmMode::X64) }.expect("Failed to decode x64");
+        let x86 = unsafe { get_instruction_mode(address, code.len(), DisasmMode::X86) }.expect("Failed to decode x86");
+        assert_eq!((x64.size, x86.size), (7, 1));
+
+        let hash = insn_cache::hash_bytes(&code);
+        assert_eq!(insn_cache::get(address as usize, DisasmMode::X64, hash).map(|entry| entry.0.len()), Some(7));
+        assert_eq!(insn_cache::get(address as usize, DisasmMode::X86, hash).map(|entry| entry.0.len()), Some(1));
+    }
 }
\ No newline at end of file
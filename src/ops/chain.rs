@@ -0,0 +1,253 @@
+use crate::errors::{ReadMemoryError, WriteMemoryError};
+
+use super::query::is_committed;
+use super::read::read_memory;
+use super::write::write_memory;
+
+/// Resolves a multi-level pointer to the final address.
+///
+/// The pointer stored at `base` is read first. Every offset but the last is then added to the
+/// current address and the pointer stored there is read, and the last offset is added to give the
+/// final address. This matches the pointer paths produced by common memory scanners, where `base` is
+/// the address of a static pointer such as `module_base + 0x1234`.
+///
+/// # Safety
+/// This function is `unsafe` because it dereferences every intermediate pointer of the chain.
+///
+/// # Parameters
+/// - `base`: The address of the first pointer of the chain.
+/// - `offsets`: The offsets applied at each level.
+///
+/// # Returns
+/// - `Ok(usize)`: The final address of the chain.
+/// - `Err(ReadMemoryError)`: If a pointer of the chain could not be read.
+///
+/// # Errors
+/// - `ReadMemoryError::NullPointer`: If `base` or an intermediate address is null.
+/// - Any other error returned by [`read_memory`] for an intermediate pointer.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::chain;
+///
+/// let value = 1337i32;
+/// let level = [0usize, &value as *const i32 as usize];
+/// let root = level.as_ptr() as usize;
+///
+/// let address = unsafe {
+///     chain::resolve_pointer_chain(&root as *const usize as usize, &[std::mem::size_of::<usize>() as isize, 0])
+/// };
+/// assert_eq!(address, Ok(&value as *const i32 as usize));
+/// ```
+pub unsafe fn resolve_pointer_chain(base: usize, offsets: &[isize]) -> Result<usize, ReadMemoryError> {
+    resolve_pointer_chain_with(base, offsets, false)
+}
+
+/// Resolves a multi-level pointer like [`resolve_pointer_chain`], optionally checking every
+/// pointer before dereferencing it.
+///
+/// Without validation only null pointers are caught, so a garbage intermediate pointer (e.g. a
+/// freed object) faults somewhere down the chain. With `validate` set, the slot read at each step
+/// must lie in committed, accessible memory according to `VirtualQuery`, and the first step that
+/// doesn't is reported.
+///
+/// Steps are numbered from 0: step 0 reads the pointer at `base`, and step `n` reads the pointer
+/// at the address produced by step `n - 1` plus `offsets[n - 1]`.
+///
+/// # Safety
+/// This function is `unsafe` because it dereferences every intermediate pointer of the chain.
+/// Validation doesn't prevent another thread from freeing the memory between the check and the read.
+///
+/// # Parameters
+/// - `base`: The address of the first pointer of the chain.
+/// - `offsets`: The offsets applied at each level, see [`resolve_pointer_chain`].
+/// - `validate`: Whether to check each pointer with `VirtualQuery` before reading it.
+///
+/// # Errors
+/// - `ReadMemoryError::InvalidChainStep`: If `validate` is set and the slot read at the given
+///   step is null or not committed and accessible.
+/// - Same as [`resolve_pointer_chain`] otherwise.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::errors::ReadMemoryError;
+/// use verity_memory::ops::chain;
+///
+/// let level = [0usize, 0x10]; // the second slot holds a bogus pointer
+/// let root = level.as_ptr() as usize;
+/// let offsets = [std::mem::size_of::<usize>() as isize, 8, 0];
+///
+/// let result = unsafe { chain::resolve_pointer_chain_with(&root as *const usize as usize, &offsets, true) };
+/// assert_eq!(result, Err(ReadMemoryError::InvalidChainStep(2)));
+/// ```
+pub unsafe fn resolve_pointer_chain_with(
+    base: usize,
+    offsets: &[isize],
+    validate: bool,
+) -> Result<usize, ReadMemoryError> {
+    let read_step = |step: usize, slot: usize| -> Result<usize, ReadMemoryError> {
+        if validate && !is_committed(slot as *const u8, std::mem::size_of::<usize>()) {
+            return Err(ReadMemoryError::InvalidChainStep(step));
+        }
+        read_memory(slot as *const usize)
+    };
+
+    let mut address = read_step(0, base)?;
+
+    if let Some((last, levels)) = offsets.split_last() {
+        for (level, offset) in levels.iter().enumerate() {
+            address = read_step(level + 1, address.wrapping_add_signed(*offset))?;
+        }
+        address = address.wrapping_add_signed(*last);
+    }
+
+    Ok(address)
+}
+
+/// Resolves a pointer chain and reads a value of type `T` at the final address.
+///
+/// # Safety
+/// This function is `unsafe` because it dereferences every pointer of the chain and reinterprets the
+/// bytes at the final address as `T`.
+///
+/// # Parameters
+/// - `base`: The address of the first pointer of the chain.
+/// - `offsets`: The offsets applied at each level, see [`resolve_pointer_chain`].
+///
+/// # Returns
+/// - `Ok(T)`: The value at the end of the chain.
+/// - `Err(ReadMemoryError)`: If the chain could not be resolved or the value could not be read.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::chain;
+///
+/// let value = Box::new(1337i32);
+/// let level = Box::new([0usize, &*value as *const i32 as usize]);
+/// let root = level.as_ptr() as usize;
+///
+/// let result = unsafe {
+///     chain::read_chain::<i32>(&root as *const usize as usize, &[std::mem::size_of::<usize>() as isize, 0])
+/// };
+/// assert_eq!(result, Ok(1337));
+/// ```
+pub unsafe fn read_chain<T: Copy>(base: usize, offsets: &[isize]) -> Result<T, ReadMemoryError> {
+    let address = resolve_pointer_chain(base, offsets)?;
+    read_memory(address as *const T)
+}
+
+/// Resolves a pointer chain and writes a value of type `T` at the final address.
+///
+/// # Safety
+/// This function is `unsafe` because it dereferences every pointer of the chain and modifies the
+/// memory at the final address.
+///
+/// # Parameters
+/// - `base`: The address of the first pointer of the chain.
+/// - `offsets`: The offsets applied at each level, see [`resolve_pointer_chain`].
+/// - `value`: The value to write at the end of the chain.
+///
+/// # Returns
+/// - `Ok(())`: If the value was written.
+/// - `Err(WriteMemoryError)`: If the chain could not be resolved or the value could not be written.
+///   A failure to resolve the chain is reported with the matching `WriteMemoryError` variant.
+pub unsafe fn write_chain<T: Copy>(base: usize, offsets: &[isize], value: T) -> Result<(), WriteMemoryError> {
+    let address = resolve_pointer_chain(base, offsets).map_err(to_write_error)?;
+    write_memory(address as *mut T, value)
+}
+
+fn to_write_error(error: ReadMemoryError) -> WriteMemoryError {
+    match error {
+        ReadMemoryError::NullPointer => WriteMemoryError::NullPointer,
+        ReadMemoryError::InvalidAlignment => WriteMemoryError::InvalidAlignment,
+        ReadMemoryError::InvalidBit => WriteMemoryError::InvalidBit,
+        ReadMemoryError::FailedToChangeProtection => WriteMemoryError::FailedToChangeProtection,
+        ReadMemoryError::FailedToRestoreProtection => WriteMemoryError::FailedToRestoreProtection,
+        ReadMemoryError::InvalidAccess
+        | ReadMemoryError::RegionFree
+        | ReadMemoryError::RegionReserved
+        | ReadMemoryError::InvalidDiscriminant(_)
+        | ReadMemoryError::InvalidChainStep(_) => WriteMemoryError::InvalidAccess,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::size_of;
+
+    // Every level is leaked with `Box::into_raw` and reclaimed at the end, so the pointers the chain
+    // follows (and writes through) carry write permission for the whole test.
+
+    #[test]
+    fn test_read_write_chain_two_levels() {
+        let value = Box::into_raw(Box::new(1337i32));
+        let level = Box::into_raw(Box::new([0usize, value as usize]));
+        let root = Box::into_raw(Box::new(level as usize));
+
+        let base = root as usize;
+        let offsets = [size_of::<usize>() as isize, 0];
+
+        unsafe {
+            assert_eq!(read_chain::<i32>(base, &offsets), Ok(1337));
+            assert!(write_chain(base, &offsets, 7i32).is_ok());
+            assert_eq!(read_chain::<i32>(base, &offsets), Ok(7));
+            assert_eq!(*value, 7);
+
+            drop(Box::from_raw(root));
+            drop(Box::from_raw(level));
+            drop(Box::from_raw(value));
+        }
+    }
+
+    #[test]
+    fn test_read_chain_null_level() {
+        let level = Box::into_raw(Box::new([0usize, 0usize]));
+        let root = Box::into_raw(Box::new(level as usize));
+
+        let base = root as usize;
+        let offsets = [size_of::<usize>() as isize, 0];
+
+        unsafe {
+            assert_eq!(read_chain::<i32>(base, &offsets), Err(ReadMemoryError::NullPointer));
+            assert_eq!(write_chain(base, &offsets, 7i32), Err(WriteMemoryError::NullPointer));
+
+            drop(Box::from_raw(root));
+            drop(Box::from_raw(level));
+        }
+    }
+
+    #[test]
+    fn test_resolve_pointer_chain_validated_bogus_level() {
+        let value = Box::into_raw(Box::new(1337i32));
+        let inner = Box::into_raw(Box::new([0usize, value as usize]));
+        let level = Box::into_raw(Box::new([0usize, inner as usize, 0x10]));
+        let root = Box::into_raw(Box::new(level as usize));
+        let base = root as usize;
+        let size = size_of::<usize>() as isize;
+
+        unsafe {
+            assert_eq!(resolve_pointer_chain_with(base, &[size, size, 0], true), Ok(value as usize));
+            assert_eq!(
+                resolve_pointer_chain_with(base, &[size, size, 0], true),
+                resolve_pointer_chain(base, &[size, size, 0])
+            );
+
+            // The third slot of `level` holds 0x10, which is never mapped.
+            assert_eq!(
+                resolve_pointer_chain_with(base, &[2 * size, 0, 0], true),
+                Err(ReadMemoryError::InvalidChainStep(2))
+            );
+            assert_eq!(
+                resolve_pointer_chain_with(base, &[0, 0, 0], true),
+                Err(ReadMemoryError::InvalidChainStep(2))
+            );
+            assert_eq!(resolve_pointer_chain_with(0, &[0], true), Err(ReadMemoryError::InvalidChainStep(0)));
+
+            drop(Box::from_raw(root));
+            drop(Box::from_raw(level));
+            drop(Box::from_raw(inner));
+            drop(Box::from_raw(value));
+        }
+    }
+}
\ No newline at end of file
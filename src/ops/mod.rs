@@ -1,12 +1,74 @@
+pub mod access;
+pub mod alloc;
 #[cfg(feature = "advanced-write")]
 pub mod asm;
+pub mod chain;
+pub mod patch;
+pub mod protection;
+pub mod query;
 pub mod read;
 pub mod write;
 
+pub use access::CurrentProcess;
+pub use alloc::run_code;
+pub use alloc::ExecutableMemory;
+pub use chain::read_chain;
+pub use chain::resolve_pointer_chain;
+pub use chain::resolve_pointer_chain_with;
+pub use chain::write_chain;
+pub use patch::Togglable;
+pub use protection::ProtectGuard;
+pub use query::for_each_region;
+pub use query::PageInfo;
+pub use read::read_array;
+pub use read::read_be;
+pub use read::read_bit;
+pub use read::read_bytes;
+pub use read::read_c_string;
+pub use read::read_enum;
+pub use read::read_into;
+pub use read::read_le;
 pub use read::read_memory;
+pub use read::read_memory_with;
+pub use read::read_memory_with_alignment;
+pub use read::read_prefixed_array;
+pub use read::read_relative;
+pub use read::read_string_ptr_array;
+pub use read::read_vec128;
+pub use read::region_slice;
+pub use read::try_read_code_ptr;
+pub use write::write_bit;
+pub use write::write_bytes;
+pub use write::write_if_changed;
+pub use write::write_if_changed_with;
 pub use write::write_memory;
+pub use write::write_memory_verified;
+pub use write::write_memory_verified_with;
+pub use write::write_memory_with;
+pub use write::write_memory_with_alignment;
+pub use write::write_vec128;
+pub use write::WriteBuilder;
+pub use write::WRITE_PROTECTION_ESCALATION;
 
+#[cfg(feature = "runtime")]
+pub use write::write_memory_guarded;
+#[cfg(feature = "runtime")]
+pub use write::write_memory_guarded_with;
+#[cfg(feature = "advanced-write")]
+pub use asm::disassemble_detailed;
+#[cfg(feature = "advanced-write")]
+pub use asm::get_instruction_mode;
+#[cfg(feature = "advanced-write")]
+pub use asm::instructions_covering;
+#[cfg(feature = "advanced-write")]
+pub use write::fill_instructions;
 #[cfg(feature = "advanced-write")]
 pub use write::nop_instructions;
 #[cfg(feature = "advanced-write")]
-pub use write::replace_return_value;
\ No newline at end of file
+pub use write::MAX_FILL_INSTRUCTIONS;
+#[cfg(feature = "advanced-write")]
+pub use write::redirect_function;
+#[cfg(feature = "advanced-write")]
+pub use write::replace_return_value;
+#[cfg(feature = "advanced-write")]
+pub use write::replace_return_value_with_conv;
\ No newline at end of file
@@ -0,0 +1,202 @@
+use crate::errors::{ReadMemoryError, WriteMemoryError};
+
+use super::read::read_bytes;
+use super::write::write_bytes;
+
+/// The enabled/disabled state of something switched on and off repeatedly, shared by
+/// [`Togglable`] and [`Cheat`](crate::cheat::Cheat).
+///
+/// The state only changes once switching succeeded, so a failed switch can be retried.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct ToggleState {
+    enabled: bool,
+}
+
+impl ToggleState {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Calls `switch` with `enabled` unless the state already is `enabled`.
+    pub(crate) fn set<E>(&mut self, enabled: bool, switch: impl FnOnce(bool) -> Result<(), E>) -> Result<(), E> {
+        if self.enabled != enabled {
+            switch(enabled)?;
+            self.enabled = enabled;
+        }
+        Ok(())
+    }
+
+    /// Switches to the opposite state, returning the new one.
+    pub(crate) fn toggle<E>(&mut self, switch: impl FnOnce(bool) -> Result<(), E>) -> Result<bool, E> {
+        self.set(!self.enabled, switch)?;
+        Ok(self.enabled)
+    }
+}
+
+/// A byte patch that can be enabled and disabled any number of times.
+///
+/// Both the original and the patched bytes are kept, so toggling neither re-scans nor re-reads
+/// the target. The patch starts disabled. Dropping it while enabled restores the original bytes.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::patch::Togglable;
+///
+/// let mut code = [0xFFu8, 0x4E, 0x10]; // dec dword ptr [rsi + 0x10]
+///
+/// unsafe {
+///     let mut patch = Togglable::new(code.as_mut_ptr(), &[0x90, 0x90, 0x90]).unwrap();
+///     patch.enable().unwrap();
+///     assert!(patch.is_enabled());
+///     patch.disable().unwrap();
+/// }
+/// assert_eq!(code, [0xFF, 0x4E, 0x10]);
+/// ```
+#[derive(Debug)]
+pub struct Togglable {
+    address: *mut u8,
+    original: Vec<u8>,
+    patched: Vec<u8>,
+    state: ToggleState,
+}
+
+impl Togglable {
+    /// Saves the bytes at `address` that `patched` will replace, without writing anything yet.
+    ///
+    /// # Safety
+    /// This function is unsafe because the patch writes through `address` whenever it is
+    /// enabled, disabled or dropped. The range must stay mapped for the lifetime of the patch,
+    /// and no thread may execute it while it is written.
+    ///
+    /// # Errors
+    /// - Any error returned by [`read_bytes`], e.g. `ReadMemoryError::NullPointer`.
+    pub unsafe fn new(address: *mut u8, patched: &[u8]) -> Result<Togglable, ReadMemoryError> {
+        let original = read_bytes(address, patched.len())?;
+        Ok(Togglable {
+            address,
+            original,
+            patched: patched.to_vec(),
+            state: ToggleState::default(),
+        })
+    }
+
+    /// Returns the address of the patch.
+    pub fn address(&self) -> *mut u8 {
+        self.address
+    }
+
+    /// Returns the bytes the patch replaced.
+    pub fn original(&self) -> &[u8] {
+        &self.original
+    }
+
+    /// Returns the bytes written while the patch is enabled.
+    pub fn patched(&self) -> &[u8] {
+        &self.patched
+    }
+
+    /// Returns whether the patched bytes are currently written.
+    pub fn is_enabled(&self) -> bool {
+        self.state.is_enabled()
+    }
+
+    /// Writes the patched bytes. Does nothing if the patch is already enabled.
+    ///
+    /// # Errors
+    /// - Any error returned by [`write_bytes`].
+    pub fn enable(&mut self) -> Result<(), WriteMemoryError> {
+        self.state.set(true, |_| unsafe { write_bytes(self.address, &self.patched) })
+    }
+
+    /// Writes the original bytes back. Does nothing if the patch is already disabled.
+    ///
+    /// # Errors
+    /// - Any error returned by [`write_bytes`].
+    pub fn disable(&mut self) -> Result<(), WriteMemoryError> {
+        self.state.set(false, |_| unsafe { write_bytes(self.address, &self.original) })
+    }
+
+    /// Enables the patch if it is disabled and the other way around, returning the new state.
+    ///
+    /// # Errors
+    /// - Any error returned by [`write_bytes`].
+    pub fn toggle(&mut self) -> Result<bool, WriteMemoryError> {
+        self.state.toggle(|enabled| unsafe {
+            write_bytes(self.address, if enabled { &self.patched } else { &self.original })
+        })
+    }
+}
+
+impl Drop for Togglable {
+    fn drop(&mut self) {
+        let _ = self.disable();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINAL: [u8; 4] = [0xFF, 0x4E, 0x10, 0xC3];
+    const PATCHED: [u8; 3] = [0x90, 0x90, 0x90];
+
+    #[test]
+    fn test_togglable_alternates() {
+        let mut code = ORIGINAL;
+        let mut patch = unsafe { Togglable::new(code.as_mut_ptr(), &PATCHED) }.expect("Failed to create patch");
+
+        assert_eq!(patch.original(), &ORIGINAL[..3]);
+        assert_eq!(patch.patched(), &PATCHED);
+        assert!(!patch.is_enabled());
+
+        for expected in [true, false, true] {
+            assert_eq!(patch.toggle(), Ok(expected));
+            assert_eq!(patch.is_enabled(), expected);
+
+            let bytes = unsafe { std::slice::from_raw_parts(patch.address(), 4) };
+            if expected {
+                assert_eq!(bytes, &[0x90, 0x90, 0x90, 0xC3]);
+            } else {
+                assert_eq!(bytes, &ORIGINAL);
+            }
+        }
+
+        drop(patch);
+        assert_eq!(code, ORIGINAL);
+    }
+
+    #[test]
+    fn test_togglable_idempotent() {
+        let mut code = ORIGINAL;
+        let mut patch = unsafe { Togglable::new(code.as_mut_ptr(), &PATCHED) }.unwrap();
+
+        assert_eq!(patch.disable(), Ok(()));
+        assert_eq!(patch.enable(), Ok(()));
+        assert_eq!(patch.enable(), Ok(()));
+        assert_eq!(patch.disable(), Ok(()));
+        drop(patch);
+        assert_eq!(code, ORIGINAL);
+    }
+
+    #[test]
+    fn test_togglable_null() {
+        let result = unsafe { Togglable::new(std::ptr::null_mut(), &PATCHED) };
+        assert_eq!(result.unwrap_err(), ReadMemoryError::NullPointer);
+    }
+
+    #[test]
+    fn test_toggle_state_keeps_state_on_failure() {
+        let mut state = ToggleState::default();
+
+        let failed = state.set(true, |_| Err(WriteMemoryError::FailedToChangeProtection));
+        assert_eq!(failed, Err(WriteMemoryError::FailedToChangeProtection));
+        assert!(!state.is_enabled());
+
+        assert_eq!(state.toggle(|enabled| if enabled { Ok::<(), ()>(()) } else { Err(()) }), Ok(true));
+        assert_eq!(state.toggle(|enabled| if enabled { Ok(()) } else { Err(()) }), Err(()));
+        assert!(state.is_enabled());
+
+        // Already enabled, so the switch isn't called.
+        assert_eq!(state.set(true, |_| Err(())), Ok(()));
+    }
+}
\ No newline at end of file
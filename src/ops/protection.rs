@@ -0,0 +1,424 @@
+use std::ops::ControlFlow;
+
+use winapi::{shared::minwindef::LPVOID, um::memoryapi::VirtualProtect};
+
+use crate::errors::WriteMemoryError;
+use crate::types::Protection;
+
+use super::query::{for_each_region, PAGE_SIZE};
+
+/// Changes the protection of memory ranges on behalf of the read and write operations.
+///
+/// The default implementation, [`Win32Protection`], calls `VirtualProtect`. Supplying another
+/// implementation lets the operations be routed through an alternate (e.g. hooked or remote)
+/// protection routine, or observed from tests.
+pub trait ProtectionProvider {
+    /// Changes the protection of `size` bytes starting at `address` to `new_protect`.
+    ///
+    /// # Safety
+    /// Implementations may change the protection of arbitrary memory, which can crash the process
+    /// if the range is in use.
+    ///
+    /// # Returns
+    /// - `Some(u32)`: The previous protection of the range.
+    /// - `None`: If the protection could not be changed.
+    unsafe fn protect(&self, address: LPVOID, size: usize, new_protect: u32) -> Option<u32>;
+
+    /// The current protection of every region overlapping `[address, address + size)`, clipped to
+    /// that range, as `(start, size, protection)`.
+    ///
+    /// The default implementation walks the regions with `VirtualQuery`. Providers that don't act
+    /// on the memory of the current process should override it.
+    fn regions(&self, address: usize, size: usize) -> Vec<(usize, usize, u32)> {
+        let end = address.saturating_add(size);
+        let mut regions = Vec::new();
+        let _ = for_each_region(address, end, |info| {
+            let start = info.base.max(address);
+            regions.push((start, info.end().min(end) - start, info.protect));
+            ControlFlow::Continue(())
+        });
+        regions
+    }
+}
+
+impl<P: ProtectionProvider + ?Sized> ProtectionProvider for &P {
+    unsafe fn protect(&self, address: LPVOID, size: usize, new_protect: u32) -> Option<u32> {
+        (**self).protect(address, size, new_protect)
+    }
+
+    fn regions(&self, address: usize, size: usize) -> Vec<(usize, usize, u32)> {
+        (**self).regions(address, size)
+    }
+}
+
+/// The original protection of every region of `[address, address + size)`, as reported by
+/// [`ProtectionProvider::regions`], when the range spans more than one page.
+///
+/// `VirtualProtect` only reports the previous protection of the first page, so restoring a range
+/// that straddles pages with different protections needs them queried beforehand. Ranges within a
+/// single page return an empty list without querying anything.
+pub(crate) fn spanned_protections<P: ProtectionProvider + ?Sized>(
+    provider: &P,
+    address: usize,
+    size: usize,
+) -> Vec<(usize, usize, u32)> {
+    let end = address.saturating_add(size);
+    if size == 0 || address / PAGE_SIZE == (end - 1) / PAGE_SIZE {
+        return Vec::new();
+    }
+
+    provider.regions(address, size)
+}
+
+/// Restores the protection of `[address, address + size)` after an access, region by region when
+/// `regions` (from [`spanned_protections`]) holds more than one.
+///
+/// Every region is restored even if an earlier one fails. Returns whether all of them were.
+pub(crate) unsafe fn restore_protections<P: ProtectionProvider + ?Sized>(
+    provider: &P,
+    address: LPVOID,
+    size: usize,
+    old_protect: u32,
+    regions: &[(usize, usize, u32)],
+) -> bool {
+    if regions.len() > 1 {
+        regions.iter().fold(true, |restored, &(start, len, protect)| {
+            provider.protect(start as LPVOID, len, protect).is_some() && restored
+        })
+    } else {
+        provider.protect(address, size, old_protect).is_some()
+    }
+}
+
+/// The default [`ProtectionProvider`], backed by `VirtualProtect`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Win32Protection;
+
+impl ProtectionProvider for Win32Protection {
+    unsafe fn protect(&self, address: LPVOID, size: usize, new_protect: u32) -> Option<u32> {
+        let mut old_protect = 0;
+        if VirtualProtect(address, size, new_protect, &mut old_protect) == 0 {
+            None
+        } else {
+            Some(old_protect)
+        }
+    }
+}
+
+/// Holds a range of memory at a different protection for as long as the guard is alive.
+///
+/// The protection is changed once on construction and restored when the guard is dropped, also
+/// when unwinding from a panic. This avoids toggling the protection for every access when many
+/// reads and writes are done on the same region, e.g. in a loop.
+///
+/// The protection of every page the range spans is recorded beforehand, so a range straddling
+/// pages with different protections gets each of them back.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::protection::ProtectGuard;
+/// use verity_memory::types::Protection;
+///
+/// let mut buffer = [0u8; 16];
+///
+/// unsafe {
+///     let mut guard = ProtectGuard::new(buffer.as_mut_ptr(), buffer.len(), Protection::ReadWrite).unwrap();
+///     for (i, byte) in guard.as_mut_slice().iter_mut().enumerate() {
+///         *byte = i as u8;
+///     }
+/// }
+///
+/// assert_eq!(buffer[15], 15);
+/// ```
+pub struct ProtectGuard<P: ProtectionProvider = Win32Protection> {
+    address: *mut u8,
+    size: usize,
+    old_protect: u32,
+    regions: Vec<(usize, usize, u32)>,
+    provider: P,
+    restored: bool,
+}
+
+impl ProtectGuard {
+    /// Changes the protection of `size` bytes starting at `address` to `protection`.
+    ///
+    /// # Safety
+    /// The caller must ensure that the range is valid, and that no other code relies on its
+    /// protection while the guard is alive.
+    ///
+    /// # Errors
+    /// - `WriteMemoryError::NullPointer`: If `address` is null.
+    /// - `WriteMemoryError::FailedToChangeProtection`: If changing the protection fails.
+    pub unsafe fn new(address: *mut u8, size: usize, protection: Protection) -> Result<ProtectGuard, WriteMemoryError> {
+        ProtectGuard::new_with(address, size, protection, Win32Protection)
+    }
+}
+
+impl<P: ProtectionProvider> ProtectGuard<P> {
+    /// Changes the protection of `size` bytes starting at `address` to `protection` through
+    /// `provider`, which also restores it.
+    ///
+    /// This behaves exactly like [`ProtectGuard::new`], which uses [`Win32Protection`].
+    ///
+    /// # Safety
+    /// Same as [`ProtectGuard::new`].
+    ///
+    /// # Errors
+    /// - Same as [`ProtectGuard::new`].
+    pub unsafe fn new_with(
+        address: *mut u8,
+        size: usize,
+        protection: Protection,
+        provider: P,
+    ) -> Result<ProtectGuard<P>, WriteMemoryError> {
+        if address.is_null() {
+            return Err(WriteMemoryError::NullPointer);
+        }
+
+        let regions = spanned_protections(&provider, address as usize, size);
+
+        let old_protect = provider
+            .protect(address as LPVOID, size, protection.flags())
+            .ok_or(WriteMemoryError::FailedToChangeProtection)?;
+
+        Ok(ProtectGuard {
+            address,
+            size,
+            old_protect,
+            regions,
+            provider,
+            restored: false,
+        })
+    }
+
+    /// The start of the guarded range.
+    pub fn address(&self) -> *mut u8 {
+        self.address
+    }
+
+    /// The size of the guarded range in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The `PAGE_*` protection the first page of the range had before the guard was created.
+    pub fn old_protect(&self) -> u32 {
+        self.old_protect
+    }
+
+    /// Returns the guarded range as a mutable slice.
+    ///
+    /// # Safety
+    /// The protection given to [`ProtectGuard::new`] must allow reading and writing, and the
+    /// memory must not be accessed through other pointers while the slice is in use.
+    pub unsafe fn as_mut_slice(&mut self) -> &mut [u8] {
+        std::slice::from_raw_parts_mut(self.address, self.size)
+    }
+
+    /// Restores the previous protection now instead of on drop.
+    ///
+    /// # Errors
+    /// - `WriteMemoryError::FailedToRestoreProtection`: If restoring the protection of any page
+    ///   fails.
+    pub fn restore(mut self) -> Result<(), WriteMemoryError> {
+        self.restore_protection()
+    }
+
+    fn restore_protection(&mut self) -> Result<(), WriteMemoryError> {
+        if self.restored {
+            return Ok(());
+        }
+        self.restored = true;
+
+        let restored = unsafe {
+            restore_protections(
+                &self.provider,
+                self.address as LPVOID,
+                self.size,
+                self.old_protect,
+                &self.regions,
+            )
+        };
+
+        if restored {
+            Ok(())
+        } else {
+            Err(WriteMemoryError::FailedToRestoreProtection)
+        }
+    }
+}
+
+impl<P: ProtectionProvider> Drop for ProtectGuard<P> {
+    fn drop(&mut self) {
+        let _ = self.restore_protection();
+    }
+}
+
+/// Allocates a page with the protection `protect` for the tests, to be released with `VirtualFree`.
+#[cfg(test)]
+pub(crate) fn alloc_test_page(protect: u32) -> *mut u8 {
+    let page = unsafe {
+        winapi::um::memoryapi::VirtualAlloc(
+            std::ptr::null_mut(),
+            0x1000,
+            winapi::um::winnt::MEM_COMMIT | winapi::um::winnt::MEM_RESERVE,
+            protect,
+        )
+    };
+    assert!(!page.is_null());
+    page as *mut u8
+}
+
+#[cfg(test)]
+pub(crate) struct MockProtection {
+    pub(crate) calls: std::cell::RefCell<Vec<(usize, usize, u32)>>,
+    pub(crate) current: std::cell::Cell<u32>,
+    pub(crate) fail_on_call: Option<usize>,
+}
+
+#[cfg(test)]
+impl MockProtection {
+    pub(crate) fn new(initial: u32, fail_on_call: Option<usize>) -> Self {
+        MockProtection {
+            calls: std::cell::RefCell::new(Vec::new()),
+            current: std::cell::Cell::new(initial),
+            fail_on_call,
+        }
+    }
+
+    pub(crate) fn protections(&self) -> Vec<u32> {
+        self.calls.borrow().iter().map(|call| call.2).collect()
+    }
+}
+
+#[cfg(test)]
+impl ProtectionProvider for MockProtection {
+    unsafe fn protect(&self, address: LPVOID, size: usize, new_protect: u32) -> Option<u32> {
+        let index = self.calls.borrow().len();
+        self.calls.borrow_mut().push((address as usize, size, new_protect));
+
+        if self.fail_on_call == Some(index) {
+            return None;
+        }
+
+        Some(self.current.replace(new_protect))
+    }
+
+    fn regions(&self, address: usize, size: usize) -> Vec<(usize, usize, u32)> {
+        vec![(address, size, self.current.get())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::query::query;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use winapi::um::memoryapi::VirtualFree;
+    use winapi::um::winnt::{MEM_RELEASE, PAGE_EXECUTE_READWRITE, PAGE_READONLY, PAGE_READWRITE};
+
+    #[test]
+    fn test_protect_guard_restores_on_drop() {
+        let page = alloc_test_page(PAGE_READONLY);
+
+        unsafe {
+            let mut guard = ProtectGuard::new(page, 0x10, Protection::ReadWrite).expect("Failed to change protection");
+            assert_eq!(guard.old_protect(), PAGE_READONLY);
+            assert_eq!(query(page).unwrap().Protect, PAGE_READWRITE);
+
+            guard.as_mut_slice().fill(0xAB);
+        }
+
+        assert_eq!(query(page).unwrap().Protect, PAGE_READONLY);
+        assert_eq!(unsafe { *page.add(0xF) }, 0xAB);
+
+        unsafe { VirtualFree(page as LPVOID, 0, MEM_RELEASE) };
+    }
+
+    #[test]
+    fn test_protect_guard_restores_on_panic() {
+        let page = alloc_test_page(PAGE_READONLY);
+
+        let result = catch_unwind(AssertUnwindSafe(|| unsafe {
+            let mut guard = ProtectGuard::new(page, 0x10, Protection::ReadWrite).unwrap();
+            guard.as_mut_slice()[0] = 0x42;
+            panic!("panic while the guard is alive");
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(query(page).unwrap().Protect, PAGE_READONLY);
+        assert_eq!(unsafe { *page }, 0x42);
+
+        unsafe { VirtualFree(page as LPVOID, 0, MEM_RELEASE) };
+    }
+
+    #[test]
+    fn test_protect_guard_explicit_restore() {
+        let page = alloc_test_page(PAGE_READONLY);
+
+        let guard = unsafe { ProtectGuard::new(page, 0x10, Protection::ExecuteReadWrite) }.unwrap();
+        assert_eq!(guard.restore(), Ok(()));
+        assert_eq!(query(page).unwrap().Protect, PAGE_READONLY);
+
+        unsafe { VirtualFree(page as LPVOID, 0, MEM_RELEASE) };
+    }
+
+    #[test]
+    fn test_protect_guard_restores_mixed_protections() {
+        unsafe {
+            let pages = winapi::um::memoryapi::VirtualAlloc(
+                std::ptr::null_mut(),
+                0x2000,
+                winapi::um::winnt::MEM_COMMIT | winapi::um::winnt::MEM_RESERVE,
+                PAGE_READONLY,
+            ) as *mut u8;
+            assert!(!pages.is_null());
+            Win32Protection.protect(pages.add(0x1000) as LPVOID, 0x1000, PAGE_READWRITE).unwrap();
+
+            let guard = ProtectGuard::new(pages.add(0xFF0), 0x20, Protection::ExecuteReadWrite).unwrap();
+            assert_eq!(query(pages.add(0x1000)).unwrap().Protect, PAGE_EXECUTE_READWRITE);
+            drop(guard);
+
+            assert_eq!(query(pages).unwrap().Protect, PAGE_READONLY);
+            assert_eq!(query(pages.add(0x1000)).unwrap().Protect, PAGE_READWRITE);
+
+            VirtualFree(pages as LPVOID, 0, MEM_RELEASE);
+        }
+    }
+
+    #[test]
+    fn test_protect_guard_with_provider() {
+        let provider = MockProtection::new(PAGE_READONLY, None);
+        let mut buffer = [0u8; 4];
+
+        let guard = unsafe { ProtectGuard::new_with(buffer.as_mut_ptr(), 4, Protection::ReadWrite, &provider) }.unwrap();
+        assert_eq!(guard.restore(), Ok(()));
+        assert_eq!(provider.protections(), vec![PAGE_READWRITE, PAGE_READONLY]);
+    }
+
+    #[test]
+    fn test_protect_guard_null() {
+        let result = unsafe { ProtectGuard::new(std::ptr::null_mut(), 0x10, Protection::ReadWrite) };
+        assert!(matches!(result, Err(WriteMemoryError::NullPointer)));
+    }
+
+    #[test]
+    fn test_restore_protections_attempts_every_region() {
+        let provider = MockProtection::new(PAGE_READWRITE, Some(0));
+        let regions = [(0x1FFE, 2, PAGE_READWRITE), (0x2000, 2, PAGE_READONLY)];
+
+        let restored = unsafe { restore_protections(&provider, 0x1FFE as LPVOID, 4, PAGE_READWRITE, &regions) };
+        assert!(!restored);
+        assert_eq!(*provider.calls.borrow(), vec![(0x1FFE, 2, PAGE_READWRITE), (0x2000, 2, PAGE_READONLY)]);
+    }
+
+    #[test]
+    fn test_spanned_protections_uses_provider() {
+        let provider = MockProtection::new(PAGE_READONLY, None);
+
+        // The range isn't mapped; only the provider is asked about it.
+        assert_eq!(spanned_protections(&provider, 0x1FFE, 4), vec![(0x1FFE, 4, PAGE_READONLY)]);
+        assert!(spanned_protections(&provider, 0x1000, 4).is_empty());
+        assert!(provider.calls.borrow().is_empty());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,307 @@
+use std::mem::{size_of, MaybeUninit};
+use std::ops::ControlFlow;
+
+use winapi::shared::minwindef::LPCVOID;
+use winapi::um::memoryapi::VirtualQuery;
+use winapi::um::winnt::{
+    MEMORY_BASIC_INFORMATION, MEM_COMMIT, PAGE_EXECUTE, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE,
+    PAGE_EXECUTE_WRITECOPY, PAGE_GUARD, PAGE_NOACCESS, PAGE_WRITECOPY,
+};
+
+/// The size of a page, the granularity of memory protection on Windows.
+pub(crate) const PAGE_SIZE: usize = 0x1000;
+
+/// Queries the region of pages containing `address` with `VirtualQuery`.
+///
+/// # Returns
+/// - `Some(MEMORY_BASIC_INFORMATION)`: The information about the region.
+/// - `None`: If the address is outside the user address space.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::query;
+///
+/// let value = 42i32;
+/// let info = query::query(&value as *const i32 as *const u8).unwrap();
+/// assert!(info.RegionSize > 0);
+/// ```
+pub fn query(address: *const u8) -> Option<MEMORY_BASIC_INFORMATION> {
+    let mut info = MaybeUninit::<MEMORY_BASIC_INFORMATION>::uninit();
+
+    let written = unsafe {
+        VirtualQuery(
+            address as LPCVOID,
+            info.as_mut_ptr(),
+            size_of::<MEMORY_BASIC_INFORMATION>(),
+        )
+    };
+
+    if written == 0 {
+        None
+    } else {
+        Some(unsafe { info.assume_init() })
+    }
+}
+
+/// A region of pages with the same state and protection, as reported by `VirtualQuery`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageInfo {
+    /// The first address of the region.
+    pub base: usize,
+    /// The size of the region in bytes.
+    pub size: usize,
+    /// `MEM_COMMIT`, `MEM_RESERVE` or `MEM_FREE`.
+    pub state: u32,
+    /// The `PAGE_*` protection of the region; `0` for reserved or free pages.
+    pub protect: u32,
+    /// `MEM_IMAGE`, `MEM_MAPPED` or `MEM_PRIVATE`; `0` for free pages.
+    pub kind: u32,
+}
+
+impl PageInfo {
+    /// The end of the region, exclusive.
+    pub fn end(&self) -> usize {
+        self.base.saturating_add(self.size)
+    }
+
+    /// Whether the region is committed and can be read without faulting.
+    pub fn is_readable(&self) -> bool {
+        self.state == MEM_COMMIT
+            && self.protect & (PAGE_NOACCESS | PAGE_GUARD) == 0
+            && self.protect & 0xFF != PAGE_EXECUTE
+    }
+}
+
+impl From<MEMORY_BASIC_INFORMATION> for PageInfo {
+    fn from(info: MEMORY_BASIC_INFORMATION) -> Self {
+        PageInfo {
+            base: info.BaseAddress as usize,
+            size: info.RegionSize,
+            state: info.State,
+            protect: info.Protect,
+            kind: info.Type,
+        }
+    }
+}
+
+/// Calls `f` for every region of pages overlapping `[start, end)`, in ascending order.
+///
+/// The regions are passed as `VirtualQuery` reports them, so the first one may begin before
+/// `start` and the last one may extend past `end`. The walk stops when `f` returns
+/// [`ControlFlow::Break`], when `end` is reached or when an address can't be queried, i.e. past
+/// the user address space. Nothing is allocated.
+///
+/// # Returns
+/// - `ControlFlow::Break(())`: If `f` stopped the walk.
+/// - `ControlFlow::Continue(())`: If every region was visited.
+///
+/// # Example
+/// ```rust
+/// use std::ops::ControlFlow;
+/// use verity_memory::ops::query;
+///
+/// let buffer = [0u8; 64];
+/// let start = buffer.as_ptr() as usize;
+///
+/// let mut regions = 0;
+/// query::for_each_region(start, start + buffer.len(), |info| {
+///     assert!(info.is_readable());
+///     regions += 1;
+///     ControlFlow::Continue(())
+/// });
+/// assert_eq!(regions, 1);
+/// ```
+pub fn for_each_region<F>(start: usize, end: usize, mut f: F) -> ControlFlow<()>
+where
+    F: FnMut(&PageInfo) -> ControlFlow<()>,
+{
+    let mut current = start;
+
+    while current < end {
+        let info = match query(current as *const u8) {
+            Some(info) => PageInfo::from(info),
+            None => break,
+        };
+
+        f(&info)?;
+
+        let next = info.end();
+        if next <= current {
+            break;
+        }
+        current = next;
+    }
+
+    ControlFlow::Continue(())
+}
+
+/// Returns whether every page of `[address, address + len)` is committed and readable.
+///
+/// Pages marked `PAGE_NOACCESS`, `PAGE_GUARD` or execute-only `PAGE_EXECUTE` are treated as
+/// inaccessible, since reading them raises an exception. See [`PageInfo::is_readable`].
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::query;
+///
+/// let buffer = [0u8; 16];
+/// assert!(query::is_committed(buffer.as_ptr(), buffer.len()));
+/// ```
+pub fn is_committed(address: *const u8, len: usize) -> bool {
+    let end = match (address as usize).checked_add(len) {
+        Some(end) => end,
+        None => return false,
+    };
+
+    let mut current = address as usize;
+    while current < end {
+        let info = match query(current as *const u8) {
+            Some(info) => PageInfo::from(info),
+            None => return false,
+        };
+
+        if !info.is_readable() {
+            return false;
+        }
+
+        current = info.end();
+    }
+
+    true
+}
+
+/// Returns whether the page containing `address` is copy-on-write (`PAGE_WRITECOPY` or
+/// `PAGE_EXECUTE_WRITECOPY`).
+///
+/// Writing to such a page doesn't modify the shared mapping, e.g. the code of a DLL used by other
+/// processes: the system gives the current process a private copy of the page instead, so the
+/// change is only visible in this process.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::query;
+///
+/// let value = 42i32;
+/// assert!(!query::is_copy_on_write(&value as *const i32 as *const u8));
+/// ```
+pub fn is_copy_on_write(address: *const u8) -> bool {
+    match query(address) {
+        Some(info) => info.Protect & (PAGE_WRITECOPY | PAGE_EXECUTE_WRITECOPY) != 0,
+        None => false,
+    }
+}
+
+/// Returns whether the page containing `address` is committed and executable.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::query;
+///
+/// let value = 42i32;
+/// assert!(!query::is_executable(&value as *const i32 as *const u8));
+/// ```
+pub fn is_executable(address: *const u8) -> bool {
+    let executable = PAGE_EXECUTE | PAGE_EXECUTE_READ | PAGE_EXECUTE_READWRITE | PAGE_EXECUTE_WRITECOPY;
+
+    match query(address) {
+        Some(info) => {
+            info.State == MEM_COMMIT
+                && info.Protect & PAGE_GUARD == 0
+                && info.Protect & executable != 0
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::protection::alloc_test_page;
+    use winapi::um::memoryapi::{VirtualAlloc, VirtualFree};
+    use winapi::um::winnt::{MEM_RELEASE, MEM_RESERVE, PAGE_READONLY, PAGE_READWRITE};
+
+    #[test]
+    fn test_is_committed_stack() {
+        let buffer = [0u8; 64];
+        assert!(is_committed(buffer.as_ptr(), buffer.len()));
+    }
+
+    #[test]
+    fn test_is_committed_reserved_only() {
+        unsafe {
+            let reserved = VirtualAlloc(std::ptr::null_mut(), 0x1000, MEM_RESERVE, PAGE_READWRITE);
+            assert!(!reserved.is_null());
+
+            assert!(query(reserved as *const u8).is_some());
+            assert!(!is_committed(reserved as *const u8, 0x10));
+
+            VirtualFree(reserved, 0, MEM_RELEASE);
+        }
+    }
+
+    #[test]
+    fn test_is_committed_execute_only() {
+        let page = alloc_test_page(PAGE_EXECUTE);
+        assert!(!is_committed(page, 0x10));
+
+        unsafe { VirtualFree(page as _, 0, MEM_RELEASE) };
+    }
+
+    #[test]
+    fn test_is_executable() {
+        let value = 0u64;
+        assert!(is_executable(test_is_executable as fn() as *const u8));
+        assert!(!is_executable(&value as *const u64 as *const u8));
+        assert!(!is_executable(std::ptr::null()));
+    }
+
+    #[test]
+    fn test_is_committed_overflow() {
+        assert!(!is_committed(usize::MAX as *const u8, 2));
+    }
+
+    #[test]
+    fn test_for_each_region_layout() {
+        unsafe {
+            let base = VirtualAlloc(std::ptr::null_mut(), 0x4000, MEM_RESERVE, PAGE_READWRITE) as usize;
+            assert_ne!(base, 0);
+
+            // RW | RO | RW | reserved
+            VirtualAlloc(base as _, 0x1000, MEM_COMMIT, PAGE_READWRITE);
+            VirtualAlloc((base + 0x1000) as _, 0x1000, MEM_COMMIT, PAGE_READONLY);
+            VirtualAlloc((base + 0x2000) as _, 0x1000, MEM_COMMIT, PAGE_READWRITE);
+
+            let mut regions = Vec::new();
+            let flow = for_each_region(base, base + 0x4000, |info| {
+                regions.push((info.base - base, info.size, info.state, info.protect));
+                ControlFlow::Continue(())
+            });
+
+            assert_eq!(flow, ControlFlow::Continue(()));
+            assert_eq!(
+                regions,
+                vec![
+                    (0x0000, 0x1000, MEM_COMMIT, PAGE_READWRITE),
+                    (0x1000, 0x1000, MEM_COMMIT, PAGE_READONLY),
+                    (0x2000, 0x1000, MEM_COMMIT, PAGE_READWRITE),
+                    (0x3000, 0x1000, MEM_RESERVE, 0),
+                ]
+            );
+
+            let mut visited = 0;
+            let flow = for_each_region(base, base + 0x4000, |info| {
+                visited += 1;
+                if info.protect == PAGE_READONLY {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            });
+
+            assert_eq!(flow, ControlFlow::Break(()));
+            assert_eq!(visited, 2);
+
+            VirtualFree(base as _, 0, MEM_RELEASE);
+        }
+    }
+}
\ No newline at end of file
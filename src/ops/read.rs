@@ -1,8 +1,15 @@
+use std::ops::ControlFlow;
+#[cfg(not(feature = "runtime"))]
 use std::panic::{catch_unwind, AssertUnwindSafe};
 
-use winapi::{shared::minwindef::LPVOID, um::{memoryapi::VirtualProtect, winnt::PAGE_EXECUTE_READWRITE}};
+use winapi::{shared::minwindef::LPVOID, um::winnt::{MEM_COMMIT, MEM_FREE, MEM_RESERVE, PAGE_EXECUTE_READWRITE}};
 
-use crate::{errors::ReadMemoryError, utils};
+use crate::{errors::ReadMemoryError, types::{vec128::Vec128, AlignmentPolicy, FromEndianBytes}, utils};
+
+use super::protection::{restore_protections, spanned_protections, ProtectionProvider, Win32Protection};
+use super::query::{for_each_region, is_committed, is_executable, query, PAGE_SIZE};
+#[cfg(feature = "runtime")]
+use crate::runtime::guard::guarded_copy;
 
 /// Reads a value from the specified memory address with the specified type.
 /// 
@@ -25,6 +32,8 @@ use crate::{errors::ReadMemoryError, utils};
 /// - `ReadMemoryError::FailedToChangeProtection`: If changing the memory protection fails.
 /// - `ReadMemoryError::FailedToRestoreProtection`: If restoring the memory protection fails.
 /// - `ReadMemoryError::InvalidAccess`: If there is an error during the read operation.
+/// - `ReadMemoryError::RegionFree`: If the address is not allocated at all.
+/// - `ReadMemoryError::RegionReserved`: If the address is reserved but not committed.
 /// 
 /// # Example
 /// ```
@@ -37,42 +46,684 @@ use crate::{errors::ReadMemoryError, utils};
 /// }
 /// ```
 pub unsafe fn read_memory<T: Copy>(address: *const T) -> Result<T, ReadMemoryError> {
+    read_memory_with(address, &Win32Protection)
+}
+
+/// Reads a value from the specified memory address, changing protection through `provider`.
+/// 
+/// This behaves exactly like [`read_memory`], which uses [`Win32Protection`], but lets callers
+/// route the protection changes through an alternate [`ProtectionProvider`].
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences a raw pointer, which could lead to undefined behavior if the pointer is invalid.
+/// 
+/// # Errors
+/// - Same as [`read_memory`].
+/// 
+/// # Example
+/// ```
+/// use verity_memory::ops::{protection::Win32Protection, read};
+/// let value = 42i32;
+/// let result = unsafe { read::read_memory_with(&value as *const i32, &Win32Protection) };
+/// assert_eq!(result, Ok(42));
+/// ```
+pub unsafe fn read_memory_with<T: Copy, P: ProtectionProvider + ?Sized>(
+    address: *const T,
+    provider: &P,
+) -> Result<T, ReadMemoryError> {
+    read_memory_impl(address, provider, AlignmentPolicy::Strict)
+}
+
+/// Reads a value from the specified memory address, checking its alignment according to `policy`.
+/// 
+/// With `AlignmentPolicy::Strict` this behaves exactly like [`read_memory`]. With
+/// `AlignmentPolicy::Unaligned` misaligned pointers are accepted and read with `read_unaligned`,
+/// e.g. for fields of packed structs.
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences a raw pointer, which could lead to undefined behavior if the pointer is invalid.
+/// 
+/// # Errors
+/// - Same as [`read_memory`], except that `ReadMemoryError::InvalidAlignment` is only returned under `AlignmentPolicy::Strict`.
+/// 
+/// # Example
+/// ```
+/// use verity_memory::{ops::read, types::AlignmentPolicy};
+/// let buffer = [0u8, 0x78, 0x56, 0x34, 0x12];
+/// let address = unsafe { buffer.as_ptr().add(1) } as *const u32;
+/// let value = unsafe { read::read_memory_with_alignment(address, AlignmentPolicy::Unaligned) };
+/// assert_eq!(value, Ok(u32::from_ne_bytes([0x78, 0x56, 0x34, 0x12])));
+/// ```
+pub unsafe fn read_memory_with_alignment<T: Copy>(
+    address: *const T,
+    policy: AlignmentPolicy,
+) -> Result<T, ReadMemoryError> {
+    read_memory_impl(address, &Win32Protection, policy)
+}
+
+unsafe fn read_memory_impl<T: Copy, P: ProtectionProvider + ?Sized>(
+    address: *const T,
+    provider: &P,
+    policy: AlignmentPolicy,
+) -> Result<T, ReadMemoryError> {
     if address.is_null() {
         return Err(ReadMemoryError::NullPointer);
     }
 
-    if !utils::check_alignment(address) {
+    if policy == AlignmentPolicy::Strict && !utils::check_alignment(address) {
         return Err(ReadMemoryError::InvalidAlignment);
     }
 
-    let mut old_protect = 0;
+    check_region_state(address as *const u8)?;
+
     let size = std::mem::size_of::<T>();
+    let regions = spanned_protections(provider, address as usize, size);
+
+    let old_protect = provider
+        .protect(address as LPVOID, size, PAGE_EXECUTE_READWRITE)
+        .ok_or(ReadMemoryError::FailedToChangeProtection)?;
 
-    let res = VirtualProtect(
-        address as LPVOID,
-        size,
-        PAGE_EXECUTE_READWRITE,
-        &mut old_protect,
-    );
+    let result = read_value(address, policy);
 
-    if res == 0 {
-        return Err(ReadMemoryError::FailedToChangeProtection);
+    if !restore_protections(provider, address as LPVOID, size, old_protect, &regions) {
+        return Err(ReadMemoryError::FailedToRestoreProtection);
+    }
+
+    result
+}
+
+/// Reads the value once its protection allows it.
+///
+/// With the `runtime` feature the read runs under the guarded copy of
+/// [`guard`](crate::runtime::guard), so a read that still faults, e.g. because the protection was
+/// changed again in between, returns `ReadMemoryError::InvalidAccess` instead of crashing.
+#[cfg(feature = "runtime")]
+unsafe fn read_value<T: Copy>(address: *const T, _policy: AlignmentPolicy) -> Result<T, ReadMemoryError> {
+    let mut value = std::mem::MaybeUninit::<T>::uninit();
+    if !guarded_copy(value.as_mut_ptr() as *mut u8, address as *const u8, std::mem::size_of::<T>()) {
+        return Err(ReadMemoryError::InvalidAccess);
+    }
+
+    Ok(value.assume_init())
+}
+
+#[cfg(not(feature = "runtime"))]
+unsafe fn read_value<T: Copy>(address: *const T, policy: AlignmentPolicy) -> Result<T, ReadMemoryError> {
+    catch_unwind(AssertUnwindSafe(|| match policy {
+        AlignmentPolicy::Strict => *address,
+        AlignmentPolicy::Unaligned => std::ptr::read_unaligned(address),
+    }))
+    .map_err(|_| ReadMemoryError::InvalidAccess)
+}
+
+/// Rejects addresses in free or reserved regions before their protection is touched, since
+/// `VirtualProtect` fails on those with an error that looks like a permission problem.
+fn check_region_state(address: *const u8) -> Result<(), ReadMemoryError> {
+    match query(address) {
+        Some(info) if info.State == MEM_FREE => Err(ReadMemoryError::RegionFree),
+        Some(info) if info.State == MEM_RESERVE => Err(ReadMemoryError::RegionReserved),
+        Some(_) => Ok(()),
+        None => Err(ReadMemoryError::InvalidAccess),
+    }
+}
+
+/// Checks that every page of `[start, end)` is committed. Their protection is left to the
+/// caller, which changes it for the read, so guarded and no-access pages are accepted.
+fn check_range_committed(start: usize, end: usize) -> Result<(), ReadMemoryError> {
+    let mut covered = start;
+    let flow = for_each_region(start, end, |info| {
+        if info.state != MEM_COMMIT {
+            return ControlFlow::Break(());
+        }
+        covered = info.end();
+        ControlFlow::Continue(())
+    });
+
+    if flow.is_break() || covered < end {
+        return Err(ReadMemoryError::InvalidAccess);
     }
 
-    let result = catch_unwind(AssertUnwindSafe(|| *address))
-        .map_err(|_| ReadMemoryError::InvalidAccess);
+    Ok(())
+}
 
-    let res_restore = VirtualProtect(address as LPVOID, size, old_protect, &mut old_protect);
-    if res_restore == 0 {
+/// Reads a number of bytes from the specified memory address under a single protection change.
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences a raw pointer, which could lead to undefined behavior if the range is invalid.
+/// 
+/// # Parameters
+/// - `address`: A raw pointer to the first byte to read.
+/// - `len`: The number of bytes to read.
+/// 
+/// # Returns
+/// - `Ok(Vec<u8>)`: A copy of the bytes in the range if successful.
+/// - `Err(ReadMemoryError)`: Returns an error if the pointer is null or the protection could not be changed.
+/// 
+/// # Errors
+/// - `ReadMemoryError::NullPointer`: If the provided pointer is null.
+/// - `ReadMemoryError::RegionFree`: If the address is not allocated at all.
+/// - `ReadMemoryError::RegionReserved`: If the address is reserved but not committed.
+/// - `ReadMemoryError::FailedToChangeProtection`: If changing the memory protection fails.
+/// - `ReadMemoryError::FailedToRestoreProtection`: If restoring the memory protection fails.
+/// - `ReadMemoryError::InvalidAccess`: If the range overflows or is not committed memory, or the
+///   buffer for it could not be allocated.
+/// 
+/// # Example
+/// ```
+/// use verity_memory::ops::read;
+/// let buffer = [0x48u8, 0x8B, 0x05];
+/// let bytes = unsafe { read::read_bytes(buffer.as_ptr(), buffer.len()) };
+/// assert_eq!(bytes, Ok(vec![0x48, 0x8B, 0x05]));
+/// ```
+pub unsafe fn read_bytes(address: *const u8, len: usize) -> Result<Vec<u8>, ReadMemoryError> {
+    // Reserved fallibly, so a bogus length can't abort the process on allocation failure.
+    let mut bytes = Vec::new();
+    bytes.try_reserve_exact(len).map_err(|_| ReadMemoryError::InvalidAccess)?;
+    bytes.resize(len, 0);
+
+    read_into(address, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Reads `buf.len()` bytes from the specified memory address into `buf` under a single protection
+/// change.
+/// 
+/// Unlike [`read_bytes`], nothing is allocated, so the same buffer can be reused across the reads
+/// of a scan loop.
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences a raw pointer, which could lead to undefined behavior if the range is invalid.
+/// 
+/// # Parameters
+/// - `address`: A raw pointer to the first byte to read.
+/// - `buf`: The buffer to fill. If `ReadMemoryError::FailedToRestoreProtection` is returned, the
+///   bytes have already been copied into it; on any other error it is left untouched.
+/// 
+/// # Errors
+/// - Same as [`read_bytes`].
+/// 
+/// # Example
+/// ```
+/// use verity_memory::ops::read;
+/// let source = [0x48u8, 0x8B, 0x05, 0x90];
+/// let mut buf = [0u8; 3];
+/// assert_eq!(unsafe { read::read_into(source.as_ptr(), &mut buf) }, Ok(()));
+/// assert_eq!(buf, [0x48, 0x8B, 0x05]);
+/// ```
+pub unsafe fn read_into(address: *const u8, buf: &mut [u8]) -> Result<(), ReadMemoryError> {
+    if address.is_null() {
+        return Err(ReadMemoryError::NullPointer);
+    }
+
+    let len = buf.len();
+    if len == 0 {
+        return Ok(());
+    }
+
+    let end = (address as usize).checked_add(len).ok_or(ReadMemoryError::InvalidAccess)?;
+    check_region_state(address)?;
+    check_range_committed(address as usize, end)?;
+
+    let provider = Win32Protection;
+    let regions = spanned_protections(&provider, address as usize, len);
+
+    let old_protect = provider
+        .protect(address as LPVOID, len, PAGE_EXECUTE_READWRITE)
+        .ok_or(ReadMemoryError::FailedToChangeProtection)?;
+
+    std::ptr::copy_nonoverlapping(address, buf.as_mut_ptr(), len);
+
+    if !restore_protections(&provider, address as LPVOID, len, old_protect, &regions) {
         return Err(ReadMemoryError::FailedToRestoreProtection);
     }
 
-    result
+    Ok(())
+}
+
+/// Reads `count` consecutive values of type `T`, e.g. a fixed-size array of structs.
+/// 
+/// The whole array is read under a single protection change, and no alignment is required.
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences a raw pointer, which could lead to undefined behavior if the range is invalid.
+/// 
+/// # Parameters
+/// - `address`: A raw pointer to the first element.
+/// - `count`: The number of elements to read.
+/// 
+/// # Errors
+/// - `ReadMemoryError::InvalidAccess`: If the size of the array overflows `usize`.
+/// - Any other error returned by [`read_bytes`].
+/// 
+/// # Example
+/// ```
+/// use verity_memory::ops::read;
+/// let values = [1u16, 2, 3];
+/// let result = unsafe { read::read_array::<u16>(values.as_ptr() as *const u8, 3) };
+/// assert_eq!(result, Ok(vec![1, 2, 3]));
+/// ```
+pub unsafe fn read_array<T: Copy>(address: *const u8, count: usize) -> Result<Vec<T>, ReadMemoryError> {
+    let size = std::mem::size_of::<T>();
+    let len = count.checked_mul(size).ok_or(ReadMemoryError::InvalidAccess)?;
+
+    let bytes = read_bytes(address, len)?;
+    if size == 0 {
+        // A zero-sized `T` occupies no memory, so every element is the same value.
+        return Ok(vec![std::mem::zeroed(); count]);
+    }
+
+    Ok(bytes
+        .chunks_exact(size)
+        .take(count)
+        .map(|chunk| std::ptr::read_unaligned(chunk.as_ptr() as *const T))
+        .collect())
+}
+
+/// Reads an array laid out as a `u32` element count followed by the elements, e.g. a game
+/// inventory.
+/// 
+/// The count is clamped to `max` before anything else is read, so a corrupt or uninitialized
+/// count can't trigger a huge read.
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences a raw pointer, which could lead to undefined behavior if the range is invalid.
+/// 
+/// # Parameters
+/// - `address`: A raw pointer to the count, which is followed directly by the first element.
+/// - `max`: The maximum number of elements to read.
+/// 
+/// # Returns
+/// - `Ok(Vec<T>)`: The first `min(count, max)` elements.
+/// - `Err(ReadMemoryError)`: Returns an error if the count or the elements could not be read.
+/// 
+/// # Errors
+/// - Same as [`read_array`].
+/// 
+/// # Example
+/// ```
+/// use verity_memory::ops::read;
+/// let layout = [2u32, 10, 20, 30];
+/// let result = unsafe { read::read_prefixed_array::<u32>(layout.as_ptr() as *const u8, 16) };
+/// assert_eq!(result, Ok(vec![10, 20]));
+/// ```
+pub unsafe fn read_prefixed_array<T: Copy>(address: *const u8, max: usize) -> Result<Vec<T>, ReadMemoryError> {
+    let count = read_memory_with_alignment(address as *const u32, AlignmentPolicy::Unaligned)?;
+    let count = (count as usize).min(max);
+
+    read_array(address.add(std::mem::size_of::<u32>()), count)
+}
+
+/// Reads a null-terminated C string.
+/// 
+/// The string is scanned page by page, checking with `VirtualQuery` that each page is committed
+/// before it is touched, and decoded as UTF-8 with invalid sequences replaced.
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences a raw pointer, which could lead to undefined behavior if the pointer is invalid.
+/// 
+/// # Parameters
+/// - `address`: A raw pointer to the first character.
+/// 
+/// # Errors
+/// - `ReadMemoryError::NullPointer`: If the provided pointer is null.
+/// - `ReadMemoryError::InvalidAccess`: If the string runs into inaccessible memory or is longer than
+///   `MAX_C_STRING_LEN` bytes without a terminator.
+/// 
+/// # Example
+/// ```
+/// use verity_memory::ops::read;
+/// let text = b"hello\0";
+/// assert_eq!(unsafe { read::read_c_string(text.as_ptr()) }, Ok("hello".to_string()));
+/// ```
+pub unsafe fn read_c_string(address: *const u8) -> Result<String, ReadMemoryError> {
+    if address.is_null() {
+        return Err(ReadMemoryError::NullPointer);
+    }
+
+    let mut len = 0;
+    loop {
+        let current = address as usize + len;
+        let chunk = (PAGE_SIZE - current % PAGE_SIZE).min(MAX_C_STRING_LEN - len);
+        if chunk == 0 || !is_committed(current as *const u8, chunk) {
+            return Err(ReadMemoryError::InvalidAccess);
+        }
+
+        let bytes = std::slice::from_raw_parts(current as *const u8, chunk);
+        if let Some(terminator) = bytes.iter().position(|&byte| byte == 0) {
+            len += terminator;
+            break;
+        }
+        len += chunk;
+    }
+
+    let bytes = std::slice::from_raw_parts(address, len);
+    Ok(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// The longest string [`read_c_string`] reads before giving up on finding a terminator.
+pub const MAX_C_STRING_LEN: usize = 0x10000;
+
+/// Reads a null-terminated array of pointers to C strings, such as `argv` or an environment block.
+/// 
+/// Pointers are read until a null pointer or `max` strings, whichever comes first; a null pointer
+/// in the middle of the array ends it like the terminator would.
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences raw pointers, which could lead to undefined behavior if they are invalid.
+/// 
+/// # Parameters
+/// - `address`: A raw pointer to the first element of the pointer array.
+/// - `max`: The maximum number of strings to read.
+/// 
+/// # Returns
+/// - `Ok(Vec<String>)`: The strings, in array order.
+/// - `Err(ReadMemoryError)`: Returns an error if a pointer or a string could not be read.
+/// 
+/// # Errors
+/// - Any error returned by [`read_memory_with_alignment`] or [`read_c_string`].
+/// 
+/// # Example
+/// ```
+/// use verity_memory::ops::read;
+/// let first = b"game.exe\0";
+/// let second = b"-windowed\0";
+/// let argv = [first.as_ptr() as usize, second.as_ptr() as usize, 0];
+/// 
+/// let args = unsafe { read::read_string_ptr_array(argv.as_ptr(), 8) };
+/// assert_eq!(args, Ok(vec!["game.exe".to_string(), "-windowed".to_string()]));
+/// ```
+pub unsafe fn read_string_ptr_array(address: *const usize, max: usize) -> Result<Vec<String>, ReadMemoryError> {
+    let mut strings = Vec::new();
+
+    for index in 0..max {
+        let pointer = read_memory_with_alignment(address.add(index), AlignmentPolicy::Unaligned)?;
+        if pointer == 0 {
+            break;
+        }
+        strings.push(read_c_string(pointer as *const u8)?);
+    }
+
+    Ok(strings)
+}
+
+/// Reads a single bit of the byte at the specified memory address, e.g. a boolean flag packed
+/// into a bitfield.
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences a raw pointer, which could lead to undefined behavior if the pointer is invalid.
+/// 
+/// # Parameters
+/// - `address`: A raw pointer to the byte containing the bit.
+/// - `bit`: The index of the bit, from 0 (least significant) to 7.
+/// 
+/// # Errors
+/// - `ReadMemoryError::InvalidBit`: If `bit` is not below 8.
+/// - Any other error returned by [`read_memory`].
+/// 
+/// # Example
+/// ```
+/// use verity_memory::ops::read;
+/// let flags = 0b0000_0100u8;
+/// assert_eq!(unsafe { read::read_bit(&flags, 2) }, Ok(true));
+/// assert_eq!(unsafe { read::read_bit(&flags, 3) }, Ok(false));
+/// ```
+pub unsafe fn read_bit(address: *const u8, bit: u8) -> Result<bool, ReadMemoryError> {
+    if bit >= 8 {
+        return Err(ReadMemoryError::InvalidBit);
+    }
+
+    let byte = read_memory(address)?;
+    Ok(byte & (1 << bit) != 0)
+}
+
+/// Reads a `u32` discriminant from the specified memory address and converts it into `T`,
+/// e.g. a `#[repr(u32)]` state enum.
+/// 
+/// A raw read of an enum whose value isn't one of its variants is undefined behavior, and usually
+/// means the address is wrong. Going through `TryFrom` rejects such values instead.
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences a raw pointer, which could lead to undefined behavior if the pointer is invalid.
+/// 
+/// # Parameters
+/// - `address`: A raw pointer to the discriminant.
+/// 
+/// # Errors
+/// - `ReadMemoryError::InvalidDiscriminant`: If `T::try_from` rejects the value read, which is
+///   carried by the error.
+/// - Any other error returned by [`read_memory`].
+/// 
+/// # Example
+/// ```
+/// use verity_memory::ops::read;
+/// 
+/// #[derive(Debug, PartialEq)]
+/// enum State {
+///     Idle,
+///     Running,
+/// }
+/// 
+/// impl TryFrom<u32> for State {
+///     type Error = ();
+/// 
+///     fn try_from(value: u32) -> Result<Self, ()> {
+///         match value {
+///             0 => Ok(State::Idle),
+///             1 => Ok(State::Running),
+///             _ => Err(()),
+///         }
+///     }
+/// }
+/// 
+/// let value = 1u32;
+/// assert_eq!(unsafe { read::read_enum::<State>(&value) }, Ok(State::Running));
+/// ```
+pub unsafe fn read_enum<T: TryFrom<u32>>(address: *const u32) -> Result<T, ReadMemoryError> {
+    let value = read_memory(address)?;
+    T::try_from(value).map_err(|_| ReadMemoryError::InvalidDiscriminant(value))
+}
+
+/// Reads a value of type `T` at a signed offset from `anchor`, e.g. the field `0x10` bytes before
+/// a value found by a scan.
+/// 
+/// The offset address isn't required to be aligned for `T`, since neighbouring fields of packed
+/// structures often aren't.
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences a raw pointer, which could lead to undefined behavior if the pointer is invalid.
+/// 
+/// # Parameters
+/// - `anchor`: The address the offset is relative to.
+/// - `delta`: The signed offset in bytes from `anchor`.
+/// 
+/// # Errors
+/// - `ReadMemoryError::InvalidAccess`: If `anchor + delta` overflows or underflows.
+/// - Any other error returned by [`read_memory_with_alignment`].
+/// 
+/// # Example
+/// ```
+/// use verity_memory::ops::read;
+/// 
+/// let values = [7i32, 1337, 42];
+/// let anchor = &values[1] as *const i32 as usize;
+/// 
+/// assert_eq!(unsafe { read::read_relative::<i32>(anchor, -4) }, Ok(7));
+/// assert_eq!(unsafe { read::read_relative::<i32>(anchor, 4) }, Ok(42));
+/// ```
+pub unsafe fn read_relative<T: Copy>(anchor: usize, delta: isize) -> Result<T, ReadMemoryError> {
+    let address = anchor.checked_add_signed(delta).ok_or(ReadMemoryError::InvalidAccess)?;
+    read_memory_with_alignment(address as *const T, AlignmentPolicy::Unaligned)
+}
+
+/// Reads a pointer-sized value and returns it only if it looks like a code pointer.
+/// 
+/// Meant for heuristics such as "is this slot still part of the vtable?": the slot itself must be
+/// committed and accessible, and the value read from it must point into committed, executable
+/// memory. Both checks are done with `VirtualQuery`, so no exception is raised for bad addresses.
+/// 
+/// # Parameters
+/// - `address`: The address of the slot to read.
+/// 
+/// # Returns
+/// - `Some(usize)`: The value of the slot, which points into executable memory.
+/// - `None`: If the slot can't be read or its value doesn't point into executable memory.
+/// 
+/// # Example
+/// ```
+/// use verity_memory::ops::read;
+/// 
+/// fn function() {}
+/// 
+/// let code_slot = function as fn() as usize;
+/// let data = 42u32;
+/// let data_slot = &data as *const u32 as usize;
+/// 
+/// assert_eq!(read::try_read_code_ptr(&code_slot as *const usize as usize), Some(code_slot));
+/// assert_eq!(read::try_read_code_ptr(&data_slot as *const usize as usize), None);
+/// ```
+pub fn try_read_code_ptr(address: usize) -> Option<usize> {
+    if address == 0 || !is_committed(address as *const u8, std::mem::size_of::<usize>()) {
+        return None;
+    }
+
+    let value = unsafe { std::ptr::read_volatile(address as *const [u8; std::mem::size_of::<usize>()]) };
+    let value = usize::from_ne_bytes(value);
+
+    if is_executable(value as *const u8) {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+/// Reads a big-endian integer from the specified memory address.
+/// 
+/// Unlike [`read_memory`], which uses the native endianness, the bytes are always decoded as
+/// big-endian, e.g. for network buffers or file formats held in memory. No alignment is required.
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences a raw pointer, which could lead to undefined behavior if the pointer is invalid.
+/// 
+/// # Errors
+/// - Same as [`read_bytes`].
+/// 
+/// # Example
+/// ```
+/// use verity_memory::ops::read;
+/// let buffer = [0x12u8, 0x34, 0x56, 0x78];
+/// let value = unsafe { read::read_be::<u32>(buffer.as_ptr()) };
+/// assert_eq!(value, Ok(0x12345678));
+/// ```
+pub unsafe fn read_be<T: FromEndianBytes>(address: *const u8) -> Result<T, ReadMemoryError> {
+    let bytes = read_bytes(address, std::mem::size_of::<T>())?;
+    Ok(T::from_be_slice(&bytes))
+}
+
+/// Reads a little-endian integer from the specified memory address.
+/// 
+/// The bytes are always decoded as little-endian, regardless of the endianness of the host.
+/// No alignment is required.
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences a raw pointer, which could lead to undefined behavior if the pointer is invalid.
+/// 
+/// # Errors
+/// - Same as [`read_bytes`].
+/// 
+/// # Example
+/// ```
+/// use verity_memory::ops::read;
+/// let buffer = [0x78u8, 0x56, 0x34, 0x12];
+/// let value = unsafe { read::read_le::<u32>(buffer.as_ptr()) };
+/// assert_eq!(value, Ok(0x12345678));
+/// ```
+pub unsafe fn read_le<T: FromEndianBytes>(address: *const u8) -> Result<T, ReadMemoryError> {
+    let bytes = read_bytes(address, std::mem::size_of::<T>())?;
+    Ok(T::from_le_slice(&bytes))
+}
+
+/// Reads a 16-byte vector of four floats, such as an `__m128` position or velocity.
+/// 
+/// The whole vector is copied under a single protection change, instead of reading the four
+/// floats one by one.
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences a raw pointer, which could lead to undefined behavior if the pointer is invalid.
+/// 
+/// # Errors
+/// - `ReadMemoryError::InvalidAlignment`: If `address` is not 16-byte aligned.
+/// - Any other error returned by [`read_memory`].
+/// 
+/// # Example
+/// ```
+/// use verity_memory::ops::read;
+/// 
+/// #[repr(C, align(16))]
+/// struct Position([f32; 4]);
+/// 
+/// let position = Position([1.0, 2.0, 3.0, 0.0]);
+/// let value = unsafe { read::read_vec128(&position as *const Position as *const u8) };
+/// assert_eq!(value, Ok([1.0, 2.0, 3.0, 0.0]));
+/// ```
+pub unsafe fn read_vec128(address: *const u8) -> Result<[f32; 4], ReadMemoryError> {
+    read_memory(address as *const Vec128).map(|vector| vector.0)
+}
+
+/// Borrows a range of memory as a slice without copying it.
+/// 
+/// Unlike [`read_bytes`], the protection of the range is left untouched; instead the range is
+/// checked with `VirtualQuery` to be committed and accessible before the slice is created.
+/// 
+/// # Safety
+/// This function is `unsafe` because the returned slice is not backed by Rust ownership:
+/// - The caller picks the lifetime `'a`, and must ensure the memory stays committed for all of it.
+///   Using the slice after the range is freed or decommitted is undefined behavior.
+/// - The bytes may be changed by other threads or by the process being inspected while the slice
+///   is alive, which breaks the aliasing guarantees of `&[u8]`. Copy the bytes out (e.g. with
+///   [`read_bytes`]) if they must not change under you.
+/// - The range must be readable; a page made inaccessible after the check will still fault.
+/// 
+/// # Parameters
+/// - `address`: A raw pointer to the first byte of the range.
+/// - `len`: The number of bytes in the range.
+/// 
+/// # Returns
+/// - `Ok(&[u8])`: A slice over the range if it is committed.
+/// - `Err(ReadMemoryError)`: Returns an error if the pointer is null or the range is not accessible.
+/// 
+/// # Errors
+/// - `ReadMemoryError::NullPointer`: If the provided pointer is null.
+/// - `ReadMemoryError::InvalidAccess`: If any page of the range is not committed or not accessible.
+/// 
+/// # Example
+/// ```
+/// use verity_memory::ops::read;
+/// let buffer = [0x48u8, 0x8B, 0x05];
+/// let slice = unsafe { read::region_slice(buffer.as_ptr(), buffer.len()) };
+/// assert_eq!(slice, Ok(&buffer[..]));
+/// ```
+pub unsafe fn region_slice<'a>(address: *const u8, len: usize) -> Result<&'a [u8], ReadMemoryError> {
+    if address.is_null() {
+        return Err(ReadMemoryError::NullPointer);
+    }
+
+    if len == 0 {
+        return Ok(&[]);
+    }
+
+    if !is_committed(address, len) {
+        return Err(ReadMemoryError::InvalidAccess);
+    }
+
+    Ok(std::slice::from_raw_parts(address, len))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ops::protection::{alloc_test_page, MockProtection};
+    use winapi::um::memoryapi::{VirtualAlloc, VirtualFree};
+    use winapi::um::winnt::{MEM_COMMIT, MEM_RELEASE, PAGE_EXECUTE, PAGE_NOACCESS, PAGE_READONLY, PAGE_READWRITE};
 
     #[test]
     fn test_read_memory_valid() {
@@ -101,4 +752,399 @@ mod tests {
         let result = unsafe { read_memory(unaligned_ptr) };
         assert_eq!(result, Err(ReadMemoryError::InvalidAlignment));
     }
+
+    #[test]
+    fn test_read_bytes_valid() {
+        let data = [1u8, 2, 3, 4, 5];
+
+        let result = unsafe { read_bytes(data.as_ptr().add(1), 3) };
+        assert_eq!(result, Ok(vec![2, 3, 4]));
+    }
+
+    #[test]
+    fn test_read_bytes_null_pointer() {
+        let result = unsafe { read_bytes(std::ptr::null(), 4) };
+        assert_eq!(result, Err(ReadMemoryError::NullPointer));
+    }
+
+    #[test]
+    fn test_read_bytes_huge_length() {
+        let data = [1u8, 2, 3, 4];
+
+        let result = unsafe { read_bytes(data.as_ptr(), usize::MAX / 2) };
+        assert_eq!(result, Err(ReadMemoryError::InvalidAccess));
+    }
+
+    #[test]
+    fn test_read_bytes_no_access_page() {
+        let page = alloc_test_page(PAGE_READWRITE);
+        unsafe {
+            page.add(0x10).copy_from_nonoverlapping([0xDEu8, 0xAD].as_ptr(), 2);
+            Win32Protection.protect(page as LPVOID, 0x1000, PAGE_NOACCESS).unwrap();
+
+            assert_eq!(read_bytes(page.add(0x10), 2), Ok(vec![0xDE, 0xAD]));
+            assert_eq!(query(page).unwrap().Protect, PAGE_NOACCESS);
+            assert_eq!(read_bytes(page.add(0xFFF), 2), Err(ReadMemoryError::InvalidAccess));
+
+            VirtualFree(page as LPVOID, 0, MEM_RELEASE);
+        }
+    }
+
+    #[test]
+    fn test_read_into_reused_buffer() {
+        let first = [1u8, 2, 3, 4];
+        let second = [9u8, 8, 7, 6];
+        let mut buf = [0u8; 4];
+
+        assert_eq!(unsafe { read_into(first.as_ptr(), &mut buf) }, Ok(()));
+        assert_eq!(buf, first);
+
+        assert_eq!(unsafe { read_into(second.as_ptr(), &mut buf) }, Ok(()));
+        assert_eq!(buf, second);
+
+        assert_eq!(unsafe { read_into(std::ptr::null(), &mut buf) }, Err(ReadMemoryError::NullPointer));
+        assert_eq!(buf, second);
+    }
+
+    #[test]
+    fn test_read_into_spans_reserved_page() {
+        unsafe {
+            let base = VirtualAlloc(std::ptr::null_mut(), 0x2000, MEM_RESERVE, PAGE_READWRITE) as *mut u8;
+            assert!(!base.is_null());
+            VirtualAlloc(base as LPVOID, 0x1000, MEM_COMMIT, PAGE_READWRITE);
+
+            let mut buf = [0xAAu8; 0x20];
+            assert_eq!(read_into(base.add(0xFF0), &mut buf), Err(ReadMemoryError::InvalidAccess));
+            assert_eq!(buf, [0xAA; 0x20]);
+            assert_eq!(read_bytes(base.add(0xFF0), 0x20), Err(ReadMemoryError::InvalidAccess));
+
+            VirtualFree(base as LPVOID, 0, MEM_RELEASE);
+        }
+    }
+
+    #[test]
+    fn test_read_memory_with_restores_protection() {
+        let value = 42i32;
+        let provider = MockProtection::new(PAGE_READONLY, None);
+
+        let result = unsafe { read_memory_with(&value as *const i32, &provider) };
+        assert_eq!(result, Ok(42));
+        assert_eq!(provider.protections(), vec![PAGE_EXECUTE_READWRITE, PAGE_READONLY]);
+        assert_eq!(provider.current.get(), PAGE_READONLY);
+    }
+
+    #[test]
+    #[cfg(feature = "runtime")]
+    fn test_read_memory_with_restore_attempted_on_error() {
+        // The mock leaves the page inaccessible, so the read itself faults.
+        let page = unsafe { VirtualAlloc(std::ptr::null_mut(), 0x1000, MEM_COMMIT | MEM_RESERVE, PAGE_NOACCESS) };
+        assert!(!page.is_null());
+        let provider = MockProtection::new(PAGE_READONLY, None);
+
+        let result = unsafe { read_memory_with(page as *const i32, &provider) };
+        assert_eq!(result, Err(ReadMemoryError::InvalidAccess));
+        assert_eq!(provider.protections(), vec![PAGE_EXECUTE_READWRITE, PAGE_READONLY]);
+
+        unsafe { VirtualFree(page, 0, MEM_RELEASE) };
+    }
+
+    #[test]
+    fn test_read_memory_with_failed_restore() {
+        let value = 42i32;
+        let provider = MockProtection::new(PAGE_READONLY, Some(1));
+
+        let result = unsafe { read_memory_with(&value as *const i32, &provider) };
+        assert_eq!(result, Err(ReadMemoryError::FailedToRestoreProtection));
+        assert_eq!(provider.protections(), vec![PAGE_EXECUTE_READWRITE, PAGE_READONLY]);
+    }
+
+    #[test]
+    fn test_read_memory_with_failed_change_skips_restore() {
+        let value = 42i32;
+        let provider = MockProtection::new(PAGE_READONLY, Some(0));
+
+        let result = unsafe { read_memory_with(&value as *const i32, &provider) };
+        assert_eq!(result, Err(ReadMemoryError::FailedToChangeProtection));
+        assert_eq!(provider.protections(), vec![PAGE_EXECUTE_READWRITE]);
+    }
+
+    #[test]
+    fn test_region_slice_valid() {
+        let data = [1u8, 2, 3, 4, 5];
+
+        let result = unsafe { region_slice(data.as_ptr().add(1), 3) };
+        assert_eq!(result, Ok(&[2u8, 3, 4][..]));
+    }
+
+    #[test]
+    fn test_region_slice_execute_only() {
+        let page = alloc_test_page(PAGE_EXECUTE);
+
+        let result = unsafe { region_slice(page, 0x10) };
+        assert_eq!(result, Err(ReadMemoryError::InvalidAccess));
+
+        unsafe { VirtualFree(page as LPVOID, 0, MEM_RELEASE) };
+    }
+
+    #[test]
+    fn test_region_slice_unmapped() {
+        unsafe {
+            let page = VirtualAlloc(std::ptr::null_mut(), 0x1000, MEM_COMMIT | MEM_RESERVE, PAGE_READWRITE);
+            assert!(!page.is_null());
+            VirtualFree(page, 0, MEM_RELEASE);
+
+            let result = region_slice(page as *const u8, 0x10);
+            assert_eq!(result, Err(ReadMemoryError::InvalidAccess));
+        }
+    }
+
+    #[test]
+    fn test_read_bit() {
+        let flags = 0b1000_0001u8;
+
+        assert_eq!(unsafe { read_bit(&flags, 0) }, Ok(true));
+        assert_eq!(unsafe { read_bit(&flags, 1) }, Ok(false));
+        assert_eq!(unsafe { read_bit(&flags, 7) }, Ok(true));
+        assert_eq!(unsafe { read_bit(&flags, 8) }, Err(ReadMemoryError::InvalidBit));
+    }
+
+    #[test]
+    fn test_read_enum() {
+        #[repr(u32)]
+        #[derive(Debug, PartialEq)]
+        enum State {
+            Idle = 0,
+            Running = 1,
+            Stopped = 5,
+        }
+
+        impl TryFrom<u32> for State {
+            type Error = u32;
+
+            fn try_from(value: u32) -> Result<Self, u32> {
+                match value {
+                    0 => Ok(State::Idle),
+                    1 => Ok(State::Running),
+                    5 => Ok(State::Stopped),
+                    _ => Err(value),
+                }
+            }
+        }
+
+        let values = [0u32, 1, 5, 2, 0xDEAD_BEEF];
+
+        assert_eq!(unsafe { read_enum::<State>(&values[0]) }, Ok(State::Idle));
+        assert_eq!(unsafe { read_enum::<State>(&values[1]) }, Ok(State::Running));
+        assert_eq!(unsafe { read_enum::<State>(&values[2]) }, Ok(State::Stopped));
+        assert_eq!(unsafe { read_enum::<State>(&values[3]) }, Err(ReadMemoryError::InvalidDiscriminant(2)));
+        assert_eq!(
+            unsafe { read_enum::<State>(&values[4]) },
+            Err(ReadMemoryError::InvalidDiscriminant(0xDEAD_BEEF))
+        );
+        assert_eq!(unsafe { read_enum::<State>(std::ptr::null()) }, Err(ReadMemoryError::NullPointer));
+    }
+
+    #[test]
+    fn test_read_relative() {
+        let values = [0x11u32, 0x22, 0x33, 0x44];
+        let anchor = &values[2] as *const u32 as usize;
+
+        assert_eq!(unsafe { read_relative::<u32>(anchor, 4) }, Ok(0x44));
+        assert_eq!(unsafe { read_relative::<u32>(anchor, -8) }, Ok(0x11));
+        assert_eq!(unsafe { read_relative::<u32>(anchor, 0) }, Ok(0x33));
+        assert_eq!(unsafe { read_relative::<u8>(anchor, -4) }, Ok(0x22));
+    }
+
+    #[test]
+    fn test_read_relative_overflow() {
+        assert_eq!(unsafe { read_relative::<u32>(8, -16) }, Err(ReadMemoryError::InvalidAccess));
+        assert_eq!(unsafe { read_relative::<u32>(usize::MAX, 1) }, Err(ReadMemoryError::InvalidAccess));
+    }
+
+    #[test]
+    fn test_read_be_swaps_on_little_endian() {
+        let value: u32 = 0x1234_5678;
+        let ptr = &value as *const u32 as *const u8;
+
+        let result = unsafe { read_be::<u32>(ptr) };
+        assert_eq!(result, Ok(value.swap_bytes()));
+    }
+
+    #[test]
+    fn test_read_le_unaligned() {
+        let buffer = [0xFFu8, 0x34, 0x12, 0xFF];
+
+        let result = unsafe { read_le::<i16>(buffer.as_ptr().add(1)) };
+        assert_eq!(result, Ok(0x1234));
+    }
+
+    #[test]
+    fn test_read_memory_alignment_policies() {
+        let buffer = [0u8, 0x78, 0x56, 0x34, 0x12, 0];
+        let unaligned_ptr = unsafe { buffer.as_ptr().add(1) } as *const u32;
+
+        let strict = unsafe { read_memory_with_alignment(unaligned_ptr, AlignmentPolicy::Strict) };
+        assert_eq!(strict, Err(ReadMemoryError::InvalidAlignment));
+
+        let unaligned = unsafe { read_memory_with_alignment(unaligned_ptr, AlignmentPolicy::Unaligned) };
+        assert_eq!(unaligned, Ok(u32::from_ne_bytes([0x78, 0x56, 0x34, 0x12])));
+    }
+
+    #[test]
+    fn test_read_vec128() {
+        let vector = Vec128([1.5, -2.0, 3.25, 4.0]);
+        let ptr = &vector as *const Vec128 as *const u8;
+
+        assert_eq!(unsafe { read_vec128(ptr) }, Ok([1.5, -2.0, 3.25, 4.0]));
+        assert_eq!(unsafe { read_vec128(ptr.add(4)) }, Err(ReadMemoryError::InvalidAlignment));
+    }
+
+    #[test]
+    fn test_read_memory_reserved_region() {
+        let page = unsafe { VirtualAlloc(std::ptr::null_mut(), 0x1000, MEM_RESERVE, PAGE_NOACCESS) } as *const u32;
+        assert!(!page.is_null());
+
+        assert_eq!(unsafe { read_memory(page) }, Err(ReadMemoryError::RegionReserved));
+        assert_eq!(unsafe { read_bytes(page as *const u8, 4) }, Err(ReadMemoryError::RegionReserved));
+
+        unsafe { VirtualFree(page as LPVOID, 0, MEM_RELEASE) };
+        assert_eq!(unsafe { read_memory(page) }, Err(ReadMemoryError::RegionFree));
+    }
+
+    #[test]
+    fn test_try_read_code_ptr() {
+        let code_slot = test_try_read_code_ptr as fn() as usize;
+        let data = 42u32;
+        let data_slot = &data as *const u32 as usize;
+        let null_slot = 0usize;
+
+        assert_eq!(try_read_code_ptr(&code_slot as *const usize as usize), Some(code_slot));
+        assert_eq!(try_read_code_ptr(&data_slot as *const usize as usize), None);
+        assert_eq!(try_read_code_ptr(&null_slot as *const usize as usize), None);
+        assert_eq!(try_read_code_ptr(0), None);
+    }
+
+    #[test]
+    fn test_try_read_code_ptr_reserved_slot() {
+        let page = unsafe { VirtualAlloc(std::ptr::null_mut(), 0x1000, MEM_RESERVE, PAGE_NOACCESS) };
+        assert!(!page.is_null());
+
+        assert_eq!(try_read_code_ptr(page as usize), None);
+
+        unsafe { VirtualFree(page, 0, MEM_RELEASE) };
+    }
+
+    #[test]
+    fn test_read_prefixed_array() {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[repr(C)]
+        struct Entry {
+            id: u32,
+            amount: u16,
+            slot: u16,
+        }
+
+        let entries = [
+            Entry { id: 7, amount: 1, slot: 0 },
+            Entry { id: 42, amount: 99, slot: 1 },
+            Entry { id: 1337, amount: 5, slot: 2 },
+        ];
+
+        let mut layout = 3u32.to_ne_bytes().to_vec();
+        for entry in &entries {
+            layout.extend_from_slice(&entry.id.to_ne_bytes());
+            layout.extend_from_slice(&entry.amount.to_ne_bytes());
+            layout.extend_from_slice(&entry.slot.to_ne_bytes());
+        }
+
+        assert_eq!(unsafe { read_prefixed_array::<Entry>(layout.as_ptr(), 16) }, Ok(entries.to_vec()));
+        assert_eq!(unsafe { read_prefixed_array::<Entry>(layout.as_ptr(), 2) }, Ok(entries[..2].to_vec()));
+    }
+
+    #[test]
+    fn test_read_prefixed_array_corrupt_count() {
+        let mut layout = u32::MAX.to_ne_bytes().to_vec();
+        layout.extend_from_slice(&[1, 2, 3, 4]);
+
+        assert_eq!(unsafe { read_prefixed_array::<u8>(layout.as_ptr(), 4) }, Ok(vec![1, 2, 3, 4]));
+        assert_eq!(unsafe { read_prefixed_array::<u8>([0u8; 4].as_ptr(), 4) }, Ok(vec![]));
+        assert_eq!(unsafe { read_prefixed_array::<u8>(std::ptr::null(), 4) }, Err(ReadMemoryError::NullPointer));
+    }
+
+    #[test]
+    fn test_read_array_overflow() {
+        let buffer = [0u8; 8];
+        assert_eq!(unsafe { read_array::<u64>(buffer.as_ptr(), usize::MAX) }, Err(ReadMemoryError::InvalidAccess));
+    }
+
+    #[test]
+    fn test_read_array_zero_sized() {
+        let buffer = [0u8; 1];
+        assert_eq!(unsafe { read_array::<()>(buffer.as_ptr(), 3) }, Ok(vec![(); 3]));
+    }
+
+    #[test]
+    fn test_read_string_ptr_array() {
+        let first = b"alpha\0";
+        let second = b"beta\0";
+        let array = [first.as_ptr() as usize, second.as_ptr() as usize, 0];
+
+        let strings = unsafe { read_string_ptr_array(array.as_ptr(), 16) };
+        assert_eq!(strings, Ok(vec!["alpha".to_string(), "beta".to_string()]));
+
+        let limited = unsafe { read_string_ptr_array(array.as_ptr(), 1) };
+        assert_eq!(limited, Ok(vec!["alpha".to_string()]));
+    }
+
+    #[test]
+    fn test_read_string_ptr_array_null_mid_array() {
+        let first = b"alpha\0";
+        let third = b"gamma\0";
+        let array = [first.as_ptr() as usize, 0, third.as_ptr() as usize, 0];
+
+        let strings = unsafe { read_string_ptr_array(array.as_ptr(), 16) };
+        assert_eq!(strings, Ok(vec!["alpha".to_string()]));
+        assert_eq!(unsafe { read_string_ptr_array(std::ptr::null(), 16) }, Err(ReadMemoryError::NullPointer));
+    }
+
+    #[test]
+    fn test_read_c_string_unterminated_at_page_end() {
+        unsafe {
+            let page = VirtualAlloc(std::ptr::null_mut(), 0x1000, MEM_COMMIT | MEM_RESERVE, PAGE_READWRITE) as *mut u8;
+            assert!(!page.is_null());
+
+            std::ptr::write_bytes(page, b'A', 0x1000);
+            assert_eq!(read_c_string(page.add(0xFFC)), Err(ReadMemoryError::InvalidAccess));
+
+            *page.add(0xFFF) = 0;
+            assert_eq!(read_c_string(page.add(0xFFC)), Ok("AAA".to_string()));
+
+            VirtualFree(page as LPVOID, 0, MEM_RELEASE);
+        }
+    }
+
+    #[test]
+    fn test_read_memory_across_pages_restores_each_page() {
+        unsafe {
+            let pages = VirtualAlloc(std::ptr::null_mut(), 0x2000, MEM_COMMIT | MEM_RESERVE, PAGE_READWRITE) as *mut u8;
+            assert!(!pages.is_null());
+            std::ptr::write_unaligned(pages.add(0xFFE) as *mut u32, 0x12345678);
+
+            let mut old_protect = 0;
+            assert_ne!(winapi::um::memoryapi::VirtualProtect(pages.add(0x1000) as LPVOID, 0x1000, PAGE_NOACCESS, &mut old_protect), 0);
+
+            let straddling = pages.add(0xFFE) as *const u32;
+            assert_eq!(read_memory_with_alignment(straddling, AlignmentPolicy::Unaligned), Ok(0x12345678));
+            assert_eq!(query(pages).unwrap().Protect, PAGE_READWRITE);
+            assert_eq!(query(pages.add(0x1000)).unwrap().Protect, PAGE_NOACCESS);
+
+            let mut buf = [0u8; 4];
+            assert_eq!(read_into(pages.add(0xFFE), &mut buf), Ok(()));
+            assert_eq!(buf, 0x12345678u32.to_ne_bytes());
+            assert_eq!(query(pages).unwrap().Protect, PAGE_READWRITE);
+            assert_eq!(query(pages.add(0x1000)).unwrap().Protect, PAGE_NOACCESS);
+
+            VirtualFree(pages as LPVOID, 0, MEM_RELEASE);
+        }
+    }
 }
\ No newline at end of file
@@ -1,16 +1,23 @@
-use winapi::um::winnt::PAGE_EXECUTE_READWRITE;
-use winapi::{shared::minwindef::LPVOID, um::memoryapi::VirtualProtect};
+use winapi::um::processthreadsapi::FlushInstructionCache;
+use winapi::um::winnt::{PAGE_EXECUTE_READWRITE, PAGE_READWRITE, PAGE_WRITECOPY};
+use winapi::shared::minwindef::LPVOID;
 
 #[cfg(feature = "advanced-write")]
 use crate::macros::match_number::{FloatType, IntegerType, IntegralType, NumberType};
 #[cfg(feature = "advanced-write")]
-use crate::types::Instruction;
-use crate::{errors::WriteMemoryError, utils};
+use crate::types::{CallConv, Filler, Instruction, NopResult};
+use crate::{errors::WriteMemoryError, types::{vec128::Vec128, AlignmentPolicy, Protection}, utils};
 #[cfg(feature = "advanced-write")]
 use crate::match_number;
 
+use super::access::CurrentProcess;
+use super::protection::{restore_protections, spanned_protections, ProtectionProvider, Win32Protection};
+use super::query::{is_committed, is_copy_on_write};
+use super::read::read_memory_with;
+#[cfg(feature = "runtime")]
+use crate::runtime::guard::guarded_copy;
 #[cfg(feature = "advanced-write")]
-use super::asm::{float_ret, get_instruction, integer_ret, integral_ret};
+use super::asm::{float_ret, get_instruction, integer_ret, integral_ret, jump};
 
 /// Writes a value of type `T` to the specified memory location.
 ///
@@ -29,9 +36,16 @@ use super::asm::{float_ret, get_instruction, integer_ret, integral_ret};
 /// # Errors
 /// - `WriteMemoryError::NullPointer` if `dest_ptr` is null.
 /// - `WriteMemoryError::InvalidAlignment` if `dest_ptr` is not correctly aligned.
-/// - `WriteMemoryError::FailedToChangeProtection` if memory protection could not be modified.
+/// - `WriteMemoryError::FailedToChangeProtection` if none of the protections in
+///   [`WRITE_PROTECTION_ESCALATION`] could be applied.
 /// - `WriteMemoryError::FailedToRestoreProtection` if memory protection could not be restored.
 ///
+/// # Protection escalation
+/// The destination is first made `PAGE_EXECUTE_READWRITE`. Some pages refuse that, e.g. under
+/// arbitrary code guard or in mappings without execute access, so on failure the write falls back
+/// to `PAGE_READWRITE` and then `PAGE_WRITECOPY`, in the order of [`WRITE_PROTECTION_ESCALATION`],
+/// before giving up. The previous protection is restored afterwards whichever one succeeded.
+///
 /// # Example
 /// ```rust
 /// use verity_memory::ops::write;
@@ -43,6 +57,139 @@ use super::asm::{float_ret, get_instruction, integer_ret, integral_ret};
 /// }
 /// ```
 pub unsafe fn write_memory<T: Copy>(dest_ptr: *mut T, value: T) -> Result<(), WriteMemoryError> {
+    write_memory_with(dest_ptr, value, &Win32Protection)
+}
+
+/// Writes a value of type `T` to the specified memory location, changing protection through `provider`.
+///
+/// This behaves exactly like [`write_memory`], which uses [`Win32Protection`], but lets callers
+/// route the protection changes through an alternate [`ProtectionProvider`].
+///
+/// # Safety
+/// This function is unsafe because it directly manipulates raw pointers, which can cause undefined behavior
+/// if the pointer is invalid or points to memory that is not writable.
+///
+/// # Errors
+/// - Same as [`write_memory`].
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::{protection::Win32Protection, write};
+/// unsafe {
+///     let mut value: i32 = 42;
+///     let result = write::write_memory_with(&mut value as *mut i32, 100, &Win32Protection);
+///     assert!(result.is_ok());
+///     assert_eq!(value, 100);
+/// }
+/// ```
+pub unsafe fn write_memory_with<T: Copy, P: ProtectionProvider + ?Sized>(
+    dest_ptr: *mut T,
+    value: T,
+    provider: &P,
+) -> Result<(), WriteMemoryError> {
+    write_memory_impl(dest_ptr, value, provider, AlignmentPolicy::Strict).map(|_| ())
+}
+
+/// Writes a value of type `T` to the specified memory location and reads it back to confirm the
+/// write landed.
+///
+/// Some pages silently drop or redirect writes, e.g. mirrored or externally write-protected memory.
+/// Reading the value back catches those cases at the cost of an extra protection round trip.
+///
+/// # Safety
+/// This function is unsafe because it directly manipulates raw pointers, which can cause undefined behavior
+/// if the pointer is invalid or points to memory that is not writable.
+///
+/// # Errors
+/// - Same as [`write_memory`].
+/// - `WriteMemoryError::InvalidAccess` if the value could not be read back.
+/// - `WriteMemoryError::VerificationFailed` if the value read back differs from `value`.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::write;
+/// unsafe {
+///     let mut value: i32 = 42;
+///     let result = write::write_memory_verified(&mut value as *mut i32, 100);
+///     assert!(result.is_ok());
+///     assert_eq!(value, 100);
+/// }
+/// ```
+pub unsafe fn write_memory_verified<T: Copy + PartialEq>(dest_ptr: *mut T, value: T) -> Result<(), WriteMemoryError> {
+    write_memory_verified_with(dest_ptr, value, &Win32Protection)
+}
+
+/// Behaves like [`write_memory_verified`], but changes protection through `provider` for both the
+/// write and the read back.
+///
+/// # Safety
+/// See [`write_memory_verified`].
+///
+/// # Errors
+/// - Same as [`write_memory_verified`].
+pub unsafe fn write_memory_verified_with<T: Copy + PartialEq, P: ProtectionProvider + ?Sized>(
+    dest_ptr: *mut T,
+    value: T,
+    provider: &P,
+) -> Result<(), WriteMemoryError> {
+    write_memory_with(dest_ptr, value, provider)?;
+
+    let written = read_memory_with(dest_ptr as *const T, provider).map_err(|_| WriteMemoryError::InvalidAccess)?;
+    if written != value {
+        return Err(WriteMemoryError::VerificationFailed);
+    }
+
+    Ok(())
+}
+
+/// Writes a value of type `T` to the specified memory location, returning an error instead of
+/// crashing if the store faults.
+///
+/// [`write_memory`] makes the destination writable first, but the store can still fault, e.g. when
+/// a driver or another thread changes the protection in between, or the page is backed by a file
+/// that can't be paged in. Here the store runs under a vectored exception handler that turns the
+/// access violation into `WriteMemoryError::InvalidAccess`. The previous protection is restored
+/// either way.
+///
+/// The value is copied byte by byte, so unlike [`write_memory`] the store is not a single
+/// instruction, and a store that faults halfway may have written the bytes before the faulting one.
+///
+/// # Safety
+/// This function is unsafe because it directly manipulates raw pointers, which can cause undefined behavior
+/// if the pointer is invalid.
+///
+/// # Errors
+/// - Same as [`write_memory`].
+/// - `WriteMemoryError::InvalidAccess` if the store faulted.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::write;
+/// unsafe {
+///     let mut value: i32 = 42;
+///     let result = write::write_memory_guarded(&mut value as *mut i32, 100);
+///     assert!(result.is_ok());
+///     assert_eq!(value, 100);
+/// }
+/// ```
+#[cfg(feature = "runtime")]
+pub unsafe fn write_memory_guarded<T: Copy>(dest_ptr: *mut T, value: T) -> Result<(), WriteMemoryError> {
+    write_memory_guarded_with(dest_ptr, value, &Win32Protection)
+}
+
+/// Behaves like [`write_memory_guarded`], but changes protection through `provider`.
+///
+/// # Safety
+/// See [`write_memory_guarded`].
+///
+/// # Errors
+/// - Same as [`write_memory_guarded`].
+#[cfg(feature = "runtime")]
+pub unsafe fn write_memory_guarded_with<T: Copy, P: ProtectionProvider + ?Sized>(
+    dest_ptr: *mut T,
+    value: T,
+    provider: &P,
+) -> Result<(), WriteMemoryError> {
     if dest_ptr.is_null() {
         return Err(WriteMemoryError::NullPointer);
     }
@@ -51,30 +198,527 @@ pub unsafe fn write_memory<T: Copy>(dest_ptr: *mut T, value: T) -> Result<(), Wr
         return Err(WriteMemoryError::InvalidAlignment);
     }
 
-    let mut old_protect = 0;
     let size = std::mem::size_of::<T>();
+    let regions = spanned_protections(provider, dest_ptr as usize, size);
+
+    let (old_protect, _) = protect_for_write(provider, dest_ptr as LPVOID, size)
+        .ok_or(WriteMemoryError::FailedToChangeProtection)?;
+
+    let written = guarded_copy(dest_ptr as *mut u8, &value as *const T as *const u8, size);
+
+    if !restore_protections(provider, dest_ptr as LPVOID, size, old_protect, &regions) {
+        return Err(WriteMemoryError::FailedToRestoreProtection);
+    }
 
-    let res = VirtualProtect(
-        dest_ptr as LPVOID,
-        size,
-        PAGE_EXECUTE_READWRITE,
-        &mut old_protect,
-    );
-    if res == 0 {
-        return Err(WriteMemoryError::FailedToChangeProtection);
+    if written {
+        Ok(())
+    } else {
+        Err(WriteMemoryError::InvalidAccess)
     }
+}
 
-    *dest_ptr = value;
+/// Writes a value of type `T` to the specified memory location only if it holds a different value.
+///
+/// Freeze loops rewrite the same value over and over, and every [`write_memory`] toggles the
+/// protection of the destination twice. The current value is read first, without touching the
+/// protection when the destination is readable as is, and the write is skipped when it already
+/// equals `value`.
+///
+/// # Safety
+/// This function is unsafe because it directly manipulates raw pointers, which can cause undefined behavior
+/// if the pointer is invalid or points to memory that is not writable.
+///
+/// # Returns
+/// - `Ok(true)` if the value differed and was written.
+/// - `Ok(false)` if the destination already held `value`.
+///
+/// # Errors
+/// - Same as [`write_memory`].
+/// - `WriteMemoryError::InvalidAccess` if the current value could not be read.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::write;
+/// unsafe {
+///     let mut value: i32 = 42;
+///     assert_eq!(write::write_if_changed(&mut value as *mut i32, 100), Ok(true));
+///     assert_eq!(write::write_if_changed(&mut value as *mut i32, 100), Ok(false));
+///     assert_eq!(value, 100);
+/// }
+/// ```
+pub unsafe fn write_if_changed<T: Copy + PartialEq>(dest_ptr: *mut T, value: T) -> Result<bool, WriteMemoryError> {
+    write_if_changed_with(dest_ptr, value, &Win32Protection)
+}
+
+/// Behaves like [`write_if_changed`], but changes protection through `provider`.
+///
+/// # Safety
+/// See [`write_if_changed`].
+///
+/// # Errors
+/// - Same as [`write_if_changed`].
+pub unsafe fn write_if_changed_with<T: Copy + PartialEq, P: ProtectionProvider + ?Sized>(
+    dest_ptr: *mut T,
+    value: T,
+    provider: &P,
+) -> Result<bool, WriteMemoryError> {
+    if dest_ptr.is_null() {
+        return Err(WriteMemoryError::NullPointer);
+    }
+
+    if !utils::check_alignment(dest_ptr) {
+        return Err(WriteMemoryError::InvalidAlignment);
+    }
+
+    let current = if is_committed(dest_ptr as *const u8, std::mem::size_of::<T>()) {
+        std::ptr::read_volatile(dest_ptr)
+    } else {
+        read_memory_with(dest_ptr as *const T, provider).map_err(|_| WriteMemoryError::InvalidAccess)?
+    };
+
+    if current == value {
+        return Ok(false);
+    }
+
+    write_memory_impl(dest_ptr, value, provider, AlignmentPolicy::Strict)?;
+    Ok(true)
+}
 
-    let res_restore = VirtualProtect(dest_ptr as LPVOID, size, old_protect, &mut old_protect);
-    if res_restore == 0 {
+/// Writes a value of type `T` to the specified memory location, checking its alignment according to `policy`.
+///
+/// With `AlignmentPolicy::Strict` this behaves exactly like [`write_memory`]. With
+/// `AlignmentPolicy::Unaligned` misaligned pointers are accepted and written with `write_unaligned`,
+/// e.g. for fields of packed structs.
+///
+/// # Safety
+/// This function is unsafe because it directly manipulates raw pointers, which can cause undefined behavior
+/// if the pointer is invalid or points to memory that is not writable.
+///
+/// # Errors
+/// - Same as [`write_memory`], except that `WriteMemoryError::InvalidAlignment` is only returned under `AlignmentPolicy::Strict`.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::{ops::write, types::AlignmentPolicy};
+/// unsafe {
+///     let mut buffer = [0u8; 5];
+///     let address = buffer.as_mut_ptr().add(1) as *mut u32;
+///     let result = write::write_memory_with_alignment(address, 0x12345678, AlignmentPolicy::Unaligned);
+///     assert!(result.is_ok());
+///     assert_eq!(&buffer[1..], &0x12345678u32.to_ne_bytes());
+/// }
+/// ```
+pub unsafe fn write_memory_with_alignment<T: Copy>(
+    dest_ptr: *mut T,
+    value: T,
+    policy: AlignmentPolicy,
+) -> Result<(), WriteMemoryError> {
+    write_memory_impl(dest_ptr, value, &Win32Protection, policy).map(|_| ())
+}
+
+/// The protections tried in turn to make a destination writable, see [`write_memory`].
+pub const WRITE_PROTECTION_ESCALATION: [u32; 3] = [PAGE_EXECUTE_READWRITE, PAGE_READWRITE, PAGE_WRITECOPY];
+
+/// Makes `size` bytes at `address` writable, trying the protections of
+/// [`WRITE_PROTECTION_ESCALATION`] in order.
+///
+/// Returns the previous protection and the protection that was applied.
+pub(crate) unsafe fn protect_for_write<P: ProtectionProvider + ?Sized>(
+    provider: &P,
+    address: LPVOID,
+    size: usize,
+) -> Option<(u32, u32)> {
+    WRITE_PROTECTION_ESCALATION
+        .iter()
+        .find_map(|&protection| provider.protect(address, size, protection).map(|old| (old, protection)))
+}
+
+/// Writes `value` to `dest_ptr` under a protection from [`protect_for_write`].
+///
+/// Returns the protection that was applied for the write.
+unsafe fn write_memory_impl<T: Copy, P: ProtectionProvider + ?Sized>(
+    dest_ptr: *mut T,
+    value: T,
+    provider: &P,
+    policy: AlignmentPolicy,
+) -> Result<u32, WriteMemoryError> {
+    if dest_ptr.is_null() {
+        return Err(WriteMemoryError::NullPointer);
+    }
+
+    if policy == AlignmentPolicy::Strict && !utils::check_alignment(dest_ptr) {
+        return Err(WriteMemoryError::InvalidAlignment);
+    }
+
+    let size = std::mem::size_of::<T>();
+    let regions = spanned_protections(provider, dest_ptr as usize, size);
+
+    let (old_protect, applied) = protect_for_write(provider, dest_ptr as LPVOID, size)
+        .ok_or(WriteMemoryError::FailedToChangeProtection)?;
+
+    match policy {
+        AlignmentPolicy::Strict => *dest_ptr = value,
+        AlignmentPolicy::Unaligned => std::ptr::write_unaligned(dest_ptr, value),
+    }
+
+    if !restore_protections(provider, dest_ptr as LPVOID, size, old_protect, &regions) {
+        return Err(WriteMemoryError::FailedToRestoreProtection);
+    }
+    Ok(applied)
+}
+
+/// Writes a 16-byte vector of four floats, such as an `__m128` position or velocity.
+///
+/// The whole vector is written under a single protection change, instead of writing the four
+/// floats one by one.
+///
+/// # Safety
+/// This function is unsafe because it directly manipulates raw pointers, which can cause undefined behavior
+/// if the pointer is invalid or points to memory that is not writable.
+///
+/// # Errors
+/// - `WriteMemoryError::InvalidAlignment`: If `dest_ptr` is not 16-byte aligned.
+/// - Any other error returned by [`write_memory`].
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::write;
+///
+/// #[repr(C, align(16))]
+/// struct Position([f32; 4]);
+///
+/// unsafe {
+///     let mut position = Position([0.0; 4]);
+///     let result = write::write_vec128(&mut position as *mut Position as *mut u8, [1.0, 2.0, 3.0, 0.0]);
+///     assert!(result.is_ok());
+///     assert_eq!(position.0, [1.0, 2.0, 3.0, 0.0]);
+/// }
+/// ```
+pub unsafe fn write_vec128(dest_ptr: *mut u8, value: [f32; 4]) -> Result<(), WriteMemoryError> {
+    write_memory(dest_ptr as *mut Vec128, Vec128(value))
+}
+
+/// Sets or clears a single bit of the byte at the specified memory location, leaving the other
+/// bits untouched.
+///
+/// The byte is read, masked and written back under a single protection change.
+///
+/// # Safety
+/// This function is unsafe because it directly manipulates raw pointers, which can cause undefined behavior
+/// if the pointer is invalid or points to memory that is not writable.
+///
+/// # Parameters
+/// - `dest_ptr`: A mutable pointer to the byte containing the bit.
+/// - `bit`: The index of the bit, from 0 (least significant) to 7.
+/// - `value`: Whether to set (`true`) or clear (`false`) the bit.
+///
+/// # Errors
+/// - `WriteMemoryError::InvalidBit` if `bit` is not below 8.
+/// - `WriteMemoryError::NullPointer` if `dest_ptr` is null.
+/// - `WriteMemoryError::FailedToChangeProtection` if none of the protections in
+///   [`WRITE_PROTECTION_ESCALATION`] could be applied.
+/// - `WriteMemoryError::FailedToRestoreProtection` if memory protection could not be restored.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::write;
+/// unsafe {
+///     let mut flags = 0b0000_0001u8;
+///     assert!(write::write_bit(&mut flags, 3, true).is_ok());
+///     assert!(write::write_bit(&mut flags, 0, false).is_ok());
+///     assert_eq!(flags, 0b0000_1000);
+/// }
+/// ```
+pub unsafe fn write_bit(dest_ptr: *mut u8, bit: u8, value: bool) -> Result<(), WriteMemoryError> {
+    if bit >= 8 {
+        return Err(WriteMemoryError::InvalidBit);
+    }
+
+    if dest_ptr.is_null() {
+        return Err(WriteMemoryError::NullPointer);
+    }
+
+    let provider = Win32Protection;
+    let (old_protect, _) = protect_for_write(&provider, dest_ptr as LPVOID, 1)
+        .ok_or(WriteMemoryError::FailedToChangeProtection)?;
+
+    let mask = 1u8 << bit;
+    let byte = std::ptr::read_volatile(dest_ptr);
+    std::ptr::write_volatile(dest_ptr, if value { byte | mask } else { byte & !mask });
+
+    if !restore_protections(&provider, dest_ptr as LPVOID, 1, old_protect, &[]) {
         return Err(WriteMemoryError::FailedToRestoreProtection);
     }
 
     Ok(())
 }
 
-/// Replaces a specified number of instructions at a memory location with NOPs (0x90).
+/// Writes a slice of bytes to the specified memory location under a single protection change.
+///
+/// # Safety
+/// This function is unsafe because it directly manipulates raw pointers, which can cause undefined behavior
+/// if the pointer is invalid or the destination range is not writable.
+///
+/// # Parameters
+/// - `dest_ptr`: A mutable pointer to the first byte of the destination memory.
+/// - `bytes`: The bytes to write, starting at `dest_ptr`.
+///
+/// # Returns
+/// - `Ok(())` if all bytes were successfully written to memory.
+/// - `Err(WriteMemoryError)` if an error occurred, such as a null pointer.
+///
+/// # Errors
+/// - `WriteMemoryError::NullPointer` if `dest_ptr` is null.
+/// - `WriteMemoryError::FailedToChangeProtection` if none of the protections in
+///   [`WRITE_PROTECTION_ESCALATION`] could be applied.
+/// - `WriteMemoryError::FailedToRestoreProtection` if memory protection could not be restored.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::write;
+/// unsafe {
+///     let mut buffer = [0u8; 4];
+///     let result = write::write_bytes(buffer.as_mut_ptr(), &[0xDE, 0xAD, 0xBE, 0xEF]);
+///     assert!(result.is_ok());
+///     assert_eq!(buffer, [0xDE, 0xAD, 0xBE, 0xEF]);
+/// }
+/// ```
+pub unsafe fn write_bytes(dest_ptr: *mut u8, bytes: &[u8]) -> Result<(), WriteMemoryError> {
+    write_bytes_with(dest_ptr, bytes, &Win32Protection)
+}
+
+/// Writes a slice of bytes to the specified memory location, changing protection through `provider`.
+///
+/// This behaves exactly like [`write_bytes`], which uses [`Win32Protection`].
+///
+/// # Safety
+/// See [`write_bytes`].
+///
+/// # Errors
+/// - Same as [`write_bytes`].
+pub unsafe fn write_bytes_with<P: ProtectionProvider + ?Sized>(
+    dest_ptr: *mut u8,
+    bytes: &[u8],
+    provider: &P,
+) -> Result<(), WriteMemoryError> {
+    if dest_ptr.is_null() {
+        return Err(WriteMemoryError::NullPointer);
+    }
+
+    if bytes.is_empty() {
+        return Ok(());
+    }
+
+    let size = bytes.len();
+    let regions = spanned_protections(provider, dest_ptr as usize, size);
+
+    let (old_protect, _) = protect_for_write(provider, dest_ptr as LPVOID, size)
+        .ok_or(WriteMemoryError::FailedToChangeProtection)?;
+
+    std::ptr::copy_nonoverlapping(bytes.as_ptr(), dest_ptr, size);
+
+    if !restore_protections(provider, dest_ptr as LPVOID, size, old_protect, &regions) {
+        return Err(WriteMemoryError::FailedToRestoreProtection);
+    }
+
+    Ok(())
+}
+
+/// A configurable write of raw bytes, for when the defaults of [`write_bytes`] don't fit.
+///
+/// By default the destination is made writable with the protections of
+/// [`WRITE_PROTECTION_ESCALATION`], its previous protection is restored afterwards and the
+/// instruction cache is left alone, which matches [`write_bytes`].
+///
+/// Writes to copy-on-write pages (`PAGE_WRITECOPY`, common for the code of shared DLLs) are allowed
+/// by default like they are everywhere else, and give the current process a private copy of the
+/// page rather than modifying the shared mapping. Disable [`WriteBuilder::allow_copy_on_write`] to
+/// get `WriteMemoryError::CopyOnWrite` instead and decide how to proceed.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::write::WriteBuilder;
+/// use verity_memory::types::Protection;
+///
+/// let mut buffer = [0u8; 4];
+/// let result = unsafe {
+///     WriteBuilder::new()
+///         .target_protection(Protection::ReadWrite)
+///         .flush_icache(true)
+///         .write(buffer.as_mut_ptr(), &[0xDE, 0xAD, 0xBE, 0xEF])
+/// };
+/// assert!(result.is_ok());
+/// assert_eq!(buffer, [0xDE, 0xAD, 0xBE, 0xEF]);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct WriteBuilder {
+    flush_icache: bool,
+    target_protection: Option<Protection>,
+    restore_protection: bool,
+    allow_copy_on_write: bool,
+}
+
+impl Default for WriteBuilder {
+    fn default() -> Self {
+        WriteBuilder {
+            flush_icache: false,
+            target_protection: None,
+            restore_protection: true,
+            allow_copy_on_write: true,
+        }
+    }
+}
+
+impl WriteBuilder {
+    /// Creates a builder with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether to flush the instruction cache for the written range, needed when patching code
+    /// that may already have been executed.
+    pub fn flush_icache(mut self, flush: bool) -> Self {
+        self.flush_icache = flush;
+        self
+    }
+
+    /// The protection applied to the destination for the write, instead of escalating through
+    /// [`WRITE_PROTECTION_ESCALATION`]. It must be writable, and is not escalated if refused.
+    pub fn target_protection(mut self, protection: Protection) -> Self {
+        self.target_protection = Some(protection);
+        self
+    }
+
+    /// Whether to restore the previous protection after the write. When disabled, the destination
+    /// keeps the target protection.
+    pub fn restore_protection(mut self, restore: bool) -> Self {
+        self.restore_protection = restore;
+        self
+    }
+
+    /// Whether to write to copy-on-write pages. When disabled, the write is refused with
+    /// `WriteMemoryError::CopyOnWrite` before anything is modified.
+    pub fn allow_copy_on_write(mut self, allow: bool) -> Self {
+        self.allow_copy_on_write = allow;
+        self
+    }
+
+    /// Writes `bytes` to `dest_ptr` with the configured behavior.
+    ///
+    /// # Safety
+    /// This function is unsafe because it directly manipulates raw pointers, which can cause undefined behavior
+    /// if the pointer is invalid or the destination range is not writable.
+    ///
+    /// # Errors
+    /// - `WriteMemoryError::NullPointer` if `dest_ptr` is null.
+    /// - `WriteMemoryError::InvalidAccess` if the target protection is not writable.
+    /// - `WriteMemoryError::CopyOnWrite` if the destination is copy-on-write and that isn't allowed.
+    /// - `WriteMemoryError::FailedToChangeProtection` if memory protection could not be modified.
+    /// - `WriteMemoryError::FailedToRestoreProtection` if memory protection could not be restored.
+    /// - `WriteMemoryError::FailedToFlushInstructionCache` if the instruction cache could not be flushed.
+    pub unsafe fn write(&self, dest_ptr: *mut u8, bytes: &[u8]) -> Result<(), WriteMemoryError> {
+        self.write_with(dest_ptr, bytes, &Win32Protection)
+    }
+
+    /// Behaves like [`WriteBuilder::write`], but changes the protection through `provider`.
+    ///
+    /// # Safety
+    /// See [`WriteBuilder::write`].
+    pub unsafe fn write_with<P: ProtectionProvider + ?Sized>(
+        &self,
+        dest_ptr: *mut u8,
+        bytes: &[u8],
+        provider: &P,
+    ) -> Result<(), WriteMemoryError> {
+        if dest_ptr.is_null() {
+            return Err(WriteMemoryError::NullPointer);
+        }
+
+        if self.target_protection.is_some_and(|protection| !protection.is_writable()) {
+            return Err(WriteMemoryError::InvalidAccess);
+        }
+
+        if bytes.is_empty() {
+            return Ok(());
+        }
+
+        if !self.allow_copy_on_write && is_copy_on_write(dest_ptr) {
+            return Err(WriteMemoryError::CopyOnWrite);
+        }
+
+        let size = bytes.len();
+        let regions = if self.restore_protection {
+            spanned_protections(provider, dest_ptr as usize, size)
+        } else {
+            Vec::new()
+        };
+
+        let old_protect = match self.target_protection {
+            Some(protection) => provider.protect(dest_ptr as LPVOID, size, protection.flags()),
+            None => protect_for_write(provider, dest_ptr as LPVOID, size).map(|(old, _)| old),
+        }
+        .ok_or(WriteMemoryError::FailedToChangeProtection)?;
+
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), dest_ptr, size);
+
+        if self.restore_protection && !restore_protections(provider, dest_ptr as LPVOID, size, old_protect, &regions) {
+            return Err(WriteMemoryError::FailedToRestoreProtection);
+        }
+
+        if self.flush_icache
+            && FlushInstructionCache(CurrentProcess.handle(), dest_ptr as LPVOID, size) == 0
+        {
+            return Err(WriteMemoryError::FailedToFlushInstructionCache);
+        }
+
+        Ok(())
+    }
+}
+
+/// Replaces a specified number of instructions at a memory location with NOPs.
+///
+/// This is a thin wrapper around [`fill_instructions`] using [`Filler::Nop`], so the span is
+/// filled with the recommended multi-byte NOP encodings.
+///
+/// # Safety
+/// This function is unsafe because it directly modifies memory, which can corrupt the process
+/// if the memory is not writable or if the replaced instructions are critical.
+///
+/// # Parameters
+/// - `dest_ptr`: A mutable pointer to the memory location where instructions will be replaced.
+/// - `num_instructions`: The number of instructions to replace with NOPs.
+///
+/// # Returns
+/// - `Ok(NopResult)` containing the original instructions and their total size if successful.
+/// - `Err(WriteMemoryError)` if the instructions could not be decoded or overwritten.
+///
+/// # Errors
+/// - Same as [`fill_instructions`].
+///
+/// # Example
+/// ```rust
+/// use verity_memory::ops::write;
+/// unsafe {
+///     let buffer = vec![0x55, 0x48, 0x89, 0xE5]; // Some sample machine code (push rbp; mov rbp, rsp)
+///     let result = write::nop_instructions(buffer.as_ptr() as *mut u8, 2).unwrap();
+///     assert_eq!(result.originals.len(), 2);
+///     assert_eq!(result.total_bytes, 4);
+/// }
+/// ```
+#[cfg(feature = "advanced-write")]
+pub unsafe fn nop_instructions(dest_ptr: *mut u8, num_instructions: usize) -> Result<NopResult, WriteMemoryError> {
+    fill_instructions(dest_ptr, num_instructions, Filler::Nop)
+}
+
+/// The maximum number of instructions [`fill_instructions`] and [`nop_instructions`] replace at
+/// once. Larger counts are almost certainly a bug and would disassemble far past the target.
+#[cfg(feature = "advanced-write")]
+pub const MAX_FILL_INSTRUCTIONS: usize = 256;
+
+/// Replaces a specified number of instructions at a memory location with the given filler.
+///
+/// Each instruction is filled on its own, so a multi-byte NOP never spans the boundary between
+/// two of the original instructions. The whole span is written under a single protection change.
 ///
 /// # Safety
 /// This function is unsafe because it directly modifies memory, which can corrupt the process
@@ -82,61 +726,73 @@ pub unsafe fn write_memory<T: Copy>(dest_ptr: *mut T, value: T) -> Result<(), Wr
 ///
 /// # Parameters
 /// - `dest_ptr`: A mutable pointer to the memory location where instructions will be replaced.
-/// - `num_instructions`: The number of instructions to replace with NOPs.
+/// - `num_instructions`: The number of instructions to replace.
+/// - `filler`: The bytes written over the instructions (`Nop`, `Int3` or a `Custom` byte).
 ///
 /// # Returns
-/// - `Some(Vec<Instruction>)` containing the original instructions if successful.
-/// - `None` if it failed to read instructions or write memory.
+/// - `Ok(NopResult)` containing the original instructions and their total size if successful.
+/// - `Err(WriteMemoryError)` if the instructions could not be decoded or overwritten.
+///
+/// # Errors
+/// - `WriteMemoryError::NullPointer` if `dest_ptr` is null.
+/// - `WriteMemoryError::InvalidInstructionCount` if `num_instructions` is 0 or above
+///   [`MAX_FILL_INSTRUCTIONS`].
+/// - `WriteMemoryError::InvalidInstruction` if one of the instructions could not be decoded.
+/// - Any error returned by [`write_bytes`] when writing the filler.
 ///
 /// # Example
 /// ```rust
 /// use verity_memory::ops::write;
+/// use verity_memory::types::Filler;
 /// unsafe {
-///     let buffer = vec![0x55, 0x48, 0x89, 0xE5]; // Some sample machine code (push rbp; mov rbp, rsp)
-///     let original_instructions = write::nop_instructions(buffer.as_ptr() as *mut u8, 2);
-///     assert!(original_instructions.is_some());
+///     let buffer = vec![0x55, 0x48, 0x89, 0xE5]; // push rbp; mov rbp, rsp
+///     let original_instructions = write::fill_instructions(buffer.as_ptr() as *mut u8, 2, Filler::Int3);
+///     assert!(original_instructions.is_ok());
+///     assert_eq!(buffer, vec![0xCC; 4]);
 /// }
 /// ```
 #[cfg(feature = "advanced-write")]
-pub unsafe fn nop_instructions(dest_ptr: *mut u8, num_instructions: usize) -> Option<Vec<Instruction>> {
-    let mut instructions = Vec::new();
-    let mut current_ptr = dest_ptr;
+pub unsafe fn fill_instructions(
+    dest_ptr: *mut u8,
+    num_instructions: usize,
+    filler: Filler,
+) -> Result<NopResult, WriteMemoryError> {
+    if dest_ptr.is_null() {
+        return Err(WriteMemoryError::NullPointer);
+    }
 
-    if num_instructions <= 0 {
-        panic!("You must nop at least one instruction...");
+    if num_instructions == 0 || num_instructions > MAX_FILL_INSTRUCTIONS {
+        return Err(WriteMemoryError::InvalidInstructionCount(num_instructions));
     }
 
+    let mut instructions = Vec::new();
+    let mut current_ptr = dest_ptr;
+
     for _ in 0..num_instructions {
-        if let Some(instr) = get_instruction(current_ptr, 16) {
-            instructions.push(instr.clone());
-            current_ptr = current_ptr.add(instr.size);
-        } else {
-            eprintln!("Failed to get instruction at memory address: {:?}", current_ptr);
-            return None;
-        }
+        let instr = get_instruction(current_ptr, 16).ok_or(WriteMemoryError::InvalidInstruction)?;
+        current_ptr = current_ptr.add(instr.size);
+        instructions.push(instr);
     }
 
-    let total_size: usize = instructions.iter().map(|instr| instr.size).sum();
+    let result = NopResult::new(instructions);
 
-    let nops = vec![0x90; total_size];
-    let mut written_size = 0;
-    for i in 0..num_instructions {
-        let instruction = &instructions[i];
-        for j in 0..instruction.size {
-            let res = write_memory(dest_ptr.add(written_size + j), nops[written_size + j]);
-            if let Err(e) = res {
-                eprintln!("Failed to write memory at offset {}: {:?}", written_size + j, e);
-                return None;
-            }
-        }
-        written_size += instruction.size;
-    }
+    // One chunk per original instruction, so code jumping to any of the old instruction
+    // boundaries still lands on the start of a NOP.
+    let fill: Vec<u8> = result
+        .originals
+        .iter()
+        .flat_map(|instr| filler.bytes(instr.size))
+        .collect();
+    write_bytes(dest_ptr, &fill)?;
 
-    Some(instructions)
+    Ok(result)
 }
 
 /// Replaces the return value of a function with a specified value or inserts a `RET` instruction.
 ///
+/// The function is assumed to use the platform ABI; see [`replace_return_value_with_conv`] for
+/// functions using another calling convention.
+///
 /// # Safety
 /// This function is unsafe because it directly modifies memory, which can cause undefined behavior
 /// if the memory is not writable or if the return value type is not correctly handled.
@@ -146,8 +802,11 @@ pub unsafe fn nop_instructions(dest_ptr: *mut u8, num_instructions: usize) -> Op
 /// - `return_value`: An optional value to return. If `None`, a `RET` instruction is written instead.
 ///
 /// # Returns
-/// - `Some(Instruction)` containing the original instruction if successful.
-/// - `None` if an error occurred during instruction writing.
+/// - `Ok(Instruction)` containing the original instruction if successful.
+/// - `Err(WriteMemoryError)` if the instruction could not be decoded or overwritten.
+///
+/// # Errors
+/// - Same as [`replace_return_value_with_conv`].
 ///
 /// # Example
 /// ```rust
@@ -155,57 +814,163 @@ pub unsafe fn nop_instructions(dest_ptr: *mut u8, num_instructions: usize) -> Op
 /// unsafe {
 ///     let buffer = vec![0x55, 0x48, 0x89, 0xE5]; // Example machine code
 ///     let result = write::replace_return_value::<i32>(buffer.as_ptr() as *mut u8, Some(123));
-///     assert!(result.is_some());
+///     assert!(result.is_ok());
 /// }
 /// ```
 #[cfg(feature = "advanced-write")]
 pub unsafe fn replace_return_value<T: Copy + 'static>(
     dest_ptr: *mut u8,
     return_value: Option<T>,
-) -> Option<Instruction> {
-    let original_instruction = get_instruction(dest_ptr, 16)?;
+) -> Result<Instruction, WriteMemoryError> {
+    replace_return_value_with_conv(dest_ptr, return_value, CallConv::Platform)
+}
+
+/// Replaces the return value of a function using the given calling convention.
+///
+/// The convention decides the register the value is returned in (`ST0` instead of `xmm0` for
+/// floats on x86, `edx:eax` for 64-bit integers on x86) and the stack cleanup of the return
+/// instruction, e.g. `ret 8` for a `stdcall` function taking two 32-bit arguments.
+///
+/// # Safety
+/// This function is unsafe because it directly modifies memory, which can cause undefined behavior
+/// if the memory is not writable or if the calling convention does not match the function.
+///
+/// # Parameters
+/// - `dest_ptr`: A mutable pointer to the function's first instruction.
+/// - `return_value`: An optional value to return. If `None`, only the return instruction is written.
+/// - `conv`: The calling convention of the function.
+///
+/// # Returns
+/// - `Ok(Instruction)` containing the original instruction if successful.
+/// - `Err(WriteMemoryError)` if the instruction could not be decoded or overwritten.
+///
+/// # Errors
+/// - `WriteMemoryError::NullPointer` if `dest_ptr` is null.
+/// - `WriteMemoryError::InvalidInstruction` if the first instruction could not be decoded.
+/// - `WriteMemoryError::UnsupportedType` if `T` is not an integer, float or integral type.
+/// - Any error returned by [`write_bytes`] when writing the return sequence.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::{ops::write, types::CallConv};
+/// unsafe {
+///     let buffer = vec![0x55, 0x8B, 0xEC, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90, 0x90];
+///     let result = write::replace_return_value_with_conv(
+///         buffer.as_ptr() as *mut u8,
+///         Some(1_u32),
+///         CallConv::Stdcall(8),
+///     );
+///     assert!(result.is_ok());
+/// }
+/// ```
+#[cfg(feature = "advanced-write")]
+pub unsafe fn replace_return_value_with_conv<T: Copy + 'static>(
+    dest_ptr: *mut u8,
+    return_value: Option<T>,
+    conv: CallConv,
+) -> Result<Instruction, WriteMemoryError> {
+    if dest_ptr.is_null() {
+        return Err(WriteMemoryError::NullPointer);
+    }
+
+    let original_instruction = get_instruction(dest_ptr, 16).ok_or(WriteMemoryError::InvalidInstruction)?;
 
     let value = match return_value {
         Some(val) => val,
         None => {
-            if let Err(e) = write_memory(dest_ptr, 0xC3) {
-                eprintln!("Failed to write RET instruction: {:?}", e);
-                return None;
-            }
-            return Some(original_instruction);
+            write_bytes(dest_ptr, &conv.epilogue())?;
+            return Ok(original_instruction);
         }
     };
 
-    let result = match_number!(value);
-    if result.is_none() {
-        return None;
+    let instruction_bytes = match match_number!(value).ok_or(WriteMemoryError::UnsupportedType)? {
+        NumberType::Float(float_type) => float_ret(float_type, conv),
+        NumberType::Integer(integer_type) => integer_ret(integer_type, conv),
+        NumberType::Integral(integral_type) => integral_ret(integral_type, conv),
+        NumberType::Unknown => return Err(WriteMemoryError::UnsupportedType),
+    };
+
+    write_bytes(dest_ptr, &instruction_bytes)?;
+
+    Ok(original_instruction)
+}
+
+/// Redirects a function to another one by writing a jump over its first instructions, so calling
+/// `from` behaves like calling `to`.
+///
+/// A relative `jmp` is written when `to` is within ±2 GiB of `from`, otherwise an absolute jump
+/// through an inline pointer (x64 only). The bytes left over in the last overwritten instruction
+/// are filled with NOPs.
+///
+/// # Safety
+/// This function is unsafe because it rewrites executable code.
+/// - `from` must point to the start of a function at least as long as the jump.
+/// - `to` must be a function with the same signature and calling convention.
+/// - No thread may be executing the first instructions of `from` while the jump is written.
+///
+/// # Parameters
+/// - `from`: A pointer to the first instruction of the function to redirect.
+/// - `to`: A pointer to the function to jump to.
+///
+/// # Returns
+/// - `Ok(Vec<Instruction>)`: The overwritten instructions, to restore with
+///   [`InstructionVecExt::restore_all`](crate::types::instruction::InstructionVecExt::restore_all).
+/// - `Err(WriteMemoryError)`: If the jump could not be written.
+///
+/// # Errors
+/// - `WriteMemoryError::NullPointer`: If `from` or `to` is null.
+/// - `WriteMemoryError::InvalidAccess`: If the instructions of `from` could not be disassembled.
+/// - `WriteMemoryError::FailedToFlushInstructionCache`: If the instruction cache could not be flushed.
+/// - Any other error returned by [`write_bytes`].
+///
+/// # Example
+/// ```rust,no_run
+/// use verity_memory::ops::write;
+/// use verity_memory::types::instruction::InstructionVecExt;
+///
+/// let from = 0x12345678 as *mut u8; // Replace with the actual addresses
+/// let to = 0x12345800 as *mut u8;
+///
+/// unsafe {
+///     let originals = write::redirect_function(from, to).unwrap();
+///     // ...
+///     originals.restore_all().unwrap();
+/// }
+/// ```
+#[cfg(feature = "advanced-write")]
+pub unsafe fn redirect_function(from: *mut u8, to: *mut u8) -> Result<Vec<Instruction>, WriteMemoryError> {
+    if from.is_null() || to.is_null() {
+        return Err(WriteMemoryError::NullPointer);
     }
 
-    let number_type = result.unwrap();
-    let instruction_bytes = match number_type {
-        NumberType::Float(float_type) => float_ret(float_type),
-        NumberType::Integer(integer_type) => integer_ret(integer_type),
-        NumberType::Integral(integral_type) => integral_ret(integral_type),
-        NumberType::Unknown => {
-            return None;
-        }
-    };
+    let mut patch = jump(from as usize, to as usize);
 
-    let mut current_ptr = dest_ptr;
-    for instruction_byte in instruction_bytes {
-        if write_memory(current_ptr, instruction_byte).is_err() {
-            return None;
-        }
-        current_ptr = current_ptr.add(1);
+    let mut originals = Vec::new();
+    let mut covered = 0;
+    while covered < patch.len() {
+        let instruction = get_instruction(from.add(covered), 16).ok_or(WriteMemoryError::InvalidAccess)?;
+        covered += instruction.size;
+        originals.push(instruction);
+    }
+    patch.resize(covered, 0x90);
+
+    write_bytes(from, &patch)?;
+
+    if FlushInstructionCache(CurrentProcess.handle(), from as LPVOID, covered) == 0 {
+        return Err(WriteMemoryError::FailedToFlushInstructionCache);
     }
 
-    Some(original_instruction)
+    Ok(originals)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ops::protection::{alloc_test_page, MockProtection};
     use std::ptr;
+    use crate::ops::query::query;
+    use winapi::um::memoryapi::{VirtualAlloc, VirtualFree};
+    use winapi::um::winnt::{MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_READONLY, PAGE_READWRITE};
 
     fn mock_dest_ptr<T: Copy>(value: T) -> *mut T {
         let mut boxed_value = Box::new(value);
@@ -232,6 +997,331 @@ mod tests {
         assert!(matches!(result, Err(WriteMemoryError::NullPointer)));
     }
     
+    #[test]
+    fn test_write_memory_with_restores_protection() {
+        let mut value: u32 = 42;
+        let provider = MockProtection::new(PAGE_READONLY, None);
+
+        let result = unsafe { write_memory_with(&mut value as *mut u32, 100, &provider) };
+        assert!(result.is_ok());
+        assert_eq!(value, 100);
+        assert_eq!(provider.protections(), vec![PAGE_EXECUTE_READWRITE, PAGE_READONLY]);
+    }
+
+    /// Refuses the given protections, like a page that can't be made executable.
+    struct RefusingProtection {
+        inner: MockProtection,
+        refused: Vec<u32>,
+    }
+
+    impl ProtectionProvider for RefusingProtection {
+        unsafe fn protect(&self, address: LPVOID, size: usize, new_protect: u32) -> Option<u32> {
+            if self.refused.contains(&new_protect) {
+                self.inner.calls.borrow_mut().push((address as usize, size, new_protect));
+                return None;
+            }
+            self.inner.protect(address, size, new_protect)
+        }
+
+        fn regions(&self, address: usize, size: usize) -> Vec<(usize, usize, u32)> {
+            self.inner.regions(address, size)
+        }
+    }
+
+    #[test]
+    fn test_write_memory_protection_escalation() {
+        let mut value: u32 = 42;
+        let provider = RefusingProtection {
+            inner: MockProtection::new(PAGE_READONLY, None),
+            refused: vec![PAGE_EXECUTE_READWRITE],
+        };
+
+        let granted = unsafe { protect_for_write(&provider, &mut value as *mut u32 as LPVOID, 4) };
+        assert_eq!(granted, Some((PAGE_READONLY, PAGE_READWRITE)));
+
+        let provider = RefusingProtection {
+            inner: MockProtection::new(PAGE_READONLY, None),
+            refused: vec![PAGE_EXECUTE_READWRITE, PAGE_READWRITE],
+        };
+        assert!(unsafe { write_memory_with(&mut value as *mut u32, 100, &provider) }.is_ok());
+        assert_eq!(value, 100);
+        assert_eq!(
+            provider.inner.protections(),
+            vec![PAGE_EXECUTE_READWRITE, PAGE_READWRITE, PAGE_WRITECOPY, PAGE_READONLY]
+        );
+
+        let provider = RefusingProtection {
+            inner: MockProtection::new(PAGE_READONLY, None),
+            refused: WRITE_PROTECTION_ESCALATION.to_vec(),
+        };
+        let result = unsafe { write_memory_with(&mut value as *mut u32, 7, &provider) };
+        assert_eq!(result, Err(WriteMemoryError::FailedToChangeProtection));
+        assert_eq!(value, 100);
+        assert_eq!(provider.inner.protections(), WRITE_PROTECTION_ESCALATION.to_vec());
+    }
+
+    #[test]
+    fn test_write_bytes_protection_escalation() {
+        let mut buffer = [0u8; 2];
+        let provider = RefusingProtection {
+            inner: MockProtection::new(PAGE_READONLY, None),
+            refused: vec![PAGE_EXECUTE_READWRITE],
+        };
+
+        assert!(unsafe { write_bytes_with(buffer.as_mut_ptr(), &[1, 2], &provider) }.is_ok());
+        assert_eq!(buffer, [1, 2]);
+        assert_eq!(provider.inner.protections(), vec![PAGE_EXECUTE_READWRITE, PAGE_READWRITE, PAGE_READONLY]);
+
+        let provider = RefusingProtection {
+            inner: MockProtection::new(PAGE_READONLY, None),
+            refused: vec![PAGE_EXECUTE_READWRITE],
+        };
+        assert!(unsafe { WriteBuilder::new().write_with(buffer.as_mut_ptr(), &[3, 4], &provider) }.is_ok());
+        assert_eq!(buffer, [3, 4]);
+        assert_eq!(provider.inner.protections(), vec![PAGE_EXECUTE_READWRITE, PAGE_READWRITE, PAGE_READONLY]);
+
+        // An explicit target protection is not escalated.
+        let provider = RefusingProtection {
+            inner: MockProtection::new(PAGE_READONLY, None),
+            refused: vec![PAGE_EXECUTE_READWRITE],
+        };
+        let builder = WriteBuilder::new().target_protection(Protection::ExecuteReadWrite);
+        let result = unsafe { builder.write_with(buffer.as_mut_ptr(), &[5, 6], &provider) };
+        assert_eq!(result, Err(WriteMemoryError::FailedToChangeProtection));
+        assert_eq!(buffer, [3, 4]);
+    }
+
+    #[test]
+    fn test_write_if_changed() {
+        let mut value: u32 = 42;
+        let provider = MockProtection::new(PAGE_READONLY, None);
+
+        assert_eq!(unsafe { write_if_changed_with(&mut value as *mut u32, 42, &provider) }, Ok(false));
+        assert_eq!(value, 42);
+        assert!(provider.protections().is_empty());
+
+        assert_eq!(unsafe { write_if_changed_with(&mut value as *mut u32, 100, &provider) }, Ok(true));
+        assert_eq!(value, 100);
+        assert_eq!(provider.protections(), vec![PAGE_EXECUTE_READWRITE, PAGE_READONLY]);
+
+        assert_eq!(unsafe { write_if_changed(&mut value as *mut u32, 100) }, Ok(false));
+        assert_eq!(unsafe { write_if_changed(ptr::null_mut::<u32>(), 1) }, Err(WriteMemoryError::NullPointer));
+    }
+
+    #[test]
+    #[cfg(feature = "runtime")]
+    fn test_write_memory_guarded_noaccess() {
+        use winapi::um::winnt::PAGE_NOACCESS;
+
+        let mut value: u32 = 42;
+        assert_eq!(unsafe { write_memory_guarded(&mut value as *mut u32, 100) }, Ok(()));
+        assert_eq!(value, 100);
+
+        unsafe {
+            let page = VirtualAlloc(std::ptr::null_mut(), 0x1000, MEM_COMMIT | MEM_RESERVE, PAGE_NOACCESS) as *mut u32;
+            assert!(!page.is_null());
+
+            // The mock provider leaves the page PAGE_NOACCESS, so the store faults.
+            let provider = MockProtection::new(PAGE_NOACCESS, None);
+            let result = write_memory_guarded_with(page, 100, &provider);
+            assert_eq!(result, Err(WriteMemoryError::InvalidAccess));
+            assert_eq!(provider.protections(), vec![PAGE_EXECUTE_READWRITE, PAGE_NOACCESS]);
+            assert_eq!(query(page as *const u8).unwrap().Protect, PAGE_NOACCESS);
+
+            VirtualFree(page as LPVOID, 0, MEM_RELEASE);
+        }
+    }
+
+    #[test]
+    fn test_write_memory_verified() {
+        let mut value: u32 = 42;
+
+        assert!(unsafe { write_memory_verified(&mut value as *mut u32, 100) }.is_ok());
+        assert_eq!(value, 100);
+    }
+
+    /// Reverts the destination to its previous value when the protection is restored after the
+    /// write, like a page whose writes are discarded.
+    struct RevertingProtection {
+        inner: MockProtection,
+        address: *mut u32,
+        original: u32,
+    }
+
+    impl ProtectionProvider for RevertingProtection {
+        unsafe fn protect(&self, address: LPVOID, size: usize, new_protect: u32) -> Option<u32> {
+            if self.inner.calls.borrow().len() == 1 {
+                *self.address = self.original;
+            }
+            self.inner.protect(address, size, new_protect)
+        }
+
+        fn regions(&self, address: usize, size: usize) -> Vec<(usize, usize, u32)> {
+            self.inner.regions(address, size)
+        }
+    }
+
+    #[test]
+    fn test_write_memory_verified_detects_reverted_write() {
+        let mut value: u32 = 42;
+        let provider = RevertingProtection {
+            inner: MockProtection::new(PAGE_READONLY, None),
+            address: &mut value as *mut u32,
+            original: 42,
+        };
+
+        let result = unsafe { write_memory_verified_with(&mut value as *mut u32, 100, &provider) };
+        assert_eq!(result, Err(WriteMemoryError::VerificationFailed));
+        assert_eq!(value, 42);
+    }
+
+    #[test]
+    fn test_write_memory_alignment_policies() {
+        let mut buffer = [0u8; 6];
+        let unaligned_ptr = unsafe { buffer.as_mut_ptr().add(1) } as *mut u32;
+
+        let strict = unsafe { write_memory_with_alignment(unaligned_ptr, 0x12345678, AlignmentPolicy::Strict) };
+        assert_eq!(strict, Err(WriteMemoryError::InvalidAlignment));
+        assert_eq!(buffer, [0; 6]);
+
+        let unaligned = unsafe { write_memory_with_alignment(unaligned_ptr, 0x12345678, AlignmentPolicy::Unaligned) };
+        assert!(unaligned.is_ok());
+        assert_eq!(&buffer[1..5], &0x12345678u32.to_ne_bytes());
+    }
+
+    #[test]
+    fn test_write_memory_across_pages_restores_each_page() {
+        unsafe {
+            let pages = VirtualAlloc(std::ptr::null_mut(), 0x2000, MEM_COMMIT | MEM_RESERVE, PAGE_READWRITE) as *mut u8;
+            assert!(!pages.is_null());
+
+            let mut old_protect = 0;
+            assert_ne!(winapi::um::memoryapi::VirtualProtect(pages.add(0x1000) as LPVOID, 0x1000, PAGE_READONLY, &mut old_protect), 0);
+
+            let straddling = pages.add(0xFFE) as *mut u32;
+            let result = write_memory_with_alignment(straddling, 0x12345678, AlignmentPolicy::Unaligned);
+            assert!(result.is_ok());
+            assert_eq!(std::ptr::read_unaligned(straddling), 0x12345678);
+
+            assert_eq!(query(pages).unwrap().Protect, PAGE_READWRITE);
+            assert_eq!(query(pages.add(0x1000)).unwrap().Protect, PAGE_READONLY);
+
+            assert!(write_bytes(pages.add(0xFFF), &[0xAA, 0xBB]).is_ok());
+            assert_eq!(query(pages).unwrap().Protect, PAGE_READWRITE);
+            assert_eq!(query(pages.add(0x1000)).unwrap().Protect, PAGE_READONLY);
+
+            VirtualFree(pages as LPVOID, 0, MEM_RELEASE);
+        }
+    }
+
+    #[test]
+    fn test_write_vec128() {
+        let mut vector = Vec128([0.0; 4]);
+        let ptr = &mut vector as *mut Vec128 as *mut u8;
+
+        assert!(unsafe { write_vec128(ptr, [1.5, -2.0, 3.25, 4.0]) }.is_ok());
+        assert_eq!(vector.0, [1.5, -2.0, 3.25, 4.0]);
+        assert_eq!(unsafe { write_vec128(ptr.add(4), [0.0; 4]) }, Err(WriteMemoryError::InvalidAlignment));
+    }
+
+    #[test]
+    fn test_write_bit_set_and_clear() {
+        let mut flags = 0b1010_0000u8;
+
+        assert!(unsafe { write_bit(&mut flags, 0, true) }.is_ok());
+        assert_eq!(flags, 0b1010_0001);
+        assert!(unsafe { write_bit(&mut flags, 0, true) }.is_ok());
+        assert_eq!(flags, 0b1010_0001);
+        assert!(unsafe { write_bit(&mut flags, 7, false) }.is_ok());
+        assert_eq!(flags, 0b0010_0001);
+        assert!(unsafe { write_bit(&mut flags, 6, false) }.is_ok());
+        assert_eq!(flags, 0b0010_0001);
+        assert_eq!(unsafe { write_bit(&mut flags, 8, true) }, Err(WriteMemoryError::InvalidBit));
+        assert_eq!(flags, 0b0010_0001);
+    }
+
+    #[test]
+    fn test_write_bytes_success() {
+        let mut buffer = [0u8; 4];
+
+        let result = unsafe { write_bytes(buffer.as_mut_ptr(), &[1, 2, 3, 4]) };
+        assert!(result.is_ok());
+        assert_eq!(buffer, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_write_builder_restores_protection() {
+        let page = alloc_test_page(PAGE_READONLY);
+
+        let result = unsafe { WriteBuilder::new().flush_icache(true).write(page, &[1, 2, 3, 4]) };
+        assert!(result.is_ok());
+        assert_eq!(unsafe { std::slice::from_raw_parts(page, 4) }, &[1, 2, 3, 4]);
+        assert_eq!(query(page).unwrap().Protect, PAGE_READONLY);
+
+        unsafe { VirtualFree(page as LPVOID, 0, MEM_RELEASE) };
+    }
+
+    #[test]
+    fn test_write_builder_keeps_target_protection() {
+        let page = alloc_test_page(PAGE_READONLY);
+
+        let result = unsafe {
+            WriteBuilder::new()
+                .target_protection(Protection::ReadWrite)
+                .restore_protection(false)
+                .write(page, &[5, 6])
+        };
+        assert!(result.is_ok());
+        assert_eq!(unsafe { std::slice::from_raw_parts(page, 2) }, &[5, 6]);
+        assert_eq!(query(page).unwrap().Protect, PAGE_READWRITE);
+
+        unsafe { VirtualFree(page as LPVOID, 0, MEM_RELEASE) };
+    }
+
+    #[test]
+    fn test_write_builder_with_provider() {
+        let mut buffer = [0u8; 2];
+        let provider = MockProtection::new(PAGE_READONLY, None);
+
+        let builder = WriteBuilder::new().target_protection(Protection::ReadWrite);
+        assert!(unsafe { builder.write_with(buffer.as_mut_ptr(), &[7, 8], &provider) }.is_ok());
+        assert_eq!(buffer, [7, 8]);
+        assert_eq!(provider.protections(), vec![PAGE_READWRITE, PAGE_READONLY]);
+
+        let readonly = WriteBuilder::new().target_protection(Protection::ReadOnly);
+        let result = unsafe { readonly.write_with(buffer.as_mut_ptr(), &[9, 9], &provider) };
+        assert_eq!(result, Err(WriteMemoryError::InvalidAccess));
+        assert_eq!(buffer, [7, 8]);
+    }
+
+    #[test]
+    fn test_write_builder_copy_on_write() {
+        use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+        use winapi::um::memoryapi::{CreateFileMappingW, MapViewOfFile, UnmapViewOfFile, FILE_MAP_COPY};
+        use winapi::um::winnt::PAGE_WRITECOPY;
+
+        unsafe {
+            let mapping = CreateFileMappingW(INVALID_HANDLE_VALUE, ptr::null_mut(), PAGE_READWRITE, 0, 0x1000, ptr::null());
+            assert!(!mapping.is_null());
+            let view = MapViewOfFile(mapping, FILE_MAP_COPY, 0, 0, 0x1000) as *mut u8;
+            assert!(!view.is_null());
+            assert_eq!(query(view).unwrap().Protect, PAGE_WRITECOPY);
+
+            let strict = WriteBuilder::new()
+                .target_protection(Protection::WriteCopy)
+                .allow_copy_on_write(false);
+            assert_eq!(strict.write(view, &[1, 2]), Err(WriteMemoryError::CopyOnWrite));
+            assert_eq!(std::slice::from_raw_parts(view, 2), &[0, 0]);
+
+            let allowed = strict.allow_copy_on_write(true);
+            assert!(allowed.write(view, &[1, 2]).is_ok());
+            assert_eq!(std::slice::from_raw_parts(view, 2), &[1, 2]);
+
+            UnmapViewOfFile(view as LPVOID);
+            CloseHandle(mapping);
+        }
+    }
+
     #[test]
     #[cfg(feature = "advanced-write")]
     fn test_nop_instructions_success() {
@@ -239,8 +1329,10 @@ mod tests {
         let dest_ptr = data.as_ptr() as *mut u8;
 
         unsafe {
-            if let Some(instructions) = nop_instructions(dest_ptr, 2) {
-                assert_eq!(instructions.len(), 2);
+            if let Ok(result) = nop_instructions(dest_ptr, 2) {
+                assert_eq!(result.originals.len(), 2);
+                let span: usize = result.originals.iter().map(|instr| instr.size).sum();
+                assert_eq!(result.total_bytes, span);
             } else {
                 panic!("Failed to retrieve instructions");
             }
@@ -254,7 +1346,46 @@ mod tests {
 
         unsafe {
             let instructions = nop_instructions(dest_ptr, 1);
-            assert!(instructions.is_none());
+            assert!(matches!(instructions, Err(WriteMemoryError::NullPointer)));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "advanced-write")]
+    fn test_nop_instructions_invalid_count() {
+        let data: Vec<u8> = vec![0x55, 0x48, 0x8B, 0xEC, 0x90];
+        let dest_ptr = data.as_ptr() as *mut u8;
+
+        unsafe {
+            assert!(matches!(nop_instructions(dest_ptr, 0), Err(WriteMemoryError::InvalidInstructionCount(0))));
+            assert!(matches!(
+                nop_instructions(dest_ptr, MAX_FILL_INSTRUCTIONS + 1),
+                Err(WriteMemoryError::InvalidInstructionCount(_))
+            ));
+            assert!(matches!(
+                nop_instructions(dest_ptr, usize::MAX),
+                Err(WriteMemoryError::InvalidInstructionCount(usize::MAX))
+            ));
+        }
+        assert_eq!(data, vec![0x55, 0x48, 0x8B, 0xEC, 0x90]);
+    }
+
+    #[test]
+    #[cfg(feature = "advanced-write")]
+    fn test_fill_instructions_span() {
+        for filler in [Filler::Nop, Filler::Int3, Filler::Custom(0xAB)] {
+            let data: Vec<u8> = vec![0x55, 0x48, 0x8B, 0xEC, 0x90, 0xC3];
+            let dest_ptr = data.as_ptr() as *mut u8;
+
+            let result = unsafe { fill_instructions(dest_ptr, 2, filler) }
+                .expect("Failed to retrieve instructions");
+
+            let span: usize = result.originals.iter().map(|instr| instr.size).sum();
+            assert_eq!(result.total_bytes, span);
+            assert_eq!(span, 4);
+            assert_eq!(&data[..1], filler.bytes(1).as_slice());
+            assert_eq!(&data[1..span], filler.bytes(3).as_slice());
+            assert_eq!(&data[span..], &[0x90, 0xC3]);
         }
     }
 
@@ -266,7 +1397,7 @@ mod tests {
 
         unsafe {
             let result = replace_return_value(dest_ptr, Some(123_u32));
-            assert!(result.is_some());
+            assert!(result.is_ok());
         }
     }
 
@@ -278,7 +1409,7 @@ mod tests {
 
         unsafe {
             let result = replace_return_value(dest_ptr, Some(123.45_f32));
-            assert!(result.is_some());
+            assert!(result.is_ok());
         }
     }
 
@@ -290,7 +1421,88 @@ mod tests {
 
         unsafe {
             let result = replace_return_value::<i32>(dest_ptr, None);
-            assert!(result.is_some());
+            assert!(result.is_ok());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "advanced-write")]
+    fn test_replace_return_value_errors() {
+        let data: Vec<u8> = vec![0x55, 0x48, 0x8B, 0xEC];
+        let dest_ptr = data.as_ptr() as *mut u8;
+
+        unsafe {
+            assert_eq!(
+                replace_return_value::<i32>(ptr::null_mut(), Some(1)).unwrap_err(),
+                WriteMemoryError::NullPointer
+            );
+            assert_eq!(replace_return_value(dest_ptr, Some('a')).unwrap_err(), WriteMemoryError::UnsupportedType);
+        }
+        assert_eq!(data, vec![0x55, 0x48, 0x8B, 0xEC]);
+    }
+
+    #[test]
+    #[cfg(feature = "advanced-write")]
+    fn test_replace_return_value_stdcall_cleanup() {
+        let mut data: Vec<u8> = vec![0x90; 16];
+        data[..3].copy_from_slice(&[0x55, 0x8B, 0xEC]);
+        let dest_ptr = data.as_ptr() as *mut u8;
+
+        let expected = integral_ret(IntegralType::U32(&123), CallConv::Stdcall(8));
+        assert!(expected.ends_with(&[0xC2, 0x08, 0x00]));
+
+        unsafe {
+            let result = replace_return_value_with_conv(dest_ptr, Some(123_u32), CallConv::Stdcall(8));
+            assert!(result.is_ok());
+        }
+        assert_eq!(&data[..expected.len()], expected.as_slice());
+    }
+
+    #[test]
+    #[cfg(feature = "advanced-write")]
+    fn test_redirect_function_and_restore() {
+        use crate::types::instruction::InstructionVecExt;
+
+        // mov eax, 1; ret | mov eax, 2; ret, each padded with NOPs.
+        let first = [0xB8, 0x01, 0x00, 0x00, 0x00, 0xC3];
+        let second = [0xB8, 0x02, 0x00, 0x00, 0x00, 0xC3];
+
+        unsafe {
+            let page = VirtualAlloc(ptr::null_mut(), 0x1000, MEM_COMMIT | MEM_RESERVE, PAGE_EXECUTE_READWRITE) as *mut u8;
+            assert!(!page.is_null());
+            ptr::write_bytes(page, 0x90, 0x200);
+            ptr::copy_nonoverlapping(first.as_ptr(), page, first.len());
+            ptr::copy_nonoverlapping(second.as_ptr(), page.add(0x100), second.len());
+
+            let from: extern "C" fn() -> u32 = std::mem::transmute(page);
+            let to = page.add(0x100);
+            assert_eq!(from(), 1);
+
+            let originals = redirect_function(page, to).expect("Failed to redirect function");
+            assert_eq!(originals[0].bytes, vec![0xB8, 0x01, 0x00, 0x00, 0x00]);
+            assert_eq!(*page, 0xE9);
+            assert_eq!(from(), 2);
+
+            originals.restore_all().expect("Failed to restore function");
+            assert_eq!(std::slice::from_raw_parts(page, first.len()), &first);
+            assert_eq!(from(), 1);
+
+            assert_eq!(redirect_function(ptr::null_mut(), to).unwrap_err(), WriteMemoryError::NullPointer);
+
+            VirtualFree(page as LPVOID, 0, MEM_RELEASE);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "advanced-write")]
+    fn test_replace_return_value_none_stdcall() {
+        let data: Vec<u8> = vec![0x55, 0x8B, 0xEC, 0x90];
+        let dest_ptr = data.as_ptr() as *mut u8;
+
+        unsafe {
+            let result = replace_return_value_with_conv::<i32>(dest_ptr, None, CallConv::Stdcall(12));
+            assert!(result.is_ok());
         }
+        assert_eq!(&data[..3], &[0xC2, 0x0C, 0x00]);
     }
 }
\ No newline at end of file
@@ -1,71 +1,392 @@
+#[cfg(feature = "stats")]
+use std::time::{Duration, Instant};
+
 use crate::errors::AobScanError;
 
+/// Statistics collected while scanning, useful to diagnose slow signatures.
+#[cfg(feature = "stats")]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScanStats {
+    /// The number of bytes of the region the scanner advanced over.
+    pub bytes_scanned: usize,
+    /// The number of pattern byte comparisons performed.
+    pub comparisons: usize,
+    /// The time spent matching, excluding copying the region.
+    pub elapsed: Duration,
+}
+
 pub(crate) fn convert_pattern(pattern: &str) -> Result<Vec<u8>, AobScanError> {
-    pattern.split_whitespace()
-        .map(|s| if s == "??" {
-            Ok(0x00)
-        } else {
-            u8::from_str_radix(s, 16).map_err(|_| AobScanError::InvalidPattern)
-        })
-        .collect()
+    let bytes = tokens(pattern)
+        .enumerate()
+        .map(|(position, s)| parse_byte(s, position))
+        .collect::<Result<Vec<u8>, AobScanError>>()?;
+
+    if bytes.is_empty() {
+        return Err(AobScanError::EmptyPattern);
+    }
+
+    Ok(bytes)
 }
 
-pub(crate) fn kmp_search_unique(data: &[u8], pattern: &[u8]) -> Result<usize, AobScanError> {
-    if pattern.is_empty() {
-        return Err(AobScanError::InvalidPattern);
+/// Splits a pattern string into its tokens, separated by any whitespace including newlines.
+///
+/// Everything from a `#` to the end of its line is a comment, so signatures can be documented
+/// inline:
+///
+/// ```text
+/// 48 8B 05 ?? ?? ?? ??   # mov rax, [rip + disp32]
+/// 48 85 C0               # test rax, rax
+/// ```
+pub(crate) fn tokens(pattern: &str) -> impl Iterator<Item = &str> {
+    pattern
+        .lines()
+        .flat_map(|line| line.split('#').next().unwrap_or_default().split_whitespace())
+}
+
+/// Parses a `??` wildcard or a byte written as exactly two hex digits. `from_str_radix` alone
+/// would also accept typos such as `8` or `+8`.
+fn parse_byte(token: &str, position: usize) -> Result<u8, AobScanError> {
+    if token == "??" {
+        return Ok(0x00);
     }
 
-    let lps = compute_lps(pattern);
-    let mut i = 0;
-    let mut j = 0;
+    if token.len() != 2 || !token.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(invalid_token(token, position));
+    }
 
-    while i < data.len() {
-        if pattern[j] == data[i] || pattern[j] == 0x00 {
-            i += 1;
-            j += 1;
+    u8::from_str_radix(token, 16).map_err(|_| invalid_token(token, position))
+}
+
+fn invalid_token(token: &str, position: usize) -> AobScanError {
+    AobScanError::InvalidPattern {
+        token: token.to_string(),
+        position,
+    }
+}
+
+/// Parses a gap token like `[0-8]` or `[4]` into its minimum and maximum length.
+fn parse_gap(token: &str) -> Option<(usize, usize)> {
+    let inner = token.strip_prefix('[')?.strip_suffix(']')?;
+    let (min, max) = match inner.split_once('-') {
+        Some((min, max)) => (min.parse().ok()?, max.parse().ok()?),
+        None => {
+            let len = inner.parse().ok()?;
+            (len, len)
         }
+    };
 
-        if j == pattern.len() {
-            return Ok(i - j);
-        } else if i < data.len() && pattern[j] != data[i] && pattern[j] != 0x00 {
-            if j != 0 {
-                j = lps[j - 1];
-            } else {
-                i += 1;
+    if min <= max {
+        Some((min, max))
+    } else {
+        None
+    }
+}
+
+/// Returns whether a pattern string contains variable-length gaps and needs a [`GapPattern`].
+pub(crate) fn has_gaps(pattern: &str) -> bool {
+    tokens(pattern).any(|token| token.starts_with('['))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    min_gap: usize,
+    max_gap: usize,
+    bytes: Vec<u8>,
+}
+
+/// A pattern with variable-length gaps between fixed runs of bytes.
+///
+/// Besides hexadecimal bytes and `??` wildcards, the pattern may contain gap tokens:
+/// - `[min-max]`: Skips anywhere from `min` to `max` bytes, e.g. `[0-8]`.
+/// - `[len]`: Skips exactly `len` bytes, e.g. `[4]` is the same as `?? ?? ?? ??`.
+///
+/// Consecutive gaps add up. A pattern can't start or end with a gap, since that only shifts or
+/// extends the match without constraining it.
+///
+/// KMP can't handle variable gaps, so the run of bytes before the first gap is located with KMP
+/// and the remaining runs are matched by backtracking over every allowed gap length. Patterns
+/// without gaps should keep using the plain scanners.
+///
+/// # Example
+/// ```
+/// use verity_memory::pattern::algorithm::GapPattern;
+///
+/// let pattern = GapPattern::new("48 8B [0-4] C3").unwrap();
+/// assert_eq!(pattern.find_all(&[0x48, 0x8B, 0xC3, 0x90, 0x48, 0x8B, 0x90, 0x90, 0xC3]), vec![0, 4]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GapPattern {
+    segments: Vec<Segment>,
+}
+
+impl GapPattern {
+    /// Parses a pattern formatted like `"48 8B ?? [0-8] 89 05"`.
+    ///
+    /// # Errors
+    /// - `AobScanError::InvalidPattern`: If a token is invalid, including a malformed gap, a gap
+    ///   whose minimum exceeds its maximum, or a gap at the start or end of the pattern.
+    /// - `AobScanError::EmptyPattern`: If the pattern string contains no tokens.
+    pub fn new(pattern: &str) -> Result<Self, AobScanError> {
+        let mut segments: Vec<Segment> = Vec::new();
+        let mut bytes = Vec::new();
+        let mut gap: Option<(usize, usize)> = None;
+        let mut last_gap = None;
+
+        for (position, token) in tokens(pattern).enumerate() {
+            if !token.starts_with('[') {
+                bytes.push(parse_byte(token, position)?);
+                continue;
             }
+
+            let (min, max) = parse_gap(token).ok_or_else(|| invalid_token(token, position))?;
+            if bytes.is_empty() && segments.is_empty() {
+                return Err(invalid_token(token, position));
+            }
+
+            if !bytes.is_empty() {
+                let (min_gap, max_gap) = gap.take().unwrap_or((0, 0));
+                segments.push(Segment {
+                    min_gap,
+                    max_gap,
+                    bytes: std::mem::take(&mut bytes),
+                });
+            }
+
+            gap = Some(match gap {
+                Some((total_min, total_max)) => (total_min + min, total_max + max),
+                None => (min, max),
+            });
+            last_gap = Some((token, position));
         }
+
+        if bytes.is_empty() {
+            return match last_gap {
+                Some((token, position)) => Err(invalid_token(token, position)),
+                None => Err(AobScanError::EmptyPattern),
+            };
+        }
+
+        let (min_gap, max_gap) = gap.unwrap_or((0, 0));
+        segments.push(Segment { min_gap, max_gap, bytes });
+        Ok(GapPattern { segments })
     }
 
-    Err(AobScanError::PatternNotFound)
+    /// Returns the index of the first match in `data`.
+    pub fn find(&self, data: &[u8]) -> Option<usize> {
+        self.matches(data).next()
+    }
+
+    /// Returns the index of every match in `data`, including overlapping ones.
+    ///
+    /// A start index is reported once, even if several gap lengths match from it.
+    pub fn find_all(&self, data: &[u8]) -> Vec<usize> {
+        self.matches(data).collect()
+    }
+
+    pub(crate) fn matches<'a>(&'a self, data: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let head = &self.segments[0].bytes;
+        KmpMatches::new(data, head).filter(move |&index| self.matches_from(data, 1, index + head.len()))
+    }
+
+    /// Matches the segments from `segment` onwards, starting `position` bytes into `data`.
+    fn matches_from(&self, data: &[u8], segment: usize, position: usize) -> bool {
+        let segment_pattern = match self.segments.get(segment) {
+            Some(segment_pattern) => segment_pattern,
+            None => return true,
+        };
+
+        for gap in segment_pattern.min_gap..=segment_pattern.max_gap {
+            let start = position + gap;
+            let end = start + segment_pattern.bytes.len();
+            if end > data.len() {
+                break;
+            }
+
+            if matches_at(&data[start..end], &segment_pattern.bytes) && self.matches_from(data, segment + 1, end) {
+                return true;
+            }
+        }
+
+        false
+    }
 }
 
-pub(crate) fn kmp_search_all(data: &[u8], pattern: &[u8]) -> Result<Vec<usize>, AobScanError> {
+/// How many matches [`scan`] collects before stopping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ScanMode {
+    /// The first match only.
+    First,
+    /// Every match, including overlapping ones.
+    All,
+    /// The only match; a second one is an error.
+    Unique,
+    /// The match at the given zero-based position.
+    Nth(usize),
+}
+
+/// Runs a single pass over `data`, stopping as soon as `mode` is satisfied.
+///
+/// Patterns with wildcards are matched by trying every offset instead of with KMP, see
+/// [`prefers_naive`].
+///
+/// # Errors
+/// - `AobScanError::EmptyPattern`: If `pattern` is empty.
+/// - `AobScanError::PatternNotFound`: If there is no match, or fewer than `n + 1` for `Nth(n)`.
+/// - `AobScanError::NotUnique`: If there is more than one match under `Unique`.
+pub(crate) fn scan(data: &[u8], pattern: &[u8], mode: ScanMode) -> Result<Vec<usize>, AobScanError> {
     if pattern.is_empty() {
-        return Err(AobScanError::InvalidPattern);
+        return Err(AobScanError::EmptyPattern);
     }
 
-    let lps = compute_lps(pattern);
-    let mut indices = Vec::new();
-    let mut i = 0;
-    let mut j = 0;
+    collect_matches(KmpMatches::new(data, pattern), mode)
+}
 
-    while i < data.len() {
-        if pattern[j] == data[i] || pattern[j] == 0x00 {
-            i += 1;
-            j += 1;
+/// Whether `pattern` has a wildcard, and must be scanned by comparing it at every offset rather
+/// than with KMP.
+///
+/// The failure table of KMP assumes that a prefix matching the text can be shifted onto itself,
+/// which a wildcard breaks: the bytes a wildcard matched are unknown, so no shift is safe without
+/// comparing again, and the table built for a wildcard pattern can skip real matches (`01 02 ?? 01 01`
+/// misses the match at 2 in `01 02 01 02 01 01 01`). Comparing the pattern at every offset has no
+/// table to get wrong, and usually rejects an offset within a byte or two.
+pub(crate) fn prefers_naive(pattern: &[u8]) -> bool {
+    pattern.contains(&0x00)
+}
+
+/// Like [`scan`], but takes the pattern string, so patterns with gaps go through [`GapPattern`]
+/// while plain patterns stay on the KMP fast path.
+///
+/// # Errors
+/// - `AobScanError::InvalidPattern`: If a token of the pattern string is invalid.
+/// - `AobScanError::EmptyPattern`: If the pattern string contains no tokens.
+/// - `AobScanError::PatternNotFound` / `AobScanError::NotUnique`: As for [`scan`].
+pub(crate) fn scan_pattern(data: &[u8], pattern: &str, mode: ScanMode) -> Result<Vec<usize>, AobScanError> {
+    if has_gaps(pattern) {
+        let pattern = GapPattern::new(pattern)?;
+        collect_matches(pattern.matches(data), mode)
+    } else {
+        scan(data, &convert_pattern(pattern)?, mode)
+    }
+}
+
+/// Like [`kmp_search_spaced`], but takes the pattern string, so patterns with gaps are accepted.
+pub(crate) fn scan_pattern_spaced(data: &[u8], pattern: &str, min_spacing: usize) -> Result<Vec<usize>, AobScanError> {
+    if !has_gaps(pattern) {
+        return kmp_search_spaced(data, &convert_pattern(pattern)?, min_spacing);
+    }
+
+    let pattern = GapPattern::new(pattern)?;
+    let mut next = 0;
+    let matches = pattern.matches(data).filter(|&index| {
+        if index < next {
+            return false;
         }
+        next = index.saturating_add(min_spacing.max(1));
+        true
+    });
+    collect_matches(matches, ScanMode::All)
+}
 
-        if j == pattern.len() {
-            indices.push(i - j);
-            j = lps[j - 1];
-        } else if i < data.len() && pattern[j] != data[i] && pattern[j] != 0x00 {
-            if j != 0 {
-                j = lps[j - 1];
-            } else {
-                i += 1;
+/// Checks that a pattern string is valid, with or without gaps, without scanning anything.
+///
+/// # Errors
+/// - Same as [`GapPattern::new`].
+pub(crate) fn validate_pattern(pattern: &str) -> Result<(), AobScanError> {
+    if has_gaps(pattern) {
+        GapPattern::new(pattern).map(|_| ())
+    } else {
+        convert_pattern(pattern).map(|_| ())
+    }
+}
+
+/// Parses a pattern string for the scanners that need every match to have the same length, e.g.
+/// to read the matched bytes back.
+///
+/// # Errors
+/// - `AobScanError::GapsUnsupported`: If the pattern is valid but contains variable-length gaps.
+/// - Any other error returned by [`validate_pattern`].
+pub(crate) fn convert_fixed_pattern(pattern: &str) -> Result<Vec<u8>, AobScanError> {
+    if has_gaps(pattern) {
+        GapPattern::new(pattern)?;
+        return Err(AobScanError::GapsUnsupported);
+    }
+
+    convert_pattern(pattern)
+}
+
+fn collect_matches(mut matches: impl Iterator<Item = usize>, mode: ScanMode) -> Result<Vec<usize>, AobScanError> {
+    let indices: Vec<usize> = match mode {
+        ScanMode::First => matches.next().into_iter().collect(),
+        ScanMode::All => matches.collect(),
+        ScanMode::Unique => {
+            let indices: Vec<usize> = matches.take(2).collect();
+            if indices.len() > 1 {
+                return Err(AobScanError::NotUnique);
             }
+            indices
         }
+        ScanMode::Nth(n) => matches.nth(n).into_iter().collect(),
+    };
+
+    if indices.is_empty() {
+        Err(AobScanError::PatternNotFound)
+    } else {
+        Ok(indices)
+    }
+}
+
+pub(crate) fn kmp_search_unique(data: &[u8], pattern: &[u8]) -> Result<usize, AobScanError> {
+    scan(data, pattern, ScanMode::First).map(|indices| indices[0])
+}
+
+pub(crate) fn kmp_search_all(data: &[u8], pattern: &[u8]) -> Result<Vec<usize>, AobScanError> {
+    scan(data, pattern, ScanMode::All)
+}
+
+/// Like [`kmp_search_all`], but resumes the search `min_spacing` bytes after the start of each
+/// match instead of right after it, so matches closer than `min_spacing` to the previous one are
+/// skipped. A spacing of 0 or 1 finds every match, including overlapping ones.
+pub(crate) fn kmp_search_spaced(
+    data: &[u8],
+    pattern: &[u8],
+    min_spacing: usize,
+) -> Result<Vec<usize>, AobScanError> {
+    if pattern.is_empty() {
+        return Err(AobScanError::EmptyPattern);
+    }
+
+    let mut matches = KmpMatches::new(data, pattern);
+    let mut indices = Vec::new();
+
+    while let Some(index) = matches.next() {
+        indices.push(index);
+        matches.restart_at(index.saturating_add(min_spacing.max(1)));
+    }
+
+    if indices.is_empty() {
+        Err(AobScanError::PatternNotFound)
+    } else {
+        Ok(indices)
     }
+}
+
+#[cfg(feature = "stats")]
+pub(crate) fn kmp_search_all_with_stats(
+    data: &[u8],
+    pattern: &[u8],
+    stats: &mut ScanStats,
+) -> Result<Vec<usize>, AobScanError> {
+    if pattern.is_empty() {
+        return Err(AobScanError::EmptyPattern);
+    }
+
+    let start = Instant::now();
+    let mut matches = KmpMatches::new(data, pattern);
+    let indices: Vec<usize> = matches.by_ref().collect();
+
+    stats.bytes_scanned = matches.i;
+    stats.comparisons = matches.comparisons;
+    stats.elapsed = start.elapsed();
 
     if indices.is_empty() {
         Err(AobScanError::PatternNotFound)
@@ -74,13 +395,317 @@ pub(crate) fn kmp_search_all(data: &[u8], pattern: &[u8]) -> Result<Vec<usize>,
     }
 }
 
+/// Finds the patterns in order, each one starting at most `max_gap` bytes after the end of the
+/// match of the previous one, and returns the index of every match.
+///
+/// Every match of the first pattern is tried in order, and within each window every candidate is
+/// tried before giving up, so the first sequence satisfying all the constraints is returned.
+///
+/// # Errors
+/// - `AobScanError::EmptyPattern`: If `patterns` or any pattern is empty.
+/// - `AobScanError::PatternNotFound`: If no sequence satisfies the constraints.
+pub(crate) fn find_sequence(data: &[u8], patterns: &[Vec<u8>], max_gap: usize) -> Result<Vec<usize>, AobScanError> {
+    if patterns.is_empty() || patterns.iter().any(|pattern| pattern.is_empty()) {
+        return Err(AobScanError::EmptyPattern);
+    }
+
+    let mut indices = Vec::with_capacity(patterns.len());
+    for start in KmpMatches::new(data, &patterns[0]) {
+        indices.push(start);
+        if find_sequence_from(data, &patterns[1..], max_gap, start + patterns[0].len(), &mut indices) {
+            return Ok(indices);
+        }
+        indices.clear();
+    }
+
+    Err(AobScanError::PatternNotFound)
+}
+
+fn find_sequence_from(
+    data: &[u8],
+    patterns: &[Vec<u8>],
+    max_gap: usize,
+    position: usize,
+    indices: &mut Vec<usize>,
+) -> bool {
+    let pattern = match patterns.first() {
+        Some(pattern) => pattern,
+        None => return true,
+    };
+
+    let window_end = position.saturating_add(max_gap).saturating_add(pattern.len()).min(data.len());
+    if position >= window_end {
+        return false;
+    }
+
+    for offset in KmpMatches::new(&data[position..window_end], pattern) {
+        indices.push(position + offset);
+        if find_sequence_from(data, &patterns[1..], max_gap, position + offset + pattern.len(), indices) {
+            return true;
+        }
+        indices.pop();
+    }
+
+    false
+}
+
+/// Returns the highest index below `limit` at which `pattern` matches, searching backwards from
+/// `limit` so nothing before the match is examined.
+pub(crate) fn rfind_before(data: &[u8], pattern: &[u8], limit: usize) -> Result<usize, AobScanError> {
+    if pattern.is_empty() {
+        return Err(AobScanError::EmptyPattern);
+    }
+
+    let last_start = match data.len().checked_sub(pattern.len()) {
+        Some(last_start) => last_start,
+        None => return Err(AobScanError::PatternNotFound),
+    };
+
+    (0..limit.min(last_start + 1))
+        .rev()
+        .find(|&index| matches_at(&data[index..index + pattern.len()], pattern))
+        .ok_or(AobScanError::PatternNotFound)
+}
+
+/// Returns how many bytes [`matches_at`] examines before it finds a mismatch or the match is complete.
+#[cfg(feature = "stats")]
+fn compared_bytes(data: &[u8], pattern: &[u8]) -> usize {
+    data.iter()
+        .zip(pattern)
+        .position(|(byte, expected)| *expected != 0x00 && byte != expected)
+        .map_or(pattern.len(), |mismatch| mismatch + 1)
+}
+
+pub(crate) fn matches_at(data: &[u8], pattern: &[u8]) -> bool {
+    data.len() == pattern.len()
+        && data
+            .iter()
+            .zip(pattern)
+            .all(|(byte, expected)| *expected == 0x00 || byte == expected)
+}
+
+/// A KMP matcher fed a stream of chunks, for memory too large to copy at once or read piecewise,
+/// e.g. from a remote process.
+///
+/// The partial match at the end of a chunk is carried over to the next one, so matches straddling
+/// chunk boundaries are found, and offsets are reported relative to the start of the stream.
+///
+/// # Example
+/// ```
+/// use verity_memory::pattern::algorithm::StreamMatcher;
+///
+/// let mut matcher = StreamMatcher::new("48 8B ?? 10").unwrap();
+/// assert_eq!(matcher.feed(&[0x90, 0x90, 0x48, 0x8B]), vec![]);
+/// assert_eq!(matcher.feed(&[0x05, 0x10, 0x90]), vec![2]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StreamMatcher {
+    pattern: Vec<u8>,
+    /// The failure table, or `None` when the pattern has wildcards and is matched naively.
+    lps: Option<Vec<usize>>,
+    j: usize,
+    /// The last bytes fed, up to one less than the pattern, for naive matches straddling chunks.
+    tail: Vec<u8>,
+    offset: usize,
+}
+
+impl StreamMatcher {
+    /// Creates a matcher for a pattern formatted like `"48 8B ?? ?? 89"`.
+    ///
+    /// # Errors
+    /// - `AobScanError::InvalidPattern`: If a token of the pattern string is invalid.
+    /// - `AobScanError::EmptyPattern`: If the pattern string contains no tokens.
+    pub fn new(pattern: &str) -> Result<Self, AobScanError> {
+        let pattern = convert_pattern(pattern)?;
+        Ok(StreamMatcher {
+            lps: (!prefers_naive(&pattern)).then(|| compute_lps(&pattern)),
+            pattern,
+            j: 0,
+            tail: Vec::new(),
+            offset: 0,
+        })
+    }
+
+    /// Feeds the next chunk of the stream, returning the stream offset of every match completed
+    /// within it.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<usize> {
+        let matches = match &self.lps {
+            Some(lps) => {
+                let pattern = &self.pattern;
+                let mut matches = Vec::new();
+                let mut i = 0;
+
+                while i < chunk.len() {
+                    if pattern[self.j] == chunk[i] {
+                        i += 1;
+                        self.j += 1;
+
+                        if self.j == pattern.len() {
+                            matches.push(self.offset + i - self.j);
+                            self.j = lps[self.j - 1];
+                        }
+                    } else if self.j != 0 {
+                        self.j = lps[self.j - 1];
+                    } else {
+                        i += 1;
+                    }
+                }
+
+                matches
+            }
+            None => self.feed_naive(chunk),
+        };
+
+        self.offset += chunk.len();
+        matches
+    }
+
+    /// Matches the pattern at every offset of the carried tail followed by `chunk`. The tail is
+    /// shorter than the pattern, so every match found ends in `chunk` and is reported only once.
+    fn feed_naive(&mut self, chunk: &[u8]) -> Vec<usize> {
+        let start = self.offset - self.tail.len();
+        self.tail.extend_from_slice(chunk);
+
+        let matches = KmpMatches::new(&self.tail, &self.pattern)
+            .map(|index| start + index)
+            .collect();
+
+        let keep = self.tail.len().min(self.pattern.len() - 1);
+        self.tail.drain(..self.tail.len() - keep);
+        matches
+    }
+
+    /// Returns the number of bytes fed so far.
+    pub fn position(&self) -> usize {
+        self.offset
+    }
+
+    /// Forgets any partial match and restarts the stream at offset 0.
+    pub fn reset(&mut self) {
+        self.j = 0;
+        self.tail.clear();
+        self.offset = 0;
+    }
+}
+
+/// Lazily yields the index of every match of `pattern` in `data`, so callers that only need
+/// the first few matches don't pay for a full scan.
+///
+/// Patterns without wildcards are matched with KMP; patterns with wildcards are compared at every
+/// offset instead, since KMP would skip matches, see [`prefers_naive`].
+pub(crate) struct KmpMatches<'a> {
+    data: &'a [u8],
+    pattern: &'a [u8],
+    /// The failure table, or `None` when the pattern is matched naively.
+    lps: Option<Vec<usize>>,
+    i: usize,
+    j: usize,
+    #[cfg(feature = "stats")]
+    comparisons: usize,
+}
+
+impl<'a> KmpMatches<'a> {
+    pub(crate) fn new(data: &'a [u8], pattern: &'a [u8]) -> Self {
+        KmpMatches {
+            data,
+            pattern,
+            lps: (!prefers_naive(pattern)).then(|| compute_lps(pattern)),
+            i: 0,
+            j: 0,
+            #[cfg(feature = "stats")]
+            comparisons: 0,
+        }
+    }
+
+    /// Restarts matching at `index` of the data, discarding any partial match.
+    pub(crate) fn restart_at(&mut self, index: usize) {
+        self.i = index.min(self.data.len());
+        self.j = 0;
+    }
+
+    /// Compares the current pattern byte with the current data byte, counting the comparison.
+    fn compare(&mut self) -> bool {
+        #[cfg(feature = "stats")]
+        {
+            self.comparisons += 1;
+        }
+
+        self.pattern[self.j] == self.data[self.i]
+    }
+
+    /// Returns where to resume in the pattern after the bytes before `j` matched, from the failure table.
+    fn fallback(&self) -> usize {
+        self.lps.as_ref().map_or(0, |lps| lps[self.j - 1])
+    }
+
+    /// Compares the pattern at every offset from `i`, which is the next offset to try.
+    fn next_naive(&mut self) -> Option<usize> {
+        let (data, pattern) = (self.data, self.pattern);
+
+        while self.i + pattern.len() <= data.len() {
+            let start = self.i;
+            self.i += 1;
+
+            let window = &data[start..start + pattern.len()];
+            #[cfg(feature = "stats")]
+            {
+                self.comparisons += compared_bytes(window, pattern);
+            }
+
+            if matches_at(window, pattern) {
+                return Some(start);
+            }
+        }
+
+        self.i = data.len();
+        None
+    }
+}
+
+impl Iterator for KmpMatches<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let (data, pattern) = (self.data, self.pattern);
+        if pattern.is_empty() {
+            return None;
+        }
+
+        if self.lps.is_none() {
+            return self.next_naive();
+        }
+
+        while self.i < data.len() {
+            if self.compare() {
+                self.i += 1;
+                self.j += 1;
+            }
+
+            if self.j == pattern.len() {
+                let index = self.i - self.j;
+                self.j = self.fallback();
+                return Some(index);
+            } else if self.i < data.len() && !self.compare() {
+                if self.j != 0 {
+                    self.j = self.fallback();
+                } else {
+                    self.i += 1;
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Computes the KMP failure table of a pattern without wildcards.
 pub(crate) fn compute_lps(pattern: &[u8]) -> Vec<usize> {
     let mut lps = vec![0; pattern.len()];
     let mut j = 0;
     let mut i = 1;
 
     while i < pattern.len() {
-        if pattern[i] == pattern[j] || pattern[j] == 0x00 {
+        if pattern[i] == pattern[j] {
             j += 1;
             lps[i] = j;
             i += 1;
@@ -94,4 +719,383 @@ pub(crate) fn compute_lps(pattern: &[u8]) -> Vec<usize> {
     }
 
     lps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kmp_matches_lazy() {
+        let data = [0xAA, 0xBB, 0xAA, 0xBB, 0xAA, 0xBB];
+        let pattern = [0xAA, 0xBB];
+
+        let mut matches = KmpMatches::new(&data, &pattern);
+        assert_eq!(matches.next(), Some(0));
+        assert_eq!(matches.next(), Some(2));
+        assert_eq!(matches.i, 4);
+        assert_eq!(matches.collect::<Vec<_>>(), vec![4]);
+    }
+
+    #[test]
+    fn test_kmp_search_spaced() {
+        let mut data = vec![0x90u8; 40];
+        for index in [0, 4, 8, 24] {
+            data[index..index + 3].copy_from_slice(&[0x55, 0x8B, 0xEC]);
+        }
+        let pattern = [0x55, 0x8B, 0xEC];
+
+        assert_eq!(kmp_search_spaced(&data, &pattern, 16), Ok(vec![0, 24]));
+        assert_eq!(kmp_search_spaced(&data, &pattern, 4), Ok(vec![0, 4, 8, 24]));
+        assert_eq!(kmp_search_spaced(&data, &pattern, 0), kmp_search_all(&data, &pattern));
+        assert_eq!(kmp_search_spaced(&data, &pattern, 64), Ok(vec![0]));
+    }
+
+    #[test]
+    fn test_kmp_search_spaced_overlapping() {
+        let data = [0xAA; 6];
+        let pattern = [0xAA, 0xAA];
+
+        assert_eq!(kmp_search_spaced(&data, &pattern, 1), Ok(vec![0, 1, 2, 3, 4]));
+        assert_eq!(kmp_search_spaced(&data, &pattern, 2), Ok(vec![0, 2, 4]));
+    }
+
+    #[test]
+    fn test_scan_pattern_spaced_gaps() {
+        let mut data = vec![0x90u8; 40];
+        for index in [0, 4, 8, 24] {
+            data[index..index + 3].copy_from_slice(&[0x55, 0x8B, 0xEC]);
+        }
+
+        assert_eq!(scan_pattern_spaced(&data, "55 [0-1] EC", 16), Ok(vec![0, 24]));
+        assert_eq!(scan_pattern_spaced(&data, "55 [0-1] EC", 4), Ok(vec![0, 4, 8, 24]));
+        assert_eq!(scan_pattern_spaced(&data, "55 8B EC", 16), Ok(vec![0, 24]));
+        assert_eq!(scan_pattern_spaced(&data, "55 [0-1] ED", 4), Err(AobScanError::PatternNotFound));
+    }
+
+    #[test]
+    fn test_rfind_before() {
+        let data = [0xAA, 0xBB, 0x90, 0xAA, 0xBB, 0x90, 0xAA, 0xBB];
+        let pattern = [0xAA, 0x00];
+
+        assert_eq!(rfind_before(&data, &pattern, data.len()), Ok(6));
+        assert_eq!(rfind_before(&data, &pattern, 6), Ok(3));
+        assert_eq!(rfind_before(&data, &pattern, 4), Ok(3));
+        assert_eq!(rfind_before(&data, &pattern, 3), Ok(0));
+        assert_eq!(rfind_before(&data, &pattern, 0), Err(AobScanError::PatternNotFound));
+        assert_eq!(rfind_before(&data, &[0xCC], data.len()), Err(AobScanError::PatternNotFound));
+        assert_eq!(rfind_before(&data[..1], &pattern, 1), Err(AobScanError::PatternNotFound));
+        assert_eq!(rfind_before(&data, &[], data.len()), Err(AobScanError::EmptyPattern));
+    }
+
+    #[test]
+    fn test_stream_matcher_straddling_chunks() {
+        let data = [0x90, 0x48, 0x8B, 0x05, 0x10, 0x90, 0x48, 0x8B, 0x0D, 0x10];
+        let pattern = "48 8B ?? 10";
+
+        let mut matcher = StreamMatcher::new(pattern).unwrap();
+        assert_eq!(matcher.feed(&data[..3]), vec![]);
+        assert_eq!(matcher.feed(&data[3..8]), vec![1]);
+        assert_eq!(matcher.feed(&data[8..]), vec![6]);
+        assert_eq!(matcher.position(), data.len());
+
+        let expected = kmp_search_all(&data, &convert_pattern(pattern).unwrap()).unwrap();
+        for chunk_size in 1..=data.len() {
+            let mut matcher = StreamMatcher::new(pattern).unwrap();
+            let found: Vec<usize> = data.chunks(chunk_size).flat_map(|chunk| matcher.feed(chunk)).collect();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn test_stream_matcher_overlapping() {
+        let mut matcher = StreamMatcher::new("AA AA").unwrap();
+        assert_eq!(matcher.feed(&[0xAA]), vec![]);
+        assert_eq!(matcher.feed(&[0xAA, 0xAA]), vec![0, 1]);
+
+        matcher.reset();
+        assert_eq!(matcher.feed(&[0xAA]), vec![]);
+        assert_eq!(matcher.position(), 1);
+        assert!(StreamMatcher::new("").is_err());
+    }
+
+    #[test]
+    fn test_convert_pattern_invalid_token() {
+        let result = convert_pattern("48 8B ?? XY 89");
+        assert_eq!(
+            result,
+            Err(AobScanError::InvalidPattern {
+                token: "XY".to_string(),
+                position: 3,
+            })
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "InvalidPattern: token \"XY\" at position 3"
+        );
+    }
+
+    #[test]
+    fn test_convert_pattern_rejects_malformed_tokens() {
+        for (pattern, token, position) in [
+            ("48 8 05", "8", 1),
+            ("48 8B 0", "0", 2),
+            ("488B", "488B", 0),
+            ("48 +8", "+8", 1),
+            ("48 ? 05", "?", 1),
+            ("48 ??? 05", "???", 1),
+        ] {
+            assert_eq!(
+                convert_pattern(pattern),
+                Err(AobScanError::InvalidPattern {
+                    token: token.to_string(),
+                    position,
+                })
+            );
+        }
+
+        assert_eq!(convert_pattern("0a FF ?? 00"), Ok(vec![0x0A, 0xFF, 0x00, 0x00]));
+    }
+
+    #[test]
+    fn test_convert_pattern_empty() {
+        assert_eq!(convert_pattern("   "), Err(AobScanError::EmptyPattern));
+    }
+
+    #[test]
+    #[cfg(feature = "stats")]
+    fn test_scan_stats_not_found() {
+        let data = [0x10, 0x20, 0x30, 0x40, 0x50, 0x60];
+        let mut stats = ScanStats::default();
+
+        let result = kmp_search_all_with_stats(&data, &[0x30, 0x99], &mut stats);
+        assert_eq!(result, Err(AobScanError::PatternNotFound));
+        assert_eq!(stats.bytes_scanned, data.len());
+        assert!(stats.comparisons >= data.len());
+    }
+
+    #[test]
+    #[cfg(feature = "stats")]
+    fn test_scan_stats_counts_naive_comparisons() {
+        let data = [0x01, 0x02, 0x03, 0x01, 0x02, 0x04];
+        let mut stats = ScanStats::default();
+
+        // 3 bytes at offset 0, 1 at offsets 1 and 2, and 3 for the match at offset 3.
+        let result = kmp_search_all_with_stats(&data, &[0x01, 0x00, 0x04], &mut stats);
+        assert_eq!(result, Ok(vec![3]));
+        assert_eq!(stats.comparisons, 8);
+        assert_eq!(stats.bytes_scanned, data.len());
+    }
+
+    #[test]
+    #[cfg(feature = "stats")]
+    fn test_scan_stats_found() {
+        let data = [0x10, 0x20, 0x30, 0x40, 0x30, 0x40];
+        let mut stats = ScanStats::default();
+
+        let result = kmp_search_all_with_stats(&data, &[0x30, 0x40], &mut stats);
+        assert_eq!(result, Ok(vec![2, 4]));
+        assert_eq!(stats.bytes_scanned, data.len());
+        assert!(stats.comparisons > 0);
+    }
+
+    #[test]
+    fn test_gap_pattern_spacings() {
+        let pattern = GapPattern::new("48 8B [0-8] 89 05").unwrap();
+
+        for spacing in 0..=8 {
+            let mut data = vec![0x90u8; 4];
+            data.extend_from_slice(&[0x48, 0x8B]);
+            data.resize(data.len() + spacing, 0xCC);
+            data.extend_from_slice(&[0x89, 0x05, 0x90]);
+            assert_eq!(pattern.find(&data), Some(4), "spacing {}", spacing);
+        }
+
+        let mut too_far = vec![0x48, 0x8B];
+        too_far.resize(too_far.len() + 9, 0xCC);
+        too_far.extend_from_slice(&[0x89, 0x05]);
+        assert_eq!(pattern.find(&too_far), None);
+    }
+
+    #[test]
+    fn test_gap_pattern_multiple_gaps() {
+        let pattern = GapPattern::new("AA [1-2] BB ?? [0-1] CC").unwrap();
+        let data = [0xAA, 0x00, 0xBB, 0x11, 0xCC, 0xAA, 0x00, 0x00, 0xBB, 0x11, 0x00, 0xCC, 0xAA, 0xBB, 0x11, 0xCC];
+
+        assert_eq!(pattern.find_all(&data), vec![0, 5]);
+        assert_eq!(scan_pattern(&data, "AA [1-2] BB ?? [0-1] CC", ScanMode::All), Ok(vec![0, 5]));
+        assert_eq!(scan_pattern(&data, "AA [1-2] BB ?? [0-1] CC", ScanMode::Unique), Err(AobScanError::NotUnique));
+        assert_eq!(scan_pattern(&data, "AA [1-2] BB ?? [0-1] CC", ScanMode::Nth(1)), Ok(vec![5]));
+        assert_eq!(scan_pattern(&data, "AA [3] CC", ScanMode::All), Ok(vec![0]));
+        assert_eq!(scan_pattern(&data, "AA [2] [2] AA", ScanMode::All), Ok(vec![0]));
+        assert_eq!(scan_pattern(&data, "BB 11", ScanMode::All), Ok(vec![2, 8, 13]));
+    }
+
+    #[test]
+    fn test_gap_pattern_invalid() {
+        let invalid = |token: &str, position| {
+            Err(AobScanError::InvalidPattern {
+                token: token.to_string(),
+                position,
+            })
+        };
+
+        assert_eq!(GapPattern::new("[0-4] AA"), invalid("[0-4]", 0));
+        assert_eq!(GapPattern::new("AA BB [0-4]"), invalid("[0-4]", 2));
+        assert_eq!(GapPattern::new("AA [4-2] BB"), invalid("[4-2]", 1));
+        assert_eq!(GapPattern::new("AA [x] BB"), invalid("[x]", 1));
+        assert_eq!(GapPattern::new("AA [1-2 BB"), invalid("[1-2", 1));
+        assert_eq!(GapPattern::new(" "), Err(AobScanError::EmptyPattern));
+    }
+
+    #[test]
+    fn test_find_sequence() {
+        let mut data = vec![0x90u8; 64];
+        data[4..7].copy_from_slice(&[0x55, 0x8B, 0xEC]);
+        data[10..12].copy_from_slice(&[0x6A, 0x00]);
+        data[20..22].copy_from_slice(&[0xFF, 0xD0]);
+        let patterns = [
+            convert_pattern("55 8B EC").unwrap(),
+            convert_pattern("6A ??").unwrap(),
+            convert_pattern("FF D0").unwrap(),
+        ];
+
+        assert_eq!(find_sequence(&data, &patterns, 8), Ok(vec![4, 10, 20]));
+        assert_eq!(find_sequence(&data, &patterns, 3), Err(AobScanError::PatternNotFound));
+
+        let reversed = [patterns[2].clone(), patterns[1].clone(), patterns[0].clone()];
+        assert_eq!(find_sequence(&data, &reversed, 64), Err(AobScanError::PatternNotFound));
+        assert_eq!(find_sequence(&data, &[], 8), Err(AobScanError::EmptyPattern));
+    }
+
+    #[test]
+    fn test_find_sequence_backtracks() {
+        // The first "6A" after the prologue leads nowhere, the second one is followed by the call.
+        let data = [0x55, 0x6A, 0x01, 0x90, 0x90, 0x6A, 0x02, 0x90, 0x90, 0xFF, 0xD0];
+        let patterns = [vec![0x55], vec![0x6A, 0x00], vec![0xFF, 0xD0]];
+
+        assert_eq!(find_sequence(&data, &patterns, 4), Ok(vec![0, 5, 9]));
+        assert_eq!(find_sequence(&data, &patterns, 0), Err(AobScanError::PatternNotFound));
+    }
+
+    #[test]
+    fn test_scan_modes() {
+        let data = [0x90, 0x55, 0x8B, 0xEC, 0x55, 0x8B, 0xEC, 0x90, 0x55, 0x8B, 0xEC];
+        let pattern = convert_pattern("55 8B ??").unwrap();
+
+        assert_eq!(scan(&data, &pattern, ScanMode::First), Ok(vec![kmp_search_unique(&data, &pattern).unwrap()]));
+        assert_eq!(scan(&data, &pattern, ScanMode::All), kmp_search_all(&data, &pattern));
+        assert_eq!(scan(&data, &pattern, ScanMode::All), Ok(vec![1, 4, 8]));
+        assert_eq!(scan(&data, &pattern, ScanMode::Nth(0)), Ok(vec![1]));
+        assert_eq!(scan(&data, &pattern, ScanMode::Nth(2)), Ok(vec![8]));
+        assert_eq!(scan(&data, &pattern, ScanMode::Nth(3)), Err(AobScanError::PatternNotFound));
+        assert_eq!(scan(&data, &pattern, ScanMode::Unique), Err(AobScanError::NotUnique));
+
+        let unique = convert_pattern("EC 90 55").unwrap();
+        assert_eq!(scan(&data, &unique, ScanMode::Unique), Ok(vec![6]));
+        assert_eq!(scan(&data, &unique, ScanMode::Unique), kmp_search_all(&data, &unique));
+
+        let missing = convert_pattern("CC CC").unwrap();
+        for mode in [ScanMode::First, ScanMode::All, ScanMode::Unique, ScanMode::Nth(0)] {
+            assert_eq!(scan(&data, &missing, mode), Err(AobScanError::PatternNotFound));
+            assert_eq!(scan(&data, &[], mode), Err(AobScanError::EmptyPattern));
+        }
+    }
+
+    #[test]
+    fn test_prefers_naive() {
+        assert!(!prefers_naive(&convert_pattern("48 8B 05 10").unwrap()));
+        assert!(prefers_naive(&convert_pattern("48 8B 05 ?? 10 20 30 40").unwrap()));
+        assert!(prefers_naive(&convert_pattern("48 8B 05 ??").unwrap()));
+        assert!(prefers_naive(&convert_pattern("01 ?? 01").unwrap()));
+    }
+
+    #[test]
+    fn test_naive_matches_overlapping_wildcards() {
+        let data = [0x01, 0x01, 0x01, 0x01];
+        let pattern = convert_pattern("01 ?? 01").unwrap();
+
+        assert_eq!(KmpMatches::new(&data, &pattern).collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(scan(&data, &pattern, ScanMode::All), Ok(vec![0, 1]));
+    }
+
+    #[test]
+    fn test_wildcard_match_skipped_by_kmp() {
+        let data = [0x01, 0x02, 0x01, 0x02, 0x01, 0x01, 0x01, 0x01];
+        let pattern_str = "01 02 ?? 01 01";
+        let pattern = convert_pattern(pattern_str).unwrap();
+
+        assert_eq!(scan(&data, &pattern, ScanMode::All), Ok(vec![2]));
+        assert_eq!(kmp_search_spaced(&data, &pattern, 1), Ok(vec![2]));
+        assert_eq!(find_sequence(&data, &[pattern.clone()], 0), Ok(vec![2]));
+        assert_eq!(GapPattern::new("01 02 ?? [0] 01 01").unwrap().find_all(&data), vec![2]);
+
+        let mut matcher = StreamMatcher::new(pattern_str).unwrap();
+        let found: Vec<usize> = data.chunks(3).flat_map(|chunk| matcher.feed(chunk)).collect();
+        assert_eq!(found, vec![2]);
+    }
+
+    #[test]
+    fn test_naive_matches_parity() {
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let data: Vec<u8> = (0..4096)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state % 4) as u8 + 1
+            })
+            .collect();
+        let brute_force = |pattern: &[u8]| -> Vec<usize> {
+            (0..=data.len() - pattern.len())
+                .filter(|&index| matches_at(&data[index..index + pattern.len()], pattern))
+                .collect()
+        };
+
+        for pattern in ["01 02 03", "04 04 01 02", "02 03 02 03 02"] {
+            let pattern = convert_pattern(pattern).unwrap();
+            assert!(!prefers_naive(&pattern));
+            assert_eq!(KmpMatches::new(&data, &pattern).collect::<Vec<_>>(), brute_force(&pattern));
+        }
+
+        for pattern in [
+            "01 ?? 02",
+            "01 02 ?? 01 01",
+            "03 ?? ?? 04 ?? 01",
+            "02 ?? 02 ?? 02 ?? 02 ?? 02 ?? 02 ?? 02 ?? 02 ?? 02 ?? 02 ?? 02 ?? 02",
+        ] {
+            let mut matcher = StreamMatcher::new(pattern).unwrap();
+            let pattern = convert_pattern(pattern).unwrap();
+            assert!(prefers_naive(&pattern));
+            assert_eq!(scan(&data, &pattern, ScanMode::All).unwrap_or_default(), brute_force(&pattern));
+
+            let streamed: Vec<usize> = data.chunks(7).flat_map(|chunk| matcher.feed(chunk)).collect();
+            assert_eq!(streamed, brute_force(&pattern));
+        }
+    }
+
+    #[test]
+    fn test_naive_matches_short_data() {
+        let pattern = convert_pattern("01 ?? 01").unwrap();
+        assert_eq!(KmpMatches::new(&[0x01, 0x01], &pattern).count(), 0);
+        assert_eq!(scan(&[0x01], &pattern, ScanMode::First), Err(AobScanError::PatternNotFound));
+    }
+
+    #[test]
+    fn test_convert_pattern_comments() {
+        let documented = "
+            48 8B 05 ?? ?? ?? ??   # mov rax, [rip + disp32]
+            48 85 C0               # test rax, rax
+\t\t74 ??                  #je short
+            # ret follows
+        ";
+
+        assert_eq!(convert_pattern(documented), convert_pattern("48 8B 05 ?? ?? ?? ?? 48 85 C0 74 ??"));
+        assert!(!has_gaps(documented));
+        assert_eq!(convert_pattern("# nothing but a comment"), Err(AobScanError::EmptyPattern));
+        assert_eq!(
+            GapPattern::new("48 8B [0-4] # any register\r\nC3"),
+            GapPattern::new("48 8B [0-4] C3")
+        );
+    }
 }
\ No newline at end of file
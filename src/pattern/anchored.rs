@@ -0,0 +1,167 @@
+use crate::{errors::AobScanError, ops::read::read_bytes};
+
+use super::aob::scan_unique;
+
+struct Field {
+    name: String,
+    disp_at: usize,
+    opcode_len: usize,
+}
+
+/// A signature together with the addresses derived from it.
+///
+/// Many addresses are reached through the rel32/disp32 operand of an instruction next to a stable
+/// signature, e.g. the global referenced by `mov rax, [rip + disp32]`. An `AnchoredScan` scans the
+/// signature once and resolves every registered field from the same match, so a set of offsets can
+/// be kept up to date across versions by updating a single signature.
+///
+/// # Example
+/// ```
+/// use verity_memory::pattern::anchored::AnchoredScan;
+///
+/// // mov eax, [rip + 0x10]; mov ecx, [rip + 0x20]
+/// let code = [0x8B, 0x05, 0x10, 0x00, 0x00, 0x00, 0x8B, 0x0D, 0x20, 0x00, 0x00, 0x00];
+///
+/// let scan = AnchoredScan::new("8B 05 ?? ?? ?? ?? 8B 0D ?? ?? ?? ??")
+///     .field("health", 2, 6)
+///     .field("armor", 8, 12);
+///
+/// let resolved = unsafe { scan.resolve(code.as_ptr()) }.unwrap();
+/// assert_eq!(resolved.get("health"), Some(code.as_ptr().wrapping_add(6 + 0x10) as *mut u8));
+/// assert_eq!(resolved.get("armor"), Some(code.as_ptr().wrapping_add(12 + 0x20) as *mut u8));
+/// ```
+pub struct AnchoredScan {
+    signature: String,
+    fields: Vec<Field>,
+}
+
+impl AnchoredScan {
+    /// Creates a scan for the given signature, formatted like `"48 8B 05 ?? ?? ?? ??"`.
+    pub fn new(signature: &str) -> Self {
+        AnchoredScan {
+            signature: signature.to_string(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Registers an address resolved through a 32-bit displacement in the match.
+    ///
+    /// The field resolves to `match + opcode_len + disp`, the way the CPU resolves RIP-relative
+    /// operands and relative branches.
+    ///
+    /// # Parameters
+    /// - `name`: The name the address is looked up by in the [`AnchoredMatch`].
+    /// - `disp_at`: The offset of the displacement from the start of the match.
+    /// - `opcode_len`: The offset of the end of the instruction from the start of the match, i.e. the
+    ///   length of the instruction if it is the first one of the signature.
+    pub fn field(mut self, name: &str, disp_at: usize, opcode_len: usize) -> Self {
+        self.fields.push(Field {
+            name: name.to_string(),
+            disp_at,
+            opcode_len,
+        });
+        self
+    }
+
+    /// Scans for the signature and resolves every registered field.
+    ///
+    /// # Safety
+    /// This function is unsafe because it reads the memory around the match.
+    ///
+    /// # Errors
+    /// - `AobScanError::PatternNotFound`: If the signature is not found.
+    /// - `AobScanError::InvalidPattern`: If a token of the signature is invalid.
+    /// - `AobScanError::EmptyPattern`: If the signature is empty.
+    /// - `AobScanError::InvalidAccess`: If a displacement could not be read.
+    pub unsafe fn scan(&self) -> Result<AnchoredMatch, AobScanError> {
+        let base = scan_unique(&self.signature).as_ptr()?;
+        self.resolve(base)
+    }
+
+    /// Resolves every registered field against a match at `base`, without scanning.
+    ///
+    /// # Safety
+    /// This function is unsafe because it reads the memory at the registered offsets from `base`.
+    ///
+    /// # Errors
+    /// - `AobScanError::InvalidAccess`: If a displacement could not be read.
+    pub unsafe fn resolve(&self, base: *const u8) -> Result<AnchoredMatch, AobScanError> {
+        let mut fields = Vec::with_capacity(self.fields.len());
+
+        for field in &self.fields {
+            let bytes = read_bytes(base.wrapping_add(field.disp_at), 4)
+                .map_err(|_| AobScanError::InvalidAccess)?;
+            let disp = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+            let address = base
+                .wrapping_add(field.opcode_len)
+                .wrapping_offset(disp as isize) as *mut u8;
+            fields.push((field.name.clone(), address));
+        }
+
+        Ok(AnchoredMatch {
+            base: base as *mut u8,
+            fields,
+        })
+    }
+}
+
+/// The addresses resolved by an [`AnchoredScan`].
+#[derive(Debug, Clone)]
+pub struct AnchoredMatch {
+    base: *mut u8,
+    fields: Vec<(String, *mut u8)>,
+}
+
+impl AnchoredMatch {
+    /// Returns the start of the signature match.
+    pub fn base(&self) -> *mut u8 {
+        self.base
+    }
+
+    /// Returns the address of the field registered under `name`.
+    pub fn get(&self, name: &str) -> Option<*mut u8> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, address)| *address)
+    }
+
+    /// Returns every field in registration order.
+    pub fn fields(&self) -> &[(String, *mut u8)] {
+        &self.fields
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anchored_resolve_two_fields() {
+        // call rel32; lea rcx, [rip - 0x20]
+        let code = [
+            0xE8, 0x00, 0x01, 0x00, 0x00, 0x48, 0x8D, 0x0D, 0xE0, 0xFF, 0xFF, 0xFF,
+        ];
+        let base = code.as_ptr();
+
+        let scan = AnchoredScan::new("E8 ?? ?? ?? ?? 48 8D 0D ?? ?? ?? ??")
+            .field("update", 1, 5)
+            .field("global", 8, 12);
+
+        let resolved = unsafe { scan.resolve(base) }.expect("Failed to resolve fields");
+        assert_eq!(resolved.base(), base as *mut u8);
+        assert_eq!(resolved.get("update"), Some(base.wrapping_add(5 + 0x100) as *mut u8));
+        assert_eq!(resolved.get("global"), Some(base.wrapping_add(12).wrapping_sub(0x20) as *mut u8));
+        assert_eq!(resolved.get("missing"), None);
+        assert_eq!(resolved.fields().len(), 2);
+    }
+
+    #[test]
+    fn test_anchored_resolve_null() {
+        let scan = AnchoredScan::new("E8 ?? ?? ?? ??").field("update", 1, 5);
+
+        let result = unsafe { scan.resolve(std::ptr::null()) };
+        assert!(matches!(result, Err(AobScanError::InvalidAccess)));
+    }
+}
\ No newline at end of file
@@ -1,9 +1,18 @@
 use crate::{
     errors::AobScanError,
-    pattern::algorithm::{convert_pattern, kmp_search_all, kmp_search_unique},
+    ops::read::read_bytes,
+    pattern::algorithm::{
+        convert_fixed_pattern, find_sequence, kmp_search_all, kmp_search_unique, matches_at, rfind_before, scan_pattern,
+        scan_pattern_spaced, validate_pattern, ScanMode,
+    },
 };
+#[cfg(feature = "stats")]
+use crate::pattern::algorithm::{kmp_search_all_with_stats, ScanStats};
+
+use std::time::Duration;
 
 use super::memory::get_text_section;
+use super::resolve::ScanResult;
 
 /// # Safety
 ///
@@ -15,40 +24,369 @@ use super::memory::get_text_section;
 /// Scans the text section of the current process's memory for a unique occurrence of a byte pattern
 /// specified by the given `pattern` string.
 ///
-/// This function uses the Knuth-Morris-Pratt (KMP) algorithm to efficiently search for the byte pattern.
-/// If the pattern is found, it returns a mutable pointer to the first byte of the matched pattern.
+/// Patterns without wildcards are searched with the Knuth-Morris-Pratt (KMP) algorithm. Patterns with
+/// `??` wildcards are compared at every offset instead, since the KMP failure table can skip real
+/// matches once a wildcard is involved.
+/// The match is returned as a [`ScanResult`], so resolve steps can be chained onto it before taking
+/// the final pointer with [`ScanResult::as_ptr`].
 ///
 /// # Parameters
 /// - `pattern`: A string representing the byte pattern to search for. This pattern must be formatted as
-///   a hexadecimal string with wildcards (e.g., `"48 8B ?? ?? 89 ?? 74 0F"`).
+///   a hexadecimal string with wildcards (e.g., `"48 8B ?? ?? 89 ?? 74 0F"`), and may contain
+///   variable-length gaps such as `[0-8]` (see [`GapPattern`](crate::pattern::algorithm::GapPattern)).
 ///
 /// # Returns
-/// - `Ok(*mut u8)`: A mutable pointer to the first byte of the unique matched pattern.
-/// - `Err(AobScanError)`: An error if the pattern is not found or is invalid.
+/// - A [`ScanResult`] starting at the first byte of the unique matched pattern, or carrying the
+///   error if the pattern is not found or is invalid.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if the pattern is not found in the text section.
+/// - `AobScanError::InvalidPattern`: Returned if a token of the pattern string is invalid.
+/// - `AobScanError::EmptyPattern`: Returned if the pattern string contains no tokens.
+/// - `AobScanError::Pe`: Returned if the module headers are invalid, e.g. the text section lies outside the module.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::aob;
+///
+/// unsafe {
+///     match aob::scan_unique("48 8B ?? ?? 89 ?? 74 0F").as_ptr() {
+///         Ok(ptr) => println!("Pattern found at address: {:?}", ptr),
+///         Err(e) => println!("Failed to find pattern: {}", e),
+///     }
+///
+///     // Follow the displacement of a `mov rax, [rip + disp32]` to the global it loads.
+///     if let Ok(global) = aob::scan_unique("48 8B 05 ?? ?? ?? ??").rel32(7, 3).as_ptr() {
+///         println!("Global at address: {:?}", global);
+///     }
+/// }
+/// ```
+pub unsafe fn scan_unique(pattern: &str) -> ScanResult {
+    ScanResult::from(find_unique(pattern))
+}
+
+pub(crate) unsafe fn find_unique(pattern: &str) -> Result<*mut u8, AobScanError> {
+    let test_region = get_text_section()?;
+
+    let indices = scan_pattern(&test_region.0, pattern, ScanMode::First)?;
+    Ok((test_region.1 + indices[0]) as *mut u8)
+}
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that the returned pointer is handled safely.
+///
+/// # Description
+///
+/// Behaves like [`scan_unique`], but re-reads the live bytes at the match before returning it.
+///
+/// The scan runs over a copy of the text section, so self-modifying or JIT-compiled code may have
+/// changed between the copy and the pointer being handed out. Verifying the live bytes catches a
+/// match that is already stale.
+///
+/// # Parameters
+/// - `pattern`: A string representing the byte pattern to search for (e.g., `"48 8B ?? ?? 89 ?? 74 0F"`).
+///
+/// # Returns
+/// - `Ok(*mut u8)`: A mutable pointer to the first byte of the matched pattern.
+/// - `Err(AobScanError)`: An error if the pattern is not found, is invalid or no longer matches.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if the pattern is not found in the text section.
+/// - `AobScanError::InvalidPattern`: Returned if a token of the pattern string is invalid.
+/// - `AobScanError::EmptyPattern`: Returned if the pattern string contains no tokens.
+/// - `AobScanError::GapsUnsupported`: Returned if the pattern contains variable-length gaps, which
+///   this scanner doesn't support.
+/// - `AobScanError::Changed`: Returned if the live bytes at the match no longer match the pattern.
+/// - `AobScanError::InvalidAccess`: Returned if the live bytes could not be read.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::aob;
+///
+/// unsafe {
+///     match aob::scan_unique_verified("48 8B ?? ?? 89 ?? 74 0F") {
+///         Ok(ptr) => println!("Pattern found at address: {:?}", ptr),
+///         Err(e) => println!("Failed to find pattern: {}", e),
+///     }
+/// }
+/// ```
+pub unsafe fn scan_unique_verified(pattern: &str) -> Result<*mut u8, AobScanError> {
+    let pattern_bytes = convert_fixed_pattern(pattern)?;
+    let test_region = get_text_section()?;
+
+    let index = kmp_search_unique(&test_region.0, &pattern_bytes)?;
+    let ptr = (test_region.1 + index) as *mut u8;
+
+    verify_match(ptr, &pattern_bytes)?;
+    Ok(ptr)
+}
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that the returned pointer is handled safely.
+///
+/// # Description
+///
+/// Behaves like [`scan_unique`], but re-scans up to `attempts` times, sleeping `delay` between
+/// tries. This is useful when injecting early into a process, before the code containing the
+/// pattern has been loaded or unpacked.
+///
+/// # Parameters
+/// - `pattern`: A string representing the byte pattern to search for (e.g., `"48 8B ?? ?? 89 ?? 74 0F"`).
+/// - `attempts`: The maximum number of scans.
+/// - `delay`: The time to wait between two scans.
+///
+/// # Returns
+/// - `Ok(*mut u8)`: A mutable pointer to the first byte of the matched pattern, from the first successful scan.
+/// - `Err(AobScanError)`: The error of the last scan if every attempt failed.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if the pattern was never found, or `attempts` is zero.
+/// - `AobScanError::InvalidPattern`: Returned if a token of the pattern string is invalid. Invalid
+///   patterns are reported immediately instead of being retried.
+/// - `AobScanError::EmptyPattern`: Returned if the pattern string contains no tokens.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use verity_memory::pattern::aob;
+///
+/// unsafe {
+///     match aob::scan_unique_retry("48 8B ?? ?? 89 ?? 74 0F", 3, Duration::from_millis(10)) {
+///         Ok(ptr) => println!("Pattern found at address: {:?}", ptr),
+///         Err(e) => println!("Failed to find pattern: {}", e),
+///     }
+/// }
+/// ```
+pub unsafe fn scan_unique_retry(pattern: &str, attempts: usize, delay: Duration) -> Result<*mut u8, AobScanError> {
+    validate_pattern(pattern)?;
+    retry(attempts, delay, || find_unique(pattern))
+}
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that the returned pointer is handled safely.
+///
+/// # Description
+///
+/// Behaves like [`scan_unique`], but rejects a match lying within `margin` bytes of the start or
+/// end of the text section.
+///
+/// Placing a hook writes a jump or trampoline over and around the match, which must not run past
+/// the mapped section. Pass the number of bytes the patch may reach beyond the match in either
+/// direction.
+///
+/// # Parameters
+/// - `pattern`: A string representing the byte pattern to search for (e.g., `"48 8B ?? ?? 89 ?? 74 0F"`).
+/// - `margin`: The minimum number of bytes between the section start and the match, and between
+///   the end of the match and the section end.
+///
+/// # Returns
+/// - `Ok(*mut u8)`: A mutable pointer to the first byte of the matched pattern.
+/// - `Err(AobScanError)`: An error if the pattern is not found, is invalid or is too close to an edge.
 ///
 /// # Errors
 /// - `AobScanError::PatternNotFound`: Returned if the pattern is not found in the text section.
-/// - `AobScanError::InvalidPattern`: Returned if the pattern string is invalid.
+/// - `AobScanError::TooCloseToEdge`: Returned if the match is within `margin` bytes of either edge.
+/// - `AobScanError::InvalidPattern`: Returned if a token of the pattern string is invalid.
+/// - `AobScanError::EmptyPattern`: Returned if the pattern string contains no tokens.
+/// - `AobScanError::GapsUnsupported`: Returned if the pattern contains variable-length gaps, which
+///   this scanner doesn't support.
+/// - `AobScanError::Pe`: Returned if the module headers are invalid, e.g. the text section lies outside the module.
 ///
 /// # Examples
 /// ```
 /// use verity_memory::pattern::aob;
 ///
 /// unsafe {
-///     match aob::scan_unique("48 8B ?? ?? 89 ?? 74 0F") {
+///     match aob::scan_unique_with_margin("48 8B ?? ?? 89 ?? 74 0F", 16) {
 ///         Ok(ptr) => println!("Pattern found at address: {:?}", ptr),
 ///         Err(e) => println!("Failed to find pattern: {}", e),
 ///     }
 /// }
 /// ```
-pub unsafe fn scan_unique(pattern: &str) -> Result<*mut u8, AobScanError> {
-    let pattern_bytes = convert_pattern(pattern)?;
-    let test_region = get_text_section();
+pub unsafe fn scan_unique_with_margin(pattern: &str, margin: usize) -> Result<*mut u8, AobScanError> {
+    let pattern_bytes = convert_fixed_pattern(pattern)?;
+    let test_region = get_text_section()?;
 
     let index = kmp_search_unique(&test_region.0, &pattern_bytes)?;
+    check_margin(index, pattern_bytes.len(), test_region.0.len(), margin)?;
+    Ok((test_region.1 + index) as *mut u8)
+}
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that the returned pointer is handled safely.
+///
+/// # Description
+///
+/// Behaves like [`scan_unique`], but also checks the bytes around the match before returning it.
+///
+/// A pattern that is unique today may match somewhere else entirely after the target is updated.
+/// Each check is an `(offset, expected)` pair: the bytes at `match + offset` must equal `expected`
+/// exactly, which encodes whatever else is known about the surroundings of the match (a call a few
+/// bytes further, a prologue before it, ...) without making the pattern itself longer.
+///
+/// # Parameters
+/// - `pattern`: A string representing the byte pattern to search for (e.g., `"48 8B ?? ?? 89 ?? 74 0F"`).
+/// - `checks`: The bytes expected at offsets relative to the first byte of the match.
+///
+/// # Returns
+/// - `Ok(*mut u8)`: A mutable pointer to the first byte of the matched pattern.
+/// - `Err(AobScanError)`: An error if the pattern is not found, is invalid or a check failed.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if the pattern is not found in the text section.
+/// - `AobScanError::NotUnique`: Returned if the pattern matches more than once.
+/// - `AobScanError::ValidationFailed { offset }`: Returned for the first check whose bytes differ or
+///   lie outside the text section.
+/// - `AobScanError::InvalidPattern`: Returned if a token of the pattern string is invalid.
+/// - `AobScanError::EmptyPattern`: Returned if the pattern string contains no tokens.
+/// - `AobScanError::Pe`: Returned if the module headers are invalid, e.g. the text section lies outside the module.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::aob;
+///
+/// unsafe {
+///     // The match must be followed by a call 8 bytes later.
+///     match aob::scan_unique_validated("48 8B ?? ?? 89 ?? 74 0F", &[(8, &[0xE8])]) {
+///         Ok(ptr) => println!("Pattern found at address: {:?}", ptr),
+///         Err(e) => println!("Failed to find pattern: {}", e),
+///     }
+/// }
+/// ```
+pub unsafe fn scan_unique_validated(pattern: &str, checks: &[(isize, &[u8])]) -> Result<*mut u8, AobScanError> {
+    validate_pattern(pattern)?;
+    let test_region = get_text_section()?;
+
+    let index = find_unique_validated(&test_region.0, pattern, checks)?;
     Ok((test_region.1 + index) as *mut u8)
 }
 
+/// Finds the only match of `pattern` in `data` and checks the bytes around it.
+pub(crate) fn find_unique_validated(data: &[u8], pattern: &str, checks: &[(isize, &[u8])]) -> Result<usize, AobScanError> {
+    let index = scan_pattern(data, pattern, ScanMode::Unique)?[0];
+    check_bytes(data, index, checks)?;
+    Ok(index)
+}
+
+/// Checks that `data` holds the expected bytes of every check relative to `index`.
+pub(crate) fn check_bytes(data: &[u8], index: usize, checks: &[(isize, &[u8])]) -> Result<(), AobScanError> {
+    for &(offset, expected) in checks {
+        let actual = index
+            .checked_add_signed(offset)
+            .and_then(|start| data.get(start..start.checked_add(expected.len())?));
+
+        if actual != Some(expected) {
+            return Err(AobScanError::ValidationFailed { offset });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that a match of `len` bytes at `index` leaves `margin` bytes on both sides within a
+/// region of `region_len` bytes.
+pub(crate) fn check_margin(index: usize, len: usize, region_len: usize, margin: usize) -> Result<(), AobScanError> {
+    let end = index.saturating_add(len);
+    if index < margin || region_len.saturating_sub(end) < margin {
+        Err(AobScanError::TooCloseToEdge)
+    } else {
+        Ok(())
+    }
+}
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that the returned pointers are handled safely.
+///
+/// # Description
+///
+/// Scans the text section for several patterns that must appear in order, each one starting at
+/// most `max_gap` bytes after the end of the previous match, e.g. to find the function containing
+/// a few distinctive code idioms when none of them is unique on its own.
+///
+/// The first sequence satisfying the constraints is returned.
+///
+/// # Parameters
+/// - `patterns`: The patterns to find, in order (e.g., `&["55 8B EC", "6A ??", "FF 15"]`).
+/// - `max_gap`: The maximum number of bytes between the end of a match and the start of the next one.
+///
+/// # Returns
+/// - `Ok(Vec<*mut u8>)`: A pointer to the first byte of the match of each pattern.
+/// - `Err(AobScanError)`: An error if the sequence is not found or a pattern is invalid.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if no sequence satisfies the constraints.
+/// - `AobScanError::InvalidPattern`: Returned if a token of a pattern string is invalid.
+/// - `AobScanError::EmptyPattern`: Returned if `patterns` is empty or a pattern contains no tokens.
+/// - `AobScanError::GapsUnsupported`: Returned if the pattern contains variable-length gaps, which
+///   this scanner doesn't support.
+/// - `AobScanError::Pe`: Returned if the module headers are invalid, e.g. the text section lies outside the module.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::aob;
+///
+/// unsafe {
+///     match aob::scan_sequence(&["55 8B EC", "6A ??", "FF 15"], 32) {
+///         Ok(ptrs) => println!("Sequence found at addresses: {:?}", ptrs),
+///         Err(e) => println!("Failed to find sequence: {}", e),
+///     }
+/// }
+/// ```
+pub unsafe fn scan_sequence(patterns: &[&str], max_gap: usize) -> Result<Vec<*mut u8>, AobScanError> {
+    let patterns = patterns
+        .iter()
+        .map(|pattern| convert_fixed_pattern(pattern))
+        .collect::<Result<Vec<_>, _>>()?;
+    let test_region = get_text_section()?;
+
+    let indices = find_sequence(&test_region.0, &patterns, max_gap)?;
+    Ok(indices
+        .into_iter()
+        .map(|index| (test_region.1 + index) as *mut u8)
+        .collect())
+}
+
+pub(crate) fn retry<T>(
+    attempts: usize,
+    delay: Duration,
+    mut scan: impl FnMut() -> Result<T, AobScanError>,
+) -> Result<T, AobScanError> {
+    let mut result = Err(AobScanError::PatternNotFound);
+
+    for attempt in 0..attempts {
+        if attempt > 0 {
+            std::thread::sleep(delay);
+        }
+
+        result = scan();
+        if result.is_ok() {
+            break;
+        }
+    }
+
+    result
+}
+
+pub(crate) unsafe fn verify_match(ptr: *const u8, pattern_bytes: &[u8]) -> Result<(), AobScanError> {
+    let live = read_bytes(ptr, pattern_bytes.len()).map_err(|_| AobScanError::InvalidAccess)?;
+
+    if matches_at(&live, pattern_bytes) {
+        Ok(())
+    } else {
+        Err(AobScanError::Changed)
+    }
+}
+
 /// # Safety
 ///
 /// This function is unsafe because it involves direct manipulation of memory pointers. The caller
@@ -59,12 +397,15 @@ pub unsafe fn scan_unique(pattern: &str) -> Result<*mut u8, AobScanError> {
 /// Scans the text section of the current process's memory for all occurrences of a byte pattern
 /// specified by the given `pattern` string.
 ///
-/// This function uses the Knuth-Morris-Pratt (KMP) algorithm to efficiently search for the byte pattern.
+/// Patterns without wildcards are searched with the Knuth-Morris-Pratt (KMP) algorithm. Patterns with
+/// `??` wildcards are compared at every offset instead, since the KMP failure table can skip real
+/// matches once a wildcard is involved.
 /// It returns a vector of mutable pointers to the first byte of each matched pattern.
 ///
 /// # Parameters
 /// - `pattern`: A string representing the byte pattern to search for. This pattern must be formatted as
-///   a hexadecimal string with wildcards (e.g., `"48 8B ?? ?? 89 ?? 74 0F"`).
+///   a hexadecimal string with wildcards (e.g., `"48 8B ?? ?? 89 ?? 74 0F"`), and may contain
+///   variable-length gaps such as `[0-8]` (see [`GapPattern`](crate::pattern::algorithm::GapPattern)).
 ///
 /// # Returns
 /// - `Ok(Vec<*mut u8>)`: A vector of mutable pointers to the first byte of each matched pattern.
@@ -72,7 +413,9 @@ pub unsafe fn scan_unique(pattern: &str) -> Result<*mut u8, AobScanError> {
 ///
 /// # Errors
 /// - `AobScanError::PatternNotFound`: Returned if no occurrences of the pattern are found.
-/// - `AobScanError::InvalidPattern`: Returned if the pattern string is invalid.
+/// - `AobScanError::InvalidPattern`: Returned if a token of the pattern string is invalid.
+/// - `AobScanError::EmptyPattern`: Returned if the pattern string contains no tokens.
+/// - `AobScanError::Pe`: Returned if the module headers are invalid, e.g. the text section lies outside the module.
 ///
 /// # Examples
 /// ```
@@ -90,12 +433,638 @@ pub unsafe fn scan_unique(pattern: &str) -> Result<*mut u8, AobScanError> {
 /// }
 /// ```
 pub unsafe fn scan_all(pattern: &str) -> Result<Vec<*mut u8>, AobScanError> {
-    let pattern_bytes = convert_pattern(pattern)?;
-    let test_region = get_text_section();
+    let test_region = get_text_section()?;
+
+    let indices = scan_pattern(&test_region.0, pattern, ScanMode::All)?;
+    Ok(indices
+        .into_iter()
+        .map(|index| (test_region.1 + index) as *mut u8)
+        .collect())
+}
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that the returned pointer is handled safely.
+///
+/// # Description
+///
+/// Returns the `n`-th (zero-based) occurrence of the pattern in the text section. The scan stops
+/// at that match instead of collecting every occurrence like [`scan_all`].
+///
+/// # Parameters
+/// - `pattern`: A string representing the byte pattern to search for (e.g., `"48 8B ?? ?? 89 ?? 74 0F"`),
+///   which may contain variable-length gaps such as `[0-8]`.
+/// - `n`: The zero-based position of the match to return.
+///
+/// # Returns
+/// - `Ok(*mut u8)`: A mutable pointer to the first byte of the `n`-th match.
+/// - `Err(AobScanError)`: An error if there are fewer than `n + 1` matches or the pattern is invalid.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if the pattern occurs fewer than `n + 1` times.
+/// - `AobScanError::InvalidPattern`: Returned if a token of the pattern string is invalid.
+/// - `AobScanError::EmptyPattern`: Returned if the pattern string contains no tokens.
+/// - `AobScanError::Pe`: Returned if the module headers are invalid, e.g. the text section lies outside the module.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::aob;
+///
+/// unsafe {
+///     match aob::scan_nth("48 8B ?? ?? 89 ?? 74 0F", 1) {
+///         Ok(ptr) => println!("Second match at address: {:?}", ptr),
+///         Err(e) => println!("Failed to find pattern: {}", e),
+///     }
+/// }
+/// ```
+pub unsafe fn scan_nth(pattern: &str, n: usize) -> Result<*mut u8, AobScanError> {
+    let test_region = get_text_section()?;
+
+    let indices = scan_pattern(&test_region.0, pattern, ScanMode::Nth(n))?;
+    Ok((test_region.1 + indices[0]) as *mut u8)
+}
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that the returned pointers are handled safely.
+///
+/// # Description
+///
+/// Behaves like [`scan_all`], but also returns the bytes surrounding each match: up to `context`
+/// bytes before the match, the matched bytes, and up to `context` bytes after it. This helps pick
+/// the right match when a signature isn't unique. The context is clamped at the boundaries of the
+/// text section, so matches near its start or end get a shorter window.
+///
+/// # Parameters
+/// - `pattern`: A string representing the byte pattern to search for (e.g., `"48 8B ?? ?? 89 ?? 74 0F"`).
+/// - `context`: The number of bytes to include on each side of a match.
+///
+/// # Returns
+/// - `Ok(Vec<(*mut u8, Vec<u8>)>)`: A pointer to the first byte of each match with its surrounding bytes.
+/// - `Err(AobScanError)`: An error if the pattern is not found or is invalid.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if no occurrences of the pattern are found.
+/// - `AobScanError::InvalidPattern`: Returned if a token of the pattern string is invalid.
+/// - `AobScanError::EmptyPattern`: Returned if the pattern string contains no tokens.
+/// - `AobScanError::GapsUnsupported`: Returned if the pattern contains variable-length gaps, which
+///   this scanner doesn't support.
+/// - `AobScanError::Pe`: Returned if the module headers are invalid, e.g. the text section lies outside the module.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::aob;
+///
+/// unsafe {
+///     if let Ok(matches) = aob::scan_all_with_context("48 8B ?? ?? 89 ?? 74 0F", 8) {
+///         for (ptr, bytes) in matches {
+///             println!("{:?}: {:02X?}", ptr, bytes);
+///         }
+///     }
+/// }
+/// ```
+pub unsafe fn scan_all_with_context(
+    pattern: &str,
+    context: usize,
+) -> Result<Vec<(*mut u8, Vec<u8>)>, AobScanError> {
+    let pattern_bytes = convert_fixed_pattern(pattern)?;
+    let test_region = get_text_section()?;
 
     let indices = kmp_search_all(&test_region.0, &pattern_bytes)?;
+    Ok(indices
+        .into_iter()
+        .map(|index| {
+            let window = context_window(&test_region.0, index, pattern_bytes.len(), context);
+            ((test_region.1 + index) as *mut u8, window.to_vec())
+        })
+        .collect())
+}
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that the returned pointers are handled safely.
+///
+/// # Description
+///
+/// Scans the text section for function prologues matching `pattern`, assuming consecutive matches
+/// are at least `min_spacing` bytes apart. After each match the search resumes `min_spacing` bytes
+/// after its start instead of at the next byte, which saves a lot of comparisons on large modules
+/// when the spacing is known, e.g. the minimum size of the functions being looked for.
+///
+/// Any match starting less than `min_spacing` bytes after the previous one is missed, so closely
+/// packed functions such as small thunks can be skipped. Use [`scan_all`] when every match matters.
+///
+/// # Parameters
+/// - `pattern`: A string representing the byte pattern to search for (e.g., `"55 8B EC"`), which
+///   may contain variable-length gaps such as `[0-8]`.
+/// - `min_spacing`: The minimum distance between the starts of two matches. 0 and 1 find every match.
+///
+/// # Returns
+/// - `Ok(Vec<*mut u8>)`: A vector of mutable pointers to the first byte of each match found.
+/// - `Err(AobScanError)`: An error if the pattern is not found or is invalid.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if no occurrences of the pattern are found.
+/// - `AobScanError::InvalidPattern`: Returned if a token of the pattern string is invalid.
+/// - `AobScanError::EmptyPattern`: Returned if the pattern string contains no tokens.
+/// - `AobScanError::Pe`: Returned if the module headers are invalid, e.g. the text section lies outside the module.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::aob;
+///
+/// unsafe {
+///     if let Ok(prologues) = aob::scan_prologues("48 89 5C 24 ??", 16) {
+///         println!("Found {} functions", prologues.len());
+///     }
+/// }
+/// ```
+pub unsafe fn scan_prologues(pattern: &str, min_spacing: usize) -> Result<Vec<*mut u8>, AobScanError> {
+    validate_pattern(pattern)?;
+    let test_region = get_text_section()?;
+
+    let indices = scan_pattern_spaced(&test_region.0, pattern, min_spacing)?;
+    Ok(indices
+        .into_iter()
+        .map(|index| (test_region.1 + index) as *mut u8)
+        .collect())
+}
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that the returned pointer is handled safely.
+///
+/// # Description
+///
+/// Scans the text section for the highest-addressed occurrence of a byte pattern. The section is
+/// searched backwards from its end, so the scan stops at the last match instead of collecting
+/// every match first.
+///
+/// # Parameters
+/// - `pattern`: A string representing the byte pattern to search for (e.g., `"48 8B ?? ?? 89 ?? 74 0F"`).
+///
+/// # Returns
+/// - `Ok(*mut u8)`: A mutable pointer to the first byte of the last match.
+/// - `Err(AobScanError)`: An error if the pattern is not found or is invalid.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if the pattern is not found in the text section.
+/// - `AobScanError::InvalidPattern`: Returned if a token of the pattern string is invalid.
+/// - `AobScanError::EmptyPattern`: Returned if the pattern string contains no tokens.
+/// - `AobScanError::GapsUnsupported`: Returned if the pattern contains variable-length gaps, which
+///   this scanner doesn't support.
+/// - `AobScanError::Pe`: Returned if the module headers are invalid, e.g. the text section lies outside the module.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::aob;
+///
+/// unsafe {
+///     if let Ok(ptr) = aob::scan_last("CC CC CC CC") {
+///         println!("Last padding run at {:?}", ptr);
+///     }
+/// }
+/// ```
+pub unsafe fn scan_last(pattern: &str) -> Result<*mut u8, AobScanError> {
+    let pattern_bytes = convert_fixed_pattern(pattern)?;
+    let test_region = get_text_section()?;
+
+    let index = rfind_before(&test_region.0, &pattern_bytes, test_region.0.len())?;
+    Ok((test_region.1 + index) as *mut u8)
+}
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that the returned pointer is handled safely.
+///
+/// # Description
+///
+/// Scans the text section backwards from `address` for the closest occurrence of a byte pattern
+/// starting below it, e.g. the prologue of the function containing a known instruction. Only the
+/// bytes between the match and `address` are examined.
+///
+/// # Parameters
+/// - `address`: The address to search backwards from. Matches must start strictly below it.
+/// - `pattern`: A string representing the byte pattern to search for (e.g., `"48 89 5C 24 ??"`).
+///
+/// # Returns
+/// - `Ok(*mut u8)`: A mutable pointer to the first byte of the closest match below `address`.
+/// - `Err(AobScanError)`: An error if the pattern is not found or is invalid.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if no match starts between the text section and `address`.
+/// - `AobScanError::InvalidPattern`: Returned if a token of the pattern string is invalid.
+/// - `AobScanError::EmptyPattern`: Returned if the pattern string contains no tokens.
+/// - `AobScanError::GapsUnsupported`: Returned if the pattern contains variable-length gaps, which
+///   this scanner doesn't support.
+/// - `AobScanError::Pe`: Returned if the module headers are invalid, e.g. the text section lies outside the module.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::aob;
+///
+/// unsafe {
+///     if let Ok(reference) = aob::scan_unique("48 8D 0D ?? ?? ?? ?? E8").as_ptr() {
+///         if let Ok(function) = aob::scan_last_before(reference, "48 89 5C 24 ??") {
+///             println!("Function start at {:?}", function);
+///         }
+///     }
+/// }
+/// ```
+pub unsafe fn scan_last_before(address: *const u8, pattern: &str) -> Result<*mut u8, AobScanError> {
+    let pattern_bytes = convert_fixed_pattern(pattern)?;
+    let test_region = get_text_section()?;
+
+    let limit = (address as usize)
+        .checked_sub(test_region.1)
+        .ok_or(AobScanError::PatternNotFound)?;
+
+    let index = rfind_before(&test_region.0, &pattern_bytes, limit)?;
+    Ok((test_region.1 + index) as *mut u8)
+}
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that the returned pointers are handled safely.
+///
+/// # Description
+///
+/// Scans the text section for instructions using `value` as a 32-bit immediate. The common
+/// encodings are tried for every general purpose register:
+///
+/// - `mov r32, imm32` (`B8+r` and `C7 /0`)
+/// - `cmp r32, imm32` (`81 /7`, and `3D` for `eax`)
+/// - `push imm32` (`68`)
+///
+/// On x64 the `r8d`-`r15d` forms with a `REX.B` prefix are included too, as are the 64-bit
+/// `mov r64, imm32` and `cmp r64, imm32` forms with a `REX.W` prefix. Unlike a byte pattern, zero
+/// bytes of the immediate are compared exactly instead of acting as wildcards.
+///
+/// # Parameters
+/// - `value`: The immediate to search for.
+///
+/// # Returns
+/// - `Ok(Vec<*mut u8>)`: A pointer to the first byte of each instruction, in ascending order.
+/// - `Err(AobScanError)`: An error if no instruction uses the immediate.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if no instruction loading or comparing `value` is found.
+/// - `AobScanError::Pe`: Returned if the module headers are invalid, e.g. the text section lies outside the module.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::aob;
+///
+/// unsafe {
+///     if let Ok(ptrs) = aob::scan_immediate(0x1337) {
+///         for ptr in ptrs {
+///             println!("0x1337 used at {:?}", ptr);
+///         }
+///     }
+/// }
+/// ```
+pub unsafe fn scan_immediate(value: u32) -> Result<Vec<*mut u8>, AobScanError> {
+    let test_region = get_text_section()?;
+
+    let indices = find_immediate(&test_region.0, value);
+    if indices.is_empty() {
+        return Err(AobScanError::PatternNotFound);
+    }
+
     Ok(indices
         .into_iter()
         .map(|index| (test_region.1 + index) as *mut u8)
         .collect())
+}
+
+/// The opcode bytes preceding the immediate in the encodings searched by [`scan_immediate`].
+fn immediate_opcodes() -> Vec<Vec<u8>> {
+    let (rex_prefixes, rex_w_prefixes): (&[Option<u8>], &[u8]) = if cfg!(target_arch = "x86_64") {
+        (&[None, Some(0x41)], &[0x48, 0x49])
+    } else {
+        (&[None], &[])
+    };
+
+    let mut opcodes = vec![vec![0x68], vec![0x3D]];
+    for prefix in rex_prefixes {
+        for register in 0..8u8 {
+            for opcode in [vec![0xB8 + register], vec![0xC7, 0xC0 + register], vec![0x81, 0xF8 + register]] {
+                opcodes.push(prefix.into_iter().copied().chain(opcode).collect());
+            }
+        }
+    }
+
+    // `REX.W` turns `C7 /0`, `81 /7` and `3D` into their 64-bit forms, which still take an imm32.
+    // `B8+r` is left out, since with `REX.W` it takes an imm64.
+    if !rex_w_prefixes.is_empty() {
+        opcodes.push(vec![0x48, 0x3D]);
+    }
+    for &prefix in rex_w_prefixes {
+        for register in 0..8u8 {
+            opcodes.push(vec![prefix, 0xC7, 0xC0 + register]);
+            opcodes.push(vec![prefix, 0x81, 0xF8 + register]);
+        }
+    }
+
+    opcodes
+}
+
+/// Returns the start of every instruction in `data` using `value` as an immediate.
+///
+/// When several encodings end right before the same immediate, e.g. `C7 C0` and `48 C7 C0`, the
+/// longest one is taken, so the returned index is the start of the prefix.
+pub(crate) fn find_immediate(data: &[u8], value: u32) -> Vec<usize> {
+    let immediate = value.to_le_bytes();
+    let opcodes = immediate_opcodes();
+
+    let mut indices: Vec<usize> = data
+        .windows(immediate.len())
+        .enumerate()
+        .filter(|(_, window)| *window == immediate)
+        .filter_map(|(index, _)| {
+            opcodes
+                .iter()
+                .filter(|opcode| index >= opcode.len() && data[index - opcode.len()..index] == opcode[..])
+                .map(|opcode| index - opcode.len())
+                .min()
+        })
+        .collect();
+
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+pub(crate) fn context_window(data: &[u8], index: usize, len: usize, context: usize) -> &[u8] {
+    let start = index.saturating_sub(context);
+    let end = index.saturating_add(len).saturating_add(context).min(data.len());
+    &data[start..end]
+}
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that the returned pointers are handled safely.
+///
+/// # Description
+///
+/// Behaves like [`scan_all`], but also reports statistics about the scan. This helps diagnose why
+/// a signature is slow, e.g. a wildcard-heavy pattern that has to be compared at every offset.
+///
+/// The statistics are returned whether or not the pattern was found, since a slow scan that finds
+/// nothing is the one most worth diagnosing. They are zero if the scan never ran.
+///
+/// Only available with the `stats` feature, so the default scan path carries no bookkeeping.
+///
+/// # Parameters
+/// - `pattern`: A string representing the byte pattern to search for (e.g., `"48 8B ?? ?? 89 ?? 74 0F"`).
+///
+/// # Returns
+/// A tuple of:
+/// - `Ok(Vec<*mut u8>)` with the matches, or `Err(AobScanError)` if the pattern is not found or is invalid.
+/// - The statistics of the scan.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if no occurrences of the pattern are found.
+/// - `AobScanError::InvalidPattern`: Returned if a token of the pattern string is invalid.
+/// - `AobScanError::EmptyPattern`: Returned if the pattern string contains no tokens.
+/// - `AobScanError::GapsUnsupported`: Returned if the pattern contains variable-length gaps, which
+///   this scanner doesn't support.
+/// - `AobScanError::Pe`: Returned if the module headers are invalid, e.g. the text section lies outside the module.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::aob;
+///
+/// unsafe {
+///     let (result, stats) = aob::scan_all_with_stats("48 8B ?? ?? 89 ?? 74 0F");
+///     println!("{} comparisons over {} bytes in {:?}", stats.comparisons, stats.bytes_scanned, stats.elapsed);
+///     if let Ok(ptrs) = result {
+///         println!("{} matches", ptrs.len());
+///     }
+/// }
+/// ```
+#[cfg(feature = "stats")]
+pub unsafe fn scan_all_with_stats(pattern: &str) -> (Result<Vec<*mut u8>, AobScanError>, ScanStats) {
+    let mut stats = ScanStats::default();
+    let result = convert_fixed_pattern(pattern).and_then(|pattern_bytes| {
+        let test_region = get_text_section()?;
+        let indices = kmp_search_all_with_stats(&test_region.0, &pattern_bytes, &mut stats)?;
+
+        Ok(indices
+            .into_iter()
+            .map(|index| (test_region.1 + index) as *mut u8)
+            .collect())
+    });
+
+    (result, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::algorithm::convert_pattern;
+
+    #[test]
+    fn test_verify_match_detects_change() {
+        let mut buffer = vec![0x90, 0x48, 0x8B, 0x05, 0x10, 0x20, 0xC3];
+        let pattern_bytes = convert_pattern("48 8B ?? ?? 20").unwrap();
+
+        let index = kmp_search_unique(&buffer, &pattern_bytes).unwrap();
+        let ptr = unsafe { buffer.as_ptr().add(index) };
+        assert_eq!(unsafe { verify_match(ptr, &pattern_bytes) }, Ok(()));
+
+        buffer[index + 2] = 0x8D;
+        let ptr = unsafe { buffer.as_ptr().add(index) };
+        assert_eq!(unsafe { verify_match(ptr, &pattern_bytes) }, Err(AobScanError::Changed));
+    }
+
+    #[test]
+    fn test_verify_match_ignores_wildcards() {
+        let mut buffer = vec![0x48, 0x8B, 0x05, 0x10];
+        let pattern_bytes = convert_pattern("48 8B ?? ??").unwrap();
+
+        buffer[3] = 0xFF;
+        assert_eq!(unsafe { verify_match(buffer.as_ptr(), &pattern_bytes) }, Ok(()));
+    }
+
+    #[test]
+    fn test_check_margin_near_end() {
+        let mut buffer = vec![0x90u8; 64];
+        buffer[58..62].copy_from_slice(&[0x48, 0x8B, 0x05, 0x10]);
+        let pattern_bytes = convert_pattern("48 8B ?? 10").unwrap();
+
+        let index = kmp_search_unique(&buffer, &pattern_bytes).unwrap();
+        assert_eq!(index, 58);
+        assert_eq!(check_margin(index, pattern_bytes.len(), buffer.len(), 32), Err(AobScanError::TooCloseToEdge));
+        assert_eq!(check_margin(index, pattern_bytes.len(), buffer.len(), 3), Err(AobScanError::TooCloseToEdge));
+        assert_eq!(check_margin(index, pattern_bytes.len(), buffer.len(), 2), Ok(()));
+        assert_eq!(check_margin(index, pattern_bytes.len(), buffer.len(), 0), Ok(()));
+    }
+
+    #[test]
+    fn test_check_margin_near_start() {
+        assert_eq!(check_margin(4, 4, 64, 5), Err(AobScanError::TooCloseToEdge));
+        assert_eq!(check_margin(4, 4, 64, 4), Ok(()));
+        assert_eq!(check_margin(0, 4, 4, 0), Ok(()));
+        assert_eq!(check_margin(0, 4, 4, 1), Err(AobScanError::TooCloseToEdge));
+    }
+
+    /// `push rbp` at 4, `mov rax, [rip + ..]` at 8 and `call [rip + ..]` at 16, padded with `int3`.
+    fn validated_fixture() -> Vec<u8> {
+        let mut buffer = vec![0xCCu8; 32];
+        buffer[4] = 0x55;
+        buffer[8..12].copy_from_slice(&[0x48, 0x8B, 0x05, 0x10]);
+        buffer[16..18].copy_from_slice(&[0xFF, 0x15]);
+        buffer
+    }
+
+    #[test]
+    fn test_check_bytes() {
+        let buffer = validated_fixture();
+        let cases: &[(&[(isize, &[u8])], Result<(), AobScanError>)] = &[
+            (&[(-4, &[0x55]), (8, &[0xFF, 0x15])], Ok(())),
+            (&[], Ok(())),
+            (&[(-4, &[0x55]), (8, &[0xE8])], Err(AobScanError::ValidationFailed { offset: 8 })),
+            (&[(-9, &[0xCC])], Err(AobScanError::ValidationFailed { offset: -9 })),
+            (&[(22, &[0xCC, 0xCC, 0xCC])], Err(AobScanError::ValidationFailed { offset: 22 })),
+        ];
+
+        for (checks, expected) in cases {
+            assert_eq!(&check_bytes(&buffer, 8, checks), expected, "checks {:?}", checks);
+        }
+    }
+
+    #[test]
+    fn test_find_unique_validated_rejects_duplicates() {
+        let mut buffer = validated_fixture();
+
+        assert_eq!(find_unique_validated(&buffer, "48 8B ?? 10", &[(-4, &[0x55])]), Ok(8));
+        assert_eq!(find_unique_validated(&buffer, "48 [0-2] 10", &[(-4, &[0x55])]), Ok(8));
+
+        buffer[20..24].copy_from_slice(&[0x48, 0x8B, 0x0D, 0x10]);
+        let result = find_unique_validated(&buffer, "48 8B ?? 10", &[(-4, &[0x55])]);
+        assert_eq!(result, Err(AobScanError::NotUnique));
+    }
+
+    #[test]
+    #[cfg(feature = "stats")]
+    fn test_scan_all_with_stats_reports_errors() {
+        let (result, stats) = unsafe { scan_all_with_stats("48 8B ZZ") };
+        assert!(matches!(result, Err(AobScanError::InvalidPattern { .. })));
+        assert_eq!(stats, ScanStats::default());
+    }
+
+    #[test]
+    fn test_retry_succeeds_once_pattern_appears() {
+        let pattern_bytes = convert_pattern("48 8B ?? ?? 20").unwrap();
+        let mut buffer = vec![0x90u8; 8];
+        let mut scans = 0;
+
+        let result = retry(5, Duration::from_millis(1), || {
+            scans += 1;
+            if scans == 3 {
+                buffer[2..7].copy_from_slice(&[0x48, 0x8B, 0x05, 0x10, 0x20]);
+            }
+            kmp_search_unique(&buffer, &pattern_bytes)
+        });
+
+        assert_eq!(result, Ok(2));
+        assert_eq!(scans, 3);
+    }
+
+    #[test]
+    fn test_retry_returns_last_error() {
+        let mut scans = 0;
+
+        let result: Result<usize, AobScanError> = retry(3, Duration::ZERO, || {
+            scans += 1;
+            Err(AobScanError::NotUnique)
+        });
+
+        assert_eq!(result, Err(AobScanError::NotUnique));
+        assert_eq!(scans, 3);
+        assert_eq!(retry(0, Duration::ZERO, || Ok(1)), Err(AobScanError::PatternNotFound));
+    }
+
+    #[test]
+    fn test_scan_unique_retry_accepts_gaps() {
+        assert_eq!(validate_pattern("48 8B [0-4] C3"), Ok(()));
+
+        let result = unsafe { scan_unique_retry("F1 F1 [0-4] F1 F1 F1 F1 F1 F1", 1, Duration::ZERO) };
+        assert!(!matches!(result, Err(AobScanError::InvalidPattern { .. })));
+        assert_eq!(convert_fixed_pattern("48 8B [0-4] C3"), Err(AobScanError::GapsUnsupported));
+    }
+
+    #[test]
+    fn test_find_immediate() {
+        let data = [
+            0xB8, 0x37, 0x13, 0x00, 0x00, // mov eax, 0x1337
+            0x90, 0x37, 0x13, 0x00, 0x00, // nop; not an immediate
+            0x81, 0xF9, 0x37, 0x13, 0x00, 0x00, // cmp ecx, 0x1337
+            0x68, 0x37, 0x13, 0x00, 0x00, // push 0x1337
+            0xBA, 0x37, 0x13, 0x01, 0x00, // mov edx, 0x11337
+            0x3D, 0x37, 0x13, 0x00, 0x00, // cmp eax, 0x1337
+        ];
+
+        assert_eq!(find_immediate(&data, 0x1337), vec![0, 10, 16, 26]);
+        assert_eq!(find_immediate(&data, 0x11337), vec![21]);
+        assert!(find_immediate(&data, 0xDEAD).is_empty());
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_find_immediate_extended_registers() {
+        // mov r9d, 0x1337
+        let data = [0x41, 0xB9, 0x37, 0x13, 0x00, 0x00];
+        assert_eq!(find_immediate(&data, 0x1337), vec![0]);
+
+        let data = [
+            0x48, 0xC7, 0xC0, 0x37, 0x13, 0x00, 0x00, // mov rax, 0x1337
+            0x49, 0x81, 0xF9, 0x37, 0x13, 0x00, 0x00, // cmp r9, 0x1337
+            0x48, 0x3D, 0x37, 0x13, 0x00, 0x00, // cmp rax, 0x1337
+        ];
+        assert_eq!(find_immediate(&data, 0x1337), vec![0, 7, 14]);
+    }
+
+    #[test]
+    fn test_scan_immediate_live() {
+        #[inline(never)]
+        fn constant() -> u32 {
+            0x5EC2_7A11
+        }
+
+        assert_eq!(std::hint::black_box(constant as fn() -> u32)(), 0x5EC2_7A11);
+
+        let ptrs = unsafe { scan_immediate(0x5EC2_7A11) }.expect("Failed to find the immediate");
+        for ptr in ptrs {
+            let bytes = unsafe { read_bytes(ptr, 8) }.unwrap();
+            assert!(bytes.windows(4).any(|window| window == 0x5EC2_7A11u32.to_le_bytes()));
+        }
+    }
+
+    #[test]
+    fn test_context_window() {
+        let data: Vec<u8> = (0..16).collect();
+        let pattern_bytes = convert_pattern("07 08").unwrap();
+        let index = kmp_search_unique(&data, &pattern_bytes).unwrap();
+
+        assert_eq!(context_window(&data, index, pattern_bytes.len(), 3), &[4, 5, 6, 7, 8, 9, 10, 11]);
+        assert_eq!(context_window(&data, index, pattern_bytes.len(), 0), &[7, 8]);
+    }
+
+    #[test]
+    fn test_context_window_clamped() {
+        let data: Vec<u8> = (0..16).collect();
+
+        assert_eq!(context_window(&data, 1, 2, 4), &[0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(context_window(&data, 14, 2, 4), &[10, 11, 12, 13, 14, 15]);
+        assert_eq!(context_window(&data, 0, 16, usize::MAX), data.as_slice());
+    }
 }
\ No newline at end of file
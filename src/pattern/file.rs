@@ -0,0 +1,192 @@
+use std::fs::File;
+use std::os::windows::io::AsRawHandle;
+use std::path::Path;
+
+use winapi::shared::minwindef::LPCVOID;
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::memoryapi::{CreateFileMappingW, MapViewOfFile, UnmapViewOfFile, FILE_MAP_READ};
+use winapi::um::winnt::{HANDLE, PAGE_READONLY};
+
+use crate::{
+    errors::{AobScanError, PeParseError},
+    pattern::algorithm::{scan_pattern, validate_pattern, ScanMode},
+    pe::{image::check_headers_in_bounds, parse_pe},
+};
+
+/// A read-only view of a whole file, unmapped when dropped.
+struct FileView {
+    mapping: HANDLE,
+    view: *const u8,
+    len: usize,
+}
+
+impl FileView {
+    fn map(file: &File) -> Result<FileView, AobScanError> {
+        let len = file.metadata().map_err(|_| AobScanError::InvalidAccess)?.len() as usize;
+
+        let mapping = unsafe {
+            CreateFileMappingW(
+                file.as_raw_handle() as HANDLE,
+                std::ptr::null_mut(),
+                PAGE_READONLY,
+                0,
+                0,
+                std::ptr::null(),
+            )
+        };
+        if mapping.is_null() {
+            return Err(AobScanError::InvalidAccess);
+        }
+
+        let view = unsafe { MapViewOfFile(mapping, FILE_MAP_READ, 0, 0, 0) } as *const u8;
+        if view.is_null() {
+            unsafe { CloseHandle(mapping) };
+            return Err(AobScanError::InvalidAccess);
+        }
+
+        Ok(FileView { mapping, view, len })
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.view, self.len) }
+    }
+}
+
+impl Drop for FileView {
+    fn drop(&mut self) {
+        unsafe {
+            UnmapViewOfFile(self.view as LPCVOID);
+            CloseHandle(self.mapping);
+        }
+    }
+}
+
+/// # Description
+///
+/// Scans the `.text` section of a PE file on disk for all occurrences of a byte pattern, without
+/// loading or running it. This allows developing and testing signatures offline.
+///
+/// The file is memory-mapped as-is rather than loaded as an image, so the section is read from its
+/// raw data in the file. The matches are returned as RVAs, which are independent of where the module
+/// is loaded: add them to the base address of the loaded module to get the live addresses.
+///
+/// # Parameters
+/// - `path`: The path of the PE file (`.exe` or `.dll`).
+/// - `pattern`: A string representing the byte pattern to search for (e.g., `"48 8B ?? ?? 89 ?? 74 0F"`),
+///   which may contain variable-length gaps such as `[0-8]`.
+///
+/// # Returns
+/// - `Ok(Vec<usize>)`: The RVA of every match.
+/// - `Err(AobScanError)`: An error if the pattern is not found or is invalid, or the file is not a valid PE.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if no occurrences of the pattern are found, or the file has no `.text` section.
+/// - `AobScanError::InvalidPattern`: Returned if a token of the pattern string is invalid.
+/// - `AobScanError::EmptyPattern`: Returned if the pattern string contains no tokens.
+/// - `AobScanError::InvalidAccess`: Returned if the file could not be opened or mapped.
+/// - `AobScanError::Pe`: Returned if the headers of the file are invalid or its `.text` section is truncated.
+///
+/// # Examples
+/// ```no_run
+/// use std::path::Path;
+/// use verity_memory::pattern::file;
+///
+/// match file::scan_file(Path::new("C:\\Games\\game.exe"), "48 8B ?? ?? 89 ?? 74 0F") {
+///     Ok(rvas) => {
+///         for rva in rvas {
+///             println!("Pattern found at RVA {:#X}", rva);
+///         }
+///     }
+///     Err(e) => println!("Failed to find pattern: {}", e),
+/// }
+/// ```
+pub fn scan_file(path: &Path, pattern: &str) -> Result<Vec<usize>, AobScanError> {
+    validate_pattern(pattern)?;
+
+    let file = File::open(path).map_err(|_| AobScanError::InvalidAccess)?;
+    let view = FileView::map(&file)?;
+
+    scan_file_bytes(view.as_slice(), pattern)
+}
+
+pub(crate) fn scan_file_bytes(data: &[u8], pattern: &str) -> Result<Vec<usize>, AobScanError> {
+    check_headers_in_bounds(data)?;
+    let image = unsafe { parse_pe(data.as_ptr() as usize) }?;
+
+    let text = image.section(".text").ok_or(AobScanError::PatternNotFound)?;
+    let start = text.pointer_to_raw_data as usize;
+    let end = start
+        .checked_add(text.size_of_raw_data as usize)
+        .filter(|&end| end <= data.len())
+        .ok_or(PeParseError::SectionOutOfBounds)?;
+
+    let indices = scan_pattern(&data[start..end], pattern, ScanMode::All)?;
+    Ok(indices
+        .into_iter()
+        .map(|index| text.virtual_address as usize + index)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pe::test_image::TestImage;
+
+    const TEXT_OFFSET: usize = 0x200;
+    const TEXT_RVA: u32 = 0x1000;
+
+    /// Builds a minimal PE file whose `.text` section holds `code`.
+    fn tiny_file(code: &[u8]) -> Vec<u8> {
+        TestImage::new(TEXT_OFFSET + code.len())
+            .size_of_image(TEXT_RVA + 0x1000)
+            .size_of_headers(TEXT_OFFSET as u32)
+            .section(".text", TEXT_RVA, TEXT_OFFSET, code.len())
+            .put(TEXT_OFFSET, code)
+            .build()
+    }
+
+    fn fixture_code() -> Vec<u8> {
+        let mut code = vec![0xCCu8; 0x20];
+        code[0x04..0x0A].copy_from_slice(&[0x55, 0x8B, 0xEC, 0x83, 0xEC, 0x10]);
+        code[0x10..0x16].copy_from_slice(&[0x55, 0x8B, 0xEC, 0x83, 0xEC, 0x20]);
+        code
+    }
+
+    #[test]
+    fn test_scan_file() {
+        let path = std::env::temp_dir().join(format!("verity_memory_scan_file_{}.bin", std::process::id()));
+        std::fs::write(&path, tiny_file(&fixture_code())).expect("Failed to write fixture");
+
+        let all = scan_file(&path, "55 8B EC 83 EC ??");
+        let unique = scan_file(&path, "55 8B EC 83 EC 20");
+        let missing = scan_file(&path, "55 8B EC 83 EC 30");
+        let gapped = scan_file(&path, "55 [1-3] EC 20");
+        std::fs::remove_file(&path).expect("Failed to remove fixture");
+
+        assert_eq!(all, Ok(vec![TEXT_RVA as usize + 0x04, TEXT_RVA as usize + 0x10]));
+        assert_eq!(unique, Ok(vec![TEXT_RVA as usize + 0x10]));
+        assert_eq!(missing, Err(AobScanError::PatternNotFound));
+        assert_eq!(gapped, Ok(vec![TEXT_RVA as usize + 0x10]));
+    }
+
+    #[test]
+    fn test_scan_file_truncated_section() {
+        let mut file = tiny_file(&fixture_code());
+        file.truncate(TEXT_OFFSET + 0x08);
+
+        assert_eq!(
+            scan_file_bytes(&file, "55 8B EC"),
+            Err(AobScanError::Pe(PeParseError::SectionOutOfBounds))
+        );
+        assert_eq!(
+            scan_file_bytes(&file[..0x20], "55 8B EC"),
+            Err(AobScanError::Pe(PeParseError::OutOfBounds))
+        );
+    }
+
+    #[test]
+    fn test_scan_file_missing() {
+        let result = scan_file(Path::new("non_existent_file.exe"), "55 8B EC");
+        assert_eq!(result, Err(AobScanError::InvalidAccess));
+    }
+}
\ No newline at end of file
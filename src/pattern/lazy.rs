@@ -0,0 +1,130 @@
+use std::sync::Mutex;
+
+use crate::errors::AobScanError;
+
+use super::algorithm::{scan_pattern, ScanMode};
+use super::memory::get_module_text_section;
+
+/// A signature resolved on first use and cached afterwards.
+///
+/// This backs the [`signature!`](crate::signature) macro, but can also be declared directly as a
+/// `static`. The first call to [`LazySignature::resolve`] scans the `.text` section of the module
+/// and caches the first match, without checking that it is unique; later calls return the cached
+/// address. Failed scans are not cached, so they are retried on the next call.
+///
+/// # Example
+/// ```
+/// use verity_memory::pattern::LazySignature;
+///
+/// static RET: LazySignature = LazySignature::new("C3", None);
+///
+/// let first = unsafe { RET.resolve() }.unwrap();
+/// assert_eq!(unsafe { RET.resolve() }, Ok(first));
+/// RET.reset();
+/// ```
+#[derive(Debug)]
+pub struct LazySignature {
+    pattern: &'static str,
+    module: Option<&'static str>,
+    address: Mutex<Option<usize>>,
+}
+
+impl LazySignature {
+    /// Declares a signature scanned in `module`, or in the executable if `module` is `None`.
+    pub const fn new(pattern: &'static str, module: Option<&'static str>) -> Self {
+        LazySignature {
+            pattern,
+            module,
+            address: Mutex::new(None),
+        }
+    }
+
+    /// Returns the pattern string.
+    pub fn pattern(&self) -> &'static str {
+        self.pattern
+    }
+
+    /// Returns the module the pattern is scanned in, `None` being the executable.
+    pub fn module(&self) -> Option<&'static str> {
+        self.module
+    }
+
+    /// Returns the address of the first match, scanning only if it isn't cached yet.
+    ///
+    /// Concurrent callers wait for a single scan instead of each scanning.
+    ///
+    /// # Safety
+    /// This function is unsafe because it scans the memory of the current process. The caller
+    /// must ensure that the returned pointer is handled safely.
+    ///
+    /// # Errors
+    /// - `AobScanError::PatternNotFound`: If the pattern is not found in the `.text` section. Further
+    ///   matches are not an error: the first one is returned.
+    /// - `AobScanError::InvalidPattern`: If a token of the pattern string is invalid.
+    /// - `AobScanError::EmptyPattern`: If the pattern string contains no tokens.
+    /// - `AobScanError::Pe`: If the module isn't loaded or its headers are invalid.
+    pub unsafe fn resolve(&self) -> Result<*mut u8, AobScanError> {
+        let mut address = self.address.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(address) = *address {
+            return Ok(address as *mut u8);
+        }
+
+        let region = get_module_text_section(self.module)?;
+        let indices = scan_pattern(&region.0, self.pattern, ScanMode::First)?;
+
+        let resolved = region.1 + indices[0];
+        *address = Some(resolved);
+        Ok(resolved as *mut u8)
+    }
+
+    /// Returns the cached address without scanning.
+    pub fn cached(&self) -> Option<*mut u8> {
+        let address = self.address.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        address.map(|address| address as *mut u8)
+    }
+
+    /// Forgets the cached address, so the next [`LazySignature::resolve`] scans again, e.g.
+    /// between tests or after the module was reloaded.
+    pub fn reset(&self) {
+        *self.address.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::PeParseError;
+
+    #[test]
+    fn test_lazy_signature_caches_until_reset() {
+        static RET: LazySignature = LazySignature::new("C3", None);
+
+        assert_eq!(RET.cached(), None);
+        let first = unsafe { RET.resolve() }.expect("Failed to resolve signature");
+        assert_eq!(RET.cached(), Some(first));
+        assert_eq!(unsafe { *first }, 0xC3);
+        assert_eq!(unsafe { RET.resolve() }, Ok(first));
+
+        RET.reset();
+        assert_eq!(RET.cached(), None);
+        assert_eq!(unsafe { RET.resolve() }, Ok(first));
+    }
+
+    #[test]
+    fn test_lazy_signature_module() {
+        let signature = LazySignature::new("C3", Some("kernel32.dll"));
+        let address = unsafe { signature.resolve() }.expect("Failed to resolve signature in kernel32");
+        assert_eq!(unsafe { *address }, 0xC3);
+
+        let missing = LazySignature::new("C3", Some("not_loaded.dll"));
+        assert_eq!(unsafe { missing.resolve() }, Err(AobScanError::Pe(PeParseError::ModuleNotFound)));
+        assert_eq!(missing.cached(), None);
+    }
+
+    #[test]
+    fn test_lazy_signature_errors_not_cached() {
+        let invalid = LazySignature::new("C3 XY", None);
+        assert!(matches!(unsafe { invalid.resolve() }, Err(AobScanError::InvalidPattern { .. })));
+        assert_eq!(invalid.cached(), None);
+    }
+}
\ No newline at end of file
@@ -1,77 +1,172 @@
-use std::ptr;
 use std::slice;
-use winapi::um::libloaderapi::GetModuleHandleA;
-use winapi::um::winnt::{
-    IMAGE_DOS_HEADER, IMAGE_SECTION_HEADER,
-};
 
-#[cfg(target_arch = "x86")]
-use winapi::um::winnt::IMAGE_NT_HEADERS32;
+use crate::errors::PeParseError;
+use crate::pe::{parse_pe_as, PeImage};
+use crate::utils::module_base;
 
-#[cfg(target_arch = "x86_64")]
-use winapi::um::winnt::IMAGE_NT_HEADERS64;
+/// Copies the `.text` section of the executable.
+///
+/// # Errors
+/// - `PeParseError::SectionNotFound`: If the executable has no `.text` section.
+/// - `PeParseError::SectionOutOfBounds`: If the `.text` section lies outside the image.
+/// - Any other error returned by [`parse_pe_as`].
+pub(crate) unsafe fn get_text_section() -> Result<(Vec<u8>, usize), PeParseError> {
+    get_section(b".text")?.ok_or(PeParseError::SectionNotFound)
+}
 
-pub(crate) unsafe fn get_text_section() -> (Vec<u8>, usize) {
+pub(crate) unsafe fn get_section(name: &[u8]) -> Result<Option<(Vec<u8>, usize)>, PeParseError> {
+    let image = get_image()?;
 
-    let base_address = GetModuleHandleA(ptr::null());
-    if base_address.is_null() {
-        panic!("Failed to get module handle");
-    }
-    let base_address = base_address as usize;
+    let section = match image
+        .sections
+        .iter()
+        .find(|section| section.name.as_bytes().starts_with(name))
+    {
+        Some(section) => section,
+        None => return Ok(None),
+    };
 
-    let dos_header = &*(base_address as *const IMAGE_DOS_HEADER);
-    if dos_header.e_magic != 0x5A4D {
-        panic!("Invalid DOS header signature");
-    }
+    let (section_address, section_size) = image.section_bounds(section)?;
+
+    let section_slice = slice::from_raw_parts(section_address as *const u8, section_size);
+
+    Ok(Some((section_slice.to_vec(), section_address)))
+}
 
-    let nt_header_ptr = base_address + dos_header.e_lfanew as usize;
-    let signature = *(nt_header_ptr as *const u32);
-    if signature != 0x4550 {
-        panic!("Invalid NT header signature");
+/// Copies the `.text` section of a loaded module, or of the executable if `module` is `None`.
+///
+/// # Errors
+/// - `PeParseError::ModuleNotFound`: If the module isn't loaded.
+/// - `PeParseError::InvalidModuleName`: If `module` is empty or contains a NUL character.
+/// - `PeParseError::SectionNotFound`: If the module has no `.text` section.
+/// - `PeParseError::SectionOutOfBounds`: If the `.text` section lies outside the image.
+/// - Any other error returned by [`parse_pe_as`].
+pub(crate) unsafe fn get_module_text_section(module: Option<&str>) -> Result<(Vec<u8>, usize), PeParseError> {
+    let text = TextSection::of_module(module)?;
+    Ok((text.bytes().to_vec(), text.address()))
+}
+
+/// The `.text` section of a loaded module, borrowed in place instead of copied.
+///
+/// Unlike the scanners, which copy the section before searching it, [`TextSection::bytes`] builds
+/// a slice over the live memory on demand. Its lifetime is tied to the `TextSection`, so repeated
+/// scans don't clone the section and the slice can't outlive the value it was borrowed from.
+///
+/// The memory of the section must stay mapped for as long as the `TextSection` exists, which is
+/// always the case for the executable, and must not be modified while its bytes are borrowed, see
+/// [`TextSection::main`].
+///
+/// # Example
+/// ```rust
+/// use verity_memory::pattern::TextSection;
+///
+/// let text = unsafe { TextSection::main() }.unwrap();
+/// assert_eq!(text.bytes().len(), text.len());
+/// assert!(text.bytes().contains(&0xC3));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextSection {
+    address: usize,
+    size: usize,
+}
+
+impl TextSection {
+    /// Returns the `.text` section of the executable, which stays mapped for the lifetime of the process.
+    ///
+    /// # Safety
+    /// The bytes returned by [`TextSection::bytes`] must not be modified while they are borrowed,
+    /// e.g. by code patches or hooks installed from other threads.
+    ///
+    /// # Errors
+    /// - Same as [`TextSection::of_module`].
+    pub unsafe fn main() -> Result<TextSection, PeParseError> {
+        TextSection::of_module(None)
     }
 
-    let (number_of_sections, section_header_ptr) = get_nt_headers(nt_header_ptr);
+    /// Returns the `.text` section of a loaded module, or of the executable if `module` is `None`.
+    ///
+    /// # Safety
+    /// The module must not be unloaded while the `TextSection` exists, and the bytes returned by
+    /// [`TextSection::bytes`] must not be modified while they are borrowed, e.g. by code patches
+    /// from other threads.
+    ///
+    /// # Errors
+    /// - `PeParseError::ModuleNotFound`: If the module isn't loaded.
+    /// - `PeParseError::InvalidModuleName`: If `module` is empty or contains a NUL character.
+    /// - `PeParseError::SectionNotFound`: If the module has no `.text` section.
+    /// - `PeParseError::SectionOutOfBounds`: If the `.text` section lies outside the image.
+    /// - Any other error returned by [`parse_pe_as`].
+    pub unsafe fn of_module(module: Option<&str>) -> Result<TextSection, PeParseError> {
+        let base = module_base(module)?;
+        let image = parse_pe_as(base as usize, cfg!(target_pointer_width = "64"))?;
+
+        let section = image
+            .sections
+            .iter()
+            .find(|section| section.name.as_bytes().starts_with(b".text"))
+            .ok_or(PeParseError::SectionNotFound)?;
+        let (address, size) = image.section_bounds(section)?;
 
-    let mut section = section_header_ptr as *const IMAGE_SECTION_HEADER;
-    let mut text_section_ptr: *const IMAGE_SECTION_HEADER = ptr::null();
+        Ok(TextSection { address, size })
+    }
 
-    for _ in 0..number_of_sections {
-        let section_name = (*section).Name;
-        if section_name.starts_with(b".text") {
-            text_section_ptr = section;
-            break;
-        }
-        section = section.add(1);
+    /// Returns the address of the first byte of the section.
+    pub fn address(&self) -> usize {
+        self.address
     }
 
-    if text_section_ptr.is_null() {
-        panic!("Failed to locate .text section");
+    /// Returns the size of the section in bytes.
+    pub fn len(&self) -> usize {
+        self.size
     }
 
-    let text_section = &*text_section_ptr;
-    let text_address = base_address + text_section.VirtualAddress as usize;
-    let text_size = text_section.SizeOfRawData as usize;
+    /// Returns whether the section is empty.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
 
-    let text_slice = slice::from_raw_parts(text_address as *const u8, text_size);
+    /// Returns whether `address` lies within the section.
+    pub fn contains(&self, address: usize) -> bool {
+        address >= self.address && address - self.address < self.size
+    }
 
-    (text_slice.to_vec(), text_address)
-    
+    /// Returns the bytes of the section, borrowed from the live memory.
+    pub fn bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.address as *const u8, self.size) }
+    }
 }
 
-#[cfg(target_arch = "x86_64")]
-unsafe fn get_nt_headers(nt_header_ptr: usize) -> (usize, usize) {
-    let nt_headers = &*(nt_header_ptr as *const IMAGE_NT_HEADERS64);
-    (
-        nt_headers.FileHeader.NumberOfSections as usize,
-        nt_header_ptr + std::mem::size_of::<IMAGE_NT_HEADERS64>(),
-    )
+unsafe fn get_image() -> Result<PeImage, PeParseError> {
+    let base = module_base(None)?;
+    parse_pe_as(base as usize, cfg!(target_pointer_width = "64"))
 }
 
-#[cfg(target_arch = "x86")]
-unsafe fn get_nt_headers(nt_header_ptr: usize) -> (usize, usize) {
-    let nt_headers = &*(nt_header_ptr as *const IMAGE_NT_HEADERS32);
-    (
-        nt_headers.FileHeader.NumberOfSections as usize,
-        nt_header_ptr + std::mem::size_of::<IMAGE_NT_HEADERS32>(),
-    )
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_section_bytes() {
+        let text = unsafe { TextSection::main() }.expect("Failed to locate .text section");
+        let (copy, address) = unsafe { get_text_section() }.unwrap();
+
+        assert_eq!(text.address(), address);
+        assert_eq!(text.len(), copy.len());
+        assert_eq!(text.bytes().len(), text.len());
+        assert!(!text.is_empty());
+        assert!(text.contains(text.address()));
+        assert!(!text.contains(text.address() + text.len()));
+
+        fn local_function() {}
+        assert!(text.contains(local_function as fn() as usize));
+    }
+
+    #[test]
+    fn test_text_section_module() {
+        let text = unsafe { TextSection::of_module(Some("kernel32.dll")) }.expect("Failed to locate kernel32 .text");
+        assert_eq!(text.bytes().len(), text.len());
+
+        let missing = unsafe { TextSection::of_module(Some("not_loaded.dll")) };
+        assert_eq!(missing, Err(PeParseError::ModuleNotFound));
+    }
 }
\ No newline at end of file
@@ -1,6 +1,39 @@
 pub mod algorithm;
+pub mod anchored;
 pub mod aob;
+pub mod file;
+pub mod lazy;
 pub mod memory;
+pub mod resolve;
+pub mod scanner;
+pub mod signature;
+pub mod string;
+pub mod xref;
 
+pub use anchored::AnchoredScan;
+pub use algorithm::GapPattern;
+pub use algorithm::StreamMatcher;
 pub use aob::scan_unique;
-pub use aob::scan_all;
\ No newline at end of file
+pub use aob::scan_all;
+pub use aob::scan_all_with_context;
+pub use aob::scan_immediate;
+pub use aob::scan_last;
+pub use aob::scan_last_before;
+pub use aob::scan_nth;
+pub use aob::scan_prologues;
+pub use aob::scan_sequence;
+pub use aob::scan_unique_retry;
+pub use aob::scan_unique_validated;
+pub use aob::scan_unique_verified;
+pub use aob::scan_unique_with_margin;
+pub use file::scan_file;
+pub use lazy::LazySignature;
+pub use memory::TextSection;
+pub use resolve::ScanResult;
+pub use scanner::Scanner;
+pub use string::scan_string;
+pub use string::StringEncoding;
+pub use xref::find_callers;
+pub use xref::find_string_xrefs;
+#[cfg(feature = "stats")]
+pub use algorithm::ScanStats;
\ No newline at end of file
@@ -0,0 +1,177 @@
+use crate::{
+    errors::AobScanError,
+    ops::read::{read_bytes, read_memory_with_alignment},
+    types::AlignmentPolicy,
+};
+
+use super::aob::find_unique;
+
+/// A scanned address together with the steps resolving it, chained as a pipeline.
+///
+/// Scans are often followed by a few arithmetic and dereference steps, e.g. "skip to the operand,
+/// follow the rel32, dereference the global". Each step runs only if the previous ones succeeded,
+/// and the first error is carried through to [`ScanResult::as_ptr`] or [`ScanResult::read`].
+///
+/// # Example
+/// ```
+/// use verity_memory::pattern::resolve::ScanResult;
+///
+/// // call rel32 +0x10
+/// let code = [0x90, 0xE8, 0x10, 0x00, 0x00, 0x00];
+///
+/// let target = unsafe { ScanResult::new(code.as_ptr()).add(1).rel32(5, 1).as_ptr() };
+/// assert_eq!(target, Ok(code.as_ptr().wrapping_add(6 + 0x10) as *mut u8));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanResult {
+    address: Result<*mut u8, AobScanError>,
+}
+
+impl ScanResult {
+    /// Starts a pipeline at a known address.
+    pub fn new(address: *const u8) -> Self {
+        ScanResult {
+            address: Ok(address as *mut u8),
+        }
+    }
+
+    /// Starts a pipeline at the unique match of `pattern`, like
+    /// [`scan_unique`](crate::pattern::aob::scan_unique).
+    ///
+    /// # Safety
+    /// This function is unsafe because it scans the memory of the current process.
+    pub unsafe fn scan(pattern: &str) -> Self {
+        ScanResult {
+            address: find_unique(pattern),
+        }
+    }
+
+    /// Offsets the address by `offset` bytes.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(self, offset: isize) -> Self {
+        self.map(|address| Ok(address.wrapping_offset(offset)))
+    }
+
+    /// Replaces the address with the pointer stored at it.
+    ///
+    /// # Safety
+    /// This function is unsafe because it reads the memory at the current address.
+    pub unsafe fn deref(self) -> Self {
+        self.map(|address| {
+            read_memory_with_alignment(address as *const usize, AlignmentPolicy::Unaligned)
+                .map(|pointer| pointer as *mut u8)
+                .map_err(|_| AobScanError::InvalidAccess)
+        })
+    }
+
+    /// Follows the 32-bit displacement of the instruction at the current address, resolving to
+    /// `address + opcode_len + disp` like the CPU does for relative branches and RIP-relative operands.
+    ///
+    /// # Safety
+    /// This function is unsafe because it reads the memory at the current address.
+    ///
+    /// # Parameters
+    /// - `opcode_len`: The length of the instruction.
+    /// - `disp_at`: The offset of the displacement from the start of the instruction.
+    pub unsafe fn rel32(self, opcode_len: usize, disp_at: usize) -> Self {
+        self.map(|address| {
+            let bytes = read_bytes(address.wrapping_add(disp_at), 4)
+                .map_err(|_| AobScanError::InvalidAccess)?;
+            let disp = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+            Ok(address
+                .wrapping_add(opcode_len)
+                .wrapping_offset(disp as isize))
+        })
+    }
+
+    /// Returns the resolved address.
+    ///
+    /// # Errors
+    /// - The error of the scan, or `AobScanError::InvalidAccess` if a step could not read memory.
+    pub fn as_ptr(self) -> Result<*mut u8, AobScanError> {
+        self.address
+    }
+
+    /// Reads a `T` at the resolved address.
+    ///
+    /// # Safety
+    /// This function is unsafe because it reads the memory at the resolved address and reinterprets
+    /// the bytes as `T`.
+    ///
+    /// # Errors
+    /// - The error of the scan, or `AobScanError::InvalidAccess` if a step or the read failed.
+    pub unsafe fn read<T: Copy>(self) -> Result<T, AobScanError> {
+        let address = self.address?;
+        read_memory_with_alignment(address as *const T, AlignmentPolicy::Unaligned)
+            .map_err(|_| AobScanError::InvalidAccess)
+    }
+
+    fn map(self, step: impl FnOnce(*mut u8) -> Result<*mut u8, AobScanError>) -> Self {
+        ScanResult {
+            address: self.address.and_then(step),
+        }
+    }
+}
+
+impl From<Result<*mut u8, AobScanError>> for ScanResult {
+    fn from(address: Result<*mut u8, AobScanError>) -> Self {
+        ScanResult { address }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_rel32() {
+        // nop; lea rax, [rip + 0x20]; ret
+        let code = [0x90, 0x48, 0x8D, 0x05, 0x20, 0x00, 0x00, 0x00, 0xC3];
+
+        let resolved = unsafe { ScanResult::new(code.as_ptr()).add(1).rel32(7, 3).as_ptr() };
+        assert_eq!(resolved, Ok(code.as_ptr().wrapping_add(8 + 0x20) as *mut u8));
+
+        let backwards = [0xE8, 0xFB, 0xFF, 0xFF, 0xFF];
+        let resolved = unsafe { ScanResult::new(backwards.as_ptr()).rel32(5, 1).as_ptr() };
+        assert_eq!(resolved, Ok(backwards.as_ptr() as *mut u8));
+    }
+
+    #[test]
+    fn test_deref_read() {
+        let value: u32 = 0xDEAD_BEEF;
+        let pointer = &value as *const u32 as usize;
+        let holder = [0usize, pointer];
+
+        let result = unsafe {
+            ScanResult::new(holder.as_ptr() as *const u8)
+                .add(std::mem::size_of::<usize>() as isize)
+                .deref()
+                .read::<u32>()
+        };
+        assert_eq!(result, Ok(0xDEAD_BEEF));
+    }
+
+    #[test]
+    fn test_error_propagates() {
+        let result = unsafe { ScanResult::from(Err(AobScanError::NotUnique)).add(4).deref().rel32(5, 1).as_ptr() };
+        assert_eq!(result, Err(AobScanError::NotUnique));
+
+        let result = unsafe { ScanResult::new(std::ptr::null()).deref().as_ptr() };
+        assert_eq!(result, Err(AobScanError::InvalidAccess));
+    }
+
+    #[test]
+    fn test_scan_unique_chains() {
+        use crate::pattern::aob::scan_unique;
+
+        let result = unsafe { scan_unique("").add(4).as_ptr() };
+        assert_eq!(result, Err(AobScanError::EmptyPattern));
+
+        let text = unsafe { crate::pattern::TextSection::main() }.expect("Failed to locate .text section");
+        let pattern = text.bytes()[0x40..0x50].iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" ");
+        let expected = unsafe { find_unique(&pattern) }.map(|address| address.wrapping_add(2));
+        assert!(expected.is_ok());
+        assert_eq!(unsafe { scan_unique(&pattern).add(2).as_ptr() }, expected);
+    }
+}
@@ -0,0 +1,157 @@
+use std::borrow::Cow;
+
+use crate::errors::AobScanError;
+
+use super::algorithm::{scan_pattern, ScanMode};
+use super::memory::{get_text_section, TextSection};
+
+/// A scanner over a section fetched once, for running many patterns against the same bytes.
+///
+/// The free functions of [`aob`](crate::pattern::aob) parse the headers and copy the `.text`
+/// section for every pattern. A `Scanner` holds the bytes, either borrowed from a [`TextSection`]
+/// or snapshotted once, and runs every scan against them, which matters when resolving hundreds of
+/// signatures at startup.
+///
+/// # Example
+/// ```
+/// use verity_memory::pattern::{Scanner, TextSection};
+///
+/// let text = unsafe { TextSection::main() }.unwrap();
+/// let scanner = Scanner::new(&text);
+///
+/// for pattern in ["48 8B ?? ?? 89 ?? 74 0F", "55 8B EC", "E8 ?? ?? ?? ?? 90"] {
+///     match scanner.find_unique(pattern) {
+///         Ok(ptr) => println!("{} found at address: {:?}", pattern, ptr),
+///         Err(e) => println!("Failed to find {}: {}", pattern, e),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scanner<'a> {
+    bytes: Cow<'a, [u8]>,
+    address: usize,
+}
+
+impl<'a> Scanner<'a> {
+    /// Creates a scanner over the live bytes of `text`, without copying them.
+    pub fn new(text: &'a TextSection) -> Scanner<'a> {
+        Scanner {
+            bytes: Cow::Borrowed(text.bytes()),
+            address: text.address(),
+        }
+    }
+
+    /// Creates a scanner over `bytes`, reporting matches as if the bytes were mapped at `address`,
+    /// e.g. for a section dumped from another process.
+    pub fn from_bytes(bytes: &'a [u8], address: usize) -> Scanner<'a> {
+        Scanner {
+            bytes: Cow::Borrowed(bytes),
+            address,
+        }
+    }
+
+    /// Creates a scanner over a copy of the `.text` section of the executable, taken once, like the
+    /// one every free scanning function takes.
+    ///
+    /// # Errors
+    /// - `AobScanError::Pe`: If the module headers are invalid, e.g. the text section lies outside the module.
+    pub fn snapshot() -> Result<Scanner<'static>, AobScanError> {
+        let (bytes, address) = unsafe { get_text_section() }?;
+        Ok(Scanner {
+            bytes: Cow::Owned(bytes),
+            address,
+        })
+    }
+
+    /// Returns the address the first scanned byte is reported at.
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Returns the scanned bytes.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Behaves like [`scan_unique`](crate::pattern::aob::scan_unique) over the bytes of the scanner.
+    ///
+    /// # Errors
+    /// - `AobScanError::PatternNotFound`: Returned if the pattern is not found.
+    /// - `AobScanError::InvalidPattern`: Returned if a token of the pattern string is invalid.
+    /// - `AobScanError::EmptyPattern`: Returned if the pattern string contains no tokens.
+    pub fn find_unique(&self, pattern: &str) -> Result<*mut u8, AobScanError> {
+        let indices = scan_pattern(&self.bytes, pattern, ScanMode::First)?;
+        Ok(self.pointer(indices[0]))
+    }
+
+    /// Behaves like [`scan_all`](crate::pattern::aob::scan_all) over the bytes of the scanner.
+    ///
+    /// # Errors
+    /// - Same as [`Scanner::find_unique`].
+    pub fn find_all(&self, pattern: &str) -> Result<Vec<*mut u8>, AobScanError> {
+        let indices = scan_pattern(&self.bytes, pattern, ScanMode::All)?;
+        Ok(indices.into_iter().map(|index| self.pointer(index)).collect())
+    }
+
+    /// Behaves like [`scan_nth`](crate::pattern::aob::scan_nth) over the bytes of the scanner.
+    ///
+    /// # Errors
+    /// - `AobScanError::PatternNotFound`: Returned if the pattern occurs fewer than `n + 1` times.
+    /// - Any other error of [`Scanner::find_unique`].
+    pub fn find_nth(&self, pattern: &str, n: usize) -> Result<*mut u8, AobScanError> {
+        let indices = scan_pattern(&self.bytes, pattern, ScanMode::Nth(n))?;
+        Ok(self.pointer(indices[0]))
+    }
+
+    fn pointer(&self, index: usize) -> *mut u8 {
+        (self.address + index) as *mut u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::aob::{scan_all, scan_nth, scan_unique};
+
+    /// Builds a pattern from 12 bytes of `bytes` at `offset`, with the fourth byte as a wildcard.
+    fn pattern_at(bytes: &[u8], offset: usize) -> String {
+        bytes[offset..offset + 12]
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| if i == 3 { "??".to_string() } else { format!("{:02X}", byte) })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn test_scanner_matches_free_functions() {
+        let text = unsafe { TextSection::main() }.expect("Failed to locate .text section");
+        let scanner = Scanner::new(&text);
+        let snapshot = Scanner::snapshot().expect("Failed to copy .text section");
+        assert_eq!(snapshot.address(), scanner.address());
+
+        let len = text.len();
+        for offset in [0x10, len / 3, len / 2] {
+            let pattern = pattern_at(text.bytes(), offset);
+
+            let all = scanner.find_all(&pattern).expect("Failed to scan");
+            assert!(all.contains(&((text.address() + offset) as *mut u8)));
+            assert_eq!(all, unsafe { scan_all(&pattern) }.unwrap());
+            assert_eq!(snapshot.find_all(&pattern), Ok(all));
+
+            assert_eq!(scanner.find_unique(&pattern), unsafe { scan_unique(&pattern) }.as_ptr());
+            assert_eq!(scanner.find_nth(&pattern, 0), unsafe { scan_nth(&pattern, 0) });
+        }
+    }
+
+    #[test]
+    fn test_scanner_from_bytes() {
+        let bytes = [0x90, 0x48, 0x8B, 0x05, 0x90, 0x48, 0x8B, 0x0D];
+        let scanner = Scanner::from_bytes(&bytes, 0x1000);
+
+        assert_eq!(scanner.find_all("48 8B ??"), Ok(vec![0x1001 as *mut u8, 0x1005 as *mut u8]));
+        assert_eq!(scanner.find_nth("48 8B ??", 1), Ok(0x1005 as *mut u8));
+        assert_eq!(scanner.find_unique("8B 0D"), Ok(0x1006 as *mut u8));
+        assert_eq!(scanner.find_unique("CC CC"), Err(AobScanError::PatternNotFound));
+    }
+}
\ No newline at end of file
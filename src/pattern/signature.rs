@@ -0,0 +1,227 @@
+use crate::{errors::AobScanError, pattern::algorithm::{scan, ScanMode}};
+#[cfg(feature = "advanced-write")]
+use crate::{errors::ReadMemoryError, ops::asm::relative_operand_mask, ops::read::read_bytes};
+
+use super::memory::get_text_section;
+
+pub(crate) fn format_pattern(bytes: &[u8], wildcards: &[bool]) -> String {
+    bytes
+        .iter()
+        .zip(wildcards)
+        .map(|(byte, &wildcard)| {
+            if wildcard {
+                "??".to_string()
+            } else {
+                format!("{:02X}", byte)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// # Safety
+///
+/// This function is unsafe because it reads `len` bytes starting at a raw pointer. The caller
+/// must ensure the whole range is mapped.
+///
+/// # Description
+///
+/// Generates a signature for the bytes at `address`, the inverse of scanning.
+///
+/// When `wildcard_relocs` is set, the bytes are disassembled and every rel32 branch displacement
+/// and disp32 memory displacement (RIP-relative on x64, absolute on x86) is replaced by `??`,
+/// since those bytes change whenever the surrounding code or data moves.
+///
+/// # Parameters
+/// - `address`: A pointer to the first byte of the signature.
+/// - `len`: The number of bytes to include in the signature.
+/// - `wildcard_relocs`: Whether relocation-dependent operand bytes should be wildcarded.
+///
+/// # Returns
+/// - `Ok(String)`: The signature, formatted like `"E8 ?? ?? ?? ?? 48 8B C8"`.
+/// - `Err(ReadMemoryError)`: An error if the bytes could not be read.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::signature;
+///
+/// let code = [0xE8, 0x10, 0x00, 0x00, 0x00]; // call rel32
+/// let sig = unsafe { signature::generate_signature(code.as_ptr(), code.len(), true) };
+/// assert_eq!(sig, Ok("E8 ?? ?? ?? ??".to_string()));
+/// ```
+#[cfg(feature = "advanced-write")]
+pub unsafe fn generate_signature(
+    address: *const u8,
+    len: usize,
+    wildcard_relocs: bool,
+) -> Result<String, ReadMemoryError> {
+    let bytes = read_bytes(address, len)?;
+
+    let wildcards = if wildcard_relocs {
+        relative_operand_mask(&bytes, address as usize, false)
+    } else {
+        vec![false; bytes.len()]
+    };
+
+    Ok(format_pattern(&bytes, &wildcards))
+}
+
+/// # Safety
+///
+/// This function is unsafe because it reads `len` bytes starting at a raw pointer. The caller
+/// must ensure the whole range is mapped.
+///
+/// # Description
+///
+/// Builds a pattern for the code at `address` that stays valid across builds of the same program.
+///
+/// The code is disassembled and every relative operand is replaced by `??`: rel32 and rel8 branch
+/// displacements as well as RIP-relative (or, on x86, absolute) memory displacements. Only the
+/// opcodes and the operands that don't depend on the layout of the binary are kept, so the pattern
+/// can be scanned for in a later build.
+///
+/// Unlike [`generate_signature`], short (`rel8`) branches are wildcarded too, since they shift as
+/// soon as the surrounding code changes size.
+///
+/// # Parameters
+/// - `address`: A pointer to the first instruction of the code.
+/// - `len`: The number of bytes to include in the pattern.
+///
+/// # Returns
+/// - `Ok(String)`: The normalized pattern, formatted like `"E8 ?? ?? ?? ?? EB ??"`.
+/// - `Err(ReadMemoryError)`: An error if the bytes could not be read.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::signature;
+///
+/// let code = [0xE8, 0x10, 0x00, 0x00, 0x00, 0xEB, 0x05]; // call rel32; jmp rel8
+/// let pattern = unsafe { signature::normalize_pattern(code.as_ptr(), code.len()) };
+/// assert_eq!(pattern, Ok("E8 ?? ?? ?? ?? EB ??".to_string()));
+/// ```
+#[cfg(feature = "advanced-write")]
+pub unsafe fn normalize_pattern(address: *const u8, len: usize) -> Result<String, ReadMemoryError> {
+    let bytes = read_bytes(address, len)?;
+    let wildcards = relative_operand_mask(&bytes, address as usize, true);
+
+    Ok(format_pattern(&bytes, &wildcards))
+}
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that `address` lies within the text section of the current process.
+///
+/// # Description
+///
+/// Finds the shortest signature starting at `address` that matches exactly once in the text
+/// section of the current process's memory.
+///
+/// The pattern grows one byte at a time and is re-scanned until it is unique. Each scan stops as
+/// soon as a second match is found, so short ambiguous prefixes are cheap to reject.
+///
+/// # Parameters
+/// - `address`: A pointer into the text section where the signature starts.
+/// - `max_len`: The maximum number of bytes the signature may contain.
+///
+/// # Returns
+/// - `Ok(String)`: The shortest unique signature, formatted like `"48 8B 05"`.
+/// - `Err(AobScanError)`: An error if no unique signature exists within `max_len` bytes.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if `address` is outside the text section.
+/// - `AobScanError::NotUnique`: Returned if no signature of at most `max_len` bytes is unique.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::{aob, signature};
+///
+/// unsafe {
+///     if let Ok(ptr) = aob::scan_unique("48 8B ?? ?? 89 ?? 74 0F").as_ptr() {
+///         match signature::shortest_unique_signature(ptr, 64) {
+///             Ok(sig) => println!("Shortest signature: {}", sig),
+///             Err(e) => println!("No unique signature: {}", e),
+///         }
+///     }
+/// }
+/// ```
+pub unsafe fn shortest_unique_signature(address: *const u8, max_len: usize) -> Result<String, AobScanError> {
+    let text_region = get_text_section()?;
+
+    let offset = (address as usize)
+        .checked_sub(text_region.1)
+        .filter(|&offset| offset < text_region.0.len())
+        .ok_or(AobScanError::PatternNotFound)?;
+
+    let len = shortest_unique_len(&text_region.0, offset, max_len)?;
+    Ok(format_pattern(&text_region.0[offset..offset + len], &vec![false; len]))
+}
+
+pub(crate) fn shortest_unique_len(data: &[u8], offset: usize, max_len: usize) -> Result<usize, AobScanError> {
+    let available = max_len.min(data.len() - offset);
+
+    for len in 1..=available {
+        let pattern = &data[offset..offset + len];
+        if scan(data, pattern, ScanMode::Unique).is_ok() {
+            return Ok(len);
+        }
+    }
+
+    Err(AobScanError::NotUnique)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_pattern() {
+        let pattern = format_pattern(&[0x48, 0x8B, 0x05], &[false, true, false]);
+        assert_eq!(pattern, "48 ?? 05");
+    }
+
+    #[test]
+    fn test_shortest_unique_len_repetitive() {
+        let data = [0x11, 0x22, 0x11, 0x22, 0x11, 0x33, 0x11, 0x22];
+
+        assert_eq!(shortest_unique_len(&data, 2, 8), Ok(4));
+        assert_eq!(shortest_unique_len(&data, 5, 8), Ok(1));
+        assert_eq!(shortest_unique_len(&data, 2, 3), Err(AobScanError::NotUnique));
+    }
+
+    #[test]
+    #[cfg(feature = "advanced-write")]
+    fn test_generate_signature_call_rel32() {
+        let code = [0xE8, 0x10, 0x00, 0x00, 0x00];
+
+        let wildcarded = unsafe { generate_signature(code.as_ptr(), code.len(), true) };
+        assert_eq!(wildcarded, Ok("E8 ?? ?? ?? ??".to_string()));
+
+        let exact = unsafe { generate_signature(code.as_ptr(), code.len(), false) };
+        assert_eq!(exact, Ok("E8 10 00 00 00".to_string()));
+    }
+
+    #[test]
+    #[cfg(all(feature = "advanced-write", target_arch = "x86_64"))]
+    fn test_generate_signature_rip_relative() {
+        // mov rax, [rip + 0x1234]; ret
+        let code = [0x48, 0x8B, 0x05, 0x34, 0x12, 0x00, 0x00, 0xC3];
+
+        let sig = unsafe { generate_signature(code.as_ptr(), code.len(), true) };
+        assert_eq!(sig, Ok("48 8B 05 ?? ?? ?? ?? C3".to_string()));
+    }
+
+    #[test]
+    #[cfg(feature = "advanced-write")]
+    fn test_normalize_pattern_ignores_targets() {
+        // call rel32; jmp rel8; ret
+        let first = [0xE8, 0x10, 0x00, 0x00, 0x00, 0xEB, 0x05, 0xC3];
+        let second = [0xE8, 0x20, 0x30, 0x00, 0x00, 0xEB, 0x10, 0xC3];
+
+        let first_pattern = unsafe { normalize_pattern(first.as_ptr(), first.len()) };
+        let second_pattern = unsafe { normalize_pattern(second.as_ptr(), second.len()) };
+
+        assert_eq!(first_pattern, Ok("E8 ?? ?? ?? ?? EB ?? C3".to_string()));
+        assert_eq!(first_pattern, second_pattern);
+    }
+}
\ No newline at end of file
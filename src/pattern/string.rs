@@ -0,0 +1,160 @@
+use crate::errors::AobScanError;
+
+use super::memory::get_section;
+
+const STRING_SECTIONS: [&[u8]; 2] = [b".rdata", b".data"];
+
+/// The encoding a string literal is stored in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StringEncoding {
+    /// Single byte characters. Only valid for strings without non-ASCII characters.
+    Ascii,
+    /// UTF-8, as used by Rust string literals and most narrow C strings.
+    Utf8,
+    /// UTF-16 little-endian, as used by wide (`L"..."`) strings on Windows.
+    Utf16Le,
+}
+
+impl StringEncoding {
+    /// Encodes `text` into the bytes it is stored as in memory, without a terminator.
+    ///
+    /// # Errors
+    /// - `AobScanError::EmptyPattern`: If `text` is empty.
+    /// - `AobScanError::InvalidPattern`: If `text` contains a non-ASCII character when encoding as
+    ///   `StringEncoding::Ascii`; the character and its index are reported.
+    ///
+    /// # Example
+    /// ```rust
+    /// use verity_memory::pattern::string::StringEncoding;
+    ///
+    /// assert_eq!(StringEncoding::Utf16Le.encode("Hi"), Ok(vec![0x48, 0x00, 0x69, 0x00]));
+    /// ```
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>, AobScanError> {
+        if text.is_empty() {
+            return Err(AobScanError::EmptyPattern);
+        }
+
+        match self {
+            StringEncoding::Ascii => match text.chars().enumerate().find(|(_, c)| !c.is_ascii()) {
+                Some((position, c)) => Err(AobScanError::InvalidPattern {
+                    token: c.to_string(),
+                    position,
+                }),
+                None => Ok(text.as_bytes().to_vec()),
+            },
+            StringEncoding::Utf8 => Ok(text.as_bytes().to_vec()),
+            StringEncoding::Utf16Le => Ok(text
+                .encode_utf16()
+                .flat_map(|unit| unit.to_le_bytes())
+                .collect()),
+        }
+    }
+}
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that the returned pointers are handled safely.
+///
+/// # Description
+///
+/// Scans the `.rdata` and `.data` sections of the current process for every occurrence of `text`
+/// stored in the given `encoding`. This is usually the first step of finding the code that uses a
+/// string, without having to convert the string to a hex pattern by hand.
+///
+/// Unlike [`scan_all`](super::aob::scan_all), the bytes are matched exactly, so the zero bytes of
+/// UTF-16 strings are not treated as wildcards.
+///
+/// # Parameters
+/// - `text`: The string to search for.
+/// - `encoding`: The encoding the string is stored in.
+///
+/// # Returns
+/// - `Ok(Vec<*mut u8>)`: A vector of pointers to the first byte of each occurrence.
+/// - `Err(AobScanError)`: An error if the string is not found or cannot be encoded.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if no occurrences of the string are found.
+/// - `AobScanError::EmptyPattern`: Returned if the string is empty.
+/// - `AobScanError::InvalidPattern`: Returned if the string cannot be encoded.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::string::{scan_string, StringEncoding};
+///
+/// unsafe {
+///     match scan_string("Hello, world!", StringEncoding::Utf8) {
+///         Ok(ptrs) => println!("String found at {} addresses", ptrs.len()),
+///         Err(e) => println!("Failed to find string: {}", e),
+///     }
+/// }
+/// ```
+pub unsafe fn scan_string(text: &str, encoding: StringEncoding) -> Result<Vec<*mut u8>, AobScanError> {
+    let needle = encoding.encode(text)?;
+
+    let mut ptrs = Vec::new();
+    for name in STRING_SECTIONS {
+        if let Some((data, address)) = get_section(name)? {
+            ptrs.extend(
+                find_exact(&data, &needle)
+                    .into_iter()
+                    .map(|index| (address + index) as *mut u8),
+            );
+        }
+    }
+
+    if ptrs.is_empty() {
+        Err(AobScanError::PatternNotFound)
+    } else {
+        Ok(ptrs)
+    }
+}
+
+pub(crate) fn find_exact(data: &[u8], needle: &[u8]) -> Vec<usize> {
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    data.windows(needle.len())
+        .enumerate()
+        .filter(|(_, window)| *window == needle)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static MARKER: &str = "verity-memory string scan marker";
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(StringEncoding::Ascii.encode("ab"), Ok(vec![0x61, 0x62]));
+        assert_eq!(
+            StringEncoding::Ascii.encode("aé"),
+            Err(AobScanError::InvalidPattern {
+                token: "é".to_string(),
+                position: 1,
+            })
+        );
+        assert_eq!(StringEncoding::Utf8.encode("é"), Ok(vec![0xC3, 0xA9]));
+        assert_eq!(StringEncoding::Utf16Le.encode("é"), Ok(vec![0xE9, 0x00]));
+        assert_eq!(StringEncoding::Utf8.encode(""), Err(AobScanError::EmptyPattern));
+    }
+
+    #[test]
+    fn test_find_exact_ignores_wildcards() {
+        let data = [0x41, 0x00, 0x42, 0x00, 0x41, 0x01, 0x42, 0x00];
+        assert_eq!(find_exact(&data, &[0x41, 0x00, 0x42]), vec![0]);
+    }
+
+    #[test]
+    fn test_scan_string_finds_literal() {
+        let marker = std::hint::black_box(MARKER);
+
+        let result = unsafe { scan_string(marker, StringEncoding::Utf8) };
+        let ptrs = result.expect("Failed to find string literal");
+        assert!(ptrs.contains(&(MARKER.as_ptr() as *mut u8)));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,273 @@
+use crate::errors::AobScanError;
+
+use super::memory::get_text_section;
+use super::string::{scan_string, StringEncoding};
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that the returned pointers are handled safely.
+///
+/// # Description
+///
+/// Finds the code referencing a string literal. The string is located with
+/// [`scan_string`](super::string::scan_string), then the `.text` section is scanned for `lea` and
+/// `mov` instructions whose operand points at any occurrence of it.
+///
+/// On x64 the operand is RIP-relative (`lea rax, [rip + disp32]`). On x86 it is an absolute address,
+/// which is also matched for `push imm32` and `mov r32, imm32`.
+///
+/// # Parameters
+/// - `text`: The string to find references to, stored as UTF-8.
+///
+/// # Returns
+/// - `Ok(Vec<*mut u8>)`: A vector of pointers to the first byte of each referencing instruction.
+/// - `Err(AobScanError)`: An error if the string or any reference to it is not found.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if the string is not found or is never referenced.
+/// - `AobScanError::EmptyPattern`: Returned if the string is empty.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::xref;
+///
+/// unsafe {
+///     match xref::find_string_xrefs("Hello, world!") {
+///         Ok(ptrs) => println!("String referenced by {} instructions", ptrs.len()),
+///         Err(e) => println!("Failed to find references: {}", e),
+///     }
+/// }
+/// ```
+pub unsafe fn find_string_xrefs(text: &str) -> Result<Vec<*mut u8>, AobScanError> {
+    let strings = scan_string(text, StringEncoding::Utf8)?;
+    let (code, code_address) = get_text_section()?;
+
+    let ptrs: Vec<*mut u8> = strings
+        .into_iter()
+        .flat_map(|string| find_xrefs(&code, code_address, string as usize))
+        .map(|index| (code_address + index) as *mut u8)
+        .collect();
+
+    if ptrs.is_empty() {
+        Err(AobScanError::PatternNotFound)
+    } else {
+        Ok(ptrs)
+    }
+}
+
+/// # Safety
+///
+/// This function is unsafe because it involves direct manipulation of memory pointers. The caller
+/// must ensure that the returned pointers are handled safely.
+///
+/// # Description
+///
+/// Finds the near `call rel32` (`E8`) and `jmp rel32` (`E9`) instructions of the `.text` section
+/// whose destination is `target`, i.e. answers "who calls this function". Calls made through a
+/// register or a memory operand, such as imports, are not found.
+///
+/// Every `E8`/`E9` byte is considered, so a match can occasionally be part of another instruction
+/// that happens to encode a displacement pointing at `target`.
+///
+/// # Parameters
+/// - `target`: The address the calls and jumps must lead to.
+///
+/// # Returns
+/// - `Ok(Vec<*mut u8>)`: A pointer to the opcode of each branch, in ascending order.
+/// - `Err(AobScanError)`: An error if nothing branches to `target`.
+///
+/// # Errors
+/// - `AobScanError::PatternNotFound`: Returned if no `call` or `jmp` leads to `target`.
+/// - `AobScanError::Pe`: Returned if the module headers are invalid, e.g. the text section lies outside the module.
+///
+/// # Examples
+/// ```
+/// use verity_memory::pattern::xref;
+///
+/// fn function() {}
+///
+/// unsafe {
+///     match xref::find_callers(function as fn() as usize) {
+///         Ok(ptrs) => println!("Function called from {} places", ptrs.len()),
+///         Err(e) => println!("Failed to find callers: {}", e),
+///     }
+/// }
+/// ```
+pub unsafe fn find_callers(target: usize) -> Result<Vec<*mut u8>, AobScanError> {
+    let (code, code_address) = get_text_section()?;
+
+    let ptrs: Vec<*mut u8> = find_branches(&code, code_address, target)
+        .into_iter()
+        .map(|index| (code_address + index) as *mut u8)
+        .collect();
+
+    if ptrs.is_empty() {
+        Err(AobScanError::PatternNotFound)
+    } else {
+        Ok(ptrs)
+    }
+}
+
+/// Returns the offset of every `call rel32`/`jmp rel32` in `code` leading to `target`, with `code`
+/// loaded at `code_address`.
+pub(crate) fn find_branches(code: &[u8], code_address: usize, target: usize) -> Vec<usize> {
+    let mut indices = Vec::new();
+
+    for opcode_at in 0..code.len().saturating_sub(4) {
+        if !matches!(code[opcode_at], 0xE8 | 0xE9) {
+            continue;
+        }
+
+        let disp = read_i32(code, opcode_at + 1);
+        let next = code_address.wrapping_add(opcode_at + 5);
+        if next.wrapping_add(disp as isize as usize) == target {
+            indices.push(opcode_at);
+        }
+    }
+
+    indices
+}
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn find_xrefs(code: &[u8], code_address: usize, target: usize) -> Vec<usize> {
+    let mut indices = Vec::new();
+
+    for opcode_at in 0..code.len().saturating_sub(5) {
+        if !matches!(code[opcode_at], 0x8B | 0x8D) || code[opcode_at + 1] & 0xC7 != 0x05 {
+            continue;
+        }
+
+        let disp = read_i32(code, opcode_at + 2);
+        let next = code_address + opcode_at + 6;
+        if next.wrapping_add(disp as isize as usize) == target {
+            let start = match opcode_at.checked_sub(1).map(|rex| code[rex]) {
+                Some(0x40..=0x4F) => opcode_at - 1,
+                _ => opcode_at,
+            };
+            indices.push(start);
+        }
+    }
+
+    indices
+}
+
+#[cfg(target_arch = "x86")]
+pub(crate) fn find_xrefs(code: &[u8], _code_address: usize, target: usize) -> Vec<usize> {
+    let mut indices = Vec::new();
+
+    for start in 0..code.len() {
+        let operand_at = match code[start] {
+            0x68 | 0xB8..=0xBF => start + 1,
+            0x8B | 0x8D if code.get(start + 1).is_some_and(|modrm| modrm & 0xC7 == 0x05) => {
+                start + 2
+            }
+            _ => continue,
+        };
+
+        if operand_at + 4 <= code.len() && read_i32(code, operand_at) as u32 as usize == target {
+            indices.push(start);
+        }
+    }
+
+    indices
+}
+
+fn read_i32(code: &[u8], offset: usize) -> i32 {
+    i32::from_le_bytes([
+        code[offset],
+        code[offset + 1],
+        code[offset + 2],
+        code[offset + 3],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static XREF_MARKER: &str = "verity-memory xref marker";
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_find_xrefs_rip_relative() {
+        // lea rcx, [rip + 0x10]; nop; mov eax, [rip + 0x09]
+        let code = [
+            0x48, 0x8D, 0x0D, 0x10, 0x00, 0x00, 0x00, 0x90, 0x8B, 0x05, 0x09, 0x00, 0x00, 0x00,
+        ];
+        let base = 0x1000;
+
+        assert_eq!(find_xrefs(&code, base, base + 7 + 0x10), vec![0]);
+        assert_eq!(find_xrefs(&code, base, base + 14 + 0x09), vec![8]);
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86")]
+    fn test_find_xrefs_absolute() {
+        // push 0x00402000; mov ecx, 0x00402000; lea edx, [0x00402000]
+        let code = [
+            0x68, 0x00, 0x20, 0x40, 0x00, 0xB9, 0x00, 0x20, 0x40, 0x00, 0x8D, 0x15, 0x00, 0x20,
+            0x40, 0x00,
+        ];
+
+        assert_eq!(find_xrefs(&code, 0x1000, 0x0040_2000), vec![0, 5, 10]);
+    }
+
+    #[test]
+    fn test_find_branches() {
+        // call +0x10; nop; jmp +0x0A; jmp -0x20; call +0x10
+        let code = [
+            0xE8, 0x10, 0x00, 0x00, 0x00, 0x90, 0xE9, 0x0A, 0x00, 0x00, 0x00, 0xE9, 0xE0, 0xFF,
+            0xFF, 0xFF, 0xE8, 0x10, 0x00, 0x00, 0x00,
+        ];
+        let base = 0x1000;
+        let target = base + 5 + 0x10;
+
+        assert_eq!(find_branches(&code, base, target), vec![0, 6]);
+        assert_eq!(find_branches(&code, base, base + 16 - 0x20), vec![11]);
+        assert_eq!(find_branches(&code, base, base + 21 + 0x10), vec![16]);
+        assert!(find_branches(&code, base, 0xDEAD).is_empty());
+    }
+
+    #[test]
+    fn test_find_branches_buffer_address() {
+        let mut code = [0x90u8; 16];
+        code[4] = 0xE8;
+        let base = code.as_ptr() as usize;
+        let target = base + 0x40;
+        let disp = (target as isize - (base + 9) as isize) as i32;
+        code[5..9].copy_from_slice(&disp.to_le_bytes());
+
+        assert_eq!(find_branches(&code, base, target), vec![4]);
+    }
+
+    #[test]
+    fn test_find_string_xrefs_function() {
+        let strings = unsafe { scan_string(XREF_MARKER, StringEncoding::Utf8) }.expect("Failed to find marker");
+        let marker = XREF_MARKER.as_ptr() as usize;
+        assert!(strings.contains(&(marker as *mut u8)));
+
+        // Code referencing the marker, as if mapped just below it: 8 nops, then the reference.
+        let mut code = vec![0x90u8; 8];
+        let code_address = marker - 0x1000;
+
+        // lea rdx, [rip + disp32]
+        #[cfg(target_arch = "x86_64")]
+        {
+            let disp = (marker as isize - (code_address + 8 + 7) as isize) as i32;
+            code.extend([0x48, 0x8D, 0x15]);
+            code.extend(disp.to_le_bytes());
+        }
+
+        // push imm32
+        #[cfg(target_arch = "x86")]
+        {
+            code.push(0x68);
+            code.extend((marker as u32).to_le_bytes());
+        }
+
+        code.push(0xC3);
+        assert_eq!(find_xrefs(&code, code_address, marker), vec![8]);
+        assert!(find_xrefs(&code, code_address, marker + 1).is_empty());
+    }
+}
\ No newline at end of file
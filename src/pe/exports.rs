@@ -0,0 +1,207 @@
+use std::ptr::read_unaligned;
+
+use crate::errors::PeParseError;
+use crate::utils::module_base;
+
+use super::image::parse_pe;
+use super::imports::read_name;
+
+/// A function exported by name from an image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Export {
+    /// The exported name.
+    pub name: String,
+    /// The biased ordinal, as passed to `GetProcAddress`.
+    pub ordinal: u32,
+    /// The RVA of the function.
+    pub rva: usize,
+    /// The address of the function in the mapped image.
+    pub address: usize,
+}
+
+/// Lists the functions an image mapped at `image_base` exports by name.
+///
+/// Forwarded exports, whose address is a string like `"NTDLL.RtlAllocateHeap"` inside the export
+/// directory rather than code, are skipped, as are exports by ordinal only and names that run
+/// past the end of the image.
+///
+/// # Safety
+/// This function is `unsafe` because it reads the export directory through raw pointers.
+/// `image_base` must point to an image in its mapped layout, e.g. a loaded module.
+///
+/// # Parameters
+/// - `image_base`: The address of the mapped image.
+///
+/// # Returns
+/// - `Ok(Vec<Export>)`: The named exports, in the order of the name table (sorted by name).
+/// - `Err(PeParseError)`: If the headers or the export directory are invalid.
+///
+/// # Errors
+/// - `PeParseError::OutOfBounds`: If a table of the export directory lies outside the image.
+/// - Any error returned by [`parse_pe`] for invalid headers.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::{pe, utils};
+///
+/// let kernel32 = utils::module_base(Some("kernel32.dll")).unwrap() as usize;
+/// let exports = unsafe { pe::parse_exports(kernel32) }.unwrap();
+/// assert!(!exports.is_empty());
+/// ```
+pub unsafe fn parse_exports(image_base: usize) -> Result<Vec<Export>, PeParseError> {
+    let image = parse_pe(image_base)?;
+    let directory = match image.export_directory() {
+        Some(directory) => directory,
+        None => return Ok(Vec::new()),
+    };
+
+    let image_size = image.size_of_image as usize;
+    let directory_start = directory.virtual_address as usize;
+    let directory_end = directory_start + directory.size as usize;
+    if directory_start + 40 > image_size {
+        return Err(PeParseError::OutOfBounds);
+    }
+
+    let field = |offset: usize| read_unaligned((image_base + directory_start + offset) as *const u32) as usize;
+    let ordinal_base = field(16) as u32;
+    let function_count = field(20);
+    let name_count = field(24);
+    let functions = field(28);
+    let names = field(32);
+    let name_ordinals = field(36);
+
+    let fits = |rva: usize, count: usize, size: usize| {
+        count.checked_mul(size).and_then(|len| rva.checked_add(len)).is_some_and(|end| end <= image_size)
+    };
+    if !fits(functions, function_count, 4) || !fits(names, name_count, 4) || !fits(name_ordinals, name_count, 2) {
+        return Err(PeParseError::OutOfBounds);
+    }
+
+    let mut exports = Vec::with_capacity(name_count);
+    for i in 0..name_count {
+        let name_rva = read_unaligned((image_base + names + i * 4) as *const u32) as usize;
+        let index = read_unaligned((image_base + name_ordinals + i * 2) as *const u16) as usize;
+        if name_rva == 0 || name_rva >= image_size || index >= function_count {
+            continue;
+        }
+
+        let function_rva = read_unaligned((image_base + functions + index * 4) as *const u32) as usize;
+        if function_rva == 0 || (directory_start..directory_end).contains(&function_rva) {
+            continue;
+        }
+
+        let name = match read_name(image_base, name_rva, image_size) {
+            Ok(name) => name,
+            Err(_) => continue,
+        };
+        exports.push(Export {
+            name: name.to_string_lossy().into_owned(),
+            ordinal: ordinal_base.wrapping_add(index as u32),
+            rva: function_rva,
+            address: image_base + function_rva,
+        });
+    }
+
+    Ok(exports)
+}
+
+/// Lists the functions a module loaded in the current process exports by name.
+///
+/// # Parameters
+/// - `module`: The name of the module (e.g. `"kernel32.dll"`).
+///
+/// # Errors
+/// - `PeParseError::ModuleNotFound`: If `module` is not loaded.
+/// - `PeParseError::InvalidModuleName`: If `module` is empty or contains a NUL character.
+/// - Any other error returned by [`parse_exports`].
+///
+/// # Example
+/// ```rust
+/// use verity_memory::pe;
+///
+/// let exports = pe::list_exports("kernel32.dll").unwrap();
+/// assert!(exports.iter().any(|export| export.name == "GetCurrentProcessId"));
+/// ```
+pub fn list_exports(module: &str) -> Result<Vec<Export>, PeParseError> {
+    let base = module_base(Some(module))?;
+    // A loaded module is mapped with its export directory.
+    unsafe { parse_exports(base as usize) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pe::test_image::TestImage;
+    use winapi::um::libloaderapi::{GetModuleHandleA, GetProcAddress};
+    use winapi::um::winnt::IMAGE_DIRECTORY_ENTRY_EXPORT;
+
+    #[test]
+    fn test_parse_exports_kernel32() {
+        let kernel32 = module_base(Some("kernel32.dll")).unwrap() as usize;
+        let exports = unsafe { parse_exports(kernel32) }.expect("Failed to parse exports");
+
+        assert!(!exports.is_empty());
+        assert!(exports.iter().all(|export| export.address > kernel32));
+
+        // Forwarders are skipped, so GetProcAddress resolves every listed export to the same address.
+        let module = unsafe { GetModuleHandleA(b"kernel32.dll\0".as_ptr() as _) };
+        for export in exports.iter().take(16) {
+            let name = std::ffi::CString::new(export.name.as_str()).unwrap();
+            let resolved = unsafe { GetProcAddress(module, name.as_ptr()) };
+            assert_eq!(resolved as usize, export.address, "{}", export.name);
+        }
+    }
+
+    #[test]
+    fn test_list_exports_kernel32() {
+        let exports = list_exports("kernel32.dll").expect("Failed to list exports");
+        let kernel32 = module_base(Some("kernel32.dll")).unwrap() as usize;
+
+        let export = exports
+            .iter()
+            .find(|export| export.name == "GetCurrentProcessId")
+            .expect("GetCurrentProcessId is not exported");
+        assert_eq!(export.address, kernel32 + export.rva);
+
+        let get_current_process_id: extern "system" fn() -> u32 = unsafe { std::mem::transmute(export.address) };
+        assert_eq!(get_current_process_id(), std::process::id());
+
+        assert_eq!(list_exports("non_existent_dll.dll"), Err(PeParseError::ModuleNotFound));
+    }
+
+    #[test]
+    fn test_parse_exports_null() {
+        assert_eq!(unsafe { parse_exports(0) }, Err(PeParseError::NullPointer));
+    }
+
+    #[test]
+    fn test_parse_exports_unterminated_name() {
+        let mut directory = [0u8; 40];
+        for (offset, value) in [(16, u32::MAX), (20, 2), (24, 2), (28, 0x240), (32, 0x250), (36, 0x260)] {
+            directory[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        }
+
+        let image = TestImage::new(0x400)
+            .size_of_image(0x400)
+            .directory(IMAGE_DIRECTORY_ENTRY_EXPORT as usize, 0x200, 40)
+            .put(0x200, &directory)
+            .put(0x240, &[0x00, 0x10, 0x00, 0x00, 0x10, 0x10, 0x00, 0x00])
+            .put(0x250, &[0x80, 0x02, 0x00, 0x00, 0xFC, 0x03, 0x00, 0x00])
+            .put(0x260, &[0x01, 0x00, 0x00, 0x00])
+            .put(0x280, b"Good\0")
+            .put(0x3FC, b"Bad!")
+            .build();
+        let base = image.as_ptr() as usize;
+
+        let exports = unsafe { parse_exports(base) }.expect("Failed to parse exports");
+        assert_eq!(
+            exports,
+            vec![Export {
+                name: "Good".to_string(),
+                ordinal: 0,
+                rva: 0x1010,
+                address: base + 0x1010,
+            }]
+        );
+    }
+}
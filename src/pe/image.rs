@@ -0,0 +1,520 @@
+use std::mem::size_of;
+use std::ptr::read_unaligned;
+
+use winapi::um::winnt::{
+    IMAGE_DATA_DIRECTORY, IMAGE_DIRECTORY_ENTRY_BASERELOC, IMAGE_DIRECTORY_ENTRY_EXPORT,
+    IMAGE_DIRECTORY_ENTRY_IMPORT, IMAGE_DOS_HEADER, IMAGE_DOS_SIGNATURE, IMAGE_FILE_HEADER,
+    IMAGE_NT_OPTIONAL_HDR32_MAGIC, IMAGE_NT_OPTIONAL_HDR64_MAGIC, IMAGE_NT_SIGNATURE,
+    IMAGE_OPTIONAL_HEADER32, IMAGE_OPTIONAL_HEADER64, IMAGE_SECTION_HEADER,
+};
+
+use crate::errors::PeParseError;
+use crate::utils::module_base;
+
+/// The fields of a PE image needed to walk its sections and data directories.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeImage {
+    /// The address the image was parsed at.
+    pub base: usize,
+    /// Whether the image is PE32+ (64-bit).
+    pub is_64: bool,
+    /// The preferred load address from the optional header.
+    pub image_base: u64,
+    /// The RVA of the entry point, or 0 if the image has none.
+    pub entry_point: u32,
+    /// The size of the image once mapped.
+    pub size_of_image: u32,
+    /// The combined size of the headers.
+    pub size_of_headers: u32,
+    /// Every data directory declared by the optional header.
+    pub data_directories: Vec<DataDirectory>,
+    /// The section table.
+    pub sections: Vec<Section>,
+}
+
+/// An entry of the data directory table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DataDirectory {
+    pub virtual_address: u32,
+    pub size: u32,
+}
+
+/// An entry of the section table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// The section name, without the trailing NUL padding.
+    pub name: String,
+    pub virtual_address: u32,
+    pub virtual_size: u32,
+    pub size_of_raw_data: u32,
+    pub pointer_to_raw_data: u32,
+    pub characteristics: u32,
+}
+
+impl PeImage {
+    /// Returns the data directory at `index`, or `None` if the image doesn't declare it or it is empty.
+    pub fn directory(&self, index: usize) -> Option<DataDirectory> {
+        self.data_directories
+            .get(index)
+            .copied()
+            .filter(|directory| directory.virtual_address != 0 && directory.size != 0)
+    }
+
+    /// Returns the import directory.
+    pub fn import_directory(&self) -> Option<DataDirectory> {
+        self.directory(IMAGE_DIRECTORY_ENTRY_IMPORT as usize)
+    }
+
+    /// Returns the export directory.
+    pub fn export_directory(&self) -> Option<DataDirectory> {
+        self.directory(IMAGE_DIRECTORY_ENTRY_EXPORT as usize)
+    }
+
+    /// Returns the base relocation directory.
+    pub fn relocation_directory(&self) -> Option<DataDirectory> {
+        self.directory(IMAGE_DIRECTORY_ENTRY_BASERELOC as usize)
+    }
+
+    /// Returns the first section whose name starts with `name`.
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections
+            .iter()
+            .find(|section| section.name.starts_with(name))
+    }
+
+    /// Returns the address and size of the raw data of `section` once mapped.
+    ///
+    /// # Errors
+    /// - `PeParseError::SectionOutOfBounds`: If the section header claims data past the end of the
+    ///   image, which would over-read the mapping. This happens with corrupt or packed modules.
+    pub fn section_bounds(&self, section: &Section) -> Result<(usize, usize), PeParseError> {
+        section
+            .virtual_address
+            .checked_add(section.size_of_raw_data)
+            .filter(|&end| end <= self.size_of_image)
+            .ok_or(PeParseError::SectionOutOfBounds)?;
+
+        Ok((
+            self.base + section.virtual_address as usize,
+            section.size_of_raw_data as usize,
+        ))
+    }
+}
+
+/// Parses the headers of a PE image mapped at `base`.
+///
+/// Both PE32 and PE32+ images are supported, regardless of the architecture of the current process.
+///
+/// # Safety
+/// This function is `unsafe` because it reads the headers through raw pointers. `base` must point
+/// to a readable image, e.g. a loaded module or a manually mapped copy.
+///
+/// # Parameters
+/// - `base`: The address of the DOS header of the image.
+///
+/// # Returns
+/// - `Ok(PeImage)`: The parsed headers.
+/// - `Err(PeParseError)`: If the headers are invalid.
+///
+/// # Errors
+/// - `PeParseError::NullPointer`: If `base` is null.
+/// - `PeParseError::InvalidDosHeader`: If the DOS header signature is not `MZ`.
+/// - `PeParseError::InvalidNtHeader`: If the NT header signature is not `PE\0\0`.
+/// - `PeParseError::UnsupportedFormat`: If the optional header is neither PE32 nor PE32+.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::{pe, utils};
+///
+/// let image = unsafe { pe::parse_pe(utils::module_base(None).unwrap() as usize) }.unwrap();
+/// assert!(image.section(".text").is_some());
+/// ```
+pub unsafe fn parse_pe(base: usize) -> Result<PeImage, PeParseError> {
+    parse_pe_impl(base, None)
+}
+
+/// Parses the headers of a PE image with an explicit bitness instead of inferring it from the
+/// optional header magic.
+///
+/// Tools inspecting another process can't rely on the bitness they were compiled for: a 64-bit
+/// tool attached to a 32-bit (WoW64) process must read PE32 headers. Pass the bitness of the
+/// target, e.g. from [`RemoteProcess::is_64bit`](crate::ops::access::RemoteProcess::is_64bit).
+/// The optional header is read with the layout for `is_64bit`, and its magic is only checked
+/// against it.
+///
+/// # Safety
+/// Same as [`parse_pe`].
+///
+/// # Parameters
+/// - `base`: The address of the first byte of the image.
+/// - `is_64bit`: Whether to read the PE32+ (64-bit) or the PE32 (32-bit) optional header.
+///
+/// # Errors
+/// - Same as [`parse_pe`].
+/// - `PeParseError::BitnessMismatch`: If the optional header magic doesn't match `is_64bit`.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::{pe, utils};
+///
+/// let base = utils::module_base(None).unwrap() as usize;
+/// let image = unsafe { pe::parse_pe_as(base, cfg!(target_pointer_width = "64")) }.unwrap();
+/// assert!(image.section(".text").is_some());
+/// ```
+pub unsafe fn parse_pe_as(base: usize, is_64bit: bool) -> Result<PeImage, PeParseError> {
+    parse_pe_impl(base, Some(is_64bit))
+}
+
+/// Returns the RVA of the entry point of a module loaded in the current process, as needed to
+/// call it after injection or manual mapping.
+///
+/// # Parameters
+/// - `module`: The name of the module (e.g. `"kernel32.dll"`), or `None` for the executable.
+///
+/// # Returns
+/// - `Ok(usize)`: The entry point relative to the module base, or 0 if the module has none.
+/// - `Err(PeParseError)`: If the module isn't loaded or its headers are invalid.
+///
+/// # Errors
+/// - `PeParseError::ModuleNotFound`: If `module` is not loaded.
+/// - `PeParseError::InvalidModuleName`: If `module` is empty or contains a NUL character.
+/// - Any other error returned by [`parse_pe`].
+///
+/// # Example
+/// ```rust
+/// use verity_memory::{pe, utils};
+///
+/// let entry_point = pe::module_entry_point(None).unwrap();
+/// let entry = utils::module_base(None).unwrap().wrapping_add(entry_point);
+/// assert!(!entry.is_null());
+/// ```
+pub fn module_entry_point(module: Option<&str>) -> Result<usize, PeParseError> {
+    parse_module(module).map(|image| image.entry_point as usize)
+}
+
+/// Returns the `SizeOfImage` of a module loaded in the current process, i.e. the number of bytes
+/// the image spans once mapped.
+///
+/// # Parameters
+/// - `module`: The name of the module (e.g. `"kernel32.dll"`), or `None` for the executable.
+///
+/// # Errors
+/// - Same as [`module_entry_point`].
+///
+/// # Example
+/// ```rust
+/// use verity_memory::pe;
+///
+/// let size = pe::module_image_size(Some("kernel32.dll")).unwrap();
+/// assert!(size > 0);
+/// ```
+pub fn module_image_size(module: Option<&str>) -> Result<usize, PeParseError> {
+    parse_module(module).map(|image| image.size_of_image as usize)
+}
+
+fn parse_module(module: Option<&str>) -> Result<PeImage, PeParseError> {
+    let base = module_base(module)?;
+    // A loaded module is mapped with its headers, so they are always readable.
+    unsafe { parse_pe(base as usize) }
+}
+
+unsafe fn parse_pe_impl(base: usize, expected_64bit: Option<bool>) -> Result<PeImage, PeParseError> {
+    if base == 0 {
+        return Err(PeParseError::NullPointer);
+    }
+
+    let dos_header = read_unaligned(base as *const IMAGE_DOS_HEADER);
+    if dos_header.e_magic != IMAGE_DOS_SIGNATURE {
+        return Err(PeParseError::InvalidDosHeader);
+    }
+
+    let nt_header_ptr = base + dos_header.e_lfanew as usize;
+    if read_unaligned(nt_header_ptr as *const u32) != IMAGE_NT_SIGNATURE {
+        return Err(PeParseError::InvalidNtHeader);
+    }
+
+    let file_header = read_unaligned((nt_header_ptr + 4) as *const IMAGE_FILE_HEADER);
+    let optional_header_ptr = nt_header_ptr + 4 + size_of::<IMAGE_FILE_HEADER>();
+
+    let magic = read_unaligned(optional_header_ptr as *const u16);
+    let is_64 = match expected_64bit {
+        Some(is_64bit) => {
+            let expected_magic = if is_64bit {
+                IMAGE_NT_OPTIONAL_HDR64_MAGIC
+            } else {
+                IMAGE_NT_OPTIONAL_HDR32_MAGIC
+            };
+            if magic != expected_magic {
+                return Err(PeParseError::BitnessMismatch);
+            }
+            is_64bit
+        }
+        None => match magic {
+            IMAGE_NT_OPTIONAL_HDR64_MAGIC => true,
+            IMAGE_NT_OPTIONAL_HDR32_MAGIC => false,
+            _ => return Err(PeParseError::UnsupportedFormat),
+        },
+    };
+
+    let (image_base, entry_point, size_of_image, size_of_headers, directories_ptr, directory_count) = if is_64 {
+        let header = read_unaligned(optional_header_ptr as *const IMAGE_OPTIONAL_HEADER64);
+        (
+            header.ImageBase,
+            header.AddressOfEntryPoint,
+            header.SizeOfImage,
+            header.SizeOfHeaders,
+            optional_header_ptr + data_directory_offset::<IMAGE_OPTIONAL_HEADER64>(),
+            header.NumberOfRvaAndSizes,
+        )
+    } else {
+        let header = read_unaligned(optional_header_ptr as *const IMAGE_OPTIONAL_HEADER32);
+        (
+            header.ImageBase as u64,
+            header.AddressOfEntryPoint,
+            header.SizeOfImage,
+            header.SizeOfHeaders,
+            optional_header_ptr + data_directory_offset::<IMAGE_OPTIONAL_HEADER32>(),
+            header.NumberOfRvaAndSizes,
+        )
+    };
+
+    let data_directories = (0..directory_count.min(16) as usize)
+        .map(|index| {
+            let entry = read_unaligned(
+                (directories_ptr + index * size_of::<IMAGE_DATA_DIRECTORY>()) as *const IMAGE_DATA_DIRECTORY,
+            );
+            DataDirectory {
+                virtual_address: entry.VirtualAddress,
+                size: entry.Size,
+            }
+        })
+        .collect();
+
+    let section_header_ptr = optional_header_ptr + file_header.SizeOfOptionalHeader as usize;
+    let sections = (0..file_header.NumberOfSections as usize)
+        .map(|index| {
+            let header = read_unaligned(
+                (section_header_ptr + index * size_of::<IMAGE_SECTION_HEADER>()) as *const IMAGE_SECTION_HEADER,
+            );
+            let name_len = header.Name.iter().position(|&b| b == 0).unwrap_or(header.Name.len());
+            Section {
+                name: String::from_utf8_lossy(&header.Name[..name_len]).into_owned(),
+                virtual_address: header.VirtualAddress,
+                virtual_size: *header.Misc.VirtualSize(),
+                size_of_raw_data: header.SizeOfRawData,
+                pointer_to_raw_data: header.PointerToRawData,
+                characteristics: header.Characteristics,
+            }
+        })
+        .collect();
+
+    Ok(PeImage {
+        base,
+        is_64,
+        image_base,
+        entry_point,
+        size_of_image,
+        size_of_headers,
+        data_directories,
+        sections,
+    })
+}
+
+/// Checks that the headers of the image in `image` lie within the slice, so it can be parsed
+/// with [`parse_pe`] without reading out of bounds.
+///
+/// The optional header is read whole by the parser, so `SizeOfOptionalHeader` must be at least
+/// the size of the layout its magic selects.
+pub(crate) fn check_headers_in_bounds(image: &[u8]) -> Result<(), PeParseError> {
+    let read_u16 = |offset: usize| -> Result<u16, PeParseError> {
+        image
+            .get(offset..offset + 2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+            .ok_or(PeParseError::OutOfBounds)
+    };
+
+    if image.len() < size_of::<IMAGE_DOS_HEADER>() {
+        return Err(PeParseError::OutOfBounds);
+    }
+
+    let e_lfanew = u32::from_le_bytes([image[0x3C], image[0x3D], image[0x3E], image[0x3F]]) as usize;
+    if e_lfanew > image.len() {
+        return Err(PeParseError::OutOfBounds);
+    }
+
+    let file_header = e_lfanew + 4;
+    let number_of_sections = read_u16(file_header + 2)? as usize;
+    let size_of_optional_header = read_u16(file_header + 16)? as usize;
+
+    let optional_header = file_header + size_of::<IMAGE_FILE_HEADER>();
+    let optional_header_layout = match read_u16(optional_header)? {
+        IMAGE_NT_OPTIONAL_HDR64_MAGIC => size_of::<IMAGE_OPTIONAL_HEADER64>(),
+        IMAGE_NT_OPTIONAL_HDR32_MAGIC => size_of::<IMAGE_OPTIONAL_HEADER32>(),
+        _ => return Err(PeParseError::UnsupportedFormat),
+    };
+    if size_of_optional_header < optional_header_layout {
+        return Err(PeParseError::OutOfBounds);
+    }
+
+    let end = optional_header + size_of_optional_header + number_of_sections * size_of::<IMAGE_SECTION_HEADER>();
+
+    if end > image.len() {
+        return Err(PeParseError::OutOfBounds);
+    }
+
+    Ok(())
+}
+
+/// The data directory table is the last field of both optional header layouts.
+const fn data_directory_offset<T>() -> usize {
+    size_of::<T>() - 16 * size_of::<IMAGE_DATA_DIRECTORY>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winapi::um::libloaderapi::GetModuleHandleA;
+    use crate::pe::test_image::{TestImage, NT_OFFSET};
+
+    fn current_module() -> usize {
+        unsafe { GetModuleHandleA(std::ptr::null()) as usize }
+    }
+
+    #[test]
+    fn test_parse_pe_current_module() {
+        let image = unsafe { parse_pe(current_module()) }.expect("Failed to parse current module");
+
+        assert_eq!(image.base, current_module());
+        assert_eq!(image.is_64, cfg!(target_pointer_width = "64"));
+        assert_eq!(image.data_directories.len(), 16);
+        assert!(image.size_of_image > 0);
+        assert!(image.import_directory().is_some());
+
+        let text = image.section(".text").expect("Missing .text section");
+        assert!(text.virtual_size > 0);
+        assert!(text.virtual_address < image.size_of_image);
+    }
+
+    #[test]
+    fn test_module_entry_point_and_image_size() {
+        let entry_point = module_entry_point(None).expect("Failed to get entry point");
+        let image_size = module_image_size(None).expect("Failed to get image size");
+
+        assert!(entry_point > 0);
+        assert!(entry_point < image_size);
+
+        let kernel32_size = module_image_size(Some("kernel32.dll")).expect("Failed to get kernel32 size");
+        assert!(kernel32_size > 0);
+        assert!(module_entry_point(Some("kernel32.dll")).unwrap() < kernel32_size);
+
+        assert_eq!(module_entry_point(Some("not_loaded.dll")), Err(PeParseError::ModuleNotFound));
+        assert_eq!(module_image_size(Some("")), Err(PeParseError::InvalidModuleName));
+        assert_eq!(module_entry_point(Some("kernel32.dll\0")), Err(PeParseError::InvalidModuleName));
+    }
+
+    #[test]
+    fn test_section_bounds() {
+        let mut image = unsafe { parse_pe(current_module()) }.expect("Failed to parse current module");
+        let mut text = image.section(".text").expect("Missing .text section").clone();
+
+        let (address, size) = image.section_bounds(&text).expect("Section out of bounds");
+        assert_eq!(address, image.base + text.virtual_address as usize);
+        assert_eq!(size, text.size_of_raw_data as usize);
+
+        text.size_of_raw_data = u32::MAX;
+        assert_eq!(image.section_bounds(&text), Err(PeParseError::SectionOutOfBounds));
+
+        text.size_of_raw_data = image.size_of_image;
+        assert_eq!(image.section_bounds(&text), Err(PeParseError::SectionOutOfBounds));
+
+        image.size_of_image = text.virtual_address;
+        text.size_of_raw_data = 0;
+        assert!(image.section_bounds(&text).is_ok());
+    }
+
+    #[test]
+    fn test_parse_pe_null() {
+        assert_eq!(unsafe { parse_pe(0) }, Err(PeParseError::NullPointer));
+    }
+
+    #[test]
+    fn test_parse_pe_invalid_dos_header() {
+        let buffer = [0u8; 0x100];
+        assert_eq!(unsafe { parse_pe(buffer.as_ptr() as usize) }, Err(PeParseError::InvalidDosHeader));
+    }
+
+    /// Builds the headers of an image with no sections, in the PE32 or PE32+ layout.
+    fn headers(is_64bit: bool) -> Vec<u8> {
+        TestImage::new(0x200)
+            .is_64bit(is_64bit)
+            .image_base(if is_64bit { 0x1_4000_0000 } else { 0x40_0000 })
+            .size_of_image(0x2000)
+            .build()
+    }
+
+    #[test]
+    fn test_parse_pe_as_32bit() {
+        let buffer = headers(false);
+        let image = unsafe { parse_pe_as(buffer.as_ptr() as usize, false) }.expect("Failed to parse PE32 headers");
+
+        assert!(!image.is_64);
+        assert_eq!(image.image_base, 0x40_0000);
+        assert_eq!(image.size_of_image, 0x2000);
+        assert_eq!(image.data_directories.len(), 16);
+        assert_eq!(unsafe { parse_pe_as(buffer.as_ptr() as usize, true) }, Err(PeParseError::BitnessMismatch));
+    }
+
+    #[test]
+    fn test_parse_pe_as_64bit() {
+        let buffer = headers(true);
+        let image = unsafe { parse_pe_as(buffer.as_ptr() as usize, true) }.expect("Failed to parse PE32+ headers");
+
+        assert!(image.is_64);
+        assert_eq!(image.image_base, 0x1_4000_0000);
+        assert_eq!(image.size_of_image, 0x2000);
+        assert_eq!(image.data_directories.len(), 16);
+        assert_eq!(unsafe { parse_pe_as(buffer.as_ptr() as usize, false) }, Err(PeParseError::BitnessMismatch));
+    }
+
+    #[test]
+    fn test_parse_pe_sections() {
+        let buffer = TestImage::new(0x400)
+            .size_of_image(0x2000)
+            .size_of_headers(0x200)
+            .section(".text", 0x1000, 0x200, 0x100)
+            .build();
+        let image = unsafe { parse_pe(buffer.as_ptr() as usize) }.expect("Failed to parse headers");
+
+        assert_eq!(image.size_of_headers, 0x200);
+        assert_eq!(image.sections.len(), 1);
+
+        let text = image.section(".text").expect("Missing .text section");
+        assert_eq!(text.virtual_address, 0x1000);
+        assert_eq!(text.pointer_to_raw_data, 0x200);
+        assert_eq!(image.section_bounds(text), Ok((image.base + 0x1000, 0x100)));
+    }
+
+    #[test]
+    fn test_check_headers_in_bounds_optional_header_size() {
+        for is_64bit in [false, true] {
+            let mut buffer = headers(is_64bit);
+            assert_eq!(check_headers_in_bounds(&buffer), Ok(()));
+
+            // A SizeOfOptionalHeader smaller than the layout would let the parser read past it.
+            let field = NT_OFFSET + 4 + 16;
+            buffer[field..field + 2].copy_from_slice(&0x60u16.to_le_bytes());
+            assert_eq!(check_headers_in_bounds(&buffer), Err(PeParseError::OutOfBounds));
+
+            let truncated = headers(is_64bit);
+            let optional_header = NT_OFFSET + 4 + size_of::<IMAGE_FILE_HEADER>();
+            assert_eq!(
+                check_headers_in_bounds(&truncated[..optional_header + 0x60]),
+                Err(PeParseError::OutOfBounds)
+            );
+        }
+
+        let mut buffer = headers(true);
+        buffer[NT_OFFSET + 4 + size_of::<IMAGE_FILE_HEADER>()] = 0;
+        assert_eq!(check_headers_in_bounds(&buffer), Err(PeParseError::UnsupportedFormat));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,382 @@
+use std::ffi::{CStr, CString};
+use std::ptr::read_unaligned;
+
+use winapi::um::libloaderapi::{GetProcAddress, LoadLibraryA};
+use winapi::um::winnt::LPCSTR;
+
+use crate::errors::PeParseError;
+use crate::ops::write::write_bytes;
+use crate::utils::module_base;
+
+use super::image::{parse_pe, PeImage};
+
+const IMPORT_DESCRIPTOR_SIZE: usize = 20;
+
+/// Resolves the imports of a manually mapped image and writes them into its IAT.
+///
+/// Every DLL in the import directory is loaded with `LoadLibraryA` and every thunk resolved with
+/// `GetProcAddress`, by name or by ordinal. Imports are always looked up from the import name table
+/// when the image has one, so addresses pre-bound at link time are replaced by the ones of the DLLs
+/// actually loaded.
+///
+/// # Safety
+/// This function is `unsafe` because it loads libraries and writes to the IAT of the image.
+/// `image_base` must point to an image in its mapped layout, built for the architecture of the
+/// current process.
+///
+/// # Parameters
+/// - `image_base`: The address of the mapped image.
+///
+/// # Returns
+/// - `Ok(())`: If every import was resolved, or the image has no imports.
+/// - `Err(PeParseError)`: If the headers are invalid or an import could not be resolved.
+///
+/// # Errors
+/// - `PeParseError::UnsupportedFormat`: If the image isn't built for the architecture of the current process.
+/// - `PeParseError::FailedToLoadLibrary`: If a DLL could not be loaded.
+/// - `PeParseError::FailedToResolveImport`: If a function could not be found or written to the IAT.
+/// - `PeParseError::OutOfBounds`: If a descriptor, thunk or name lies outside the image.
+/// - Any error returned by [`parse_pe`] for invalid headers.
+pub unsafe fn resolve_imports(image_base: usize) -> Result<(), PeParseError> {
+    let image = parse_pe(image_base)?;
+    if image.is_64 != cfg!(target_pointer_width = "64") {
+        return Err(PeParseError::UnsupportedFormat);
+    }
+
+    for descriptor in read_import_descriptors(&image)? {
+        let module = LoadLibraryA(descriptor.dll.as_ptr());
+        if module.is_null() {
+            return Err(PeParseError::FailedToLoadLibrary);
+        }
+
+        for thunk in &descriptor.thunks {
+            let proc_name = match &thunk.import {
+                Import::ByName { name, .. } => name.as_ptr(),
+                Import::ByOrdinal(ordinal) => *ordinal as usize as LPCSTR,
+            };
+
+            let address = GetProcAddress(module, proc_name);
+            if address.is_null() {
+                return Err(PeParseError::FailedToResolveImport);
+            }
+
+            let iat_entry = (image_base + thunk.iat_rva) as *mut u8;
+            write_bytes(iat_entry, &(address as usize).to_ne_bytes())
+                .map_err(|_| PeParseError::FailedToResolveImport)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A function imported by an image, as listed by [`parse_imports`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportEntry {
+    /// The name of the DLL the function is imported from, as written in the import directory.
+    pub dll: String,
+    /// The imported name, or `None` for an import by ordinal.
+    pub name: Option<String>,
+    /// The ordinal for an import by ordinal, or the hint for an import by name.
+    pub ordinal: u16,
+    /// The RVA of the IAT slot of the import.
+    pub rva: usize,
+    /// The address currently stored in the IAT slot, i.e. the resolved function once the image is loaded.
+    pub address: usize,
+}
+
+/// Lists the functions imported by an image mapped at `image_base`, in the order of its import
+/// directory.
+///
+/// Nothing is loaded or resolved: each entry reports the address the IAT currently holds. Both
+/// 32-bit and 64-bit images are supported, whatever the architecture of the current process.
+///
+/// # Safety
+/// This function is `unsafe` because it reads the import directory through raw pointers.
+/// `image_base` must point to an image in its mapped layout, e.g. a loaded module.
+///
+/// # Parameters
+/// - `image_base`: The address of the mapped image.
+///
+/// # Returns
+/// - `Ok(Vec<ImportEntry>)`: The imports, or an empty list if the image has none.
+/// - `Err(PeParseError)`: If the headers or the import directory are invalid.
+///
+/// # Errors
+/// - `PeParseError::OutOfBounds`: If a descriptor, thunk or name lies outside the image.
+/// - Any error returned by [`parse_pe`] for invalid headers.
+pub unsafe fn parse_imports(image_base: usize) -> Result<Vec<ImportEntry>, PeParseError> {
+    let image = parse_pe(image_base)?;
+
+    let mut entries = Vec::new();
+    for descriptor in read_import_descriptors(&image)? {
+        let dll = descriptor.dll.to_string_lossy().into_owned();
+
+        for thunk in descriptor.thunks {
+            let (name, ordinal) = match thunk.import {
+                Import::ByName { hint, name } => (Some(name.to_string_lossy().into_owned()), hint),
+                Import::ByOrdinal(ordinal) => (None, ordinal),
+            };
+
+            entries.push(ImportEntry {
+                dll: dll.clone(),
+                name,
+                ordinal,
+                rva: thunk.iat_rva,
+                address: read_thunk(image_base + thunk.iat_rva, image.is_64) as usize,
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Lists the functions imported by a module loaded in the current process.
+///
+/// # Parameters
+/// - `module`: The name of the module (e.g. `"kernel32.dll"`).
+///
+/// # Errors
+/// - `PeParseError::ModuleNotFound`: If `module` is not loaded.
+/// - `PeParseError::InvalidModuleName`: If `module` is empty or contains a NUL character.
+/// - Any other error returned by [`parse_imports`].
+///
+/// # Example
+/// ```rust
+/// use verity_memory::pe;
+///
+/// let imports = pe::list_imports("kernel32.dll").unwrap();
+/// assert!(imports.iter().all(|import| import.address != 0));
+/// ```
+pub fn list_imports(module: &str) -> Result<Vec<ImportEntry>, PeParseError> {
+    let base = module_base(Some(module))?;
+    // A loaded module is mapped with its import directory, and its IAT is bound.
+    unsafe { parse_imports(base as usize) }
+}
+
+/// How a thunk of the import name table names its function.
+enum Import {
+    ByName { hint: u16, name: CString },
+    ByOrdinal(u16),
+}
+
+/// A thunk of the import directory, with the RVA of the IAT slot it resolves.
+struct ImportThunk {
+    import: Import,
+    iat_rva: usize,
+}
+
+/// The imports of one DLL, in the order of the import directory.
+struct ImportDescriptor {
+    dll: CString,
+    thunks: Vec<ImportThunk>,
+}
+
+/// Walks the import directory of a mapped image, checking that every descriptor, thunk and name
+/// lies within the image before reading it.
+///
+/// Thunks are read from the import name table whenever the image has one, since bound imports
+/// have their IAT pre-filled with addresses instead of names.
+///
+/// # Errors
+/// - `PeParseError::OutOfBounds`: If a descriptor, thunk or name lies outside the image.
+unsafe fn read_import_descriptors(image: &PeImage) -> Result<Vec<ImportDescriptor>, PeParseError> {
+    let directory = match image.import_directory() {
+        Some(directory) => directory,
+        None => return Ok(Vec::new()),
+    };
+
+    let image_base = image.base;
+    let image_size = image.size_of_image as usize;
+    let thunk_size = if image.is_64 { 8 } else { 4 };
+    let ordinal_flag = 1u64 << (thunk_size * 8 - 1);
+    let within = |rva: usize, len: usize| rva.checked_add(len).is_some_and(|end| end <= image_size);
+
+    let mut descriptors = Vec::new();
+    let mut descriptor = directory.virtual_address as usize;
+    loop {
+        if !within(descriptor, IMPORT_DESCRIPTOR_SIZE) {
+            return Err(PeParseError::OutOfBounds);
+        }
+
+        let original_first_thunk = read_unaligned((image_base + descriptor) as *const u32) as usize;
+        let name = read_unaligned((image_base + descriptor + 12) as *const u32) as usize;
+        let first_thunk = read_unaligned((image_base + descriptor + 16) as *const u32) as usize;
+
+        if name == 0 || first_thunk == 0 {
+            break;
+        }
+
+        let dll = read_name(image_base, name, image_size)?;
+        let lookup = if original_first_thunk != 0 {
+            original_first_thunk
+        } else {
+            first_thunk
+        };
+
+        let mut thunks = Vec::new();
+        let mut index = 0;
+        loop {
+            let lookup_rva = lookup + index * thunk_size;
+            let iat_rva = first_thunk + index * thunk_size;
+            if !within(lookup_rva, thunk_size) || !within(iat_rva, thunk_size) {
+                return Err(PeParseError::OutOfBounds);
+            }
+
+            let thunk = read_thunk(image_base + lookup_rva, image.is_64);
+            if thunk == 0 {
+                break;
+            }
+
+            let import = if thunk & ordinal_flag != 0 {
+                Import::ByOrdinal((thunk & 0xFFFF) as u16)
+            } else {
+                // IMAGE_IMPORT_BY_NAME: a hint followed by the name.
+                let by_name = thunk as usize;
+                if !within(by_name, 2) {
+                    return Err(PeParseError::OutOfBounds);
+                }
+                Import::ByName {
+                    hint: read_unaligned((image_base + by_name) as *const u16),
+                    name: read_name(image_base, by_name + 2, image_size)?,
+                }
+            };
+
+            thunks.push(ImportThunk { import, iat_rva });
+            index += 1;
+        }
+
+        descriptors.push(ImportDescriptor { dll, thunks });
+        descriptor += IMPORT_DESCRIPTOR_SIZE;
+    }
+
+    Ok(descriptors)
+}
+
+unsafe fn read_thunk(address: usize, is_64: bool) -> u64 {
+    if is_64 {
+        read_unaligned(address as *const u64)
+    } else {
+        read_unaligned(address as *const u32) as u64
+    }
+}
+
+/// Reads the NUL-terminated name at `rva`, failing if it runs past the end of the image.
+pub(super) unsafe fn read_name(image_base: usize, rva: usize, image_size: usize) -> Result<CString, PeParseError> {
+    if rva >= image_size {
+        return Err(PeParseError::OutOfBounds);
+    }
+
+    let bytes = std::slice::from_raw_parts((image_base + rva) as *const u8, image_size - rva);
+    let name = CStr::from_bytes_until_nul(bytes).map_err(|_| PeParseError::OutOfBounds)?;
+    Ok(name.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pe::test_image::TestImage;
+    use std::mem::size_of;
+    use winapi::um::libloaderapi::GetModuleHandleA;
+    use winapi::um::winnt::IMAGE_DIRECTORY_ENTRY_IMPORT;
+
+    const DESCRIPTOR_RVA: usize = 0x200;
+    const NAME_TABLE_RVA: usize = 0x240;
+    const IAT_RVA: usize = 0x260;
+    const BY_NAME_RVA: usize = 0x280;
+    const DLL_NAME_RVA: usize = 0x2C0;
+
+    /// A minimal image importing `GetCurrentProcessId` from kernel32.dll.
+    fn tiny_image() -> TestImage {
+        TestImage::new(0x400)
+            .size_of_image(0x400)
+            .directory(
+                IMAGE_DIRECTORY_ENTRY_IMPORT as usize,
+                DESCRIPTOR_RVA as u32,
+                2 * IMPORT_DESCRIPTOR_SIZE as u32,
+            )
+            .put(DESCRIPTOR_RVA, &(NAME_TABLE_RVA as u32).to_le_bytes())
+            .put(DESCRIPTOR_RVA + 12, &(DLL_NAME_RVA as u32).to_le_bytes())
+            .put(DESCRIPTOR_RVA + 16, &(IAT_RVA as u32).to_le_bytes())
+            .put(NAME_TABLE_RVA, &BY_NAME_RVA.to_ne_bytes())
+            .put(IAT_RVA, &BY_NAME_RVA.to_ne_bytes())
+            .put(BY_NAME_RVA + 2, b"GetCurrentProcessId\0")
+            .put(DLL_NAME_RVA, b"kernel32.dll\0")
+    }
+
+    #[test]
+    fn test_resolve_imports_tiny_image() {
+        let mut image = tiny_image().build();
+        let image_base = image.as_mut_ptr() as usize;
+
+        let result = unsafe { resolve_imports(image_base) };
+        assert_eq!(result, Ok(()));
+
+        let resolved = usize::from_ne_bytes(image[IAT_RVA..IAT_RVA + size_of::<usize>()].try_into().unwrap());
+        let expected = unsafe {
+            let kernel32 = GetModuleHandleA(b"kernel32.dll\0".as_ptr() as LPCSTR);
+            GetProcAddress(kernel32, b"GetCurrentProcessId\0".as_ptr() as LPCSTR) as usize
+        };
+        assert_eq!(resolved, expected);
+
+        let get_current_process_id: extern "system" fn() -> u32 = unsafe { std::mem::transmute(resolved) };
+        assert_eq!(get_current_process_id(), std::process::id());
+    }
+
+    #[test]
+    fn test_parse_imports_tiny_image() {
+        let mut image = tiny_image().build();
+
+        let imports = unsafe { parse_imports(image.as_mut_ptr() as usize) }.expect("Failed to parse imports");
+        assert_eq!(
+            imports,
+            vec![ImportEntry {
+                dll: "kernel32.dll".to_string(),
+                name: Some("GetCurrentProcessId".to_string()),
+                ordinal: 0,
+                rva: IAT_RVA,
+                address: BY_NAME_RVA,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_list_imports_current_module() {
+        let imports = list_imports(&current_module_name()).expect("Failed to list imports");
+
+        // The test harness reads the clock, so the executable imports kernel32.
+        let import = imports
+            .iter()
+            .find(|import| import.name.as_deref() == Some("QueryPerformanceCounter"))
+            .expect("QueryPerformanceCounter is not imported");
+        assert!(import.dll.to_ascii_lowercase().ends_with(".dll"));
+
+        let expected = unsafe {
+            let kernel32 = GetModuleHandleA(b"kernel32.dll\0".as_ptr() as LPCSTR);
+            GetProcAddress(kernel32, b"QueryPerformanceCounter\0".as_ptr() as LPCSTR) as usize
+        };
+        assert_eq!(import.address, expected);
+
+        assert_eq!(list_imports("non_existent_dll.dll"), Err(PeParseError::ModuleNotFound));
+    }
+
+    fn current_module_name() -> String {
+        let path = std::env::current_exe().unwrap();
+        path.file_name().unwrap().to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_resolve_imports_missing_library() {
+        let mut image = tiny_image().put(DLL_NAME_RVA, b"non_existent_dll.dll\0").build();
+
+        let result = unsafe { resolve_imports(image.as_mut_ptr() as usize) };
+        assert_eq!(result, Err(PeParseError::FailedToLoadLibrary));
+    }
+
+    #[test]
+    fn test_resolve_imports_missing_function() {
+        let mut image = tiny_image().put(BY_NAME_RVA + 2, b"NonExistentFunction\0").build();
+
+        let result = unsafe { resolve_imports(image.as_mut_ptr() as usize) };
+        assert_eq!(result, Err(PeParseError::FailedToResolveImport));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,24 @@
+pub mod exports;
+pub mod image;
+pub mod imports;
+pub mod reloc;
+pub mod remote;
+#[cfg(test)]
+pub(crate) mod test_image;
+
+pub use exports::list_exports;
+pub use exports::parse_exports;
+pub use exports::Export;
+pub use image::parse_pe;
+pub use image::parse_pe_as;
+pub use image::module_entry_point;
+pub use image::module_image_size;
+pub use image::DataDirectory;
+pub use image::PeImage;
+pub use image::Section;
+pub use imports::list_imports;
+pub use imports::parse_imports;
+pub use imports::resolve_imports;
+pub use imports::ImportEntry;
+pub use reloc::apply_relocations;
+pub use remote::parse_pe_remote;
\ No newline at end of file
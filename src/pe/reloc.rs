@@ -0,0 +1,199 @@
+use winapi::um::winnt::{IMAGE_REL_BASED_ABSOLUTE, IMAGE_REL_BASED_DIR64, IMAGE_REL_BASED_HIGHLOW};
+
+use crate::errors::PeParseError;
+
+use super::image::{check_headers_in_bounds, parse_pe};
+
+/// Applies the base relocations of a mapped image copied to a new address.
+///
+/// Walks the base relocation directory and adds `actual_base - preferred_base` to every
+/// relocated address, which is needed before running a manually mapped image. `HIGHLOW` (32-bit)
+/// and `DIR64` (64-bit) entries are patched and `ABSOLUTE` padding entries are skipped.
+///
+/// # Parameters
+/// - `image`: The image in its mapped layout, i.e. with every section at its RVA.
+/// - `preferred_base`: The image base the image was linked for.
+/// - `actual_base`: The address the image will run at.
+///
+/// # Returns
+/// - `Ok(())`: If every relocation was applied, or the image has no relocations.
+/// - `Err(PeParseError)`: If the headers or relocation blocks are invalid.
+///
+/// # Errors
+/// - `PeParseError::OutOfBounds`: If the headers or a relocation lie outside of `image`.
+/// - `PeParseError::UnsupportedRelocation`: If a relocation type other than `ABSOLUTE`,
+///   `HIGHLOW` or `DIR64` is found.
+/// - Any error returned by [`parse_pe`] for invalid headers.
+pub fn apply_relocations(
+    image: &mut [u8],
+    preferred_base: usize,
+    actual_base: usize,
+) -> Result<(), PeParseError> {
+    check_headers_in_bounds(image)?;
+    let pe = unsafe { parse_pe(image.as_ptr() as usize)? };
+
+    let delta = (actual_base as u64).wrapping_sub(preferred_base as u64);
+    if delta == 0 {
+        return Ok(());
+    }
+
+    match pe.relocation_directory() {
+        Some(directory) => apply_relocation_blocks(
+            image,
+            directory.virtual_address as usize,
+            directory.size as usize,
+            delta,
+        ),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn apply_relocation_blocks(
+    image: &mut [u8],
+    blocks_rva: usize,
+    blocks_size: usize,
+    delta: u64,
+) -> Result<(), PeParseError> {
+    let mut block = blocks_rva;
+    let end = blocks_rva
+        .checked_add(blocks_size)
+        .filter(|&end| end <= image.len())
+        .ok_or(PeParseError::OutOfBounds)?;
+
+    while block + 8 <= end {
+        let page_rva = read_u32(image, block)? as usize;
+        let block_size = read_u32(image, block + 4)? as usize;
+        // Entries are 16 bits wide, so an odd block size would split the last one.
+        if block_size < 8 || block_size % 2 != 0 || block_size > end - block {
+            return Err(PeParseError::OutOfBounds);
+        }
+
+        // The entries are copied out first since applying them writes to `image`.
+        let entries: Vec<u16> = image[block + 8..block + block_size]
+            .chunks_exact(2)
+            .map(|entry| u16::from_le_bytes([entry[0], entry[1]]))
+            .collect();
+
+        for entry in entries {
+            let kind = entry >> 12;
+            let target = page_rva
+                .checked_add((entry & 0x0FFF) as usize)
+                .ok_or(PeParseError::OutOfBounds)?;
+
+            match kind {
+                IMAGE_REL_BASED_ABSOLUTE => {}
+                IMAGE_REL_BASED_HIGHLOW => {
+                    let value = read_u32(image, target)?.wrapping_add(delta as u32);
+                    image[target..target + 4].copy_from_slice(&value.to_le_bytes());
+                }
+                IMAGE_REL_BASED_DIR64 => {
+                    let bytes = image
+                        .get(target..)
+                        .and_then(|bytes| bytes.get(..8))
+                        .ok_or(PeParseError::OutOfBounds)?;
+                    let value = u64::from_le_bytes(bytes.try_into().unwrap()).wrapping_add(delta);
+                    image[target..target + 8].copy_from_slice(&value.to_le_bytes());
+                }
+                _ => return Err(PeParseError::UnsupportedRelocation),
+            }
+        }
+
+        block += block_size;
+    }
+
+    Ok(())
+}
+
+fn read_u32(image: &[u8], offset: usize) -> Result<u32, PeParseError> {
+    image
+        .get(offset..)
+        .and_then(|bytes| bytes.get(..4))
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or(PeParseError::OutOfBounds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(page_rva: u32, entries: &[u16]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&page_rva.to_le_bytes());
+        bytes.extend_from_slice(&(8 + entries.len() as u32 * 2).to_le_bytes());
+        for entry in entries {
+            bytes.extend_from_slice(&entry.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_apply_relocation_blocks() {
+        let mut image = vec![0u8; 0x200];
+        image[0x10..0x18].copy_from_slice(&0x1_4000_1000u64.to_le_bytes());
+        image[0x20..0x24].copy_from_slice(&0x0040_2000u32.to_le_bytes());
+        image[0x30..0x38].copy_from_slice(&0xAAAA_AAAA_AAAA_AAAAu64.to_le_bytes());
+
+        let blocks = block(0, &[0xA010, 0x3020, 0x0000]);
+        image[0x100..0x100 + blocks.len()].copy_from_slice(&blocks);
+
+        let result = apply_relocation_blocks(&mut image, 0x100, blocks.len(), 0x10_0000);
+        assert_eq!(result, Ok(()));
+
+        assert_eq!(&image[0x10..0x18], &0x1_4010_1000u64.to_le_bytes());
+        assert_eq!(&image[0x20..0x24], &0x0050_2000u32.to_le_bytes());
+        assert_eq!(&image[0x30..0x38], &0xAAAA_AAAA_AAAA_AAAAu64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_apply_relocation_blocks_negative_delta() {
+        let mut image = vec![0u8; 0x100];
+        image[0x08..0x0C].copy_from_slice(&0x0040_2000u32.to_le_bytes());
+
+        let blocks = block(0, &[0x3008]);
+        image[0x80..0x80 + blocks.len()].copy_from_slice(&blocks);
+
+        let delta = 0x0030_0000u64.wrapping_sub(0x0040_0000);
+        assert_eq!(apply_relocation_blocks(&mut image, 0x80, blocks.len(), delta), Ok(()));
+        assert_eq!(&image[0x08..0x0C], &0x0030_2000u32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_apply_relocation_blocks_out_of_bounds() {
+        let mut image = vec![0u8; 0x40];
+
+        let blocks = block(0x1000, &[0x3000]);
+        image[0x20..0x20 + blocks.len()].copy_from_slice(&blocks);
+
+        let result = apply_relocation_blocks(&mut image, 0x20, blocks.len(), 0x1000);
+        assert_eq!(result, Err(PeParseError::OutOfBounds));
+    }
+
+    #[test]
+    fn test_apply_relocation_blocks_odd_size() {
+        let mut image = vec![0u8; 0x40];
+
+        let mut blocks = block(0, &[0x3000]);
+        blocks.push(0);
+        blocks[4..8].copy_from_slice(&(blocks.len() as u32).to_le_bytes());
+        let offset = image.len() - blocks.len();
+        image[offset..].copy_from_slice(&blocks);
+
+        let result = apply_relocation_blocks(&mut image, offset, blocks.len(), 0x1000);
+        assert_eq!(result, Err(PeParseError::OutOfBounds));
+        assert_eq!(
+            apply_relocation_blocks(&mut image, usize::MAX, 8, 0x1000),
+            Err(PeParseError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_apply_relocation_blocks_unsupported() {
+        let mut image = vec![0u8; 0x40];
+
+        let blocks = block(0, &[0x5000]);
+        image[0x20..0x20 + blocks.len()].copy_from_slice(&blocks);
+
+        let result = apply_relocation_blocks(&mut image, 0x20, blocks.len(), 0x1000);
+        assert_eq!(result, Err(PeParseError::UnsupportedRelocation));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,86 @@
+use crate::errors::PeParseError;
+use crate::ops::access::MemoryAccess;
+
+use super::image::{check_headers_in_bounds, parse_pe_as, PeImage};
+
+/// The number of bytes copied from the target, enough for the headers of any regular image.
+const HEADERS_SIZE: usize = 0x1000;
+
+/// Parses the headers of an image loaded in another process.
+///
+/// The headers are copied out of the target through `access` and parsed locally with
+/// [`parse_pe_as`], so the layout follows the bitness of the target rather than of the current
+/// process. The returned [`PeImage::base`] is the address in the target.
+///
+/// # Safety
+/// This function is `unsafe` because it reads memory of the target process.
+/// - `base` must be the base of an image mapped in the target.
+///
+/// # Parameters
+/// - `access`: The process the image is loaded in.
+/// - `base`: The base address of the image in that process.
+/// - `is_64bit`: Whether the target process is 64-bit, e.g. from
+///   [`RemoteProcess::is_64bit`](crate::ops::access::RemoteProcess::is_64bit).
+///
+/// # Errors
+/// - `PeParseError::NullPointer`: If `base` is 0.
+/// - `PeParseError::FailedToRead`: If the headers could not be read from the target.
+/// - `PeParseError::OutOfBounds`: If the headers extend past the first page.
+/// - Any other error returned by [`parse_pe_as`].
+///
+/// # Example
+/// ```rust
+/// use verity_memory::{ops::access::CurrentProcess, pe, utils};
+///
+/// let base = utils::module_base(None).unwrap() as usize;
+/// let image = unsafe { pe::parse_pe_remote(&CurrentProcess, base, cfg!(target_pointer_width = "64")) }.unwrap();
+/// assert_eq!(image.base, base);
+/// ```
+pub unsafe fn parse_pe_remote<A: MemoryAccess + ?Sized>(
+    access: &A,
+    base: usize,
+    is_64bit: bool,
+) -> Result<PeImage, PeParseError> {
+    if base == 0 {
+        return Err(PeParseError::NullPointer);
+    }
+
+    let mut headers = vec![0u8; HEADERS_SIZE];
+    access
+        .read_bytes(base, &mut headers)
+        .map_err(|_| PeParseError::FailedToRead)?;
+
+    check_headers_in_bounds(&headers)?;
+
+    let mut image = parse_pe_as(headers.as_ptr() as usize, is_64bit)?;
+    image.base = base;
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::access::RemoteProcess;
+    use crate::pe::parse_pe;
+    use winapi::um::libloaderapi::GetModuleHandleA;
+
+    #[test]
+    fn test_parse_pe_remote_current_process() {
+        let base = unsafe { GetModuleHandleA(std::ptr::null()) } as usize;
+        let process = RemoteProcess::open(std::process::id()).expect("Failed to open current process");
+        let is_64bit = process.is_64bit().expect("Failed to query bitness");
+        assert_eq!(is_64bit, cfg!(target_pointer_width = "64"));
+
+        let remote = unsafe { parse_pe_remote(&process, base, is_64bit) }.expect("Failed to parse remote headers");
+        let local = unsafe { parse_pe(base) }.expect("Failed to parse local headers");
+        assert_eq!(remote, local);
+
+        assert_eq!(unsafe { parse_pe_remote(&process, base, !is_64bit) }, Err(PeParseError::BitnessMismatch));
+    }
+
+    #[test]
+    fn test_parse_pe_remote_null() {
+        let process = RemoteProcess::open(std::process::id()).expect("Failed to open current process");
+        assert_eq!(unsafe { parse_pe_remote(&process, 0, true) }, Err(PeParseError::NullPointer));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,143 @@
+use std::mem::size_of;
+
+use winapi::um::winnt::{
+    IMAGE_DOS_SIGNATURE, IMAGE_FILE_HEADER, IMAGE_NT_HEADERS32, IMAGE_NT_HEADERS64, IMAGE_NT_OPTIONAL_HDR32_MAGIC,
+    IMAGE_NT_OPTIONAL_HDR64_MAGIC, IMAGE_NT_SIGNATURE, IMAGE_OPTIONAL_HEADER32, IMAGE_OPTIONAL_HEADER64,
+    IMAGE_SECTION_HEADER,
+};
+
+/// The offset of the NT headers in the images built by [`TestImage`].
+pub(crate) const NT_OFFSET: usize = 0x40;
+
+/// Builds minimal PE images, in memory or file layout, for the tests of the PE parsing and
+/// scanning code.
+///
+/// Only the fields set through the builder are filled in, everything else is zero.
+pub(crate) struct TestImage {
+    len: usize,
+    is_64bit: bool,
+    image_base: u64,
+    size_of_image: u32,
+    size_of_headers: u32,
+    directories: Vec<(usize, u32, u32)>,
+    sections: Vec<IMAGE_SECTION_HEADER>,
+    data: Vec<(usize, Vec<u8>)>,
+}
+
+impl TestImage {
+    /// An image of `len` bytes with the headers of the host bitness.
+    pub(crate) fn new(len: usize) -> Self {
+        TestImage {
+            len,
+            is_64bit: cfg!(target_pointer_width = "64"),
+            image_base: 0,
+            size_of_image: 0,
+            size_of_headers: 0,
+            directories: Vec::new(),
+            sections: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+
+    /// Whether to write the PE32+ (64-bit) or the PE32 (32-bit) optional header.
+    pub(crate) fn is_64bit(mut self, is_64bit: bool) -> Self {
+        self.is_64bit = is_64bit;
+        self
+    }
+
+    pub(crate) fn image_base(mut self, image_base: u64) -> Self {
+        self.image_base = image_base;
+        self
+    }
+
+    pub(crate) fn size_of_image(mut self, size: u32) -> Self {
+        self.size_of_image = size;
+        self
+    }
+
+    pub(crate) fn size_of_headers(mut self, size: u32) -> Self {
+        self.size_of_headers = size;
+        self
+    }
+
+    /// Sets the data directory at `index`.
+    pub(crate) fn directory(mut self, index: usize, virtual_address: u32, size: u32) -> Self {
+        self.directories.push((index, virtual_address, size));
+        self
+    }
+
+    /// Adds a section header, with its raw data at `raw_offset` in the file.
+    pub(crate) fn section(mut self, name: &str, virtual_address: u32, raw_offset: usize, raw_size: usize) -> Self {
+        let mut section: IMAGE_SECTION_HEADER = unsafe { std::mem::zeroed() };
+        section.Name[..name.len()].copy_from_slice(name.as_bytes());
+        section.VirtualAddress = virtual_address;
+        section.PointerToRawData = raw_offset as u32;
+        section.SizeOfRawData = raw_size as u32;
+        self.sections.push(section);
+        self
+    }
+
+    /// Writes `bytes` at `offset` once the headers are in place.
+    pub(crate) fn put(mut self, offset: usize, bytes: &[u8]) -> Self {
+        self.data.push((offset, bytes.to_vec()));
+        self
+    }
+
+    pub(crate) fn build(&self) -> Vec<u8> {
+        let mut image = vec![0u8; self.len];
+        put(&mut image, 0, &IMAGE_DOS_SIGNATURE.to_le_bytes());
+        put(&mut image, 0x3C, &(NT_OFFSET as u32).to_le_bytes());
+
+        let optional_header_size = if self.is_64bit {
+            let mut nt: IMAGE_NT_HEADERS64 = unsafe { std::mem::zeroed() };
+            nt.Signature = IMAGE_NT_SIGNATURE;
+            nt.FileHeader.NumberOfSections = self.sections.len() as u16;
+            nt.FileHeader.SizeOfOptionalHeader = size_of::<IMAGE_OPTIONAL_HEADER64>() as u16;
+            nt.OptionalHeader.Magic = IMAGE_NT_OPTIONAL_HDR64_MAGIC;
+            nt.OptionalHeader.ImageBase = self.image_base;
+            nt.OptionalHeader.SizeOfImage = self.size_of_image;
+            nt.OptionalHeader.SizeOfHeaders = self.size_of_headers;
+            nt.OptionalHeader.NumberOfRvaAndSizes = 16;
+            for &(index, virtual_address, size) in &self.directories {
+                nt.OptionalHeader.DataDirectory[index].VirtualAddress = virtual_address;
+                nt.OptionalHeader.DataDirectory[index].Size = size;
+            }
+            put(&mut image, NT_OFFSET, as_bytes(&nt));
+            size_of::<IMAGE_OPTIONAL_HEADER64>()
+        } else {
+            let mut nt: IMAGE_NT_HEADERS32 = unsafe { std::mem::zeroed() };
+            nt.Signature = IMAGE_NT_SIGNATURE;
+            nt.FileHeader.NumberOfSections = self.sections.len() as u16;
+            nt.FileHeader.SizeOfOptionalHeader = size_of::<IMAGE_OPTIONAL_HEADER32>() as u16;
+            nt.OptionalHeader.Magic = IMAGE_NT_OPTIONAL_HDR32_MAGIC;
+            nt.OptionalHeader.ImageBase = self.image_base as u32;
+            nt.OptionalHeader.SizeOfImage = self.size_of_image;
+            nt.OptionalHeader.SizeOfHeaders = self.size_of_headers;
+            nt.OptionalHeader.NumberOfRvaAndSizes = 16;
+            for &(index, virtual_address, size) in &self.directories {
+                nt.OptionalHeader.DataDirectory[index].VirtualAddress = virtual_address;
+                nt.OptionalHeader.DataDirectory[index].Size = size;
+            }
+            put(&mut image, NT_OFFSET, as_bytes(&nt));
+            size_of::<IMAGE_OPTIONAL_HEADER32>()
+        };
+
+        let section_table = NT_OFFSET + 4 + size_of::<IMAGE_FILE_HEADER>() + optional_header_size;
+        for (index, section) in self.sections.iter().enumerate() {
+            put(&mut image, section_table + index * size_of::<IMAGE_SECTION_HEADER>(), as_bytes(section));
+        }
+
+        for (offset, bytes) in &self.data {
+            put(&mut image, *offset, bytes);
+        }
+        image
+    }
+}
+
+fn put(image: &mut [u8], offset: usize, bytes: &[u8]) {
+    image[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+fn as_bytes<T>(value: &T) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(value as *const T as *const u8, size_of::<T>()) }
+}
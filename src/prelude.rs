@@ -0,0 +1,38 @@
+//! The most commonly used items, for glob importing.
+//!
+//! ```rust
+//! use verity_memory::prelude::*;
+//!
+//! let mut value = 10i32;
+//!
+//! unsafe {
+//!     write_memory(&mut value as *mut i32, 20).unwrap();
+//!     assert_eq!(read_memory(&value as *const i32), Ok(20));
+//! }
+//! ```
+
+pub use crate::errors::ModuleError;
+pub use crate::errors::ReadMemoryError;
+pub use crate::errors::WriteMemoryError;
+#[cfg(feature = "aob")]
+pub use crate::errors::AobScanError;
+#[cfg(feature = "advanced-write")]
+pub use crate::errors::DetourError;
+#[cfg(feature = "pe")]
+pub use crate::errors::PeParseError;
+
+pub use crate::ops::read::read_bytes;
+pub use crate::ops::read::read_memory;
+pub use crate::ops::write::write_bytes;
+pub use crate::ops::write::write_memory;
+#[cfg(feature = "advanced-write")]
+pub use crate::ops::write::nop_instructions;
+
+#[cfg(feature = "aob")]
+pub use crate::pattern::aob::scan_all;
+#[cfg(feature = "aob")]
+pub use crate::pattern::aob::scan_unique;
+
+pub use crate::types::instruction::InstructionVecExt;
+pub use crate::types::Instruction;
+pub use crate::types::Protection;
\ No newline at end of file
@@ -0,0 +1,329 @@
+use std::cell::Cell;
+use std::sync::Mutex;
+
+use winapi::um::minwinbase::{EXCEPTION_BREAKPOINT, EXCEPTION_SINGLE_STEP};
+
+use crate::errors::BpError;
+use crate::ops::read::read_bytes;
+use crate::ops::write::write_bytes;
+use crate::runtime::context::Context;
+use crate::runtime::veh::{add_veh, remove_veh, ExceptionAction, ExceptionInfo, VehHandle};
+
+const INT3: u8 = 0xCC;
+const TRAP_FLAG: u32 = 0x100;
+
+struct Breakpoint {
+    address: usize,
+    original: u8,
+    handler: fn(&mut Context),
+}
+
+static BREAKPOINTS: Mutex<Vec<Breakpoint>> = Mutex::new(Vec::new());
+/// The exception handler shared by every breakpoint, or `None` when none is set.
+static HANDLER: Mutex<Option<VehHandle>> = Mutex::new(None);
+
+thread_local! {
+    /// The breakpoint whose original byte is restored while this thread single-steps over it.
+    static REARM: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// A software breakpoint set with [`set_software_breakpoint`].
+///
+/// Dropping it (or calling [`SwBreakpoint::remove`]) restores the original byte, and removes the
+/// vectored exception handler once no breakpoint is left.
+#[derive(Debug)]
+pub struct SwBreakpoint {
+    address: *mut u8,
+    removed: bool,
+}
+
+impl SwBreakpoint {
+    /// The address of the breakpoint.
+    pub fn address(&self) -> *mut u8 {
+        self.address
+    }
+
+    /// Removes the breakpoint now instead of on drop.
+    ///
+    /// # Errors
+    /// - `BpError::FailedToWrite`: If the original byte could not be restored.
+    pub fn remove(mut self) -> Result<(), BpError> {
+        self.remove_impl()
+    }
+
+    fn remove_impl(&mut self) -> Result<(), BpError> {
+        if self.removed {
+            return Ok(());
+        }
+
+        // The original byte is put back before the breakpoint is unregistered, under the same lock
+        // the handler re-arms under, so no thread can hit an `int3` that nothing handles anymore.
+        let address = self.address as usize;
+        let remaining = {
+            let mut breakpoints = BREAKPOINTS.lock().map_err(|_| BpError::FailedToWrite)?;
+            if let Some(index) = breakpoints.iter().position(|breakpoint| breakpoint.address == address) {
+                unsafe { write_bytes(self.address, &[breakpoints[index].original]) }
+                    .map_err(|_| BpError::FailedToWrite)?;
+                breakpoints.remove(index);
+            }
+            breakpoints.len()
+        };
+        self.removed = true;
+
+        if remaining == 0 {
+            release_handler();
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for SwBreakpoint {
+    fn drop(&mut self) {
+        let _ = self.remove_impl();
+    }
+}
+
+/// Sets an `int3` software breakpoint that calls `handler` whenever `address` is executed.
+///
+/// The original byte is saved and replaced with `0xCC`. A vectored exception handler, shared by
+/// all breakpoints, catches the resulting `EXCEPTION_BREAKPOINT` and calls `handler` with the
+/// registers of the thread, which it may modify:
+///
+/// - If the handler leaves the instruction pointer at `address`, the original instruction is
+///   executed: its byte is put back, the thread single-steps over it, and the `int3` is re-armed.
+/// - If the handler moves the instruction pointer, execution resumes there instead.
+///
+/// While a thread single-steps over the original instruction, other threads executing `address`
+/// don't hit the breakpoint.
+///
+/// # Safety
+/// This function is `unsafe` because it patches code.
+/// - `address` must be the first byte of an instruction, and the code must not be patched by
+///   anything else while the breakpoint is set.
+/// - The handler runs inside an exception handler: it must not panic or take locks that the
+///   interrupted thread may hold.
+///
+/// # Errors
+/// - `BpError::NullPointer`: If `address` is null.
+/// - `BpError::AlreadySet`: If a breakpoint is already set at `address`.
+/// - `BpError::FailedToRead`: If the original byte could not be read.
+/// - `BpError::FailedToInstallHandler`: If the vectored exception handler could not be added.
+/// - `BpError::FailedToWrite`: If the `int3` could not be written.
+///
+/// # Example
+/// ```rust,no_run
+/// use verity_memory::runtime::breakpoint::set_software_breakpoint;
+/// use verity_memory::runtime::Context;
+///
+/// fn on_hit(context: &mut Context) {
+///     println!("hit at {:#x}", context.ip());
+/// }
+///
+/// let target = 0x12345678 as *mut u8; // Replace with the actual address
+/// let breakpoint = unsafe { set_software_breakpoint(target, on_hit) }.unwrap();
+/// // ...
+/// drop(breakpoint);
+/// ```
+pub unsafe fn set_software_breakpoint(address: *mut u8, handler: fn(&mut Context)) -> Result<SwBreakpoint, BpError> {
+    if address.is_null() {
+        return Err(BpError::NullPointer);
+    }
+
+    let original = read_bytes(address, 1).map_err(|_| BpError::FailedToRead)?[0];
+
+    {
+        let mut breakpoints = BREAKPOINTS.lock().map_err(|_| BpError::FailedToInstallHandler)?;
+        if breakpoints.iter().any(|breakpoint| breakpoint.address == address as usize) {
+            return Err(BpError::AlreadySet);
+        }
+
+        acquire_handler()?;
+        breakpoints.push(Breakpoint {
+            address: address as usize,
+            original,
+            handler,
+        });
+    }
+
+    if write_bytes(address, &[INT3]).is_err() {
+        // The original byte is still in place, so the breakpoint only has to be unregistered.
+        let remaining = BREAKPOINTS.lock().map(|mut breakpoints| {
+            breakpoints.retain(|breakpoint| breakpoint.address != address as usize);
+            breakpoints.len()
+        });
+        if matches!(remaining, Ok(0)) {
+            release_handler();
+        }
+        return Err(BpError::FailedToWrite);
+    }
+
+    Ok(SwBreakpoint {
+        address,
+        removed: false,
+    })
+}
+
+fn acquire_handler() -> Result<(), BpError> {
+    let mut handler = HANDLER.lock().map_err(|_| BpError::FailedToInstallHandler)?;
+    if handler.is_none() {
+        *handler = Some(unsafe { add_veh(breakpoint_handler) }.map_err(|_| BpError::FailedToInstallHandler)?);
+    }
+
+    Ok(())
+}
+
+fn release_handler() {
+    if let Ok(mut handler) = HANDLER.lock() {
+        if let Some(handle) = handler.take() {
+            let _ = remove_veh(handle);
+        }
+    }
+}
+
+fn breakpoint_handler(info: &mut ExceptionInfo) -> ExceptionAction {
+    match info.code() {
+        EXCEPTION_BREAKPOINT => {
+            let address = info.address();
+            let hit = match BREAKPOINTS.lock() {
+                Ok(breakpoints) => breakpoints
+                    .iter()
+                    .find(|breakpoint| breakpoint.address == address)
+                    .map(|breakpoint| (breakpoint.original, breakpoint.handler)),
+                Err(_) => None,
+            };
+
+            let (original, handler) = match hit {
+                Some(hit) => hit,
+                None => return ExceptionAction::ContinueSearch,
+            };
+
+            let mut context = info.context();
+            context.set_ip(address);
+            handler(&mut context);
+
+            if context.ip() == address {
+                // Execute the original instruction, then re-arm the breakpoint on the single step.
+                // Resuming on the `int3` would only hit it again, so a failed restore is passed on.
+                if unsafe { write_bytes(address as *mut u8, &[original]) }.is_err() {
+                    return ExceptionAction::ContinueSearch;
+                }
+                context.set_flags(context.flags() | TRAP_FLAG);
+                REARM.with(|rearm| rearm.set(Some(address)));
+            }
+
+            info.set_context(&context);
+            ExceptionAction::ContinueExecution
+        }
+        EXCEPTION_SINGLE_STEP => match REARM.with(|rearm| rearm.take()) {
+            Some(address) => {
+                // Re-armed under the lock, so a breakpoint being removed is never re-armed after it
+                // restored its byte.
+                if let Ok(breakpoints) = BREAKPOINTS.lock() {
+                    if breakpoints.iter().any(|breakpoint| breakpoint.address == address) {
+                        let _ = unsafe { write_bytes(address as *mut u8, &[INT3]) };
+                    }
+                }
+                ExceptionAction::ContinueExecution
+            }
+            None => ExceptionAction::ContinueSearch,
+        },
+        _ => ExceptionAction::ContinueSearch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use winapi::um::memoryapi::{VirtualAlloc, VirtualFree};
+    use winapi::um::winnt::{MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_EXECUTE_READWRITE};
+
+    static HITS: AtomicUsize = AtomicUsize::new(0);
+    static REDIRECTS: AtomicUsize = AtomicUsize::new(0);
+
+    /// `mov eax, 42; ret` in an executable page.
+    fn alloc_code() -> *mut u8 {
+        unsafe {
+            let code = VirtualAlloc(std::ptr::null_mut(), 0x1000, MEM_COMMIT | MEM_RESERVE, PAGE_EXECUTE_READWRITE) as *mut u8;
+            assert!(!code.is_null());
+            std::ptr::copy_nonoverlapping([0xB8, 0x2A, 0x00, 0x00, 0x00, 0xC3].as_ptr(), code, 6);
+            code
+        }
+    }
+
+    fn free_code(code: *mut u8) {
+        unsafe { VirtualFree(code as _, 0, MEM_RELEASE) };
+    }
+
+    fn count_hit(_: &mut Context) {
+        HITS.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn skip_to_ret(context: &mut Context) {
+        REDIRECTS.fetch_add(1, Ordering::SeqCst);
+        #[cfg(target_arch = "x86_64")]
+        context.set_rax(7);
+        #[cfg(target_arch = "x86")]
+        context.set_eax(7);
+        context.set_ip(context.ip() + 5);
+    }
+
+    #[test]
+    fn test_software_breakpoint_restores_original_byte() {
+        let code = alloc_code();
+
+        let breakpoint = unsafe { set_software_breakpoint(code, count_hit) }.expect("Failed to set breakpoint");
+        assert_eq!(breakpoint.address(), code);
+        assert_eq!(unsafe { *code }, INT3);
+        assert_eq!(unsafe { set_software_breakpoint(code, count_hit) }.unwrap_err(), BpError::AlreadySet);
+
+        drop(breakpoint);
+        assert_eq!(unsafe { *code }, 0xB8);
+
+        let breakpoint = unsafe { set_software_breakpoint(code, count_hit) }.expect("Failed to set breakpoint again");
+        assert_eq!(breakpoint.remove(), Ok(()));
+        assert_eq!(unsafe { *code }, 0xB8);
+
+        free_code(code);
+    }
+
+    #[test]
+    fn test_software_breakpoint_hit_and_rearm() {
+        let code = alloc_code();
+        let function: extern "C" fn() -> u32 = unsafe { std::mem::transmute(code) };
+
+        let breakpoint = unsafe { set_software_breakpoint(code, count_hit) }.expect("Failed to set breakpoint");
+        let before = HITS.load(Ordering::SeqCst);
+
+        assert_eq!(function(), 42);
+        assert_eq!(unsafe { *code }, INT3);
+        assert_eq!(function(), 42);
+        assert_eq!(HITS.load(Ordering::SeqCst), before + 2);
+
+        drop(breakpoint);
+        free_code(code);
+    }
+
+    #[test]
+    fn test_software_breakpoint_redirect() {
+        let code = alloc_code();
+        let function: extern "C" fn() -> u32 = unsafe { std::mem::transmute(code) };
+
+        let breakpoint = unsafe { set_software_breakpoint(code, skip_to_ret) }.expect("Failed to set breakpoint");
+
+        assert_eq!(function(), 7);
+        assert_eq!(REDIRECTS.load(Ordering::SeqCst), 1);
+        assert_eq!(unsafe { *code }, INT3);
+
+        drop(breakpoint);
+        assert_eq!(function(), 42);
+        free_code(code);
+    }
+
+    #[test]
+    fn test_software_breakpoint_null() {
+        let result = unsafe { set_software_breakpoint(std::ptr::null_mut(), count_hit) };
+        assert_eq!(result.unwrap_err(), BpError::NullPointer);
+    }
+}
\ No newline at end of file
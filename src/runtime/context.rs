@@ -0,0 +1,309 @@
+use winapi::um::processthreadsapi::{GetThreadContext, SetThreadContext};
+use winapi::um::winnt::{CONTEXT, CONTEXT_DEBUG_REGISTERS, CONTEXT_FULL, HANDLE};
+
+use crate::errors::ContextError;
+
+/// The register state of a thread.
+///
+/// Wraps the Win32 `CONTEXT` structure, which must be 16-byte aligned on x64, with accessors for
+/// the general purpose and debug registers of the current architecture. The instruction and stack
+/// pointers are also available under architecture-neutral names, see [`Context::ip`] and
+/// [`Context::sp`].
+#[derive(Clone, Copy)]
+pub struct Context {
+    raw: CONTEXT,
+}
+
+macro_rules! registers {
+    ($ty:ty; $($getter:ident, $setter:ident => $field:ident;)*) => {
+        $(
+            #[doc = concat!("The value of `", stringify!($getter), "`.")]
+            pub fn $getter(&self) -> $ty {
+                self.raw.$field
+            }
+
+            #[doc = concat!("Sets the value of `", stringify!($getter), "`.")]
+            pub fn $setter(&mut self, value: $ty) {
+                self.raw.$field = value;
+            }
+        )*
+    };
+}
+
+impl Context {
+    /// Wraps a raw `CONTEXT`, e.g. the one passed to an exception handler.
+    pub fn from_raw(raw: CONTEXT) -> Context {
+        Context { raw }
+    }
+
+    /// Returns the raw `CONTEXT`.
+    pub fn raw(&self) -> &CONTEXT {
+        &self.raw
+    }
+
+    /// Returns the raw `CONTEXT` mutably, for registers without an accessor.
+    pub fn raw_mut(&mut self) -> &mut CONTEXT {
+        &mut self.raw
+    }
+
+    /// The instruction pointer (`rip` or `eip`).
+    #[cfg(target_arch = "x86_64")]
+    pub fn ip(&self) -> usize {
+        self.raw.Rip as usize
+    }
+
+    /// The instruction pointer (`rip` or `eip`).
+    #[cfg(target_arch = "x86")]
+    pub fn ip(&self) -> usize {
+        self.raw.Eip as usize
+    }
+
+    /// Sets the instruction pointer (`rip` or `eip`).
+    #[cfg(target_arch = "x86_64")]
+    pub fn set_ip(&mut self, value: usize) {
+        self.raw.Rip = value as u64;
+    }
+
+    /// Sets the instruction pointer (`rip` or `eip`).
+    #[cfg(target_arch = "x86")]
+    pub fn set_ip(&mut self, value: usize) {
+        self.raw.Eip = value as u32;
+    }
+
+    /// The stack pointer (`rsp` or `esp`).
+    #[cfg(target_arch = "x86_64")]
+    pub fn sp(&self) -> usize {
+        self.raw.Rsp as usize
+    }
+
+    /// The stack pointer (`rsp` or `esp`).
+    #[cfg(target_arch = "x86")]
+    pub fn sp(&self) -> usize {
+        self.raw.Esp as usize
+    }
+
+    /// Sets the stack pointer (`rsp` or `esp`).
+    #[cfg(target_arch = "x86_64")]
+    pub fn set_sp(&mut self, value: usize) {
+        self.raw.Rsp = value as u64;
+    }
+
+    /// Sets the stack pointer (`rsp` or `esp`).
+    #[cfg(target_arch = "x86")]
+    pub fn set_sp(&mut self, value: usize) {
+        self.raw.Esp = value as u32;
+    }
+
+    /// The flags register.
+    pub fn flags(&self) -> u32 {
+        self.raw.EFlags
+    }
+
+    /// Sets the flags register.
+    pub fn set_flags(&mut self, value: u32) {
+        self.raw.EFlags = value;
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    registers! { u64;
+        rip, set_rip => Rip;
+        rsp, set_rsp => Rsp;
+        rbp, set_rbp => Rbp;
+        rax, set_rax => Rax;
+        rbx, set_rbx => Rbx;
+        rcx, set_rcx => Rcx;
+        rdx, set_rdx => Rdx;
+        rsi, set_rsi => Rsi;
+        rdi, set_rdi => Rdi;
+        r8, set_r8 => R8;
+        r9, set_r9 => R9;
+        r10, set_r10 => R10;
+        r11, set_r11 => R11;
+        r12, set_r12 => R12;
+        r13, set_r13 => R13;
+        r14, set_r14 => R14;
+        r15, set_r15 => R15;
+        dr0, set_dr0 => Dr0;
+        dr1, set_dr1 => Dr1;
+        dr2, set_dr2 => Dr2;
+        dr3, set_dr3 => Dr3;
+        dr6, set_dr6 => Dr6;
+        dr7, set_dr7 => Dr7;
+    }
+
+    #[cfg(target_arch = "x86")]
+    registers! { u32;
+        eip, set_eip => Eip;
+        esp, set_esp => Esp;
+        ebp, set_ebp => Ebp;
+        eax, set_eax => Eax;
+        ebx, set_ebx => Ebx;
+        ecx, set_ecx => Ecx;
+        edx, set_edx => Edx;
+        esi, set_esi => Esi;
+        edi, set_edi => Edi;
+        dr0, set_dr0 => Dr0;
+        dr1, set_dr1 => Dr1;
+        dr2, set_dr2 => Dr2;
+        dr3, set_dr3 => Dr3;
+        dr6, set_dr6 => Dr6;
+        dr7, set_dr7 => Dr7;
+    }
+}
+
+impl std::fmt::Debug for Context {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Context")
+            .field("ip", &format_args!("{:#x}", self.ip()))
+            .field("sp", &format_args!("{:#x}", self.sp()))
+            .field("flags", &format_args!("{:#x}", self.flags()))
+            .finish()
+    }
+}
+
+/// Captures the registers of a thread with `GetThreadContext`.
+///
+/// The general purpose, control, segment and debug registers are captured. The thread should be
+/// suspended, otherwise the values are stale by the time they are returned; the current thread
+/// may be passed, in which case the context reflects the call to `GetThreadContext`.
+///
+/// # Parameters
+/// - `thread`: A handle to the thread with `THREAD_GET_CONTEXT` access.
+///
+/// # Errors
+/// - `ContextError::NullHandle`: If `thread` is null.
+/// - `ContextError::FailedToGetContext`: If `GetThreadContext` fails, e.g. the handle lacks access.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::runtime::context::get_thread_context;
+/// use winapi::um::processthreadsapi::GetCurrentThread;
+///
+/// let context = get_thread_context(unsafe { GetCurrentThread() }).unwrap();
+/// assert_ne!(context.ip(), 0);
+/// ```
+pub fn get_thread_context(thread: HANDLE) -> Result<Context, ContextError> {
+    if thread.is_null() {
+        return Err(ContextError::NullHandle);
+    }
+
+    let mut raw: CONTEXT = unsafe { std::mem::zeroed() };
+    raw.ContextFlags = CONTEXT_FULL | CONTEXT_DEBUG_REGISTERS;
+
+    if unsafe { GetThreadContext(thread, &mut raw) } == 0 {
+        return Err(ContextError::FailedToGetContext);
+    }
+
+    Ok(Context { raw })
+}
+
+/// Applies registers to a thread with `SetThreadContext`.
+///
+/// The registers covered by [`get_thread_context`] are written, so the usual pattern is to
+/// suspend the thread, get its context, modify it and set it back before resuming it.
+///
+/// # Safety
+/// Changing the registers of a thread, e.g. its instruction or stack pointer, can make it crash or
+/// corrupt its state. The thread should be suspended and must not be the current thread.
+///
+/// # Errors
+/// - `ContextError::NullHandle`: If `thread` is null.
+/// - `ContextError::FailedToSetContext`: If `SetThreadContext` fails, e.g. the handle lacks
+///   `THREAD_SET_CONTEXT` access.
+pub unsafe fn set_thread_context(thread: HANDLE, context: &Context) -> Result<(), ContextError> {
+    if thread.is_null() {
+        return Err(ContextError::NullHandle);
+    }
+
+    let mut raw = context.raw;
+    raw.ContextFlags = CONTEXT_FULL | CONTEXT_DEBUG_REGISTERS;
+
+    if SetThreadContext(thread, &raw) == 0 {
+        return Err(ContextError::FailedToSetContext);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use winapi::um::processthreadsapi::{GetCurrentThread, ResumeThread, SuspendThread};
+
+    #[test]
+    fn test_get_thread_context_current() {
+        let context = get_thread_context(unsafe { GetCurrentThread() }).expect("Failed to get context");
+        let local = 0u8;
+
+        assert_ne!(context.ip(), 0);
+        assert_ne!(context.sp(), 0);
+        // The captured stack pointer is below this frame.
+        assert!(context.sp() < &local as *const u8 as usize + 0x1_0000);
+    }
+
+    #[test]
+    fn test_thread_context_null_handle() {
+        assert_eq!(get_thread_context(std::ptr::null_mut()).unwrap_err(), ContextError::NullHandle);
+
+        let context = get_thread_context(unsafe { GetCurrentThread() }).unwrap();
+        assert_eq!(unsafe { set_thread_context(std::ptr::null_mut(), &context) }, Err(ContextError::NullHandle));
+    }
+
+    #[test]
+    fn test_context_registers() {
+        let mut context = get_thread_context(unsafe { GetCurrentThread() }).unwrap();
+
+        context.set_ip(0x1234);
+        context.set_sp(0x5678);
+        assert_eq!(context.ip(), 0x1234);
+        assert_eq!(context.sp(), 0x5678);
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            context.set_rax(0xDEAD);
+            assert_eq!(context.rax(), 0xDEAD);
+            assert_eq!(context.rip(), 0x1234);
+        }
+        #[cfg(target_arch = "x86")]
+        {
+            context.set_eax(0xDEAD);
+            assert_eq!(context.eax(), 0xDEAD);
+            assert_eq!(context.eip(), 0x1234);
+        }
+    }
+
+    #[test]
+    fn test_set_thread_context_roundtrip() {
+        use std::os::windows::io::AsRawHandle;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let worker = {
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    std::hint::spin_loop();
+                }
+            })
+        };
+        let handle = worker.as_raw_handle() as HANDLE;
+
+        unsafe {
+            assert_ne!(SuspendThread(handle), u32::MAX);
+
+            let mut context = get_thread_context(handle).expect("Failed to get context");
+            let dr0 = context.dr0();
+            context.set_dr0(0);
+            assert_eq!(set_thread_context(handle, &context), Ok(()));
+            assert_eq!(get_thread_context(handle).unwrap().dr0(), 0);
+            context.set_dr0(dr0);
+            assert_eq!(set_thread_context(handle, &context), Ok(()));
+
+            ResumeThread(handle);
+        }
+
+        stop.store(true, Ordering::Relaxed);
+        worker.join().unwrap();
+    }
+}
\ No newline at end of file
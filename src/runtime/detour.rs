@@ -0,0 +1,684 @@
+use std::mem::size_of;
+use std::sync::{Arc, Mutex};
+
+use winapi::shared::minwindef::LPVOID;
+use winapi::um::memoryapi::{VirtualAlloc, VirtualFree};
+use winapi::um::minwinbase::EXCEPTION_BREAKPOINT;
+use winapi::um::winnt::{MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_EXECUTE_READWRITE};
+
+use crate::errors::DetourError;
+use crate::ops::asm::{is_terminator, jump, relocate, steal_instructions};
+#[cfg(target_arch = "x86_64")]
+use crate::ops::query::query;
+use crate::ops::read::read_bytes;
+use crate::ops::write::write_bytes;
+use crate::runtime::registry::HookRegistry;
+use crate::runtime::veh::{add_veh, remove_veh, ExceptionAction, ExceptionInfo, VehHandle};
+use crate::types::{Filler, Instruction};
+
+const TRAMPOLINE_SIZE: usize = 0x1000;
+const SHORT_JUMP_SIZE: usize = 2;
+const MAX_JUMP_SIZE: usize = 14;
+const CAVE_FILL: u8 = 0xCC;
+
+static BREAKPOINT_HOOKS: Mutex<Vec<(usize, usize)>> = Mutex::new(Vec::new());
+/// The exception handler shared by every breakpoint hook, or `None` when none is installed.
+static BREAKPOINT_HANDLER: Mutex<Option<VehHandle>> = Mutex::new(None);
+
+/// How a [`Detour`] redirects its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetourKind {
+    /// A jump to the replacement written over the start of the target.
+    Jump,
+    /// A 2-byte short jump to a code cave in the `int3` padding next to the target, which holds
+    /// the jump to the replacement. Used for functions too small for a full jump.
+    ShortJump,
+    /// An `int3` handled by a vectored exception handler, which redirects execution to the
+    /// replacement. Used when the target is too small for a short jump or no code cave is in range.
+    Breakpoint,
+}
+
+/// An inline hook redirecting a function to a replacement.
+///
+/// The first whole instructions of the target are overwritten with a jump to the replacement and
+/// copied into a trampoline, which executes them and jumps back into the target. Calling the
+/// trampoline therefore behaves like calling the unhooked function.
+///
+/// Functions smaller than a jump are hooked with a short jump to a nearby code cave, or failing
+/// that with a breakpoint; see [`DetourKind`].
+///
+/// The hook stays installed until [`Detour::remove`] or [`HookRegistry::remove_all`] is called;
+/// dropping a `Detour` does not restore the target. The trampoline is shared between the handle and
+/// the registry and is only freed once both let go of it, so a handle to a hook removed by
+/// [`HookRegistry::remove_all`] can still call the original function.
+pub struct Detour {
+    target: *mut u8,
+    trampoline: Arc<Trampoline>,
+    originals: Vec<Instruction>,
+    kind: DetourKind,
+    id: usize,
+}
+
+impl Detour {
+    /// Installs an inline hook redirecting `target` to `detour`.
+    ///
+    /// If the target function ends before a full jump fits, a short jump into a code cave of
+    /// `int3` padding within 127 bytes of the target is used instead. If that isn't possible either,
+    /// the first byte is replaced with an `int3` and a vectored exception handler redirects the
+    /// breakpoint to the replacement, which is slower but fits any function.
+    ///
+    /// # Safety
+    /// This function is unsafe because it rewrites executable code.
+    /// - `target` must point to the start of a function.
+    /// - `detour` must be a function with the same signature and calling convention as `target`.
+    /// - No thread may be executing the first instructions of `target` while the hook is written.
+    ///
+    /// # Parameters
+    /// - `target`: A pointer to the first instruction of the function to hook.
+    /// - `detour`: A pointer to the replacement function.
+    ///
+    /// # Returns
+    /// - `Ok(Detour)`: The installed hook.
+    /// - `Err(DetourError)`: If the hook could not be installed.
+    ///
+    /// # Errors
+    /// - `DetourError::NullPointer`: If `target` or `detour` is null.
+    /// - `DetourError::InvalidInstruction`: If the target instructions could not be disassembled.
+    /// - `DetourError::FailedToAllocate`: If the trampoline could not be allocated.
+    /// - `DetourError::FailedToWrite`: If the jump could not be written to the target.
+    /// - `DetourError::TargetTooSmall`: If the target is too small for every strategy.
+    pub unsafe fn install(target: *mut u8, detour: *const u8) -> Result<Detour, DetourError> {
+        if target.is_null() || detour.is_null() {
+            return Err(DetourError::NullPointer);
+        }
+
+        let patch = jump(target as usize, detour as usize);
+        let available = available_bytes(target, patch.len())?;
+
+        if available >= patch.len() {
+            return install_patch(target, patch, DetourKind::Jump, None);
+        }
+
+        if available >= SHORT_JUMP_SIZE {
+            if let Some((cave, cave_patch)) = find_cave(target, available, detour) {
+                let original_cave =
+                    read_bytes(cave, cave_patch.len()).map_err(|_| DetourError::FailedToWrite)?;
+                write_bytes(cave, &cave_patch).map_err(|_| DetourError::FailedToWrite)?;
+
+                let cave = Instruction::new(cave, original_cave);
+                let rel8 = (cave.address as isize - (target as isize + SHORT_JUMP_SIZE as isize)) as i8;
+
+                return install_patch(target, vec![0xEB, rel8 as u8], DetourKind::ShortJump, Some(cave.clone()))
+                    .inspect_err(|_| {
+                        let _ = write_bytes(cave.address, &cave.bytes);
+                    });
+            }
+        }
+
+        if available >= 1 && register_breakpoint(target as usize, detour as usize) {
+            return install_patch(target, vec![0xCC], DetourKind::Breakpoint, None)
+                .inspect_err(|_| unregister_breakpoint(target as usize));
+        }
+
+        Err(DetourError::TargetTooSmall)
+    }
+
+    /// Restores the original instructions of the target and frees the trampoline.
+    ///
+    /// If the hook was already removed by [`HookRegistry::remove_all`], only the trampoline is freed.
+    ///
+    /// # Safety
+    /// No thread may be executing the patched instructions or the trampoline while the hook is removed.
+    ///
+    /// # Errors
+    /// - `DetourError::FailedToWrite`: If the original bytes could not be written back.
+    /// - `DetourError::FailedToFree`: If the trampoline could not be released.
+    pub unsafe fn remove(self) -> Result<(), DetourError> {
+        let restored = match HookRegistry::take(self.id) {
+            Some(restore) => restore(),
+            None => Ok(()),
+        };
+        let freed = Trampoline::release(self.trampoline);
+
+        restored.and(freed)
+    }
+
+    /// Returns whether the hook is still installed, i.e. was not removed by
+    /// [`HookRegistry::remove_all`].
+    pub fn is_installed(&self) -> bool {
+        HookRegistry::contains(self.id)
+    }
+
+    /// Returns how the target is redirected.
+    pub fn kind(&self) -> DetourKind {
+        self.kind
+    }
+
+    /// Returns the hooked function.
+    pub fn target(&self) -> *mut u8 {
+        self.target
+    }
+
+    /// Returns the trampoline, which behaves like the unhooked target when called.
+    pub fn trampoline(&self) -> *const u8 {
+        self.trampoline.0 as *const u8
+    }
+
+    /// Returns the original instructions overwritten by the hook.
+    pub fn originals(&self) -> &[Instruction] {
+        &self.originals
+    }
+
+    /// Returns the trampoline as a typed function pointer.
+    ///
+    /// # Safety
+    /// `F` must be a function pointer type matching the signature and calling convention of the target.
+    ///
+    /// # Panics
+    /// - If `F` is not pointer-sized, since it then cannot be a function pointer.
+    pub unsafe fn original<F: Copy>(&self) -> F {
+        assert_eq!(
+            size_of::<F>(),
+            size_of::<*const u8>(),
+            "Original function type must be a function pointer"
+        );
+
+        std::mem::transmute_copy(&self.trampoline())
+    }
+
+    /// Calls the unhooked target through the trampoline.
+    ///
+    /// The closure receives the trampoline as a typed function pointer `F` and can invoke it with
+    /// any arguments, so callers never have to transmute the trampoline themselves.
+    ///
+    /// # Safety
+    /// `F` must be a function pointer type matching the signature and calling convention of the target.
+    ///
+    /// # Panics
+    /// - If `F` is not pointer-sized, since it then cannot be a function pointer.
+    ///
+    /// # Example
+    /// ```rust
+    /// use verity_memory::runtime::detour::Detour;
+    ///
+    /// #[inline(never)]
+    /// extern "C" fn add(a: i32, b: i32) -> i32 {
+    ///     std::hint::black_box(a) + std::hint::black_box(b)
+    /// }
+    ///
+    /// #[inline(never)]
+    /// extern "C" fn sub(a: i32, b: i32) -> i32 {
+    ///     std::hint::black_box(a) - std::hint::black_box(b)
+    /// }
+    ///
+    /// unsafe {
+    ///     let detour = Detour::install(add as *mut u8, sub as *const u8).unwrap();
+    ///
+    ///     let hooked = std::hint::black_box(add as extern "C" fn(i32, i32) -> i32);
+    ///     assert_eq!(hooked(5, 3), 2);
+    ///
+    ///     let original = detour.call_original(|add: extern "C" fn(i32, i32) -> i32| add(5, 3));
+    ///     assert_eq!(original, 8);
+    ///
+    ///     detour.remove().unwrap();
+    /// }
+    /// ```
+    pub unsafe fn call_original<F: Copy, R>(&self, call: impl FnOnce(F) -> R) -> R {
+        call(self.original::<F>())
+    }
+}
+
+unsafe fn install_patch(
+    target: *mut u8,
+    mut patch: Vec<u8>,
+    kind: DetourKind,
+    cave: Option<Instruction>,
+) -> Result<Detour, DetourError> {
+    let originals =
+        steal_instructions(target, patch.len()).ok_or(DetourError::InvalidInstruction)?;
+    let stolen_size: usize = originals.iter().map(|instr| instr.size).sum();
+
+    let trampoline = alloc_trampoline(target as usize);
+    if trampoline.is_null() {
+        return Err(DetourError::FailedToAllocate);
+    }
+    let trampoline = Arc::new(Trampoline(trampoline as usize));
+
+    let mut code = Vec::new();
+    for instruction in &originals {
+        code.extend(
+            relocate(instruction, trampoline.0 + code.len()).ok_or(DetourError::InvalidInstruction)?,
+        );
+    }
+    code.extend(jump(trampoline.0 + code.len(), target as usize + stolen_size));
+    std::ptr::copy_nonoverlapping(code.as_ptr(), trampoline.0 as *mut u8, code.len());
+
+    patch.extend(Filler::Nop.bytes(stolen_size - patch.len()));
+    if write_bytes(target, &patch).is_err() {
+        return Err(DetourError::FailedToWrite);
+    }
+
+    let original_bytes: Vec<u8> = originals
+        .iter()
+        .flat_map(|instr| instr.bytes.iter().copied())
+        .collect();
+    let original_cave = cave.map(|cave| (cave.address as usize, cave.bytes));
+    let target_address = target as usize;
+    let shared = Arc::clone(&trampoline);
+
+    let id = HookRegistry::register(move || unsafe {
+        let restored = restore(target_address, &original_bytes, original_cave, kind);
+        restored.and(Trampoline::release(shared))
+    });
+
+    Ok(Detour {
+        target,
+        trampoline,
+        originals,
+        kind,
+        id,
+    })
+}
+
+/// The executable memory holding the relocated instructions of a hook.
+///
+/// Shared by the [`Detour`] and its registry entry, and freed by whichever of them lets go of it
+/// last, so neither can leave the other with a dangling trampoline.
+struct Trampoline(usize);
+
+impl Trampoline {
+    /// Drops one reference to the trampoline, freeing it if it was the last one.
+    ///
+    /// # Errors
+    /// - `DetourError::FailedToFree`: If the trampoline was the last reference and could not be released.
+    unsafe fn release(trampoline: Arc<Trampoline>) -> Result<(), DetourError> {
+        let Ok(trampoline) = Arc::try_unwrap(trampoline) else {
+            return Ok(());
+        };
+
+        let address = trampoline.0;
+        std::mem::forget(trampoline);
+        if VirtualFree(address as LPVOID, 0, MEM_RELEASE) == 0 {
+            return Err(DetourError::FailedToFree);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Trampoline {
+    fn drop(&mut self) {
+        if self.0 != 0 {
+            unsafe { VirtualFree(self.0 as LPVOID, 0, MEM_RELEASE) };
+        }
+    }
+}
+
+/// Allocates a trampoline, within 2 GiB of `target` when possible so that RIP-relative operands
+/// of the stolen instructions can still reach their memory from it.
+unsafe fn alloc_trampoline(target: usize) -> *mut u8 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        let near = alloc_near(target);
+        if !near.is_null() {
+            return near;
+        }
+    }
+
+    #[cfg(target_arch = "x86")]
+    let _ = target;
+
+    VirtualAlloc(
+        std::ptr::null_mut(),
+        TRAMPOLINE_SIZE,
+        MEM_COMMIT | MEM_RESERVE,
+        PAGE_EXECUTE_READWRITE,
+    ) as *mut u8
+}
+
+/// Walks the free regions below and then above `target`, within 2 GiB, and allocates a trampoline
+/// in the first one that accepts it.
+#[cfg(target_arch = "x86_64")]
+unsafe fn alloc_near(target: usize) -> *mut u8 {
+    use winapi::um::winnt::MEM_FREE;
+
+    const GRANULARITY: usize = 0x10000;
+    const RANGE: usize = 0x7FFF_0000;
+
+    let align_up = |address: usize| address.saturating_add(GRANULARITY - 1) & !(GRANULARITY - 1);
+    let lowest = align_up(target.saturating_sub(RANGE).max(GRANULARITY));
+    let highest = target.saturating_add(RANGE - TRAMPOLINE_SIZE);
+
+    let try_alloc = |address: usize| {
+        VirtualAlloc(address as LPVOID, TRAMPOLINE_SIZE, MEM_COMMIT | MEM_RESERVE, PAGE_EXECUTE_READWRITE) as *mut u8
+    };
+
+    let mut address = target & !(GRANULARITY - 1);
+    while address >= lowest {
+        let info = match query(address as *const u8) {
+            Some(info) => info,
+            None => break,
+        };
+        if info.State == MEM_FREE {
+            let trampoline = try_alloc(address);
+            if !trampoline.is_null() {
+                return trampoline;
+            }
+        }
+
+        // Continue below the region containing `address`.
+        let below = (info.BaseAddress as usize).min(address).saturating_sub(1) & !(GRANULARITY - 1);
+        if below >= address {
+            break;
+        }
+        address = below;
+    }
+
+    let mut address = align_up(target);
+    while address <= highest {
+        let info = match query(address as *const u8) {
+            Some(info) => info,
+            None => break,
+        };
+        if info.State == MEM_FREE {
+            let trampoline = try_alloc(address);
+            if !trampoline.is_null() {
+                return trampoline;
+            }
+        }
+
+        let above = align_up((info.BaseAddress as usize).saturating_add(info.RegionSize));
+        if above <= address {
+            break;
+        }
+        address = above;
+    }
+
+    std::ptr::null_mut()
+}
+
+unsafe fn restore(
+    target: usize,
+    original_bytes: &[u8],
+    cave: Option<(usize, Vec<u8>)>,
+    kind: DetourKind,
+) -> Result<(), DetourError> {
+    write_bytes(target as *mut u8, original_bytes).map_err(|_| DetourError::FailedToWrite)?;
+
+    if let Some((address, bytes)) = cave {
+        write_bytes(address as *mut u8, &bytes).map_err(|_| DetourError::FailedToWrite)?;
+    }
+
+    if kind == DetourKind::Breakpoint {
+        unregister_breakpoint(target);
+    }
+
+    Ok(())
+}
+
+/// Returns how many bytes of the target can be overwritten, up to `needed`: the size of the
+/// function if it returns or jumps away before `needed` bytes.
+unsafe fn available_bytes(target: *mut u8, needed: usize) -> Result<usize, DetourError> {
+    let instructions = steal_instructions(target, needed).ok_or(DetourError::InvalidInstruction)?;
+
+    let mut size = 0;
+    for instruction in &instructions {
+        size += instruction.size;
+        if is_terminator(instruction) {
+            return Ok(size.min(needed));
+        }
+    }
+
+    Ok(size)
+}
+
+/// Finds a run of `int3` padding within short jump range of `target` large enough for a jump to
+/// `detour`, searching after the end of the function first.
+unsafe fn find_cave(
+    target: *mut u8,
+    function_len: usize,
+    detour: *const u8,
+) -> Option<(*mut u8, Vec<u8>)> {
+    let origin = target as usize + SHORT_JUMP_SIZE;
+    let after = (target as usize + function_len, origin + i8::MAX as usize, usize::MAX);
+    let before = (
+        origin.saturating_sub(i8::MIN.unsigned_abs() as usize),
+        target as usize,
+        target as usize,
+    );
+
+    for (first, last, end) in [after, before] {
+        if first > last {
+            continue;
+        }
+
+        let bytes = match read_bytes(first as *const u8, last - first + MAX_JUMP_SIZE) {
+            Ok(bytes) => bytes,
+            Err(_) => continue,
+        };
+
+        for start in first..=last {
+            let cave_patch = jump(start, detour as usize);
+            if start + cave_patch.len() > end {
+                break;
+            }
+
+            let offset = start - first;
+            if bytes[offset..offset + cave_patch.len()].iter().all(|&byte| byte == CAVE_FILL) {
+                return Some((start as *mut u8, cave_patch));
+            }
+        }
+    }
+
+    None
+}
+
+fn register_breakpoint(target: usize, detour: usize) -> bool {
+    let mut hooks = match BREAKPOINT_HOOKS.lock() {
+        Ok(hooks) => hooks,
+        Err(_) => return false,
+    };
+
+    let mut handler = match BREAKPOINT_HANDLER.lock() {
+        Ok(handler) => handler,
+        Err(_) => return false,
+    };
+    if handler.is_none() {
+        match unsafe { add_veh(breakpoint_handler) } {
+            Ok(handle) => *handler = Some(handle),
+            Err(_) => return false,
+        }
+    }
+
+    hooks.push((target, detour));
+    true
+}
+
+fn unregister_breakpoint(target: usize) {
+    // The handler is released under the hooks lock, so a hook registered concurrently can't be
+    // left without it.
+    if let Ok(mut hooks) = BREAKPOINT_HOOKS.lock() {
+        hooks.retain(|(address, _)| *address != target);
+        if hooks.is_empty() {
+            if let Some(handle) = BREAKPOINT_HANDLER.lock().ok().and_then(|mut handler| handler.take()) {
+                let _ = remove_veh(handle);
+            }
+        }
+    }
+}
+
+fn breakpoint_handler(info: &mut ExceptionInfo) -> ExceptionAction {
+    if info.code() != EXCEPTION_BREAKPOINT {
+        return ExceptionAction::ContinueSearch;
+    }
+
+    let address = info.address();
+    let detour = match BREAKPOINT_HOOKS.lock() {
+        Ok(hooks) => hooks
+            .iter()
+            .find(|(target, _)| *target == address)
+            .map(|(_, detour)| *detour),
+        Err(_) => None,
+    };
+
+    match detour {
+        Some(detour) => {
+            let mut context = info.context();
+            context.set_ip(detour);
+            info.set_context(&context);
+            ExceptionAction::ContinueExecution
+        }
+        None => ExceptionAction::ContinueSearch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::registry::HOOK_TEST_LOCK;
+    use std::hint::black_box;
+
+    #[inline(never)]
+    extern "C" fn target_fn(value: i32) -> i32 {
+        let mut total = black_box(value);
+        for i in 0..black_box(3) {
+            total = total.wrapping_mul(3).wrapping_add(i);
+        }
+        total
+    }
+
+    #[inline(never)]
+    extern "C" fn replacement_fn(value: i32) -> i32 {
+        black_box(value).wrapping_neg()
+    }
+
+    #[test]
+    fn test_detour_call_original() {
+        let _guard = HOOK_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let call_target = || black_box(target_fn as extern "C" fn(i32) -> i32)(7);
+        let expected = call_target();
+
+        unsafe {
+            let detour = Detour::install(target_fn as *mut u8, replacement_fn as *const u8)
+                .expect("Failed to install detour");
+
+            assert_eq!(call_target(), -7);
+
+            let original = detour.call_original(|original: extern "C" fn(i32) -> i32| original(7));
+            assert_eq!(original, expected);
+
+            detour.remove().expect("Failed to remove detour");
+        }
+
+        assert_eq!(call_target(), expected);
+    }
+
+    #[test]
+    fn test_detour_null_pointer() {
+        let result = unsafe { Detour::install(std::ptr::null_mut(), replacement_fn as *const u8) };
+        assert!(matches!(result, Err(DetourError::NullPointer)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Original function type must be a function pointer")]
+    fn test_detour_original_size_check() {
+        let detour = Detour {
+            target: std::ptr::null_mut(),
+            trampoline: Arc::new(Trampoline(0)),
+            originals: Vec::new(),
+            kind: DetourKind::Jump,
+            id: 0,
+        };
+
+        unsafe {
+            detour.original::<[usize; 2]>();
+        }
+    }
+
+    /// Allocates a page holding `lea eax, [rcx + 1]; ret`, a 4-byte `extern "C" fn(i32) -> i32`,
+    /// followed by `padding`.
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn tiny_function(padding: u8) -> *mut u8 {
+        let page = VirtualAlloc(
+            std::ptr::null_mut(),
+            0x1000,
+            MEM_COMMIT | MEM_RESERVE,
+            PAGE_EXECUTE_READWRITE,
+        ) as *mut u8;
+        assert!(!page.is_null());
+
+        std::ptr::write_bytes(page, padding, 0x1000);
+        std::ptr::copy_nonoverlapping([0x8D, 0x41, 0x01, 0xC3].as_ptr(), page, 4);
+        page
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn hook_tiny_function(padding: u8, expected_kind: DetourKind) {
+        let _guard = HOOK_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let target = tiny_function(padding);
+        let call_target = || black_box(std::mem::transmute::<*mut u8, extern "C" fn(i32) -> i32>(target))(5);
+        assert_eq!(call_target(), 6);
+
+        let detour = Detour::install(target, replacement_fn as *const u8).expect("Failed to install detour");
+        assert_eq!(detour.kind(), expected_kind);
+        assert_eq!(call_target(), -5);
+
+        let original = detour.call_original(|original: extern "C" fn(i32) -> i32| original(5));
+        assert_eq!(original, 6);
+
+        detour.remove().expect("Failed to remove detour");
+        assert_eq!(std::slice::from_raw_parts(target, 4), &[0x8D, 0x41, 0x01, 0xC3]);
+        assert!(std::slice::from_raw_parts(target.add(4), 0x1000 - 4).iter().all(|&byte| byte == padding));
+        assert_eq!(call_target(), 6);
+
+        VirtualFree(target as LPVOID, 0, MEM_RELEASE);
+    }
+
+    #[inline(never)]
+    extern "C" fn replacement_constant() -> i32 {
+        black_box(-1)
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_detour_relocates_rip_relative() {
+        let _guard = HOOK_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        unsafe {
+            let page = VirtualAlloc(std::ptr::null_mut(), 0x1000, MEM_COMMIT | MEM_RESERVE, PAGE_EXECUTE_READWRITE) as *mut u8;
+            assert!(!page.is_null());
+
+            // mov eax, [rip + 0x7FA] (the value at page + 0x800); 14 nops; ret
+            std::ptr::write_bytes(page, 0x90, 0x20);
+            std::ptr::copy_nonoverlapping([0x8B, 0x05, 0xFA, 0x07, 0x00, 0x00].as_ptr(), page, 6);
+            *page.add(20) = 0xC3;
+            std::ptr::write(page.add(0x800) as *mut i32, 1234);
+
+            let call_target = || black_box(std::mem::transmute::<*mut u8, extern "C" fn() -> i32>(page))();
+            assert_eq!(call_target(), 1234);
+
+            let detour = Detour::install(page, replacement_constant as *const u8).expect("Failed to install detour");
+            assert_eq!(call_target(), -1);
+
+            let original = detour.call_original(|original: extern "C" fn() -> i32| original());
+            assert_eq!(original, 1234);
+
+            detour.remove().expect("Failed to remove detour");
+            assert_eq!(call_target(), 1234);
+
+            VirtualFree(page as LPVOID, 0, MEM_RELEASE);
+        }
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_detour_tiny_function_code_cave() {
+        unsafe { hook_tiny_function(CAVE_FILL, DetourKind::ShortJump) };
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_detour_tiny_function_breakpoint() {
+        unsafe { hook_tiny_function(0x00, DetourKind::Breakpoint) };
+    }
+}
\ No newline at end of file
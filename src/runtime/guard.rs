@@ -0,0 +1,138 @@
+use std::sync::OnceLock;
+
+use winapi::um::minwinbase::{EXCEPTION_ACCESS_VIOLATION, EXCEPTION_IN_PAGE_ERROR};
+
+use crate::runtime::veh::{add_veh, ExceptionAction, ExceptionInfo};
+
+// The copy is a `rep movsb` at a known address. When it faults, the handler below resumes the
+// thread at the recovery label instead, which returns 1 from the stub like a normal function.
+#[cfg(target_arch = "x86_64")]
+std::arch::global_asm!(
+    ".globl verity_guarded_copy",
+    "verity_guarded_copy:",
+    "push rdi",
+    "push rsi",
+    "mov rdi, rcx",
+    "mov rsi, rdx",
+    "mov rcx, r8",
+    ".globl verity_guarded_copy_store",
+    "verity_guarded_copy_store:",
+    "rep movsb",
+    "xor eax, eax",
+    "pop rsi",
+    "pop rdi",
+    "ret",
+    ".globl verity_guarded_copy_recover",
+    "verity_guarded_copy_recover:",
+    "mov eax, 1",
+    "pop rsi",
+    "pop rdi",
+    "ret",
+);
+
+// C symbols are prefixed with an underscore on x86.
+#[cfg(target_arch = "x86")]
+std::arch::global_asm!(
+    ".globl _verity_guarded_copy",
+    "_verity_guarded_copy:",
+    "push edi",
+    "push esi",
+    "mov edi, [esp + 12]",
+    "mov esi, [esp + 16]",
+    "mov ecx, [esp + 20]",
+    ".globl _verity_guarded_copy_store",
+    "_verity_guarded_copy_store:",
+    "rep movsb",
+    "xor eax, eax",
+    "pop esi",
+    "pop edi",
+    "ret",
+    ".globl _verity_guarded_copy_recover",
+    "_verity_guarded_copy_recover:",
+    "mov eax, 1",
+    "pop esi",
+    "pop edi",
+    "ret",
+);
+
+extern "C" {
+    fn verity_guarded_copy(dst: *mut u8, src: *const u8, len: usize) -> u32;
+    fn verity_guarded_copy_store();
+    fn verity_guarded_copy_recover();
+}
+
+static HANDLER_INSTALLED: OnceLock<bool> = OnceLock::new();
+
+/// Resumes a copy that faulted at its store instruction at the recovery label.
+fn recover_copy_fault(info: &mut ExceptionInfo) -> ExceptionAction {
+    let code = info.code();
+    if (code != EXCEPTION_ACCESS_VIOLATION && code != EXCEPTION_IN_PAGE_ERROR)
+        || info.address() != verity_guarded_copy_store as usize
+    {
+        return ExceptionAction::ContinueSearch;
+    }
+
+    let mut context = info.context();
+    context.set_ip(verity_guarded_copy_recover as usize);
+    info.set_context(&context);
+    ExceptionAction::ContinueExecution
+}
+
+/// Copies `len` bytes from `src` to `dst`, turning an access violation during the copy into a
+/// `false` return instead of a crash.
+///
+/// The exception handler is registered the first time this is called and stays registered. Only
+/// faults raised by the copy itself are handled; every other exception is passed on.
+///
+/// # Safety
+/// A fault on either `src` or `dst` is recovered from, but the ranges must not overlap memory the
+/// caller relies on being left intact. When the copy faults, the bytes before the faulting one have
+/// already been written.
+///
+/// # Returns
+/// - `true` if every byte was copied.
+/// - `false` if the copy faulted, or the exception handler could not be registered.
+pub(crate) unsafe fn guarded_copy(dst: *mut u8, src: *const u8, len: usize) -> bool {
+    let installed = *HANDLER_INSTALLED.get_or_init(|| add_veh(recover_copy_fault).is_ok());
+    if !installed {
+        return false;
+    }
+
+    verity_guarded_copy(dst, src, len) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winapi::um::memoryapi::{VirtualAlloc, VirtualFree, VirtualProtect};
+    use winapi::um::winnt::{MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_NOACCESS, PAGE_READONLY, PAGE_READWRITE};
+
+    #[test]
+    fn test_guarded_copy() {
+        let mut buffer = [0u8; 8];
+        assert!(unsafe { guarded_copy(buffer.as_mut_ptr(), [1, 2, 3, 4].as_ptr(), 4) });
+        assert_eq!(buffer, [1, 2, 3, 4, 0, 0, 0, 0]);
+        assert!(unsafe { guarded_copy(buffer.as_mut_ptr(), [9].as_ptr(), 0) });
+    }
+
+    #[test]
+    fn test_guarded_copy_faults() {
+        unsafe {
+            let page = VirtualAlloc(std::ptr::null_mut(), 0x2000, MEM_COMMIT | MEM_RESERVE, PAGE_READWRITE) as *mut u8;
+            assert!(!page.is_null());
+
+            let mut old_protect = 0;
+            VirtualProtect(page.add(0x1000) as _, 0x1000, PAGE_NOACCESS, &mut old_protect);
+            assert!(!guarded_copy(page.add(0x1000), [1, 2, 3, 4].as_ptr(), 4));
+
+            // The bytes before the faulting page are written.
+            assert!(!guarded_copy(page.add(0xFFE), [1, 2, 3, 4].as_ptr(), 4));
+            assert_eq!(std::slice::from_raw_parts(page.add(0xFFE), 2), &[1, 2]);
+
+            VirtualProtect(page as _, 0x1000, PAGE_READONLY, &mut old_protect);
+            assert!(!guarded_copy(page, [1].as_ptr(), 1));
+
+            VirtualFree(page as _, 0, MEM_RELEASE);
+        }
+    }
+}
\ No newline at end of file
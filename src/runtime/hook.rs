@@ -0,0 +1,191 @@
+use crate::errors::DetourError;
+
+use super::detour::Detour;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A function pointer type that can be hooked with [`Hook`].
+///
+/// Implemented for `extern "C"`, `extern "system"` and Rust `fn` pointers taking up to six
+/// arguments. The trait is sealed, so only function pointers can be hooked.
+pub trait HookFn: Copy + sealed::Sealed {
+    /// Returns the address of the function.
+    fn to_ptr(self) -> *const u8;
+
+    /// Reinterprets an address as a function of this type.
+    ///
+    /// # Safety
+    /// `ptr` must point to a function with exactly this signature and calling convention.
+    unsafe fn from_ptr(ptr: *const u8) -> Self;
+}
+
+macro_rules! impl_hook_fn {
+    ($($arg:ident),*) => {
+        impl_hook_fn!(@abi [fn($($arg),*) -> R] $($arg),*);
+        impl_hook_fn!(@abi [extern "C" fn($($arg),*) -> R] $($arg),*);
+        impl_hook_fn!(@abi [extern "system" fn($($arg),*) -> R] $($arg),*);
+    };
+    (@abi [$($fn:tt)*] $($arg:ident),*) => {
+        impl<R, $($arg),*> sealed::Sealed for $($fn)* {}
+
+        impl<R, $($arg),*> HookFn for $($fn)* {
+            fn to_ptr(self) -> *const u8 {
+                self as *const u8
+            }
+
+            unsafe fn from_ptr(ptr: *const u8) -> Self {
+                std::mem::transmute_copy(&ptr)
+            }
+        }
+    };
+}
+
+impl_hook_fn!();
+impl_hook_fn!(A);
+impl_hook_fn!(A, B);
+impl_hook_fn!(A, B, C);
+impl_hook_fn!(A, B, C, D);
+impl_hook_fn!(A, B, C, D, E);
+impl_hook_fn!(A, B, C, D, E, G);
+
+/// A typed inline hook, redirecting a function to a replacement of the same type.
+///
+/// Unlike [`Detour`], which works on raw addresses, the target and the replacement must have the
+/// same function pointer type `F`, so a mismatched signature or calling convention is a compile
+/// error, and the original function is available as an `F` without any transmute.
+///
+/// # Example
+/// ```rust
+/// use std::sync::OnceLock;
+/// use verity_memory::runtime::hook::Hook;
+///
+/// static ORIGINAL: OnceLock<extern "C" fn(i32) -> i32> = OnceLock::new();
+///
+/// #[inline(never)]
+/// extern "C" fn health(base: i32) -> i32 {
+///     std::hint::black_box(base) * 10
+/// }
+///
+/// extern "C" fn god_mode(base: i32) -> i32 {
+///     ORIGINAL.get().unwrap()(base) * 100
+/// }
+///
+/// unsafe {
+///     let hook = Hook::new(health as extern "C" fn(i32) -> i32, god_mode).unwrap();
+///     ORIGINAL.set(hook.original()).unwrap();
+///
+///     let hooked = std::hint::black_box(health as extern "C" fn(i32) -> i32);
+///     assert_eq!(hooked(2), 2000);
+///
+///     hook.remove().unwrap();
+///     assert_eq!(hooked(2), 20);
+/// }
+/// ```
+pub struct Hook<F: HookFn> {
+    detour: Detour,
+    original: F,
+}
+
+impl<F: HookFn> Hook<F> {
+    /// Installs a hook redirecting `target` to `replacement`.
+    ///
+    /// # Safety
+    /// This function is unsafe because it rewrites the code of `target`. See [`Detour::install`].
+    ///
+    /// # Errors
+    /// - Same as [`Detour::install`].
+    pub unsafe fn new(target: F, replacement: F) -> Result<Hook<F>, DetourError> {
+        let detour = Detour::install(target.to_ptr() as *mut u8, replacement.to_ptr())?;
+        let original = F::from_ptr(detour.trampoline());
+
+        Ok(Hook { detour, original })
+    }
+
+    /// Returns the original function, which behaves like the unhooked target when called.
+    ///
+    /// # Safety
+    /// The returned function points into the trampoline of the hook, which is freed when the hook
+    /// is removed or, after [`HookRegistry::remove_all`](crate::runtime::registry::HookRegistry::remove_all),
+    /// when the `Hook` is dropped. It must not be called after that.
+    pub unsafe fn original(&self) -> F {
+        self.original
+    }
+
+    /// Returns the hooked function.
+    pub fn target(&self) -> F {
+        unsafe { F::from_ptr(self.detour.target()) }
+    }
+
+    /// Returns the underlying untyped detour.
+    pub fn detour(&self) -> &Detour {
+        &self.detour
+    }
+
+    /// Restores the target. See [`Detour::remove`].
+    ///
+    /// # Safety
+    /// No thread may be executing the patched instructions or the original function while the
+    /// hook is removed.
+    ///
+    /// # Errors
+    /// - Same as [`Detour::remove`].
+    pub unsafe fn remove(self) -> Result<(), DetourError> {
+        self.detour.remove()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::registry::HOOK_TEST_LOCK;
+    use std::hint::black_box;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    type TargetFn = extern "C" fn(i32) -> i32;
+
+    static ORIGINAL: AtomicUsize = AtomicUsize::new(0);
+
+    #[inline(never)]
+    extern "C" fn target_fn(value: i32) -> i32 {
+        let mut total = black_box(value);
+        for i in 0..black_box(3) {
+            total = total.wrapping_mul(2).wrapping_add(i);
+        }
+        total
+    }
+
+    extern "C" fn replacement_fn(value: i32) -> i32 {
+        let original = unsafe { TargetFn::from_ptr(ORIGINAL.load(Ordering::SeqCst) as *const u8) };
+        original(value) + 1000
+    }
+
+    #[test]
+    fn test_hook_calls_replacement_and_original() {
+        let _guard = HOOK_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let call_target = || black_box(target_fn as TargetFn)(5);
+        let expected = call_target();
+
+        unsafe {
+            let hook = Hook::new(target_fn as TargetFn, replacement_fn).expect("Failed to install hook");
+            ORIGINAL.store(hook.original().to_ptr() as usize, Ordering::SeqCst);
+
+            assert_eq!(hook.target().to_ptr(), target_fn as *const u8);
+            assert_eq!(call_target(), expected + 1000);
+            assert_eq!(hook.original()(5), expected);
+
+            hook.remove().expect("Failed to remove hook");
+        }
+
+        assert_eq!(call_target(), expected);
+    }
+
+    #[test]
+    fn test_hook_fn_round_trip() {
+        let ptr = (target_fn as TargetFn).to_ptr();
+        let function = unsafe { TargetFn::from_ptr(ptr) };
+        assert_eq!(function(1), target_fn(1));
+    }
+}
\ No newline at end of file
@@ -1,4 +1,40 @@
+pub mod breakpoint;
+pub mod context;
+pub(crate) mod guard;
+pub mod peb;
+pub mod veh;
 pub mod vtable;
+#[cfg(feature = "advanced-write")]
+pub mod detour;
+#[cfg(feature = "advanced-write")]
+pub mod hook;
+#[cfg(feature = "advanced-write")]
+pub mod registry;
 
+pub use breakpoint::set_software_breakpoint;
+pub use breakpoint::SwBreakpoint;
+pub use context::get_thread_context;
+pub use context::set_thread_context;
+pub use context::Context;
+pub use peb::peb_modules;
+pub use peb::ModuleInfo;
+pub use veh::add_veh;
+pub use veh::remove_veh;
+pub use veh::ExceptionAction;
+pub use veh::ExceptionInfo;
+pub use veh::VehHandle;
+pub use vtable::dump_vtable;
 pub use vtable::resolve_vtable;
-pub use vtable::resolve_vtable_dp;
\ No newline at end of file
+pub use vtable::resolve_vtable_dp;
+pub use vtable::try_resolve_vtable;
+pub use vtable::try_resolve_vtable_dp;
+#[cfg(feature = "advanced-write")]
+pub use detour::Detour;
+#[cfg(feature = "advanced-write")]
+pub use detour::DetourKind;
+#[cfg(feature = "advanced-write")]
+pub use hook::Hook;
+#[cfg(feature = "advanced-write")]
+pub use hook::HookFn;
+#[cfg(feature = "advanced-write")]
+pub use registry::HookRegistry;
\ No newline at end of file
@@ -0,0 +1,163 @@
+use std::ffi::c_void;
+
+use winapi::shared::ntdef::{LIST_ENTRY, UNICODE_STRING};
+
+/// The most entries [`peb_modules`] follows, in case the list is corrupted into a cycle.
+const MAX_MODULES: usize = 4096;
+
+/// A module found in the loader data of the PEB.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleInfo {
+    /// The file name of the module, e.g. `"kernel32.dll"`.
+    pub name: String,
+    /// The full path of the module.
+    pub path: String,
+    /// The address the module is mapped at.
+    pub base: usize,
+    /// The size of the mapped image.
+    pub size: usize,
+    /// The address of the entry point, or 0 if the module has none.
+    pub entry_point: usize,
+}
+
+/// The start of the PEB, up to the loader data. The layout only differs in pointer size, so the
+/// same definition fits both 32- and 64-bit processes.
+#[repr(C)]
+#[allow(dead_code)]
+struct Peb {
+    inherited_address_space: u8,
+    read_image_file_exec_options: u8,
+    being_debugged: u8,
+    bit_field: u8,
+    mutant: *mut c_void,
+    image_base_address: *mut c_void,
+    ldr: *mut PebLdrData,
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct PebLdrData {
+    length: u32,
+    initialized: u8,
+    ss_handle: *mut c_void,
+    in_load_order_module_list: LIST_ENTRY,
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct LdrDataTableEntry {
+    in_load_order_links: LIST_ENTRY,
+    in_memory_order_links: LIST_ENTRY,
+    in_initialization_order_links: LIST_ENTRY,
+    dll_base: *mut c_void,
+    entry_point: *mut c_void,
+    size_of_image: u32,
+    full_dll_name: UNICODE_STRING,
+    base_dll_name: UNICODE_STRING,
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn current_peb() -> *const Peb {
+    let peb: *const Peb;
+    std::arch::asm!("mov {}, gs:[0x60]", out(reg) peb, options(nostack, readonly, preserves_flags));
+    peb
+}
+
+#[cfg(target_arch = "x86")]
+unsafe fn current_peb() -> *const Peb {
+    let peb: *const Peb;
+    std::arch::asm!("mov {}, fs:[0x30]", out(reg) peb, options(nostack, readonly, preserves_flags));
+    peb
+}
+
+unsafe fn unicode_string(string: &UNICODE_STRING) -> String {
+    if string.Buffer.is_null() {
+        return String::new();
+    }
+
+    let units = std::slice::from_raw_parts(string.Buffer, string.Length as usize / 2);
+    String::from_utf16_lossy(units)
+}
+
+/// Lists the modules of the current process by walking the `InLoadOrderModuleList` of the PEB
+/// loader data, without going through `GetModuleHandle` or the Toolhelp APIs.
+///
+/// This still works when those APIs are hooked to hide modules, although a module unlinked from
+/// the loader list is missing here too. The executable comes first, followed by the modules in
+/// the order they were loaded.
+///
+/// The list is read without holding the loader lock, so a module loaded or unloaded concurrently
+/// may be missed.
+///
+/// # Returns
+/// - `Vec<ModuleInfo>`: The modules in load order, or an empty vector if the loader data isn't
+///   initialized.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::runtime::peb;
+///
+/// let modules = peb::peb_modules();
+/// assert!(modules.iter().any(|module| module.name.eq_ignore_ascii_case("kernel32.dll")));
+/// ```
+pub fn peb_modules() -> Vec<ModuleInfo> {
+    let mut modules = Vec::new();
+
+    unsafe {
+        let peb = current_peb();
+        if peb.is_null() || (*peb).ldr.is_null() {
+            return modules;
+        }
+
+        let head = &(*(*peb).ldr).in_load_order_module_list as *const LIST_ENTRY;
+        let mut link = (*head).Flink as *const LIST_ENTRY;
+
+        while !link.is_null() && link != head && modules.len() < MAX_MODULES {
+            // `in_load_order_links` is the first field, so the link is the address of the entry.
+            let entry = &*(link as *const LdrDataTableEntry);
+
+            modules.push(ModuleInfo {
+                name: unicode_string(&entry.base_dll_name),
+                path: unicode_string(&entry.full_dll_name),
+                base: entry.dll_base as usize,
+                size: entry.size_of_image as usize,
+                entry_point: entry.entry_point as usize,
+            });
+
+            link = entry.in_load_order_links.Flink;
+        }
+    }
+
+    modules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::module_base;
+
+    #[test]
+    fn test_peb_modules_contains_current_module() {
+        let modules = peb_modules();
+        let base = module_base(None).unwrap() as usize;
+
+        assert_eq!(modules.first().map(|module| module.base), Some(base));
+
+        let current = modules.iter().find(|module| module.base == base).expect("Missing current module");
+        assert!(current.size > 0);
+        assert!(current.path.ends_with(&current.name));
+        assert!(current.entry_point > base && current.entry_point < base + current.size);
+    }
+
+    #[test]
+    fn test_peb_modules_matches_module_base() {
+        let modules = peb_modules();
+        let kernel32 = module_base(Some("kernel32.dll")).unwrap() as usize;
+
+        let module = modules
+            .iter()
+            .find(|module| module.name.eq_ignore_ascii_case("kernel32.dll"))
+            .expect("Missing kernel32.dll");
+        assert_eq!(module.base, kernel32);
+    }
+}
\ No newline at end of file
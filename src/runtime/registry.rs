@@ -0,0 +1,179 @@
+use std::sync::Mutex;
+
+use crate::errors::DetourError;
+
+type Restore = Box<dyn FnOnce() -> Result<(), DetourError> + Send>;
+
+static HOOKS: Mutex<Vec<(usize, Restore)>> = Mutex::new(Vec::new());
+static NEXT_ID: Mutex<usize> = Mutex::new(1);
+
+/// Serializes the tests that install hooks, since [`HookRegistry::remove_all`] removes every hook
+/// of the process, including those installed by tests running in parallel.
+#[cfg(test)]
+pub(crate) static HOOK_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// The process-wide registry of installed hooks.
+///
+/// Every hook registers how to restore its target when it is installed and unregisters when it is
+/// removed, so an injected DLL can restore every hook it still has installed before it is unloaded,
+/// without keeping track of them itself.
+///
+/// # Example
+/// ```rust,no_run
+/// use verity_memory::runtime::registry::HookRegistry;
+/// use winapi::shared::minwindef::{BOOL, DWORD, HINSTANCE, LPVOID, TRUE};
+/// use winapi::um::winnt::DLL_PROCESS_DETACH;
+///
+/// #[no_mangle]
+/// extern "system" fn DllMain(_module: HINSTANCE, reason: DWORD, _reserved: LPVOID) -> BOOL {
+///     if reason == DLL_PROCESS_DETACH {
+///         let _ = unsafe { HookRegistry::remove_all() };
+///     }
+///     TRUE
+/// }
+/// ```
+pub struct HookRegistry;
+
+impl HookRegistry {
+    /// Registers a hook, returning the id it can be taken back with.
+    pub(crate) fn register(restore: impl FnOnce() -> Result<(), DetourError> + Send + 'static) -> usize {
+        let mut next_id = NEXT_ID.lock().unwrap_or_else(|e| e.into_inner());
+        let id = *next_id;
+        *next_id += 1;
+
+        HOOKS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((id, Box::new(restore)));
+        id
+    }
+
+    /// Unregisters the hook with the given id and returns how to restore it, or `None` if it was
+    /// already removed.
+    pub(crate) fn take(id: usize) -> Option<Restore> {
+        let mut hooks = HOOKS.lock().unwrap_or_else(|e| e.into_inner());
+        let index = hooks.iter().position(|(hook_id, _)| *hook_id == id)?;
+        Some(hooks.remove(index).1)
+    }
+
+    /// Returns whether the hook with the given id is still registered.
+    pub(crate) fn contains(id: usize) -> bool {
+        HOOKS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(|(hook_id, _)| *hook_id == id)
+    }
+
+    /// Returns the number of installed hooks.
+    pub fn len() -> usize {
+        HOOKS.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Returns whether no hooks are installed.
+    pub fn is_empty() -> bool {
+        Self::len() == 0
+    }
+
+    /// Removes every installed hook, most recently installed first, so hooks stacked on the same
+    /// target unwind in order.
+    ///
+    /// Every hook is removed even if restoring an earlier one fails. Handles to removed hooks stay
+    /// valid: their trampolines are kept until the handles are removed or dropped, so they can still
+    /// call the original function, and removing them only frees the trampoline.
+    ///
+    /// # Safety
+    /// No thread may be executing any of the hooked code while the hooks are removed.
+    ///
+    /// # Errors
+    /// - The first error returned while restoring a hook.
+    pub unsafe fn remove_all() -> Result<(), DetourError> {
+        let hooks = std::mem::take(&mut *HOOKS.lock().unwrap_or_else(|e| e.into_inner()));
+
+        let mut result = Ok(());
+        for (_, restore) in hooks.into_iter().rev() {
+            if let Err(error) = restore() {
+                if result.is_ok() {
+                    result = Err(error);
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::detour::Detour;
+    use std::hint::black_box;
+
+    #[inline(never)]
+    extern "C" fn first_fn(value: i32) -> i32 {
+        let mut total = black_box(value);
+        for i in 0..black_box(4) {
+            total = total.wrapping_mul(5).wrapping_add(i);
+        }
+        total
+    }
+
+    #[inline(never)]
+    extern "C" fn second_fn(value: i32) -> i32 {
+        let mut total = black_box(value);
+        for i in 0..black_box(2) {
+            total = total.wrapping_mul(7).wrapping_sub(i);
+        }
+        total
+    }
+
+    #[inline(never)]
+    extern "C" fn replacement_fn(value: i32) -> i32 {
+        black_box(value).wrapping_neg()
+    }
+
+    #[test]
+    fn test_remove_all_restores_every_hook() {
+        let _guard = HOOK_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let call_first = || black_box(first_fn as extern "C" fn(i32) -> i32)(3);
+        let call_second = || black_box(second_fn as extern "C" fn(i32) -> i32)(3);
+        let (expected_first, expected_second) = (call_first(), call_second());
+
+        unsafe {
+            let first = Detour::install(first_fn as *mut u8, replacement_fn as *const u8)
+                .expect("Failed to install detour");
+            let second = Detour::install(second_fn as *mut u8, replacement_fn as *const u8)
+                .expect("Failed to install detour");
+
+            assert_eq!(HookRegistry::len(), 2);
+            assert_eq!(call_first(), -3);
+            assert_eq!(call_second(), -3);
+
+            HookRegistry::remove_all().expect("Failed to remove hooks");
+
+            assert!(HookRegistry::is_empty());
+            assert!(!first.is_installed());
+            assert_eq!(call_first(), expected_first);
+            assert_eq!(call_second(), expected_second);
+
+            let original = first.call_original(|original: extern "C" fn(i32) -> i32| original(3));
+            assert_eq!(original, expected_first);
+
+            assert_eq!(first.remove(), Ok(()));
+            assert_eq!(second.remove(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_take_unregisters() {
+        let _guard = HOOK_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let id = HookRegistry::register(|| Err(DetourError::FailedToWrite));
+        assert_eq!(HookRegistry::len(), 1);
+
+        let restore = HookRegistry::take(id).expect("Hook not registered");
+        assert_eq!(restore(), Err(DetourError::FailedToWrite));
+        assert!(HookRegistry::take(id).is_none());
+        assert!(HookRegistry::is_empty());
+    }
+}
\ No newline at end of file
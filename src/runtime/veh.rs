@@ -0,0 +1,233 @@
+use std::sync::Mutex;
+
+use winapi::shared::ntdef::LONG;
+use winapi::um::errhandlingapi::{AddVectoredExceptionHandler, RemoveVectoredExceptionHandler};
+use winapi::um::minwinbase::EXCEPTION_ACCESS_VIOLATION;
+use winapi::um::winnt::{CONTEXT, EXCEPTION_POINTERS, EXCEPTION_RECORD};
+use winapi::vc::excpt::{EXCEPTION_CONTINUE_EXECUTION, EXCEPTION_CONTINUE_SEARCH};
+
+use crate::errors::VehError;
+use crate::runtime::context::Context;
+
+/// A handler registered with [`add_veh`].
+pub type VehHandler = fn(&mut ExceptionInfo) -> ExceptionAction;
+
+/// What to do with an exception once a [`VehHandler`] has looked at it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExceptionAction {
+    /// The exception is handled: resume the thread with its (possibly modified) context.
+    ContinueExecution,
+    /// The exception is not for this handler: pass it to the next one.
+    ContinueSearch,
+}
+
+/// The exception passed to a [`VehHandler`], a safe view of `EXCEPTION_POINTERS`.
+pub struct ExceptionInfo<'a> {
+    record: &'a EXCEPTION_RECORD,
+    context: &'a mut CONTEXT,
+}
+
+impl ExceptionInfo<'_> {
+    /// The exception code, e.g. `EXCEPTION_ACCESS_VIOLATION` or `EXCEPTION_BREAKPOINT`.
+    pub fn code(&self) -> u32 {
+        self.record.ExceptionCode
+    }
+
+    /// The address of the instruction that raised the exception.
+    pub fn address(&self) -> usize {
+        self.record.ExceptionAddress as usize
+    }
+
+    /// The code-specific parameters of the exception.
+    pub fn parameters(&self) -> &[usize] {
+        let count = (self.record.NumberParameters as usize).min(self.record.ExceptionInformation.len());
+        &self.record.ExceptionInformation[..count]
+    }
+
+    /// For an access violation, the address that could not be accessed.
+    pub fn access_address(&self) -> Option<usize> {
+        if self.code() == EXCEPTION_ACCESS_VIOLATION {
+            self.parameters().get(1).copied()
+        } else {
+            None
+        }
+    }
+
+    /// A copy of the registers of the thread when the exception was raised.
+    pub fn context(&self) -> Context {
+        Context::from_raw(*self.context)
+    }
+
+    /// Replaces the registers the thread resumes with on [`ExceptionAction::ContinueExecution`].
+    pub fn set_context(&mut self, context: &Context) {
+        *self.context = *context.raw();
+    }
+}
+
+/// A handler registered with [`add_veh`], to be passed to [`remove_veh`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct VehHandle(usize);
+
+struct Registry {
+    /// The vectored exception handler dispatching to `handlers`, or 0 when none is registered.
+    os_handle: usize,
+    handlers: Vec<(usize, VehHandler)>,
+    next_id: usize,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    os_handle: 0,
+    handlers: Vec::new(),
+    next_id: 0,
+});
+
+/// Registers `handler` to be called for every exception raised in the process.
+///
+/// A single vectored exception handler, added with `AddVectoredExceptionHandler` when the first
+/// handler is registered and removed with the last one, dispatches to the registered handlers in
+/// registration order until one returns [`ExceptionAction::ContinueExecution`].
+///
+/// # Safety
+/// This function is `unsafe` because `handler` runs inside an exception handler and can change
+/// the registers the faulting thread resumes with.
+/// - The handler must not panic, and must not take locks the faulting thread may hold.
+/// - It must return [`ExceptionAction::ContinueSearch`] for exceptions it doesn't expect, since
+///   every exception of the process reaches it.
+///
+/// # Errors
+/// - `VehError::FailedToInstallHandler`: If the vectored exception handler could not be added.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::runtime::veh::{add_veh, remove_veh, ExceptionAction, ExceptionInfo};
+///
+/// fn ignore(_: &mut ExceptionInfo) -> ExceptionAction {
+///     ExceptionAction::ContinueSearch
+/// }
+///
+/// let handle = unsafe { add_veh(ignore) }.unwrap();
+/// assert!(remove_veh(handle).is_ok());
+/// ```
+pub unsafe fn add_veh(handler: VehHandler) -> Result<VehHandle, VehError> {
+    let mut registry = REGISTRY.lock().map_err(|_| VehError::FailedToInstallHandler)?;
+
+    if registry.os_handle == 0 {
+        registry.os_handle = AddVectoredExceptionHandler(1, Some(dispatch)) as usize;
+        if registry.os_handle == 0 {
+            return Err(VehError::FailedToInstallHandler);
+        }
+    }
+
+    let id = registry.next_id;
+    registry.next_id += 1;
+    registry.handlers.push((id, handler));
+    Ok(VehHandle(id))
+}
+
+/// Unregisters a handler registered with [`add_veh`].
+///
+/// # Errors
+/// - `VehError::NotRegistered`: If the handler is not registered anymore.
+pub fn remove_veh(handle: VehHandle) -> Result<(), VehError> {
+    let mut registry = REGISTRY.lock().map_err(|_| VehError::NotRegistered)?;
+
+    let index = registry
+        .handlers
+        .iter()
+        .position(|(id, _)| *id == handle.0)
+        .ok_or(VehError::NotRegistered)?;
+    registry.handlers.remove(index);
+
+    if registry.handlers.is_empty() && registry.os_handle != 0 {
+        unsafe { RemoveVectoredExceptionHandler(registry.os_handle as _) };
+        registry.os_handle = 0;
+    }
+
+    Ok(())
+}
+
+unsafe extern "system" fn dispatch(pointers: *mut EXCEPTION_POINTERS) -> LONG {
+    let mut info = ExceptionInfo {
+        record: &*(*pointers).ExceptionRecord,
+        context: &mut *(*pointers).ContextRecord,
+    };
+
+    // The lock is not held while a handler runs, so handlers may register or remove handlers.
+    let mut index = 0;
+    loop {
+        let handler = match REGISTRY.lock() {
+            Ok(registry) => registry.handlers.get(index).map(|(_, handler)| *handler),
+            Err(_) => None,
+        };
+
+        match handler {
+            Some(handler) => {
+                if handler(&mut info) == ExceptionAction::ContinueExecution {
+                    return EXCEPTION_CONTINUE_EXECUTION;
+                }
+            }
+            None => return EXCEPTION_CONTINUE_SEARCH,
+        }
+
+        index += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use winapi::um::memoryapi::{VirtualAlloc, VirtualFree, VirtualProtect};
+    use winapi::um::winnt::{MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_NOACCESS, PAGE_READWRITE};
+
+    static GUARDED_PAGE: AtomicUsize = AtomicUsize::new(0);
+    static FAULTS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Handles access violations on `GUARDED_PAGE` by making it readable.
+    fn unlock_page(info: &mut ExceptionInfo) -> ExceptionAction {
+        let page = GUARDED_PAGE.load(Ordering::SeqCst);
+        match info.access_address() {
+            Some(address) if page != 0 && (page..page + 0x1000).contains(&address) => {
+                let mut old_protect = 0;
+                unsafe { VirtualProtect(page as _, 0x1000, PAGE_READWRITE, &mut old_protect) };
+                FAULTS.fetch_add(1, Ordering::SeqCst);
+                ExceptionAction::ContinueExecution
+            }
+            _ => ExceptionAction::ContinueSearch,
+        }
+    }
+
+    fn ignore(_: &mut ExceptionInfo) -> ExceptionAction {
+        ExceptionAction::ContinueSearch
+    }
+
+    #[test]
+    fn test_veh_handles_access_violation() {
+        unsafe {
+            let page = VirtualAlloc(std::ptr::null_mut(), 0x1000, MEM_COMMIT | MEM_RESERVE, PAGE_NOACCESS) as *mut u32;
+            assert!(!page.is_null());
+            GUARDED_PAGE.store(page as usize, Ordering::SeqCst);
+
+            let passthrough = add_veh(ignore).expect("Failed to add handler");
+            let handle = add_veh(unlock_page).expect("Failed to add handler");
+
+            assert_eq!(std::ptr::read_volatile(page.add(4)), 0);
+            assert_eq!(FAULTS.load(Ordering::SeqCst), 1);
+
+            assert_eq!(remove_veh(handle), Ok(()));
+            assert_eq!(remove_veh(passthrough), Ok(()));
+
+            GUARDED_PAGE.store(0, Ordering::SeqCst);
+            VirtualFree(page as _, 0, MEM_RELEASE);
+        }
+    }
+
+    #[test]
+    fn test_remove_veh_twice() {
+        let handle = unsafe { add_veh(ignore) }.expect("Failed to add handler");
+        let id = handle.0;
+
+        assert_eq!(remove_veh(handle), Ok(()));
+        assert_eq!(remove_veh(VehHandle(id)), Err(VehError::NotRegistered));
+    }
+}
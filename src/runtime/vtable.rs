@@ -1,3 +1,12 @@
+#[cfg(feature = "pe")]
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use crate::ops::read::try_read_code_ptr;
+#[cfg(feature = "pe")]
+use crate::pe::exports::parse_exports;
+use crate::runtime::peb::{peb_modules, ModuleInfo};
+
 /// Resolves a vtable from a given raw pointer.
 /// 
 /// # Safety
@@ -41,11 +50,34 @@
 /// };
 /// ```
 pub unsafe fn resolve_vtable<T: Copy>(vtable_ptr: *const T) -> T {
+    try_resolve_vtable(vtable_ptr).expect("Null pointer to vtable")
+}
+
+/// Resolves a vtable from a given raw pointer, returning `None` instead of panicking on null.
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences a raw pointer, which could lead to undefined behavior if the pointer is invalid.
+/// 
+/// # Parameters
+/// - `vtable_ptr`: A raw pointer to the vtable of type `T`.
+/// 
+/// # Returns
+/// - `Some(T)`: The resolved vtable value.
+/// - `None`: If `vtable_ptr` is null.
+/// 
+/// # Example
+/// ```rust
+/// use verity_memory::runtime::vtable;
+/// 
+/// let vtable_ptr: *const usize = std::ptr::null();
+/// assert!(unsafe { vtable::try_resolve_vtable(vtable_ptr) }.is_none());
+/// ```
+pub unsafe fn try_resolve_vtable<T: Copy>(vtable_ptr: *const T) -> Option<T> {
     if vtable_ptr.is_null() {
-        panic!("Null pointer to vtable");
+        return None;
     }
 
-    *vtable_ptr
+    Some(*vtable_ptr)
 }
 
 /// Resolves a vtable from a double pointer (pointer to a pointer) to the vtable.
@@ -63,7 +95,7 @@ pub unsafe fn resolve_vtable<T: Copy>(vtable_ptr: *const T) -> T {
 /// - `T`: The resolved vtable value.
 /// 
 /// # Panics
-/// - This function will panic if the provided `vtable_ptr` is null, indicating an invalid pointer to the vtable.
+/// - This function will panic if the provided `vtable_ptr` or the vtable pointer it points to is null.
 /// 
 /// # Example
 /// ```rust
@@ -92,11 +124,126 @@ pub unsafe fn resolve_vtable<T: Copy>(vtable_ptr: *const T) -> T {
 /// };
 /// ```
 pub unsafe fn resolve_vtable_dp<T: Copy>(vtable_ptr: *const *const T) -> T {
+    try_resolve_vtable_dp(vtable_ptr).expect("Null pointer to vtable")
+}
+
+/// Resolves a vtable from a double pointer, returning `None` instead of panicking on null.
+/// 
+/// Both the outer pointer and the vtable pointer it points to are checked before being dereferenced.
+/// 
+/// # Safety
+/// This function is `unsafe` because it dereferences a double raw pointer, which could lead to undefined behavior if any pointer is invalid.
+/// 
+/// # Parameters
+/// - `vtable_ptr`: A raw pointer to a pointer of type `T`.
+/// 
+/// # Returns
+/// - `Some(T)`: The resolved vtable value.
+/// - `None`: If `vtable_ptr` or `*vtable_ptr` is null.
+/// 
+/// # Example
+/// ```rust
+/// use verity_memory::runtime::vtable;
+/// 
+/// let inner: *const usize = std::ptr::null();
+/// let vtable_ptr: *const *const usize = &inner;
+/// assert!(unsafe { vtable::try_resolve_vtable_dp(vtable_ptr) }.is_none());
+/// ```
+pub unsafe fn try_resolve_vtable_dp<T: Copy>(vtable_ptr: *const *const T) -> Option<T> {
     if vtable_ptr.is_null() {
-        panic!("Null pointer to vtable");
+        return None;
+    }
+
+    try_resolve_vtable(*vtable_ptr)
+}
+
+/// Lists the slots of a vtable, to help find the index of a method before hooking it.
+/// 
+/// Slots are read until `max` entries have been listed or a slot doesn't hold a pointer into
+/// executable memory (see [`try_read_code_ptr`]), which usually marks the end of the table. Each
+/// pointer is labelled, best-effort, with the module containing it: `"module.dll!Export"` when it
+/// is the address of a named export (with the `pe` feature), `"module.dll+0x1234"` otherwise.
+/// 
+/// # Safety
+/// This function is `unsafe` because `vtable` is trusted to point to a table of pointers; every
+/// slot is checked with `VirtualQuery` before being read.
+/// 
+/// # Parameters
+/// - `vtable`: A pointer to the first slot of the vtable.
+/// - `max`: The maximum number of slots to list.
+/// 
+/// # Returns
+/// A vector of `(index, pointer, label)` tuples, one per slot until the end of the table. The
+/// label is `None` if the pointer isn't inside a module, e.g. in JIT-compiled code.
+/// 
+/// # Example
+/// ```rust
+/// use verity_memory::runtime::vtable;
+/// 
+/// extern "C" fn first() {}
+/// extern "C" fn second() {}
+/// 
+/// let table = [first as usize, second as usize, 0];
+/// for (index, pointer, label) in unsafe { vtable::dump_vtable(table.as_ptr(), 16) } {
+///     println!("[{}] {:#x} {}", index, pointer, label.unwrap_or_default());
+/// }
+/// ```
+pub unsafe fn dump_vtable(vtable: *const usize, max: usize) -> Vec<(usize, usize, Option<String>)> {
+    let mut labeller = ModuleLabeller::new();
+    let mut slots = Vec::new();
+
+    for index in 0..max {
+        let slot = (vtable as usize).wrapping_add(index * size_of::<usize>());
+        let pointer = match try_read_code_ptr(slot) {
+            Some(pointer) => pointer,
+            None => break,
+        };
+
+        slots.push((index, pointer, labeller.label(pointer)));
     }
 
-    *(*vtable_ptr)
+    slots
+}
+
+/// Labels pointers with the module containing them, parsing the exports of each module at most
+/// once however many slots point into it.
+struct ModuleLabeller {
+    modules: Vec<ModuleInfo>,
+    /// The named exports of every module parsed so far, by module base and then by address.
+    #[cfg(feature = "pe")]
+    exports: HashMap<usize, HashMap<usize, String>>,
+}
+
+impl ModuleLabeller {
+    fn new() -> Self {
+        ModuleLabeller {
+            modules: peb_modules(),
+            #[cfg(feature = "pe")]
+            exports: HashMap::new(),
+        }
+    }
+
+    fn label(&mut self, pointer: usize) -> Option<String> {
+        let module = self
+            .modules
+            .iter()
+            .find(|module| (module.base..module.base + module.size).contains(&pointer))?;
+
+        #[cfg(feature = "pe")]
+        {
+            // Reversed so that the first export of an address wins, as with a linear search.
+            let exports = self.exports.entry(module.base).or_insert_with(|| {
+                unsafe { parse_exports(module.base) }
+                    .map(|exports| exports.into_iter().rev().map(|export| (export.address, export.name)).collect())
+                    .unwrap_or_default()
+            });
+            if let Some(name) = exports.get(&pointer) {
+                return Some(format!("{}!{}", module.name, name));
+            }
+        }
+
+        Some(format!("{}+{:#x}", module.name, pointer - module.base))
+    }
 }
 
 #[cfg(test)]
@@ -167,4 +314,72 @@ mod tests {
             resolve_vtable_dp(vtable_ptr);
         }
     }
-}
+
+    #[test]
+    #[should_panic(expected = "Null pointer to vtable")]
+    fn test_resolve_vtable_dp_null_inner() {
+        unsafe {
+            let inner: *const MyVTable = std::ptr::null();
+            let vtable_ptr: *const *const MyVTable = &inner;
+            resolve_vtable_dp(vtable_ptr);
+        }
+    }
+
+    #[test]
+    fn test_try_resolve_vtable() {
+        let value = 42usize;
+
+        unsafe {
+            assert_eq!(try_resolve_vtable(&value as *const usize), Some(42));
+            assert_eq!(try_resolve_vtable::<usize>(std::ptr::null()), None);
+        }
+    }
+
+    #[test]
+    fn test_try_resolve_vtable_dp() {
+        let value = 42usize;
+        let inner: *const usize = &value;
+        let null_inner: *const usize = std::ptr::null();
+
+        unsafe {
+            assert_eq!(try_resolve_vtable_dp(&inner as *const *const usize), Some(42));
+            assert_eq!(try_resolve_vtable_dp(&null_inner as *const *const usize), None);
+            assert_eq!(try_resolve_vtable_dp::<usize>(std::ptr::null()), None);
+        }
+    }
+
+    #[test]
+    fn test_dump_vtable_mock_table() {
+        extern "C" fn first() {}
+        extern "C" fn second() {}
+        extern "C" fn third() {}
+
+        let data = 0usize;
+        let table = [first as usize, second as usize, third as usize, &data as *const usize as usize, first as usize];
+        let slots = unsafe { dump_vtable(table.as_ptr(), 16) };
+
+        assert_eq!(slots.len(), 3);
+        for (index, (slot_index, pointer, label)) in slots.iter().enumerate() {
+            assert_eq!(*slot_index, index);
+            assert_eq!(*pointer, table[index]);
+            assert!(label.as_deref().is_some_and(|label| label.contains("+0x")), "{:?}", label);
+        }
+
+        assert_eq!(unsafe { dump_vtable(table.as_ptr(), 2) }.len(), 2);
+        assert!(unsafe { dump_vtable(std::ptr::null(), 16) }.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "pe")]
+    fn test_dump_vtable_export_label() {
+        let kernel32 = crate::utils::module_base(Some("kernel32.dll")).unwrap() as usize;
+        let export = unsafe { parse_exports(kernel32) }.unwrap().remove(0);
+
+        let table = [export.address, 0];
+        let slots = unsafe { dump_vtable(table.as_ptr(), 4) };
+
+        assert_eq!(slots.len(), 1);
+        let label = slots[0].2.as_deref().unwrap().to_ascii_lowercase();
+        assert!(label.starts_with("kernel32.dll!"), "{}", label);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+pub mod snapshot;
+pub mod value;
+
+pub use snapshot::diff_regions;
+pub use snapshot::RegionSnapshot;
+pub use value::changed_value_scan;
+pub use value::value_rescan;
+pub use value::value_scan;
+pub use value::value_scan_with_options;
+pub use value::ScanPredicate;
+pub use value::ValueScanOptions;
\ No newline at end of file
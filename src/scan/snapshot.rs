@@ -0,0 +1,133 @@
+use crate::errors::ReadMemoryError;
+use crate::ops::read::read_bytes;
+
+/// A copy of a range of memory taken at one point in time.
+///
+/// Comparing a snapshot against the live memory later, with [`diff_regions`], answers "what
+/// changed when I did X", which is the basis of Cheat Engine-style value hunting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionSnapshot {
+    address: usize,
+    bytes: Vec<u8>,
+}
+
+impl RegionSnapshot {
+    /// Copies `len` bytes starting at `address`.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because it reads raw memory.
+    /// - The caller must ensure that the whole range is valid.
+    ///
+    /// # Errors
+    /// - Same as [`read_bytes`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use verity_memory::scan::RegionSnapshot;
+    ///
+    /// let buffer = [1u8, 2, 3, 4];
+    /// let snapshot = unsafe { RegionSnapshot::capture(buffer.as_ptr(), buffer.len()) }.unwrap();
+    /// assert_eq!(snapshot.bytes(), &buffer);
+    /// ```
+    pub unsafe fn capture(address: *const u8, len: usize) -> Result<RegionSnapshot, ReadMemoryError> {
+        let bytes = read_bytes(address, len)?;
+        Ok(RegionSnapshot {
+            address: address as usize,
+            bytes,
+        })
+    }
+
+    /// Creates a snapshot from bytes already copied from `address`.
+    pub fn from_bytes(address: usize, bytes: Vec<u8>) -> RegionSnapshot {
+        RegionSnapshot { address, bytes }
+    }
+
+    /// The address the snapshot was taken from.
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// The bytes of the region at the time of the snapshot.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The size of the region in bytes.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Whether the snapshot covers no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+}
+
+/// Compares a snapshot with the current contents of the same region.
+///
+/// # Parameters
+/// - `before`: The snapshot taken earlier.
+/// - `after`: The current bytes of the region, e.g. from a second [`RegionSnapshot`] or [`read_bytes`].
+///
+/// # Returns
+/// An `(offset, old, new)` tuple for every byte that differs, in ascending order of offset.
+/// Offsets are relative to [`RegionSnapshot::address`]. If the lengths differ, only the common
+/// prefix is compared.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::scan::{diff_regions, RegionSnapshot};
+///
+/// let mut buffer = [0u8; 8];
+/// let before = unsafe { RegionSnapshot::capture(buffer.as_ptr(), buffer.len()) }.unwrap();
+///
+/// buffer[3] = 0x2A;
+///
+/// assert_eq!(diff_regions(&before, &buffer), vec![(3, 0x00, 0x2A)]);
+/// ```
+pub fn diff_regions(before: &RegionSnapshot, after: &[u8]) -> Vec<(usize, u8, u8)> {
+    before
+        .bytes
+        .iter()
+        .zip(after)
+        .enumerate()
+        .filter(|(_, (old, new))| old != new)
+        .map(|(offset, (&old, &new))| (offset, old, new))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_regions() {
+        let mut buffer: Vec<u8> = (0..64).collect();
+        let before = unsafe { RegionSnapshot::capture(buffer.as_ptr(), buffer.len()) }.unwrap();
+        assert_eq!(before.address(), buffer.as_ptr() as usize);
+
+        buffer[0] = 0xFF;
+        buffer[17] = 0x00;
+        buffer[63] = 0x80;
+
+        let after = unsafe { RegionSnapshot::capture(buffer.as_ptr(), buffer.len()) }.unwrap();
+        assert_eq!(
+            diff_regions(&before, after.bytes()),
+            vec![(0, 0, 0xFF), (17, 17, 0x00), (63, 63, 0x80)]
+        );
+    }
+
+    #[test]
+    fn test_diff_regions_unchanged() {
+        let buffer = [0xAAu8; 32];
+        let before = RegionSnapshot::from_bytes(buffer.as_ptr() as usize, buffer.to_vec());
+        assert!(diff_regions(&before, &buffer).is_empty());
+    }
+
+    #[test]
+    fn test_diff_regions_different_lengths() {
+        let before = RegionSnapshot::from_bytes(0x1000, vec![1, 2, 3, 4]);
+        assert_eq!(diff_regions(&before, &[1, 9]), vec![(1, 2, 9)]);
+        assert_eq!(diff_regions(&before, &[1, 2, 3, 4, 5, 6]), vec![]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,354 @@
+use std::mem::size_of;
+use std::ops::ControlFlow;
+
+use crate::errors::ReadMemoryError;
+use crate::ops::query::{for_each_region, is_committed, query, PageInfo};
+
+/// Options for [`value_scan_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValueScanOptions {
+    /// Only consider addresses that are a multiple of `size_of::<T>()`, stepping by that size.
+    ///
+    /// Compilers lay out 2, 4 and 8-byte values at such addresses, so an aligned scan reads a
+    /// fraction of the windows of an unaligned one and doesn't report matches made of the end of
+    /// one value and the start of the next. Turn it off for packed structures or values of odd
+    /// sizes, at the cost of a slower scan with more false positives.
+    ///
+    /// Defaults to `true`.
+    pub aligned: bool,
+}
+
+impl Default for ValueScanOptions {
+    fn default() -> Self {
+        ValueScanOptions { aligned: true }
+    }
+}
+
+/// Finds every address in `[region_start, region_end)` holding `target`, like the first scan of
+/// Cheat Engine.
+///
+/// Only addresses aligned to `size_of::<T>()` are considered; see [`value_scan_with_options`] to
+/// scan every byte offset.
+///
+/// # Safety
+/// Same as [`value_scan_with_options`].
+///
+/// # Errors
+/// - Same as [`value_scan_with_options`].
+///
+/// # Example
+/// ```rust
+/// use verity_memory::scan::value_scan;
+///
+/// let values = [7i32, 1337, 42, 1337];
+/// let start = values.as_ptr() as usize;
+/// let end = start + std::mem::size_of_val(&values);
+///
+/// let found = unsafe { value_scan(start, end, 1337i32) }.unwrap();
+/// assert_eq!(found, vec![start + 4, start + 12]);
+/// ```
+pub unsafe fn value_scan<T: Copy + PartialEq>(
+    region_start: usize,
+    region_end: usize,
+    target: T,
+) -> Result<Vec<usize>, ReadMemoryError> {
+    value_scan_with_options(region_start, region_end, target, ValueScanOptions::default())
+}
+
+/// Finds every address in `[region_start, region_end)` holding `target`.
+///
+/// The range is walked region by region with `VirtualQuery`; only committed, readable pages are
+/// read, so unmapped gaps and guard pages are skipped instead of faulting. A value straddling two
+/// separately allocated regions is not found.
+///
+/// # Safety
+/// This function is `unsafe` because it reads raw memory.
+/// - Pages freed or made inaccessible by another thread while the scan runs will fault.
+///
+/// # Parameters
+/// - `region_start`: The first address to scan.
+/// - `region_end`: The end of the range, exclusive.
+/// - `target`: The value to search for, compared with `PartialEq`.
+/// - `options`: Whether to only consider aligned addresses, see [`ValueScanOptions`].
+///
+/// # Returns
+/// - `Ok(Vec<usize>)`: The addresses holding `target`, in ascending order.
+/// - `Err(ReadMemoryError)`: If the start of the range can't be queried.
+///
+/// # Errors
+/// - `ReadMemoryError::InvalidAccess`: If `region_start` is outside the user address space.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::scan::{value_scan_with_options, ValueScanOptions};
+///
+/// // A packed record: the u32 starts at offset 1.
+/// let record = [0xFFu8, 0x39, 0x05, 0x00, 0x00];
+/// let start = record.as_ptr() as usize;
+/// let options = ValueScanOptions { aligned: false };
+///
+/// let found = unsafe { value_scan_with_options(start, start + record.len(), 1337u32, options) }.unwrap();
+/// assert_eq!(found, vec![start + 1]);
+/// ```
+pub unsafe fn value_scan_with_options<T: Copy + PartialEq>(
+    region_start: usize,
+    region_end: usize,
+    target: T,
+    options: ValueScanOptions,
+) -> Result<Vec<usize>, ReadMemoryError> {
+    let stride = if options.aligned { size_of::<T>().max(1) } else { 1 };
+
+    if region_start < region_end && query(region_start as *const u8).is_none() {
+        return Err(ReadMemoryError::InvalidAccess);
+    }
+
+    let mut found = Vec::new();
+
+    let _ = for_each_region(region_start, region_end, |info| {
+        if info.is_readable() {
+            let current = info.base.max(region_start);
+            let next = info.end().min(region_end);
+
+            let mut address = match current % stride {
+                0 => current,
+                misalignment => current.saturating_add(stride - misalignment),
+            };
+            while address.saturating_add(size_of::<T>()) <= next {
+                if std::ptr::read_unaligned(address as *const T) == target {
+                    found.push(address);
+                }
+                address += stride;
+            }
+        }
+
+        ControlFlow::Continue(())
+    });
+
+    Ok(found)
+}
+
+/// Narrows the results of a previous scan to the addresses still holding `target`, like the next
+/// scan of Cheat Engine.
+///
+/// Addresses that are no longer committed and readable are dropped rather than reported as errors.
+///
+/// # Safety
+/// This function is `unsafe` because it reads raw memory.
+/// - Pages freed by another thread after being checked will fault.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::scan::{value_rescan, value_scan};
+///
+/// let mut values = [1337i32, 1337];
+/// let start = values.as_ptr() as usize;
+/// let found = unsafe { value_scan(start, start + 8, 1337i32) }.unwrap();
+///
+/// values[0] = 1336;
+///
+/// assert_eq!(unsafe { value_rescan(&found, 1337i32) }, vec![start + 4]);
+/// ```
+pub unsafe fn value_rescan<T: Copy + PartialEq>(previous: &[usize], target: T) -> Vec<usize> {
+    previous
+        .iter()
+        .copied()
+        .filter(|&address| read_value::<T>(address) == Some(target))
+        .collect()
+}
+
+/// How a value must have changed since the previous scan to be kept by [`changed_value_scan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanPredicate {
+    /// The value is greater than before.
+    Increased,
+    /// The value is less than before.
+    Decreased,
+    /// The value is different from before.
+    Changed,
+    /// The value is the same as before.
+    Unchanged,
+}
+
+impl ScanPredicate {
+    /// Whether going from `old` to `new` satisfies the predicate.
+    pub fn matches<T: PartialOrd>(&self, old: &T, new: &T) -> bool {
+        match self {
+            ScanPredicate::Increased => new > old,
+            ScanPredicate::Decreased => new < old,
+            ScanPredicate::Changed => new != old,
+            ScanPredicate::Unchanged => new == old,
+        }
+    }
+}
+
+/// Re-reads every address of a previous scan and keeps those whose value changed as described by
+/// `predicate`, like the "unknown initial value" scans of Cheat Engine.
+///
+/// This finds values that can't be searched for directly, e.g. "health, which just went down":
+/// capture the candidates with their current values, let the value change, then narrow them down
+/// with [`ScanPredicate::Decreased`]. The result holds the values read by this scan, so it can be
+/// passed straight back in for the next round.
+///
+/// Addresses that are no longer committed and readable are dropped.
+///
+/// # Safety
+/// This function is `unsafe` because it reads raw memory.
+/// - Pages freed by another thread after being checked will fault.
+///
+/// # Parameters
+/// - `previous`: The addresses to check with the value they held at the previous scan.
+/// - `predicate`: How the value must have changed.
+///
+/// # Returns
+/// The addresses satisfying `predicate`, with their current value.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::scan::{changed_value_scan, ScanPredicate};
+///
+/// let mut health = 100i32;
+/// let address = &mut health as *mut i32 as usize;
+/// let previous = vec![(address, 100i32)];
+///
+/// unsafe { std::ptr::write_volatile(address as *mut i32, 80) };
+///
+/// let decreased = unsafe { changed_value_scan(&previous, ScanPredicate::Decreased) };
+/// assert_eq!(decreased, vec![(address, 80)]);
+/// ```
+pub unsafe fn changed_value_scan<T: Copy + PartialOrd>(
+    previous: &[(usize, T)],
+    predicate: ScanPredicate,
+) -> Vec<(usize, T)> {
+    previous
+        .iter()
+        .filter_map(|&(address, old)| {
+            let new = read_value::<T>(address)?;
+            predicate.matches(&old, &new).then_some((address, new))
+        })
+        .collect()
+}
+
+/// Reads a `T` at `address` if the whole value lies in committed, readable memory.
+pub(crate) unsafe fn read_value<T: Copy>(address: usize) -> Option<T> {
+    if address == 0 || !is_committed(address as *const u8, size_of::<T>()) {
+        return None;
+    }
+
+    match query(address as *const u8) {
+        Some(info) if PageInfo::from(info).is_readable() => Some(std::ptr::read_unaligned(address as *const T)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winapi::um::memoryapi::{VirtualAlloc, VirtualFree};
+    use winapi::um::winnt::{MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_READWRITE};
+
+    #[test]
+    fn test_value_scan_i32() {
+        let values: Vec<i32> = vec![0, 0x5A5A_1234, 17, -1, 0x5A5A_1234, 0];
+        let start = values.as_ptr() as usize;
+        let end = start + values.len() * size_of::<i32>();
+
+        let found = unsafe { value_scan(start, end, 0x5A5A_1234i32) }.unwrap();
+        assert_eq!(found, vec![start + 4, start + 16]);
+    }
+
+    #[test]
+    fn test_value_scan_range_end_is_exclusive() {
+        let values: Vec<u32> = vec![1, 2, 3];
+        let start = values.as_ptr() as usize;
+
+        assert_eq!(unsafe { value_scan(start, start + 11, 3u32) }.unwrap(), vec![]);
+        assert_eq!(unsafe { value_scan(start, start + 12, 3u32) }.unwrap(), vec![start + 8]);
+    }
+
+    #[test]
+    fn test_value_scan_skips_uncommitted_pages() {
+        unsafe {
+            let base = VirtualAlloc(std::ptr::null_mut(), 0x3000, MEM_RESERVE, PAGE_READWRITE) as usize;
+            assert_ne!(base, 0);
+            VirtualAlloc(base as _, 0x1000, MEM_COMMIT, PAGE_READWRITE);
+            VirtualAlloc((base + 0x2000) as _, 0x1000, MEM_COMMIT, PAGE_READWRITE);
+
+            std::ptr::write((base + 0x10) as *mut u64, 0xFEED_FACE_CAFE_BEEF);
+            std::ptr::write((base + 0x2FF8) as *mut u64, 0xFEED_FACE_CAFE_BEEF);
+
+            let found = value_scan(base, base + 0x3000, 0xFEED_FACE_CAFE_BEEFu64).unwrap();
+            assert_eq!(found, vec![base + 0x10, base + 0x2FF8]);
+
+            VirtualFree(base as _, 0, MEM_RELEASE);
+        }
+    }
+
+    #[test]
+    fn test_value_rescan() {
+        let mut values: Vec<i32> = vec![100, 100, 100, 5];
+        let start = values.as_ptr() as usize;
+        let found = unsafe { value_scan(start, start + 16, 100i32) }.unwrap();
+        assert_eq!(found.len(), 3);
+
+        values[1] = 99;
+
+        let narrowed = unsafe { value_rescan(&found, 100i32) };
+        assert_eq!(narrowed, vec![start, start + 8]);
+        assert_eq!(unsafe { value_rescan(&[0usize], 100i32) }, vec![]);
+    }
+
+    #[test]
+    fn test_changed_value_scan() {
+        let mut values: Vec<i32> = vec![10, 10, 10, 10];
+        let address = |index: usize| values.as_ptr() as usize + index * size_of::<i32>();
+        let previous: Vec<(usize, i32)> = (0..4).map(|index| (address(index), 10)).collect();
+        let addresses: Vec<usize> = (0..4).map(address).collect();
+
+        values[0] = 15;
+        values[1] = 5;
+        values[2] = -10;
+
+        let scan = |predicate| unsafe { changed_value_scan(&previous, predicate) };
+        assert_eq!(scan(ScanPredicate::Increased), vec![(addresses[0], 15)]);
+        assert_eq!(scan(ScanPredicate::Decreased), vec![(addresses[1], 5), (addresses[2], -10)]);
+        assert_eq!(
+            scan(ScanPredicate::Changed),
+            vec![(addresses[0], 15), (addresses[1], 5), (addresses[2], -10)]
+        );
+        assert_eq!(scan(ScanPredicate::Unchanged), vec![(addresses[3], 10)]);
+    }
+
+    #[test]
+    fn test_changed_value_scan_drops_unreadable() {
+        let previous = vec![(0usize, 1.5f32)];
+        assert_eq!(unsafe { changed_value_scan(&previous, ScanPredicate::Unchanged) }, vec![]);
+    }
+
+    #[test]
+    fn test_value_scan_aligned_skips_misaligned() {
+        #[repr(C, align(8))]
+        struct Buffer([u8; 16]);
+
+        let mut buffer = Buffer([0; 16]);
+        buffer.0[5..9].copy_from_slice(&0xC0FF_EE00u32.to_ne_bytes());
+        buffer.0[12..16].copy_from_slice(&0xC0FF_EE00u32.to_ne_bytes());
+        let start = buffer.0.as_ptr() as usize;
+        let end = start + buffer.0.len();
+
+        assert_eq!(unsafe { value_scan(start, end, 0xC0FF_EE00u32) }.unwrap(), vec![start + 12]);
+
+        let unaligned = ValueScanOptions { aligned: false };
+        assert_eq!(
+            unsafe { value_scan_with_options(start, end, 0xC0FF_EE00u32, unaligned) }.unwrap(),
+            vec![start + 5, start + 12]
+        );
+    }
+
+    #[test]
+    fn test_value_scan_aligned_misaligned_start() {
+        let values: Vec<u32> = vec![9, 9, 9];
+        let start = values.as_ptr() as usize;
+
+        assert_eq!(unsafe { value_scan(start + 1, start + 12, 9u32) }.unwrap(), vec![start + 4, start + 8]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,10 @@
+/// How the read and write operations treat pointers that aren't aligned for the accessed type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AlignmentPolicy {
+    /// Reject misaligned pointers with an `InvalidAlignment` error.
+    #[default]
+    Strict,
+    /// Accept any pointer, accessing it with `read_unaligned`/`write_unaligned`. Needed for packed
+    /// structs and byte-addressed buffers.
+    Unaligned,
+}
\ No newline at end of file
@@ -0,0 +1,54 @@
+/// The calling convention of a patched function, which decides how it returns.
+///
+/// Integers are returned in `eax`/`rax` under every supported convention (64-bit integers in
+/// `edx:eax` on x86). Floats are returned in `xmm0` on x64 and in `ST0` on x86. Struct returns
+/// are not supported.
+///
+/// The callee-cleanup conventions carry the number of bytes of stack arguments the function pops
+/// on return, which is emitted as `ret imm16`. They only exist on x86; on x64 every function uses
+/// the platform ABI, so `CallConv::Platform` is the right choice there.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CallConv {
+    /// The default ABI of the target: `cdecl` on x86 and the Microsoft x64 ABI on x64.
+    #[default]
+    Platform,
+    /// Caller cleans up the stack, so the function ends in a plain `ret`.
+    Cdecl,
+    /// Callee pops the given number of bytes of arguments.
+    Stdcall(u16),
+    /// Callee pops the given number of bytes of arguments passed on the stack, i.e. excluding the
+    /// first two passed in `ecx` and `edx`.
+    Fastcall(u16),
+    /// Callee pops the given number of bytes of arguments, excluding `this` passed in `ecx`.
+    Thiscall(u16),
+}
+
+impl CallConv {
+    /// Returns the number of bytes of arguments the callee pops on return.
+    pub fn stack_cleanup(&self) -> u16 {
+        match self {
+            CallConv::Platform | CallConv::Cdecl => 0,
+            CallConv::Stdcall(bytes) | CallConv::Fastcall(bytes) | CallConv::Thiscall(bytes) => *bytes,
+        }
+    }
+
+    /// Builds the return instruction for this convention.
+    ///
+    /// # Example
+    /// ```rust
+    /// use verity_memory::types::CallConv;
+    ///
+    /// assert_eq!(CallConv::Platform.epilogue(), vec![0xC3]);
+    /// assert_eq!(CallConv::Stdcall(8).epilogue(), vec![0xC2, 0x08, 0x00]);
+    /// ```
+    pub fn epilogue(&self) -> Vec<u8> {
+        match self.stack_cleanup() {
+            0 => vec![0xC3],
+            bytes => {
+                let mut ret = vec![0xC2];
+                ret.extend_from_slice(&bytes.to_le_bytes());
+                ret
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,38 @@
+/// The instruction set used to decode machine code.
+///
+/// Code is decoded for the architecture of the current process by default. Bytes read from a
+/// process or a file of the other bitness, e.g. a 32-bit game inspected from a 64-bit tool, need
+/// the mode given explicitly, since the same bytes decode differently: `48` is a REX prefix on x64
+/// but `dec eax` on x86.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DisasmMode {
+    /// 32-bit x86.
+    X86,
+    /// 64-bit x86-64.
+    X64,
+}
+
+impl DisasmMode {
+    /// Returns the mode of the current process.
+    ///
+    /// # Example
+    /// ```rust
+    /// use verity_memory::types::DisasmMode;
+    ///
+    /// let expected = if cfg!(target_arch = "x86_64") { DisasmMode::X64 } else { DisasmMode::X86 };
+    /// assert_eq!(DisasmMode::host(), expected);
+    /// ```
+    pub fn host() -> DisasmMode {
+        if cfg!(target_arch = "x86_64") {
+            DisasmMode::X64
+        } else {
+            DisasmMode::X86
+        }
+    }
+}
+
+impl Default for DisasmMode {
+    fn default() -> Self {
+        DisasmMode::host()
+    }
+}
\ No newline at end of file
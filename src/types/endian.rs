@@ -0,0 +1,29 @@
+/// Integer types that can be decoded from bytes of an explicit endianness.
+///
+/// Implemented for every primitive integer type; used by [`read_be`](crate::ops::read::read_be)
+/// and [`read_le`](crate::ops::read::read_le).
+pub trait FromEndianBytes: Copy {
+    /// Decodes a big-endian value. `bytes` must be exactly `size_of::<Self>()` long.
+    fn from_be_slice(bytes: &[u8]) -> Self;
+
+    /// Decodes a little-endian value. `bytes` must be exactly `size_of::<Self>()` long.
+    fn from_le_slice(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_from_endian_bytes {
+    ($($ty:ty),*) => {
+        $(
+            impl FromEndianBytes for $ty {
+                fn from_be_slice(bytes: &[u8]) -> Self {
+                    <$ty>::from_be_bytes(bytes.try_into().expect("Slice length must match the integer size"))
+                }
+
+                fn from_le_slice(bytes: &[u8]) -> Self {
+                    <$ty>::from_le_bytes(bytes.try_into().expect("Slice length must match the integer size"))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_endian_bytes!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
\ No newline at end of file
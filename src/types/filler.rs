@@ -0,0 +1,54 @@
+/// The byte sequence used to overwrite instructions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filler {
+    /// Recommended multi-byte NOP encodings (`0F 1F ...`), falling back to `0x90` for single bytes.
+    Nop,
+    /// `INT3` (`0xCC`), useful to trap unexpected execution of patched code.
+    Int3,
+    /// A single custom byte repeated over the whole span.
+    Custom(u8),
+}
+
+const MULTI_BYTE_NOPS: [&[u8]; 9] = [
+    &[0x90],
+    &[0x66, 0x90],
+    &[0x0F, 0x1F, 0x00],
+    &[0x0F, 0x1F, 0x40, 0x00],
+    &[0x0F, 0x1F, 0x44, 0x00, 0x00],
+    &[0x66, 0x0F, 0x1F, 0x44, 0x00, 0x00],
+    &[0x0F, 0x1F, 0x80, 0x00, 0x00, 0x00, 0x00],
+    &[0x0F, 0x1F, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00],
+    &[0x66, 0x0F, 0x1F, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00],
+];
+
+impl Filler {
+    /// Builds exactly `size` bytes of filler.
+    ///
+    /// For `Filler::Nop` the span is covered with the longest available NOP encodings first,
+    /// so a 12 byte span becomes one 9 byte NOP followed by one 3 byte NOP.
+    ///
+    /// # Example
+    /// ```rust
+    /// use verity_memory::types::Filler;
+    ///
+    /// assert_eq!(Filler::Int3.bytes(3), vec![0xCC, 0xCC, 0xCC]);
+    /// assert_eq!(Filler::Nop.bytes(3), vec![0x0F, 0x1F, 0x00]);
+    /// assert_eq!(Filler::Nop.bytes(12).len(), 12);
+    /// ```
+    pub fn bytes(&self, size: usize) -> Vec<u8> {
+        match self {
+            Filler::Nop => {
+                let mut bytes = Vec::with_capacity(size);
+                let mut remaining = size;
+                while remaining > 0 {
+                    let chunk = remaining.min(MULTI_BYTE_NOPS.len());
+                    bytes.extend_from_slice(MULTI_BYTE_NOPS[chunk - 1]);
+                    remaining -= chunk;
+                }
+                bytes
+            }
+            Filler::Int3 => vec![0xCC; size],
+            Filler::Custom(byte) => vec![*byte; size],
+        }
+    }
+}
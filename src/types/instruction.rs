@@ -1,20 +1,155 @@
+use winapi::shared::minwindef::LPVOID;
+use winapi::um::winnt::PAGE_EXECUTE_READWRITE;
+
+use crate::errors::{ReadMemoryError, WriteMemoryError};
+#[cfg(feature = "advanced-write")]
+use crate::ops::asm::decode_first;
+use crate::ops::protection::{ProtectionProvider, Win32Protection};
+#[cfg(feature = "advanced-write")]
+use crate::ops::query::query;
+use crate::ops::query::PAGE_SIZE;
+use crate::ops::read::read_bytes;
 use crate::ops::write::write_memory;
 
+/// The longest encoding of an x86 instruction.
+#[cfg(feature = "advanced-write")]
+const MAX_INSTRUCTION_LEN: usize = 15;
+
 #[derive(Clone)]
 pub struct Instruction {
     pub address: *mut u8,
     pub bytes: Vec<u8>,
     pub size: usize,
+    /// The kind of control flow the instruction performs, as classified when it was decoded.
+    pub kind: InsnKind,
+    /// Decoder metadata, only present for instructions returned by
+    /// [`disassemble_detailed`](crate::ops::asm::disassemble_detailed).
+    pub detail: Option<InstructionDetail>,
+}
+
+/// A coarse classification of the control flow of an instruction.
+///
+/// Instructions created from raw bytes with [`Instruction::new`] are [`InsnKind::Other`]; the
+/// kind is only known for instructions produced by the disassembler.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InsnKind {
+    /// A conditional or unconditional jump, or a loop instruction.
+    Branch,
+    /// A near or far call.
+    Call,
+    /// A return, including far returns and interrupt returns.
+    Ret,
+    /// Any other instruction.
+    #[default]
+    Other,
+}
+
+/// Semantic information about a decoded instruction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InstructionDetail {
+    /// The mnemonic, e.g. `call`.
+    pub mnemonic: String,
+    /// The operands as printed by the disassembler, e.g. `qword ptr [rip + 0x10]`.
+    pub op_str: String,
+    /// Registers read, both explicit operands and implicit ones such as `rsp` for `push`.
+    pub regs_read: Vec<String>,
+    /// Registers written, both explicit operands and implicit ones such as `rsp` for `push`.
+    pub regs_written: Vec<String>,
+    /// The groups the instruction belongs to.
+    pub groups: Vec<InstructionGroup>,
+    /// How each operand is accessed, in operand order.
+    pub operands: Vec<OperandAccess>,
+    /// The absolute destination of a relative branch.
+    pub branch_target: Option<usize>,
+}
+
+impl InstructionDetail {
+    /// Whether the instruction is a `call`.
+    pub fn is_call(&self) -> bool {
+        self.groups.contains(&InstructionGroup::Call)
+    }
+
+    /// Whether the instruction transfers control: jumps, calls and returns.
+    pub fn is_branch(&self) -> bool {
+        self.groups.iter().any(|group| {
+            matches!(
+                group,
+                InstructionGroup::Jump | InstructionGroup::Call | InstructionGroup::Return
+            )
+        })
+    }
+}
+
+/// Instruction groups reported by the disassembler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InstructionGroup {
+    Jump,
+    Call,
+    Return,
+    Interrupt,
+    InterruptReturn,
+    Privilege,
+    RelativeBranch,
+}
+
+/// Whether an operand is read, written or both.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OperandAccess {
+    pub read: bool,
+    pub write: bool,
 }
 
 impl Instruction {
     pub fn new(address: *mut u8, bytes: Vec<u8>) -> Self {
+        Instruction::with_kind(address, bytes, InsnKind::Other)
+    }
+
+    /// Creates an instruction whose kind is already known, e.g. from the disassembler.
+    pub fn with_kind(address: *mut u8, bytes: Vec<u8>, kind: InsnKind) -> Self {
         let size = bytes.len();
         Instruction {
             address,
             bytes,
             size,
+            kind,
+            detail: None,
+        }
+    }
+
+    /// Decodes the instruction at `address` from a copy of the memory, without trusting the
+    /// pointer beyond what can be read.
+    ///
+    /// At most `max_len` bytes are read with [`read_bytes`], clamped to the 15 bytes an x86
+    /// instruction can span and to the end of the committed region containing `address`, so a
+    /// short instruction right before an unmapped page still decodes.
+    ///
+    /// # Returns
+    /// - `Some(Instruction)`: The first instruction at `address`, with its bytes, size and kind.
+    /// - `None`: If `address` is null or unreadable, or the bytes don't form a complete instruction.
+    ///
+    /// # Example
+    /// ```rust
+    /// use verity_memory::types::instruction::{InsnKind, Instruction};
+    ///
+    /// let mut buffer = [0x48, 0x89, 0xE5, 0xC3]; // mov rbp, rsp; ret
+    /// let instruction = Instruction::from_slice(buffer.as_mut_ptr(), buffer.len()).unwrap();
+    ///
+    /// assert_eq!(instruction.kind, InsnKind::Other);
+    /// assert!(instruction.size <= buffer.len());
+    /// ```
+    #[cfg(feature = "advanced-write")]
+    pub fn from_slice(address: *mut u8, max_len: usize) -> Option<Instruction> {
+        if address.is_null() {
+            return None;
         }
+
+        let info = query(address)?;
+        let region_end = (info.BaseAddress as usize).saturating_add(info.RegionSize);
+        let len = max_len.min(MAX_INSTRUCTION_LEN).min(region_end - address as usize);
+
+        let bytes = unsafe { read_bytes(address, len) }.ok()?;
+        let (bytes, kind) = decode_first(&bytes)?;
+        Some(Instruction::with_kind(address, bytes, kind))
     }
 
     /// Restores the original bytes at the specified memory address.
@@ -42,8 +177,8 @@ impl Instruction {
     ///     let original_instructions = nop_instructions(buffer_ptr, 1);
     ///     
     ///     // Check that the original instruction was captured successfully
-    ///     assert!(original_instructions.is_some());
-    ///     let instruction = original_instructions.unwrap().first().unwrap().clone();
+    ///     assert!(original_instructions.is_ok());
+    ///     let instruction = original_instructions.unwrap().originals.first().unwrap().clone();
     ///     
     ///     // Manually restore the first instruction using the `restore` method
     ///     instruction.restore();
@@ -66,23 +201,62 @@ impl Instruction {
             }
         }
     }
+
+    /// Checks whether the memory at the instruction's address holds its original bytes again,
+    /// e.g. to assert that hooks were removed before unloading.
+    ///
+    /// # Safety
+    /// This function is `unsafe` because it reads memory through the raw address of the instruction.
+    ///
+    /// # Returns
+    /// - `Ok(true)`: If the `size` bytes at `address` match `bytes`.
+    /// - `Ok(false)`: If any of them differs.
+    ///
+    /// # Errors
+    /// - Any error returned by [`read_bytes`], e.g. `ReadMemoryError::NullPointer`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use verity_memory::ops::write::nop_instructions;
+    ///
+    /// unsafe {
+    ///     let buffer = vec![0x55, 0x48, 0x89, 0xE5]; // push rbp; mov rbp, rsp
+    ///     let result = nop_instructions(buffer.as_ptr() as *mut u8, 1).unwrap();
+    ///     let instruction = &result.originals[0];
+    ///
+    ///     assert_eq!(instruction.verify_restored(), Ok(false));
+    ///     instruction.restore();
+    ///     assert_eq!(instruction.verify_restored(), Ok(true));
+    /// }
+    /// ```
+    pub unsafe fn verify_restored(&self) -> Result<bool, ReadMemoryError> {
+        let current = read_bytes(self.address, self.size)?;
+        Ok(current == self.bytes)
+    }
 }
 
 pub trait InstructionVecExt {
-    fn restore_all(&self);
+    fn restore_all(&self) -> Result<(), WriteMemoryError>;
 }
 
 impl InstructionVecExt for Vec<Instruction> {
-    /// Restores the original bytes at the specified memory address.
+    /// Restores the original bytes of every instruction.
     ///
-    /// This function iterates over the saved bytes in the `Instruction` and writes each byte back to the original memory address,
-    /// effectively restoring the memory to its previous state. It is commonly used to undo modifications made to executable code or data.
+    /// The instructions are restored in reverse order, so when patches overlap, the bytes saved
+    /// by the first patch are the ones left in memory. The protection of each page touched is
+    /// changed only once, with all the bytes falling into it written under that single change.
+    ///
+    /// Every page is restored even if an earlier one fails.
     ///
     /// # Safety
     /// This function is `unsafe` because it performs raw pointer arithmetic and dereferences raw pointers.
     /// - The caller must ensure that the memory address is valid and writable.
     /// - Writing to an invalid or protected memory region may cause undefined behavior or a crash.
     ///
+    /// # Errors
+    /// - The first error encountered, e.g. `WriteMemoryError::FailedToChangeProtection` if the
+    ///   protection of a page could not be changed.
+    ///
     /// # Example
     /// ```rust
     /// use verity_memory::ops::write::{nop_instructions, write_memory};
@@ -98,11 +272,11 @@ impl InstructionVecExt for Vec<Instruction> {
     ///     let original_instructions = nop_instructions(buffer_ptr, 2);
     ///     
     ///     // Check that original instructions were captured successfully
-    ///     assert!(original_instructions.is_some());
-    ///     let instructions = original_instructions.unwrap();
+    ///     assert!(original_instructions.is_ok());
+    ///     let instructions = original_instructions.unwrap().originals;
     ///     
     ///     // Restore the original instructions using the `restore_all` method
-    ///     instructions.restore_all();
+    ///     instructions.restore_all().unwrap();
     ///
     ///     //Assert that the buffer is now identical to the original buffer
     ///     assert_eq!(buffer, original_buffer, "The buffer was not correctly restored to its original state.");
@@ -113,11 +287,150 @@ impl InstructionVecExt for Vec<Instruction> {
     /// 1. We use `nop_instructions` to replace the first two instructions in the buffer with NOPs.
     /// 2. The original instructions are captured in a vector of `Instruction`.
     /// 3. Finally, we call `restore_all` to revert the changes, restoring the original machine code.
-    fn restore_all(&self) {
-        for instruction in self {
-            unsafe {
-                instruction.restore();
+    fn restore_all(&self) -> Result<(), WriteMemoryError> {
+        unsafe { restore_instructions(self, &Win32Protection) }
+    }
+}
+
+/// Restores `instructions` in reverse order, changing the protection of each page once.
+pub(crate) unsafe fn restore_instructions<P: ProtectionProvider + ?Sized>(
+    instructions: &[Instruction],
+    provider: &P,
+) -> Result<(), WriteMemoryError> {
+    if instructions.iter().any(|instruction| instruction.address.is_null()) {
+        return Err(WriteMemoryError::NullPointer);
+    }
+
+    let mut pages: Vec<usize> = instructions
+        .iter()
+        .filter(|instruction| !instruction.bytes.is_empty())
+        .flat_map(|instruction| {
+            let start = instruction.address as usize;
+            let end = start + instruction.bytes.len();
+            (start / PAGE_SIZE..=(end - 1) / PAGE_SIZE).map(|page| page * PAGE_SIZE)
+        })
+        .collect();
+    pages.sort_unstable();
+    pages.dedup();
+
+    let mut result = Ok(());
+    for page in pages {
+        let Some(old_protect) = provider.protect(page as LPVOID, PAGE_SIZE, PAGE_EXECUTE_READWRITE) else {
+            result = result.and(Err(WriteMemoryError::FailedToChangeProtection));
+            continue;
+        };
+
+        for instruction in instructions.iter().rev() {
+            let start = (instruction.address as usize).max(page);
+            let end = (instruction.address as usize + instruction.bytes.len()).min(page + PAGE_SIZE);
+            if start >= end {
+                continue;
             }
+
+            let offset = start - instruction.address as usize;
+            std::ptr::copy_nonoverlapping(
+                instruction.bytes[offset..].as_ptr(),
+                start as *mut u8,
+                end - start,
+            );
         }
+
+        if provider.protect(page as LPVOID, PAGE_SIZE, old_protect).is_none() {
+            result = result.and(Err(WriteMemoryError::FailedToRestoreProtection));
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::protection::MockProtection;
+    use winapi::um::winnt::PAGE_EXECUTE_READ;
+
+    #[repr(C, align(64))]
+    struct Code([u8; 16]);
+
+    #[test]
+    fn test_verify_restored() {
+        let mut code = Code([0x90; 16]);
+        let base = code.0.as_mut_ptr();
+        let instruction = Instruction::new(base, vec![0x48, 0x89, 0xE5]);
+
+        assert_eq!(unsafe { instruction.verify_restored() }, Ok(false));
+
+        unsafe { instruction.restore() };
+        assert_eq!(unsafe { instruction.verify_restored() }, Ok(true));
+
+        code.0[2] = 0xCC;
+        assert_eq!(unsafe { instruction.verify_restored() }, Ok(false));
+
+        let null = Instruction::new(std::ptr::null_mut(), vec![0x90]);
+        assert_eq!(unsafe { null.verify_restored() }, Err(ReadMemoryError::NullPointer));
+    }
+
+    #[test]
+    #[cfg(feature = "advanced-write")]
+    fn test_from_slice() {
+        // mov eax, 0x2A; ret
+        let mut code = Code([0xCC; 16]);
+        code.0[..6].copy_from_slice(&[0xB8, 0x2A, 0x00, 0x00, 0x00, 0xC3]);
+        let base = code.0.as_mut_ptr();
+
+        let instruction = Instruction::from_slice(base, 16).expect("Failed to decode instruction");
+        assert_eq!(instruction.address, base);
+        assert_eq!(instruction.bytes, vec![0xB8, 0x2A, 0x00, 0x00, 0x00]);
+        assert_eq!(instruction.size, 5);
+        assert_eq!(instruction.kind, InsnKind::Other);
+
+        let ret = Instruction::from_slice(unsafe { base.add(5) }, 1).expect("Failed to decode instruction");
+        assert_eq!(ret.bytes, vec![0xC3]);
+        assert_eq!(ret.kind, InsnKind::Ret);
+
+        assert!(Instruction::from_slice(base, 3).is_none());
+        assert!(Instruction::from_slice(std::ptr::null_mut(), 16).is_none());
+    }
+
+    #[test]
+    fn test_restore_instructions_single_toggle() {
+        let mut code = Code([0x90; 16]);
+        let base = code.0.as_mut_ptr();
+        let instructions = vec![
+            Instruction::new(base, vec![0x55]),
+            Instruction::new(unsafe { base.add(1) }, vec![0x48, 0x89, 0xE5]),
+            Instruction::new(unsafe { base.add(4) }, vec![0x48, 0x83, 0xEC, 0x20]),
+        ];
+        let provider = MockProtection::new(PAGE_EXECUTE_READ, None);
+
+        assert_eq!(unsafe { restore_instructions(&instructions, &provider) }, Ok(()));
+        assert_eq!(&code.0[..8], &[0x55, 0x48, 0x89, 0xE5, 0x48, 0x83, 0xEC, 0x20]);
+        assert_eq!(&code.0[8..], &[0x90; 8]);
+        assert_eq!(provider.protections(), vec![PAGE_EXECUTE_READWRITE, PAGE_EXECUTE_READ]);
+    }
+
+    #[test]
+    fn test_restore_instructions_reverse_order() {
+        let mut code = Code([0xCC; 16]);
+        let base = code.0.as_mut_ptr();
+        // The second patch saved the bytes written by the first one.
+        let instructions = vec![
+            Instruction::new(base, vec![0x55, 0x48]),
+            Instruction::new(base, vec![0x90, 0x90]),
+        ];
+
+        assert_eq!(instructions.restore_all(), Ok(()));
+        assert_eq!(&code.0[..2], &[0x55, 0x48]);
+    }
+
+    #[test]
+    fn test_restore_instructions_failed_protection() {
+        let mut code = Code([0x90; 16]);
+        let instructions = vec![Instruction::new(code.0.as_mut_ptr(), vec![0xC3])];
+        let provider = MockProtection::new(PAGE_EXECUTE_READ, Some(0));
+
+        let result = unsafe { restore_instructions(&instructions, &provider) };
+        assert_eq!(result, Err(WriteMemoryError::FailedToChangeProtection));
+        assert_eq!(code.0[0], 0x90);
     }
 }
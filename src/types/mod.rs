@@ -1,3 +1,22 @@
+pub mod alignment;
+pub mod call_conv;
+pub mod disasm_mode;
+pub mod endian;
+pub mod filler;
 pub mod instruction;
+pub mod nop_result;
+pub mod protection;
+pub(crate) mod vec128;
 
-pub use instruction::Instruction;
\ No newline at end of file
+pub use alignment::AlignmentPolicy;
+pub use call_conv::CallConv;
+pub use disasm_mode::DisasmMode;
+pub use endian::FromEndianBytes;
+pub use filler::Filler;
+pub use instruction::InsnKind;
+pub use instruction::Instruction;
+pub use instruction::InstructionDetail;
+pub use instruction::InstructionGroup;
+pub use instruction::OperandAccess;
+pub use nop_result::NopResult;
+pub use protection::Protection;
\ No newline at end of file
@@ -0,0 +1,36 @@
+use super::instruction::{Instruction, InstructionVecExt};
+use crate::errors::WriteMemoryError;
+
+/// The instructions overwritten by [`fill_instructions`](crate::ops::write::fill_instructions),
+/// together with the number of bytes they spanned.
+///
+/// The span is where a subsequent patch, e.g. a jump, can be placed without cutting an
+/// instruction in half.
+#[derive(Clone)]
+pub struct NopResult {
+    /// The original instructions, in order.
+    pub originals: Vec<Instruction>,
+    /// The combined size of the original instructions.
+    pub total_bytes: usize,
+}
+
+impl NopResult {
+    pub fn new(originals: Vec<Instruction>) -> Self {
+        let total_bytes = originals.iter().map(|instr| instr.size).sum();
+        NopResult {
+            originals,
+            total_bytes,
+        }
+    }
+
+    /// Writes the original instructions back, in reverse order and with one protection change per page.
+    ///
+    /// # Safety
+    /// The caller must ensure that the memory of the original instructions is still valid and writable.
+    ///
+    /// # Errors
+    /// - The first error encountered while restoring, see [`InstructionVecExt::restore_all`].
+    pub unsafe fn restore_all(&self) -> Result<(), WriteMemoryError> {
+        self.originals.restore_all()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,75 @@
+use winapi::um::winnt::{
+    PAGE_EXECUTE, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE, PAGE_EXECUTE_WRITECOPY, PAGE_NOACCESS,
+    PAGE_READONLY, PAGE_READWRITE, PAGE_WRITECOPY,
+};
+
+/// A page protection, mirroring the basic `PAGE_*` constants accepted by `VirtualProtect`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Protection {
+    NoAccess,
+    ReadOnly,
+    ReadWrite,
+    WriteCopy,
+    Execute,
+    ExecuteRead,
+    #[default]
+    ExecuteReadWrite,
+    ExecuteWriteCopy,
+}
+
+impl Protection {
+    /// Returns the `PAGE_*` constant for this protection.
+    pub fn flags(&self) -> u32 {
+        match self {
+            Protection::NoAccess => PAGE_NOACCESS,
+            Protection::ReadOnly => PAGE_READONLY,
+            Protection::ReadWrite => PAGE_READWRITE,
+            Protection::WriteCopy => PAGE_WRITECOPY,
+            Protection::Execute => PAGE_EXECUTE,
+            Protection::ExecuteRead => PAGE_EXECUTE_READ,
+            Protection::ExecuteReadWrite => PAGE_EXECUTE_READWRITE,
+            Protection::ExecuteWriteCopy => PAGE_EXECUTE_WRITECOPY,
+        }
+    }
+
+    /// Converts a `PAGE_*` value back into a `Protection`, ignoring modifiers such as `PAGE_GUARD`.
+    ///
+    /// # Returns
+    /// - `Some(Protection)` for one of the basic protections.
+    /// - `None` if `flags` doesn't contain a basic protection.
+    pub fn from_flags(flags: u32) -> Option<Protection> {
+        match flags & 0xFF {
+            PAGE_NOACCESS => Some(Protection::NoAccess),
+            PAGE_READONLY => Some(Protection::ReadOnly),
+            PAGE_READWRITE => Some(Protection::ReadWrite),
+            PAGE_WRITECOPY => Some(Protection::WriteCopy),
+            PAGE_EXECUTE => Some(Protection::Execute),
+            PAGE_EXECUTE_READ => Some(Protection::ExecuteRead),
+            PAGE_EXECUTE_READWRITE => Some(Protection::ExecuteReadWrite),
+            PAGE_EXECUTE_WRITECOPY => Some(Protection::ExecuteWriteCopy),
+            _ => None,
+        }
+    }
+
+    /// Whether memory with this protection can be written to.
+    pub fn is_writable(&self) -> bool {
+        matches!(
+            self,
+            Protection::ReadWrite
+                | Protection::WriteCopy
+                | Protection::ExecuteReadWrite
+                | Protection::ExecuteWriteCopy
+        )
+    }
+
+    /// Whether memory with this protection can be executed.
+    pub fn is_executable(&self) -> bool {
+        matches!(
+            self,
+            Protection::Execute
+                | Protection::ExecuteRead
+                | Protection::ExecuteReadWrite
+                | Protection::ExecuteWriteCopy
+        )
+    }
+}
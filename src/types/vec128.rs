@@ -0,0 +1,4 @@
+/// Four floats with the 16-byte alignment of `__m128`, so a whole vector is accessed at once.
+#[repr(C, align(16))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Vec128(pub(crate) [f32; 4]);
\ No newline at end of file
@@ -1,7 +1,11 @@
-use std::ptr::null_mut;
+use std::ptr::{null_mut, read_unaligned};
 
+use winapi::um::errhandlingapi::GetLastError;
 use winapi::um::libloaderapi::GetModuleHandleW;
+use winapi::um::winbase::{FormatMessageW, FORMAT_MESSAGE_FROM_SYSTEM, FORMAT_MESSAGE_IGNORE_INSERTS};
+use winapi::um::winnt::{IMAGE_DOS_HEADER, IMAGE_FILE_HEADER};
 
+use crate::errors::ModuleError;
 use crate::w;
 
 pub(crate) fn check_alignment<T>(ptr: *const T) -> bool {
@@ -12,49 +16,170 @@ pub(crate) fn check_alignment<T>(ptr: *const T) -> bool {
     (ptr as usize) % alignment == 0
 }
 
+/// Loads `dll_name` and looks up the export `proc_name` in it.
+///
+/// The library is returned alongside the symbol, which is only valid while it is loaded.
+///
+/// # Errors
+/// - Any `libloading::Error` from loading the library or looking up the symbol, e.g. if the DLL
+///   is not found or doesn't export `proc_name`.
 pub unsafe fn import_function<'a, F>(
     dll_name: &str,
     proc_name: &str,
-) -> Option<(
-    libloading::os::windows::Library,
-    libloading::os::windows::Symbol<F>,
-)>
+) -> Result<
+    (
+        libloading::os::windows::Library,
+        libloading::os::windows::Symbol<F>,
+    ),
+    libloading::Error,
+>
 where
     F: Sized,
 {
-    match libloading::os::windows::Library::new(dll_name) {
-        Ok(lib) => {
-            let proc_name_c = std::ffi::CString::new(proc_name).unwrap();
-            match lib.get::<F>(proc_name_c.as_bytes_with_nul()) {
-                Ok(symbol) => Some((lib, symbol)),
-                Err(_) => {
-                    println!("Failed to get function address for {}", proc_name);
-                    None
-                }
-            }
-        }
-        Err(_) => {
-            println!("Failed to load DLL: {}", dll_name);
-            None
-        }
-    }
+    let lib = libloading::os::windows::Library::new(dll_name)?;
+    let symbol = lib.get::<F>(proc_name.as_bytes())?;
+    Ok((lib, symbol))
+}
+
+/// Returns the error code of the last failed Win32 call on the current thread (`GetLastError`).
+///
+/// Read it right after the failing call, since any other Win32 call may overwrite it.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::utils;
+///
+/// assert!(utils::module_base(Some("non_existent_module.dll")).is_err());
+/// assert_eq!(utils::last_error(), 126); // ERROR_MOD_NOT_FOUND
+/// ```
+pub fn last_error() -> u32 {
+    unsafe { GetLastError() }
 }
 
-pub fn module_base(module_name: Option<&str>) -> *mut u8 {
-    unsafe {
+/// Returns the system message describing the error of the last failed Win32 call on the current
+/// thread, e.g. `"The specified module could not be found."`.
+///
+/// Falls back to the error code if the system has no message for it.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::utils;
+///
+/// assert!(utils::module_base(Some("non_existent_module.dll")).is_err());
+/// println!("{}", utils::last_error_message());
+/// ```
+pub fn last_error_message() -> String {
+    error_message(last_error())
+}
+
+/// Formats a Win32 error code with `FormatMessageW`.
+pub(crate) fn error_message(code: u32) -> String {
+    let mut buffer = [0u16; 512];
+    let len = unsafe {
+        FormatMessageW(
+            FORMAT_MESSAGE_FROM_SYSTEM | FORMAT_MESSAGE_IGNORE_INSERTS,
+            null_mut(),
+            code,
+            0,
+            buffer.as_mut_ptr(),
+            buffer.len() as u32,
+            null_mut(),
+        )
+    };
+
+    if len == 0 {
+        return format!("Win32 error {:#x}", code);
+    }
+
+    String::from_utf16_lossy(&buffer[..len as usize]).trim_end().to_string()
+}
 
-        let handle = match module_name {
-            Some(name) => {
-                GetModuleHandleW(w!(name))
+/// Returns the base address of a module loaded in the current process.
+///
+/// # Parameters
+/// - `module_name`: The name of the module (e.g. `"kernel32.dll"`), or `None` for the executable.
+///
+/// # Returns
+/// - `Ok(*mut u8)`: The base address of the module.
+/// - `Err(ModuleError)`: If the name is invalid or the module isn't loaded.
+///
+/// # Errors
+/// - `ModuleError::EmptyName`: If `module_name` is an empty string.
+/// - `ModuleError::InteriorNull`: If `module_name` contains a NUL character, which would truncate it.
+/// - `ModuleError::NotLoaded`: If no module with that name is loaded, with the `GetLastError` code.
+///
+/// # Example
+/// ```rust
+/// use verity_memory::utils;
+///
+/// let kernel32 = utils::module_base(Some("kernel32.dll")).unwrap();
+/// assert!(!kernel32.is_null());
+/// ```
+pub fn module_base(module_name: Option<&str>) -> Result<*mut u8, ModuleError> {
+    let handle = match module_name {
+        Some(name) => {
+            if name.is_empty() {
+                return Err(ModuleError::EmptyName);
+            }
+            if name.contains('\0') {
+                return Err(ModuleError::InteriorNull);
             }
-            None => GetModuleHandleW(null_mut()),
-        };
 
-        if handle.is_null() {
-            panic!("Failed to get module handle");
+            let wide_name = w!(name);
+            unsafe { GetModuleHandleW(wide_name.as_ptr()) }
         }
-        handle as *mut u8
+        None => unsafe { GetModuleHandleW(null_mut()) },
+    };
+
+    if handle.is_null() {
+        return Err(ModuleError::NotLoaded(last_error()));
     }
+    Ok(handle as *mut u8)
+}
+
+/// Resolves a relative virtual address of a loaded module, e.g. from a PDB or a map file.
+///
+/// # Parameters
+/// - `module`: The name of the module (e.g. `"kernel32.dll"`), or `None` for the executable.
+/// - `rva`: The offset from the base of the module.
+///
+/// # Returns
+/// - `Ok(*mut u8)`: The base address of the module plus `rva`.
+/// - `Err(ModuleError)`: If the module isn't loaded or `rva` lies outside its image.
+///
+/// # Errors
+/// - `ModuleError::OutOfBounds`: If `rva` is not below the `SizeOfImage` of the module.
+/// - Any error returned by [`module_base`].
+///
+/// # Example
+/// ```rust
+/// use verity_memory::utils;
+///
+/// let base = utils::module_base(None).unwrap();
+/// assert_eq!(utils::rva(None, 0x1000), Ok(base.wrapping_add(0x1000)));
+/// assert!(utils::rva(None, usize::MAX).is_err());
+/// ```
+pub fn rva(module: Option<&str>, rva: usize) -> Result<*mut u8, ModuleError> {
+    let base = module_base(module)?;
+
+    // A loaded module is mapped with its headers, so they are always readable.
+    if rva >= unsafe { image_size(base) } {
+        return Err(ModuleError::OutOfBounds);
+    }
+
+    Ok(base.wrapping_add(rva))
+}
+
+/// Reads `SizeOfImage`, which has the same offset in the PE32 and PE32+ optional headers.
+unsafe fn image_size(base: *const u8) -> usize {
+    const SIZE_OF_IMAGE_OFFSET: usize = 56;
+
+    let dos_header = read_unaligned(base as *const IMAGE_DOS_HEADER);
+    let optional_header = base
+        .add(dos_header.e_lfanew as usize)
+        .add(4 + std::mem::size_of::<IMAGE_FILE_HEADER>());
+
+    read_unaligned(optional_header.add(SIZE_OF_IMAGE_OFFSET) as *const u32) as usize
 }
 
 #[cfg(test)]
@@ -82,18 +207,73 @@ mod tests {
     #[test]
     fn test_import_function_fail_load() {
         let result = unsafe { import_function::<fn()>("non_existent_dll.dll", "non_existent_function") };
-        assert!(result.is_none());
+        assert!(matches!(result, Err(libloading::Error::LoadLibraryExW { .. })));
     }
 
     #[test]
     fn test_import_function_fail_get() {
         let result = unsafe { import_function::<fn()>("kernel32.dll", "non_existent_function") };
-        assert!(result.is_none());
+        assert!(matches!(result, Err(libloading::Error::GetProcAddress { .. })));
     }
 
     #[test]
     fn test_import_function_success() {
         let result = unsafe { import_function::<fn()>("kernel32.dll", "GetCurrentProcess") };
-        assert!(result.is_some());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_module_base_success() {
+        assert!(module_base(None).is_ok());
+        assert!(module_base(Some("kernel32.dll")).is_ok());
+    }
+
+    #[test]
+    fn test_module_base_invalid_name() {
+        assert_eq!(module_base(Some("")), Err(ModuleError::EmptyName));
+        assert_eq!(module_base(Some("kernel32.dll\0ntdll.dll")), Err(ModuleError::InteriorNull));
+        assert_eq!(module_base(Some("non_existent_module.dll")), Err(ModuleError::NotLoaded(126)));
+    }
+
+    #[test]
+    fn test_rva() {
+        fn local_function() {}
+
+        let base = module_base(None).unwrap();
+        let function = local_function as fn() as *mut u8;
+        let offset = function as usize - base as usize;
+
+        assert_eq!(rva(None, offset), Ok(function));
+        assert_eq!(rva(None, 0), Ok(base));
+
+        let size = unsafe { image_size(base) };
+        assert!(size > offset);
+        assert_eq!(rva(None, size - 1), Ok(base.wrapping_add(size - 1)));
+        assert_eq!(rva(None, size), Err(ModuleError::OutOfBounds));
+        assert_eq!(rva(None, usize::MAX), Err(ModuleError::OutOfBounds));
+        assert_eq!(rva(Some("non_existent_module.dll"), 0), Err(ModuleError::NotLoaded(126)));
+    }
+
+    #[test]
+    fn test_last_error_message() {
+        assert_eq!(module_base(Some("non_existent_module.dll")), Err(ModuleError::NotLoaded(126)));
+        assert_eq!(last_error(), 126);
+        assert_eq!(
+            ModuleError::NotLoaded(126).to_string(),
+            format!("NotLoaded: {}", error_message(126))
+        );
+
+        assert!(module_base(Some("non_existent_module.dll")).is_err());
+        let message = last_error_message();
+        assert!(!message.is_empty());
+        assert!(!message.ends_with('\n'));
+
+        assert_eq!(error_message(0xDEAD_BEEF), "Win32 error 0xdeadbeef");
+    }
+
+    #[test]
+    fn test_wide_string_terminated() {
+        let wide = w!("abc");
+        assert_eq!(wide, vec![b'a' as u16, b'b' as u16, b'c' as u16, 0]);
     }
 }
\ No newline at end of file